@@ -0,0 +1,76 @@
+//! Analyze module
+//!
+//! Re-parses the raw logs archived with `--save-raw-logs` using the current parser, so parser
+//! fixes apply to old sessions without re-running them.
+
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    Result,
+    benchmark::{parser::TickWindow, runner},
+    core::{
+        error::BenchmarkErrorKind,
+        factorio,
+        output::{CsvWriter, WriteData, ensure_output_dir, raw_log, write_result},
+        utils::{self, UpsDefinition},
+    },
+};
+
+/// Rebuild `results.csv` in `output_dir` from the raw logs in `raw_dir`, counting only the ticks
+/// in `window` and labeling the runs with `machine` when given
+pub fn from_raw(
+    raw_dir: &Path,
+    output_dir: &Path,
+    ups_definition: UpsDefinition,
+    window: TickWindow,
+    machine: Option<&str>,
+) -> Result<()> {
+    let logs = raw_log::find_raw_logs(raw_dir)?;
+    if logs.is_empty() {
+        return Err(BenchmarkErrorKind::NoRawLogsFound {
+            path: raw_dir.to_path_buf(),
+        }
+        .into());
+    }
+
+    let mut results_map = HashMap::new();
+    for log in &logs {
+        tracing::debug!("Re-parsing {}", log.path.display());
+        let (summary, verbose_data) = factorio::split_output(&log.read()?);
+        // Logs of sessions with --native-runs hold every run of their save
+        let runs = runner::parse_runs(
+            &summary,
+            verbose_data,
+            Path::new(&log.save_name),
+            &log.save_name,
+            log.run_index,
+            ups_definition,
+            window,
+        )?;
+        for (mut result, _) in runs {
+            result.machine = machine.map(str::to_string);
+            results_map
+                .entry(log.save_name.clone())
+                .or_insert_with(Vec::new)
+                .push(result);
+        }
+    }
+
+    let mut results = runner::sort_by_performance(results_map);
+    utils::calculate_base_differences(&mut results);
+
+    ensure_output_dir(output_dir)?;
+    write_result(
+        &CsvWriter::new(),
+        &WriteData::Benchmark(results),
+        output_dir,
+        false,
+    )?;
+
+    tracing::info!(
+        "Re-parsed {} raw logs into {}",
+        logs.len(),
+        output_dir.join("results.csv").display()
+    );
+    Ok(())
+}
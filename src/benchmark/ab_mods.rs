@@ -0,0 +1,252 @@
+//! Mods A/B comparisons (`--ab-mods`): every save benchmarked with two mods directories in
+//! alternating rounds, and the runs of each round compared as a pair, so drift over the session
+//! affects both sides alike.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    benchmark::{
+        parser::BenchmarkRun,
+        runner::{BenchmarkRunner, VerboseData},
+    },
+    core::{Result, confidence_interval_95, mean},
+};
+
+/// The side of an `--ab-mods` comparison a run belongs to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModsVariant {
+    /// 0 for the first mods directory, 1 for the second
+    pub side: usize,
+    /// Name of the mods directory, appended to the save name in brackets
+    pub label: String,
+    /// Save name without the label
+    pub save_name: String,
+}
+
+/// Labels of the two mods directories: their names, or `A` and `B` when those don't tell them
+/// apart
+pub fn labels(mods_dirs: &[PathBuf; 2]) -> [String; 2] {
+    let [a, b] = mods_dirs.each_ref().map(|dir| {
+        dir.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    if a.is_empty() || b.is_empty() || a == b {
+        ["A".to_string(), "B".to_string()]
+    } else {
+        [a, b]
+    }
+}
+
+/// Benchmark every save `runs` times with each mods directory, alternating which goes first each
+/// round. Returns the runs of both sides, their save names labeled with the mods directory.
+pub async fn run_all(
+    runner: &BenchmarkRunner,
+    mods_dirs: &[PathBuf; 2],
+    runs: u32,
+    save_files: Vec<PathBuf>,
+    running: &Arc<AtomicBool>,
+) -> Result<(Vec<BenchmarkRun>, Vec<VerboseData>)> {
+    let labels = labels(mods_dirs);
+    let save_names = runner.unique_display_names(&save_files)?;
+
+    let mut results = Vec::new();
+    let mut verbose_data = Vec::new();
+    for round in 0..runs {
+        let order = if round % 2 == 0 { [0, 1] } else { [1, 0] };
+        for side in order {
+            if !running.load(Ordering::SeqCst) {
+                return Ok((results, verbose_data));
+            }
+            tracing::info!(
+                "Mods A/B round {}/{runs}: {}",
+                round + 1,
+                mods_dirs[side].display()
+            );
+
+            let variant = runner.for_mods_variant(&mods_dirs[side], &labels[side]);
+            // The save each labeled name stands for
+            let base_names: HashMap<String, String> = variant
+                .unique_display_names(&save_files)?
+                .into_iter()
+                .map(|(save_file, name)| (name, save_names[&save_file].clone()))
+                .collect();
+
+            let (round_results, round_verbose_data) =
+                variant.run_all(save_files.clone(), running).await?;
+            for mut result in round_results {
+                result.index = round;
+                result.mods_variant = Some(ModsVariant {
+                    side,
+                    label: labels[side].clone(),
+                    save_name: base_names
+                        .get(&result.save_name)
+                        .cloned()
+                        .unwrap_or_else(|| result.save_name.clone()),
+                });
+                results.push(result);
+            }
+            verbose_data.extend(round_verbose_data);
+        }
+    }
+    Ok((results, verbose_data))
+}
+
+/// The runs of one save with both mods directories, compared round by round
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairedComparison {
+    pub save_name: String,
+    pub labels: [String; 2],
+    /// Rounds in which both sides completed
+    pub pairs: usize,
+    /// Mean UPS of each side over the pairs
+    pub ups: [f64; 2],
+    /// Mean UPS change of the second side from the first over the pairs, in percent
+    pub change_percent: f64,
+    /// 95% confidence interval of the change, with at least two pairs
+    pub confidence_interval: Option<(f64, f64)>,
+}
+
+impl PairedComparison {
+    /// Whether the confidence interval excludes no change
+    pub fn is_significant(&self) -> bool {
+        self.confidence_interval
+            .is_some_and(|(low, high)| low > 0.0 || high < 0.0)
+    }
+}
+
+/// UPS per round and label of both sides of a save
+#[derive(Debug, Default)]
+struct Sides {
+    ups: [BTreeMap<u32, f64>; 2],
+    labels: [String; 2],
+}
+
+/// Pair the runs of every save of an `--ab-mods` comparison by round
+pub fn compare(results: &[BenchmarkRun]) -> Vec<PairedComparison> {
+    let mut saves: BTreeMap<&str, Sides> = BTreeMap::new();
+    for run in results {
+        let Some(variant) = &run.mods_variant else {
+            continue;
+        };
+        let sides = saves.entry(&variant.save_name).or_default();
+        if let (Some(ups), Some(label)) = (
+            sides.ups.get_mut(variant.side),
+            sides.labels.get_mut(variant.side),
+        ) {
+            ups.insert(run.index, run.effective_ups);
+            *label = variant.label.clone();
+        }
+    }
+
+    saves
+        .into_iter()
+        .filter_map(|(save_name, sides)| {
+            let [a, b] = &sides.ups;
+            let pairs: Vec<(f64, f64)> = a
+                .iter()
+                .filter_map(|(round, &ups_a)| Some((ups_a, *b.get(round)?)))
+                .filter(|&(ups_a, _)| ups_a > 0.0)
+                .collect();
+            if pairs.is_empty() {
+                return None;
+            }
+
+            let changes: Vec<f64> = pairs
+                .iter()
+                .map(|(ups_a, ups_b)| (ups_b - ups_a) / ups_a * 100.0)
+                .collect();
+            let side_mean = |ups: fn(&(f64, f64)) -> f64| {
+                mean(&pairs.iter().map(ups).collect::<Vec<_>>()).unwrap_or(0.0)
+            };
+            Some(PairedComparison {
+                save_name: save_name.to_string(),
+                labels: sides.labels,
+                pairs: pairs.len(),
+                ups: [side_mean(|pair| pair.0), side_mean(|pair| pair.1)],
+                change_percent: mean(&changes).unwrap_or(0.0),
+                confidence_interval: confidence_interval_95(&changes),
+            })
+        })
+        .collect()
+}
+
+/// Log the outcome of every comparison
+pub fn log_comparisons(results: &[BenchmarkRun]) {
+    for comparison in compare(results) {
+        let [label_a, label_b] = &comparison.labels;
+        let verdict = match comparison.confidence_interval {
+            Some((low, high)) if comparison.is_significant() => {
+                format!("significant, 95% CI {low:+.2}% to {high:+.2}%")
+            }
+            Some((low, high)) => {
+                format!("not significant, 95% CI {low:+.2}% to {high:+.2}%")
+            }
+            None => "too few pairs to test".to_string(),
+        };
+        tracing::info!(
+            "{}: {label_b} is {:+.2}% UPS against {label_a} over {} pair(s) ({verdict})",
+            comparison.save_name,
+            comparison.change_percent,
+            comparison.pairs
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_pairs_runs_by_round() {
+        let run = |side: usize, round: u32, ups: f64| BenchmarkRun {
+            index: round,
+            save_name: format!("base [{}]", ["on", "off"][side]),
+            effective_ups: ups,
+            mods_variant: Some(ModsVariant {
+                side,
+                label: ["on", "off"][side].to_string(),
+                save_name: "base".to_string(),
+            }),
+            ..Default::default()
+        };
+        let results = vec![
+            run(0, 0, 100.0),
+            run(1, 0, 110.0),
+            run(1, 1, 121.0),
+            run(0, 1, 110.0),
+            run(0, 2, 120.0),
+            run(1, 2, 132.0),
+            // Unpaired, e.g. after an interrupt
+            run(0, 3, 50.0),
+        ];
+
+        let comparisons = compare(&results);
+
+        assert_eq!(comparisons.len(), 1);
+        let comparison = &comparisons[0];
+        assert_eq!(comparison.labels, ["on".to_string(), "off".to_string()]);
+        assert_eq!(comparison.pairs, 3);
+        assert_eq!(comparison.ups, [110.0, 121.0]);
+        assert!((comparison.change_percent - 10.0).abs() < 1e-9);
+        assert!(comparison.is_significant());
+
+        assert_eq!(
+            labels(&[PathBuf::from("mods-on"), PathBuf::from("mods-off")]),
+            ["mods-on".to_string(), "mods-off".to_string()]
+        );
+        assert_eq!(
+            labels(&[PathBuf::from("a/mods"), PathBuf::from("b/mods")]),
+            ["A".to_string(), "B".to_string()]
+        );
+    }
+}
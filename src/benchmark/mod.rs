@@ -2,26 +2,53 @@
 //!
 //! Contains logic for running, parsing, and reporting Factorio benchmarks.
 
+pub mod ab_mods;
 pub mod parser;
+pub mod preflight;
 pub mod runner;
 pub mod uprof;
 
 use std::{
     collections::HashMap,
-    path::Path,
-    sync::{Arc, atomic::AtomicBool},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::SystemTime,
 };
 
 use crate::{
-    benchmark::runner::VerboseData,
+    benchmark::{parser::BenchmarkRun, runner::VerboseData},
     core::{
         FactorioExecutor, GlobalConfig, Result,
-        config::BenchmarkConfig,
-        output::{CsvWriter, WriteData, ensure_output_dir, report::ReportWriter, write_result},
-        utils,
+        anonymize::Anonymizer,
+        config::{BenchmarkConfig, SanitizeConfig},
+        derived_metrics::DerivedMetric,
+        environment::SystemSnapshot,
+        error::{BenchmarkError, BenchmarkErrorKind},
+        instances::{self, FactorioInstance},
+        journal::Journal,
+        lock::SessionLock,
+        mods::SessionModsDir,
+        output::{
+            CsvWriter, NumberFormat, ReportOptions, WriteData, chart_data, ensure_output_dir,
+            prometheus,
+            report::{self, ReportWriter},
+            write_result,
+        },
+        platform::{FrequencyLock, NumaNode, affinity},
+        result_cache::ResultCache,
+        saves::{DisplayNameRules, SavesManifest},
+        scenarios::ScenarioSaves,
+        strict, utils,
     },
+    sanitize::{parser::SanitizerFindings, runner::SanitizeRunner},
 };
 
+/// Replaces `{timestamp}` in the output path, sorting in the order sessions ran
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+
 /// Run all of the benchmarks, capture the logs and write the results to files.
 pub async fn run(
     global_config: GlobalConfig,
@@ -29,34 +56,352 @@ pub async fn run(
     running: &Arc<AtomicBool>,
 ) -> Result<()> {
     tracing::debug!("Starting benchmark with config: {:?}", benchmark_config);
+    strict::set_enabled(benchmark_config.strict);
+
+    // Find the specified save files
+    let save_files = utils::find_save_files(
+        &benchmark_config.saves_dir,
+        benchmark_config.pattern.as_deref(),
+    )?;
+    // Apply labels and tag filters from an optional saves.toml and per-save sidecars
+    let saves_manifest =
+        SavesManifest::load(&benchmark_config.saves_dir)?.with_sidecars(&save_files)?;
+    let save_files = saves_manifest.filter_by_tags(save_files, &benchmark_config.tags);
+    if save_files.is_empty() {
+        return Err(BenchmarkErrorKind::NoSaveFilesWithTags {
+            tags: benchmark_config.tags.join(", "),
+            directory: benchmark_config.saves_dir.clone(),
+        }
+        .into());
+    }
+
+    benchmark_saves(
+        global_config,
+        benchmark_config,
+        save_files,
+        saves_manifest,
+        running,
+    )
+    .await
+}
+
+/// Benchmark the given save files and write the results to files, like `run` does for the saves
+/// it discovers. Used by `belt blueprint --benchmark` for the saves it generated.
+pub async fn benchmark_saves(
+    global_config: GlobalConfig,
+    mut benchmark_config: BenchmarkConfig,
+    save_files: Vec<PathBuf>,
+    saves_manifest: SavesManifest,
+    running: &Arc<AtomicBool>,
+) -> Result<()> {
+    let session_start = SystemTime::now();
+    strict::set_enabled(benchmark_config.strict);
 
     // Find the Factorio binary
-    let factorio = FactorioExecutor::discover(global_config.factorio_path)?;
+    let factorio = FactorioExecutor::discover(global_config.factorio_path.clone())?;
     tracing::info!(
         "Using Factorio at: {}",
         factorio.executable_path().display()
     );
 
-    // Find the specified save files
-    let save_files = utils::find_save_files(
-        &benchmark_config.saves_dir,
-        benchmark_config.pattern.as_deref(),
-    )?;
     // Validate the found save files
     utils::validate_save_files(&save_files)?;
+    preflight::check_mod_sets(&save_files, benchmark_config.strict_mods)?;
+
+    if let Some(range) = benchmark_config.tick_range
+        && range.end > benchmark_config.ticks
+    {
+        return Err(BenchmarkErrorKind::TickRangeOutOfBounds {
+            range: range.to_string(),
+            ticks: benchmark_config.ticks,
+        }
+        .into());
+    }
+
+    if benchmark_config.ab_mods.is_some() {
+        // These decide a save's runs from its earlier ones, which the alternating sides split
+        for (option, set) in [
+            ("--mods-dir", benchmark_config.mods_dir.is_some()),
+            ("--time-budget", benchmark_config.time_budget.is_some()),
+            (
+                "--stop-early-margin",
+                benchmark_config.stop_early_margin.is_some(),
+            ),
+            ("--use-cache", benchmark_config.use_cache),
+        ] {
+            if set {
+                return Err(BenchmarkErrorKind::AbModsIncompatible {
+                    option: option.to_string(),
+                }
+                .into());
+            }
+        }
+    }
+
+    if benchmark_config.native_runs {
+        // These need a Factorio process per run
+        for (option, set) in [
+            ("--jobs", benchmark_config.jobs > 1),
+            ("--time-budget", benchmark_config.time_budget.is_some()),
+            (
+                "--stop-early-margin",
+                benchmark_config.stop_early_margin.is_some(),
+            ),
+            ("--markers", benchmark_config.markers),
+            ("--ab-mods", benchmark_config.ab_mods.is_some()),
+        ] {
+            if set {
+                return Err(BenchmarkErrorKind::NativeRunsIncompatible {
+                    option: option.to_string(),
+                }
+                .into());
+            }
+        }
+    }
 
-    let output_dir = benchmark_config
-        .output
-        .as_deref()
-        .unwrap_or_else(|| Path::new("."));
+    if let Some(url) = &benchmark_config.push_url {
+        prometheus::parse_push_url(url)?;
+    }
+    // A broken template would only fail once every save has been benchmarked
+    if let Some(template) = &benchmark_config.template_path {
+        report::check_template(template, &benchmark_config.extra_vars)?;
+    }
+
+    // Markers are lined up with the tick times of the verbose metrics
+    if benchmark_config.markers && benchmark_config.verbose_metrics.is_empty() {
+        benchmark_config.verbose_metrics = vec!["wholeUpdate".to_string()];
+    }
+
+    let derived_metrics = DerivedMetric::from_config(&benchmark_config.derived_metrics)?;
+    if !derived_metrics.is_empty() && benchmark_config.verbose_metrics.is_empty() {
+        tracing::warn!("Derived metrics are only exported with --verbose-metrics");
+    }
+    if benchmark_config.metric_summary && benchmark_config.verbose_metrics.is_empty() {
+        tracing::warn!("--metric-summary only summarizes metrics exported with --verbose-metrics");
+    }
+
+    // `{timestamp}` gives every session its own directory, e.g. for the retention policy. The
+    // resolved directory replaces the configured one, as the runner archives raw logs there.
+    let output_dir = benchmark_config.output.as_deref().map_or_else(
+        || PathBuf::from("."),
+        |output| {
+            let timestamp = chrono::Local::now().format(TIMESTAMP_FORMAT).to_string();
+            PathBuf::from(output.to_string_lossy().replace("{timestamp}", &timestamp))
+        },
+    );
+    benchmark_config.output = Some(output_dir.clone());
+    let output_dir = output_dir.as_path();
     ensure_output_dir(output_dir)?;
     tracing::debug!("Output directory: {}", output_dir.display());
 
+    // Keep other sessions out of the output directory and the mod settings this one changes
+    let _output_lock = SessionLock::acquire(output_dir, benchmark_config.force)?;
+    let _mods_locks = benchmark_config
+        .mods_dir
+        .iter()
+        .chain(benchmark_config.ab_mods.iter().flatten())
+        .filter(|mods_dir| mods_dir.canonicalize().ok() != output_dir.canonicalize().ok())
+        .map(|mods_dir| SessionLock::acquire(mods_dir, benchmark_config.force))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Record every spawned command and produced file for auditing
+    let mut journal = Journal::open(output_dir, benchmark_config.append)?;
+    if benchmark_config.anonymize {
+        journal = journal.anonymized(Anonymizer::detect());
+    }
+    let journal = Arc::new(journal);
+    let factorio = factorio.with_journal(journal.clone());
+
+    // Every run is labeled with the machine, so results of several machines can be merged
+    let machine = if benchmark_config.anonymize {
+        Anonymizer::detect().text(&global_config.machine_name())
+    } else {
+        global_config.machine_name()
+    };
+    tracing::info!("Machine: {machine}");
+    journal.record_machine(&machine);
+    let system = SystemSnapshot::capture();
+    tracing::info!("System: {}", system.summary());
+
+    // Held until the benchmark ends, which restores the original settings
+    let _frequency_lock = if benchmark_config.lock_frequency {
+        let lock = FrequencyLock::apply()?;
+        tracing::info!(
+            "Locked the CPU frequency for this session; the original settings are restored afterwards"
+        );
+        journal.record_frequency_lock(&lock);
+        Some(lock)
+    } else {
+        None
+    };
+
+    // Keep Factorio's cores and memory on one NUMA node
+    let factorio = match benchmark_config.numa_node {
+        Some(node) => {
+            let numa_node = NumaNode::load(node)?;
+            tracing::info!(
+                "Binding to NUMA node {node} (cores {})",
+                instances::format_core_set(&numa_node.cores)
+            );
+            factorio.with_numa_node(Arc::new(numa_node))
+        }
+        None => factorio,
+    };
+
+    // Keep Factorio off the cores the scheduler would otherwise migrate it to
+    let factorio = match &benchmark_config.cpu_affinity {
+        Some(list) => {
+            let mut cores = affinity::parse_core_list(list)?;
+            if let Some(numa_node) = factorio.numa_node() {
+                cores.retain(|core| numa_node.cores.contains(core));
+                if cores.is_empty() {
+                    return Err(BenchmarkErrorKind::InvalidCpuAffinity {
+                        input: list.clone(),
+                        reason: format!("none of its cores are on NUMA node {}", numa_node.id),
+                    }
+                    .into());
+                }
+            }
+            if !affinity::supports_core_pinning() {
+                // Factorio inherits BELT's affinity where commands can't be pinned one by one
+                affinity::pin_current_process(&cores)?;
+            }
+            tracing::info!(
+                "Pinning Factorio to cores {}",
+                instances::format_core_set(&cores)
+            );
+            factorio.with_cpu_affinity(Arc::new(cores))
+        }
+        None => factorio,
+    };
+
+    let _marker_mods_dirs = copy_mods_dirs_for_markers(&mut benchmark_config)?;
+
+    // Sync mods in a throwaway copy of the player's mods folder, never in the folder itself
+    let factorio = match (&benchmark_config.mods_dir, utils::find_mod_directory()) {
+        (None, Some(user_mods_dir)) => {
+            factorio.with_session_mods_dir(Arc::new(SessionModsDir::assemble(&user_mods_dir)?))
+        }
+        (None, None) => {
+            tracing::warn!(
+                "No Factorio mods directory found; mods will be synced in Factorio's default one"
+            );
+            factorio
+        }
+        (Some(_), _) => factorio,
+    };
+
+    // Benchmark scenario folders through saves generated from them
+    let (_scenario_saves, save_files) = ScenarioSaves::generate(
+        &factorio,
+        save_files,
+        benchmark_config.scenario_ticks,
+        benchmark_config.mods_dir.as_deref(),
+    )
+    .await?;
+
+    // Load every save once with the sanitizer mod before benchmarking
+    let sanitizer_findings = if benchmark_config.sanitize_check
+        || benchmark_config.skip_sanitize_issues
+        || benchmark_config.surface_stats
+    {
+        run_sanitize_check(&benchmark_config, &factorio, &save_files, running).await?
+    } else {
+        HashMap::new()
+    };
+    let save_files: Vec<PathBuf> = if benchmark_config.skip_sanitize_issues {
+        save_files
+            .into_iter()
+            .filter(|save_file| {
+                let blocked = sanitizer_findings
+                    .get(save_file)
+                    .is_some_and(SanitizerFindings::has_blocking_issues);
+                if blocked {
+                    tracing::warn!(
+                        "Skipping {}: the sanitize check found benchmark-affecting issues",
+                        save_file.display()
+                    );
+                }
+                !blocked
+            })
+            .collect()
+    } else {
+        save_files
+    };
+    if save_files.is_empty() {
+        tracing::warn!("No saves left to benchmark");
+        return strict::check();
+    }
+    // Everything up to here only prepared the session
+    strict::check()?;
+
     // Run the benchmarks
-    let runner = runner::BenchmarkRunner::new(benchmark_config.clone(), factorio);
-    let (mut results, all_runs_verbose_data) = runner.run_all(save_files, running).await?;
+    let name_rules = DisplayNameRules::from_config(&benchmark_config)?;
+    let runner = runner::BenchmarkRunner::new(
+        benchmark_config.clone(),
+        factorio.clone(),
+        saves_manifest,
+        name_rules.clone(),
+    )
+    .with_sanitizer_findings(sanitizer_findings)
+    .with_instances(parallel_instances(&benchmark_config, &factorio)?);
+    // With --ab-mods, each save is followed by its other side
+    let display_names = match &benchmark_config.ab_mods {
+        Some(mods_dirs) => ab_mods::labels(mods_dirs)
+            .iter()
+            .zip(mods_dirs)
+            .map(|(label, mods_dir)| {
+                runner
+                    .for_mods_variant(mods_dir, label)
+                    .unique_display_names(&save_files)
+            })
+            .collect::<Result<Vec<_>>>()?,
+        None => vec![runner.unique_display_names(&save_files)?],
+    };
+    let input_order: Vec<String> = save_files
+        .iter()
+        .flat_map(|save_file| display_names.iter().map(|names| names[save_file].clone()))
+        .collect();
+    let (mut results, all_runs_verbose_data) = if let Some(mods_dirs) = &benchmark_config.ab_mods {
+        ab_mods::run_all(
+            &runner,
+            mods_dirs,
+            benchmark_config.runs,
+            save_files,
+            running,
+        )
+        .await?
+    } else if benchmark_config.use_cache {
+        run_with_cache(&benchmark_config, &factorio, &runner, save_files, running).await?
+    } else {
+        runner.run_all(save_files, running).await?
+    };
+    for result in &mut results {
+        result.machine = Some(machine.clone());
+        result.system = Some(system.clone());
+        result.family = name_rules
+            .family(&result.save_name, result.save_metadata.as_ref())
+            .or_else(|| {
+                // Both sides of an A/B comparison form a family
+                result
+                    .mods_variant
+                    .as_ref()
+                    .map(|variant| variant.save_name.clone())
+            });
+    }
+    if benchmark_config.markers && results.iter().all(|run| run.markers.is_empty()) {
+        tracing::warn!(
+            "The belt-sanitizer mod recorded no markers; is it enabled and recent enough to support them?"
+        );
+    }
     // Calculate the percentage difference from the worst performer
     utils::calculate_base_differences(&mut results);
+    if let Some(sort_by) = benchmark_config.sort_by {
+        utils::sort_saves(&mut results, sort_by, &input_order);
+    }
+    runner::flag_light_workloads(&mut results, benchmark_config.min_workload_ms);
+    runner::log_measurement_sensitivity(&results);
+    ab_mods::log_comparisons(&results);
 
     if !benchmark_config.verbose_metrics.is_empty() && !all_runs_verbose_data.is_empty() {
         // Group verbose data by save
@@ -73,6 +418,8 @@ pub async fn run(
             let data = WriteData::Verbose {
                 data: save_verbose_data.to_vec(),
                 metrics_to_export: benchmark_config.verbose_metrics.clone(),
+                derived_metrics: derived_metrics.clone(),
+                metric_summary: benchmark_config.metric_summary,
             };
 
             write_result(&csv_writer, &data, output_dir, benchmark_config.append)?;
@@ -85,17 +432,248 @@ pub async fn run(
 
     write_result(&csv_writer, &data, output_dir, benchmark_config.append)?;
 
+    if benchmark_config.chart_data {
+        chart_data::write_chart_data(output_dir, benchmark_config.downsample)?;
+    }
+    let metrics = if benchmark_config.prometheus || benchmark_config.push_url.is_some() {
+        Some(prometheus::write_metrics(output_dir)?)
+    } else {
+        None
+    };
+
     // Write the report
     let report_writer = ReportWriter::new();
     let data = WriteData::Report {
         data: results.clone(),
         template_path: benchmark_config.template_path.as_deref(),
+        options: ReportOptions {
+            include_runs: benchmark_config.report_runs,
+            number_format: number_format(&benchmark_config),
+            anonymize: benchmark_config.anonymize,
+            ups_definition: benchmark_config.ups_definition,
+            settle_ticks: benchmark_config.settle_ticks,
+            tick_range: benchmark_config.tick_range,
+            sort_by: benchmark_config.sort_by,
+            exclude_interference: benchmark_config.exclude_interference,
+            extra_vars: benchmark_config.extra_vars.clone(),
+        },
     };
 
     write_result(&report_writer, &data, output_dir, benchmark_config.append)?;
 
+    journal.record_runs(&results);
+    journal.record_artifacts_since(output_dir, session_start);
+    if let (Some(url), Some(metrics)) = (&benchmark_config.push_url, &metrics) {
+        prometheus::push(url, metrics).await?;
+    }
+    strict::check()?;
+
     tracing::info!("Benchmark complete!");
     tracing::info!("Total benchmarks run: {}", results.len());
 
     Ok(())
 }
+
+/// One isolated executor per job for `--jobs`, each with its own cores, write-data directory and
+/// mods directory. Empty when running a single instance.
+fn parallel_instances(
+    benchmark_config: &BenchmarkConfig,
+    factorio: &FactorioExecutor,
+) -> Result<Vec<FactorioExecutor>> {
+    let jobs = benchmark_config.jobs as usize;
+    if jobs <= 1 {
+        return Ok(Vec::new());
+    }
+
+    // These decide the next run from the previous ones, which doesn't work out of order
+    for (option, set) in [
+        (
+            "--stop-early-margin",
+            benchmark_config.stop_early_margin.is_some(),
+        ),
+        ("--time-budget", benchmark_config.time_budget.is_some()),
+        // Every instance's mod would write to the same script-output directory
+        ("--markers", benchmark_config.markers),
+        // The other instances would count as background load
+        (
+            "--background-load-threshold",
+            benchmark_config.background_load_threshold.is_some(),
+        ),
+    ] {
+        if set {
+            return Err(BenchmarkErrorKind::JobsIncompatible {
+                option: option.to_string(),
+            }
+            .into());
+        }
+    }
+
+    // Stay within the pinned cores or the NUMA node when bound to one
+    let available: Vec<usize> = match (factorio.cpu_affinity(), factorio.numa_node()) {
+        (Some(cores), _) => cores.to_vec(),
+        (None, Some(numa_node)) => numa_node.cores.clone(),
+        (None, None) => (0..std::thread::available_parallelism().map_or(1, usize::from)).collect(),
+    };
+    let core_sets = instances::partition_cores(&available, jobs)?;
+    if !affinity::supports_core_pinning() {
+        tracing::warn!(
+            "Pinning to cores isn't supported on this platform; instances will share cores"
+        );
+    }
+
+    // Every instance syncs mods for its own save, so they can't share a mods directory
+    let user_mods_dir = match &benchmark_config.mods_dir {
+        Some(_) => None,
+        None => Some(utils::find_mod_directory().ok_or_else(|| {
+            BenchmarkError::from(BenchmarkErrorKind::NoModsDirectoryFound).with_hint(Some(
+                "--jobs needs --mods-dir or the player's mods directory",
+            ))
+        })?),
+    };
+
+    core_sets
+        .into_iter()
+        .map(|cores| {
+            let instance = Arc::new(FactorioInstance::create(cores)?);
+            tracing::debug!(
+                "Instance on cores {} uses {}",
+                instance.core_set(),
+                instance.config_path().display()
+            );
+            let executor = factorio.clone().with_instance(instance);
+            Ok(match &user_mods_dir {
+                Some(dir) => {
+                    executor.with_session_mods_dir(Arc::new(SessionModsDir::assemble(dir)?))
+                }
+                None => executor,
+            })
+        })
+        .collect()
+}
+
+/// With markers on, swap `--mods-dir` and `--ab-mods` for session copies, since the markers setting
+/// is written to their `mod-settings.dat`. The copies are removed when the returned ones drop.
+pub(crate) fn copy_mods_dirs_for_markers(
+    benchmark_config: &mut BenchmarkConfig,
+) -> Result<Vec<SessionModsDir>> {
+    if !benchmark_config.markers {
+        return Ok(Vec::new());
+    }
+
+    let mut session_mods_dirs = Vec::new();
+    for mods_dir in benchmark_config
+        .mods_dir
+        .iter_mut()
+        .chain(benchmark_config.ab_mods.iter_mut().flatten())
+    {
+        let session_mods_dir = SessionModsDir::assemble(mods_dir)?;
+        *mods_dir = session_mods_dir.path().to_path_buf();
+        session_mods_dirs.push(session_mods_dir);
+    }
+    Ok(session_mods_dirs)
+}
+
+/// Run the saves that have no cached results, and cache their runs for the next session
+async fn run_with_cache(
+    benchmark_config: &BenchmarkConfig,
+    factorio: &FactorioExecutor,
+    runner: &runner::BenchmarkRunner,
+    save_files: Vec<PathBuf>,
+    running: &Arc<AtomicBool>,
+) -> Result<(Vec<BenchmarkRun>, Vec<VerboseData>)> {
+    // Verbose metrics aren't cached, and a time budget makes the runs count vary
+    if !benchmark_config.verbose_metrics.is_empty() || benchmark_config.time_budget.is_some() {
+        tracing::warn!("--use-cache is ignored with --verbose-metrics and --time-budget");
+        return runner.run_all(save_files, running).await;
+    }
+
+    let cache = ResultCache::open(factorio, benchmark_config).await?;
+    let save_names = runner.unique_display_names(&save_files)?;
+
+    let mut results_map: HashMap<PathBuf, Vec<BenchmarkRun>> = HashMap::new();
+    let mut to_run = Vec::new();
+    for save_file in save_files {
+        match cache.get(&save_file, benchmark_config.runs)? {
+            Some(mut runs) => {
+                tracing::info!("Using cached results for {}", save_file.display());
+                for run in &mut runs {
+                    run.save_name = save_names[&save_file].clone();
+                }
+                results_map.insert(save_file, runs);
+            }
+            None => to_run.push(save_file),
+        }
+    }
+    if to_run.is_empty() {
+        return Ok((runner::sort_by_performance(results_map), Vec::new()));
+    }
+
+    let files_by_name: HashMap<String, PathBuf> = runner
+        .unique_display_names(&to_run)?
+        .into_iter()
+        .map(|(save_file, name)| (name, save_file))
+        .collect();
+    let (results, verbose_data) = runner.run_all(to_run, running).await?;
+    let mut new_results: HashMap<PathBuf, Vec<BenchmarkRun>> = HashMap::new();
+    for run in results {
+        if let Some(save_file) = files_by_name.get(&run.save_name) {
+            new_results.entry(save_file.clone()).or_default().push(run);
+        }
+    }
+
+    // Only saves that completed every run are cached, so an interrupted session isn't reused
+    for (save_file, runs) in new_results {
+        if runs.len() >= benchmark_config.runs as usize {
+            cache.store(&save_file, &runs)?;
+        }
+        results_map.insert(save_file, runs);
+    }
+
+    Ok((runner::sort_by_performance(results_map), verbose_data))
+}
+
+/// Run the sanitizer mod once on every save, keyed by save file
+async fn run_sanitize_check(
+    benchmark_config: &BenchmarkConfig,
+    factorio: &FactorioExecutor,
+    save_files: &[PathBuf],
+    running: &Arc<AtomicBool>,
+) -> Result<HashMap<PathBuf, SanitizerFindings>> {
+    let sanitize_config = SanitizeConfig {
+        mods_dir: benchmark_config.mods_dir.clone(),
+        headless: benchmark_config.headless,
+        surface_stats: benchmark_config.surface_stats,
+        ..Default::default()
+    };
+    let sanitizer = SanitizeRunner::new(sanitize_config, factorio.clone());
+
+    let mut findings = HashMap::new();
+    for save_file in save_files {
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+
+        tracing::info!("Sanitize check: {}", save_file.display());
+        findings.insert(save_file.clone(), sanitizer.sanitize_save(save_file).await?);
+    }
+
+    Ok(findings)
+}
+
+/// Report number formatting from the config, falling back to the defaults
+fn number_format(benchmark_config: &BenchmarkConfig) -> NumberFormat {
+    let defaults = NumberFormat::default();
+    NumberFormat {
+        ms_decimals: benchmark_config.ms_decimals.unwrap_or(defaults.ms_decimals),
+        ups_decimals: benchmark_config
+            .ups_decimals
+            .unwrap_or(defaults.ups_decimals),
+        percent_decimals: benchmark_config
+            .percent_decimals
+            .unwrap_or(defaults.percent_decimals),
+        thousands_separator: benchmark_config.thousands_separator,
+        decimal_separator: benchmark_config
+            .decimal_separator
+            .unwrap_or(defaults.decimal_separator),
+    }
+}
@@ -2,15 +2,20 @@
 
 use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::path::Path;
 use std::sync::LazyLock;
 
+use crate::benchmark::ab_mods::ModsVariant;
 use crate::benchmark::runner::CpuFrequencyData;
 use crate::benchmark::uprof::{AmdUprofReportArtifact, AmdUprofRun};
-use crate::core::config::BenchmarkConfig;
+use crate::core::environment::SystemSnapshot;
 use crate::core::error::BenchmarkError;
 use crate::core::error::BenchmarkErrorKind;
-use crate::core::{Result, get_os_info};
+use crate::core::metrics::describe_metric;
+use crate::core::saves::SaveMetadata;
+use crate::core::{Result, TickRange, get_os_info, utils};
+use crate::sanitize::{markers::Marker, parser::SanitizerFindings};
 
 /// The result of a benchmark of a single run
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -18,6 +23,9 @@ pub struct BenchmarkRun {
     pub index: u32,
     pub save_name: String,
     pub factorio_version: String,
+    /// Build number from Factorio's banner line, which tells apart experimental builds of the
+    /// same version
+    pub factorio_build: Option<u32>,
     pub platform: String,
     pub execution_time_ms: f64,
     pub ticks: u32,
@@ -29,6 +37,77 @@ pub struct BenchmarkRun {
     pub mimalloc_stats: Option<MimallocStats>,
     pub amd_uprof: Option<AmdUprofRun>,
     pub cpu_data: Vec<CpuFrequencyData>,
+    pub save_metadata: Option<SaveMetadata>,
+    pub sanitizer: Option<SanitizerFindings>,
+    /// CPU cores the run was pinned to with `--jobs` or `--cpu-affinity`, e.g. `0-3`
+    pub core_set: Option<String>,
+    /// NUMA node the run was bound to with `--numa-node`
+    pub numa_node: Option<u32>,
+    /// Execution time from Factorio's "Performed ... updates" line, kept when the execution time
+    /// was measured from verbose timestamps instead
+    pub performed_execution_time_ms: Option<f64>,
+    /// Average CPU usage of other processes during the run, in percent of all cores
+    pub background_load_percent: Option<f64>,
+    /// Energy the CPU packages used during the run, from RAPL counters
+    pub energy_joules: Option<f64>,
+    /// Average power of the CPU packages during the run
+    pub avg_watts: Option<f64>,
+    /// Wall-clock start of the run (RFC 3339)
+    pub started_at: Option<String>,
+    /// Highest CPU temperature at the start of the run, in °C
+    pub cpu_temp_c: Option<f64>,
+    /// Mean core frequency at the start of the run, in MHz
+    pub cpu_freq_mhz: Option<u64>,
+    /// Name of the machine the run was measured on
+    pub machine: Option<String>,
+    /// Hardware and system of that machine at the start of the session
+    #[serde(default)]
+    pub system: Option<SystemSnapshot>,
+    /// Family of variants the save belongs to, from the saves manifest or `--group-regex`
+    #[serde(default)]
+    pub family: Option<String>,
+    /// Events the belt-sanitizer mod recorded during the run, with `--markers`
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+    /// Tick times of consecutive fixed-size parts of the run, with `--segment-ticks`
+    #[serde(default)]
+    pub segments: Vec<Segment>,
+    /// Time chart refreshes and CRC computations took during the run, when verbose data has them
+    #[serde(default)]
+    pub interference: Option<Interference>,
+    /// Mods directory the run used in an `--ab-mods` comparison
+    #[serde(default)]
+    pub mods_variant: Option<ModsVariant>,
+    /// Whether the save's mean tick time was below `--min-workload-ms`, when checked
+    #[serde(default)]
+    pub light_workload: Option<bool>,
+}
+
+/// Tick times of `ticks` consecutive ticks of a run, starting at measured tick `start_tick`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub start_tick: u32,
+    pub ticks: u32,
+    pub avg_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Verbose metrics of the periodic engine tasks that can drown out the rest of a tick
+pub const INTERFERENCE_METRICS: [&str; 2] = ["chartRefresh", "crcComputation"];
+
+/// Share of a tick's `wholeUpdate` from which the periodic engine tasks dominate it
+const DOMINATED_TICK_SHARE: f64 = 0.5;
+
+/// Time the periodic engine tasks of [`INTERFERENCE_METRICS`] took during a run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Interference {
+    /// Time of the tasks over every measured tick, in milliseconds
+    pub total_ms: f64,
+    /// Ticks the tasks took at least half of
+    pub dominated_ticks: u32,
+    /// `wholeUpdate` of the dominated ticks, in milliseconds
+    pub dominated_ms: f64,
 }
 
 // Build perfomance line regexs
@@ -44,36 +123,43 @@ static MS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     ).expect("Regex building failed")
 });
 
+static NEWER_MAP_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"Map version (?P<save>[0-9.\-]+) cannot be loaded because it is higher than the game version \((?P<game>[0-9.\-]+)\)",
+    )
+    .expect("Regex building failed")
+});
+
+static BUILD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(build (?P<build>[0-9]+)").expect("Regex building failed"));
+
 static MIMALLOC_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(
         r"heap\sstats:\s*peak\s*total\s*current\s*block\s*total#\s*reserved:\s*(?P<reserved_peak>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<reserved_total>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<reserved_current>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*committed:\s*(?P<committed_peak>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<committed_total>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<committed_current>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*reset:\s*(?:\d+)\s*purged:\s*(?:\d+)\s*touched:\s*(?P<touched_peak>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<touched_total>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<touched_current>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<touched_status>(?:[[:alpha:]]+[[:blank:]]?)*)\s*pages:\s*(?P<pages_peak>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<pages_total>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<pages_current>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<pages_status>(?:[[:alpha:]]+[[:blank:]]?)*)\s*-abandoned:\s*(?P<abandoned_peak>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<abandoned_total>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<abandoned_current>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<abandoned_status>(?:[[:alpha:]]+[[:blank:]]?)*).*\n.*\n.*\n.*\n.*\n.*\n.*\n.*\n.*\n\s*mmaps:\s*(?P<mmaps>\d+)\s*commits:\s*(?P<commits>\d+)\s*resets:\s*(?P<resets>\d+)\s*purges:\s*(?P<purges>\d+).*\n.*\s*threads:\s*(?P<threads_peak>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<threads_total>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<threads_current>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?)\s*(?P<threads_status>(?:[[:alpha:]]+[[:blank:]]?)*)\n.*\n.*\n.*\n.*peak rss:\s(?P<rss_peak>(?:\d+)(?:\.\d+\s[[:alpha:]]{2,3})?).*"
     ).expect("Regex building failed")
 });
 
-/// Parsing of the given Factorio output
-pub fn parse_benchmark_log(
-    log: &str,
-    save_file: &Path,
-    benchmark_config: &BenchmarkConfig,
-) -> Result<BenchmarkRun> {
+/// Parsing of the given Factorio output. The save name is the file stem; the runner replaces it
+/// with the display name.
+pub fn parse_benchmark_log(log: &str, save_file: &Path) -> Result<BenchmarkRun> {
+    if let Some(kind) = save_version_too_new(log) {
+        return Err(kind.into());
+    }
+
     // Get save name from file
     let save_name = save_file.file_stem().unwrap().to_string_lossy().to_string();
 
-    let save_name = match benchmark_config.strip_prefix.as_deref() {
-        Some(prefix) => save_name
-            .strip_prefix(prefix)
-            .unwrap_or(&save_name)
-            .to_string(),
-        None => save_name,
-    };
-
-    // Get the Factorio version from the line containing "Factorio" and "(build"
-    let version = log
+    // Get the Factorio version and build from the line containing "Factorio" and "(build"
+    let banner = log
         .lines()
-        .find(|line| line.contains("Factorio") && line.contains("(build"))
+        .find(|line| line.contains("Factorio") && line.contains("(build"));
+    let version = banner
         .and_then(|line| line.split_whitespace().nth(4))
         .unwrap_or("unknown")
         .to_string();
+    let build = banner
+        .and_then(|line| BUILD_REGEX.captures(line))
+        .and_then(|captures| captures["build"].parse().ok());
 
     // Collect all lines of the log
     let iterator = log.lines().peekable();
@@ -82,6 +168,7 @@ pub fn parse_benchmark_log(
     let mut run = BenchmarkRun {
         save_name,
         factorio_version: version,
+        factorio_build: build,
         platform: get_os_info(),
         ..Default::default()
     };
@@ -146,6 +233,69 @@ pub fn parse_benchmark_log(
     Ok(run)
 }
 
+/// The output of a Factorio invocation with `--benchmark-runs` split into one log per run: the
+/// lines before the first "Performed ... updates" line, followed by those of the run. Lines after
+/// the last run, e.g. mimalloc's heap stats at exit, stay with it. A log of a single run is
+/// returned as is.
+pub fn split_benchmark_runs(log: &str) -> Vec<String> {
+    let lines: Vec<&str> = log.lines().collect();
+    let starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| PERFORMED_REGEX.is_match(line))
+        .map(|(position, _)| position)
+        .collect();
+    if starts.len() <= 1 {
+        return vec![log.to_string()];
+    }
+
+    let preamble = &lines[..starts[0]];
+    starts
+        .iter()
+        .enumerate()
+        .map(|(run, &start)| {
+            let end = starts.get(run + 1).copied().unwrap_or(lines.len());
+            preamble
+                .iter()
+                .chain(&lines[start..end])
+                .copied()
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect()
+}
+
+/// Factorio's verbose CSV of an invocation with `--benchmark-runs` split into one CSV per run,
+/// each with the header. A run starts at a repeated header or where the ticks start over at `t0`.
+pub fn split_verbose_runs(csv_data: &str) -> Vec<String> {
+    let mut lines = csv_data.lines();
+    let header = lines.next().unwrap_or_default();
+
+    let mut runs: Vec<Vec<&str>> = vec![vec![header]];
+    for line in lines {
+        let starts_run = line == header || line.starts_with("t0,");
+        if starts_run && runs.last().is_some_and(|run| run.len() > 1) {
+            runs.push(vec![header]);
+        }
+        if line != header
+            && let Some(run) = runs.last_mut()
+        {
+            run.push(line);
+        }
+    }
+    runs.into_iter().map(|run| run.join("\n")).collect()
+}
+
+/// The error for a save written by a newer Factorio than the one that tried to load it, if `log`
+/// reports one
+pub fn save_version_too_new(log: &str) -> Option<BenchmarkErrorKind> {
+    let captures = NEWER_MAP_REGEX.captures(log)?;
+    Some(BenchmarkErrorKind::SaveVersionTooNew {
+        save_version: captures["save"].to_string(),
+        game_version: captures["game"].to_string(),
+    })
+}
+
 fn parse_amd_uprof_breadcrumbs(log: &str) -> Option<AmdUprofRun> {
     const SESSION_PREFIX: &str = "Generated data files path:";
     const REPORT_PREFIX: &str = "Generated report file:";
@@ -174,6 +324,7 @@ pub fn max_whole_update_ms_excluding_first_tick(csv_data: &str) -> Result<Option
     let Some(whole_update_index) = headers.iter().position(|header| header == "wholeUpdate") else {
         return Ok(None);
     };
+    let whole_update_unit = describe_metric("wholeUpdate").unit;
 
     reader
         .records()
@@ -184,7 +335,7 @@ pub fn max_whole_update_ms_excluding_first_tick(csv_data: &str) -> Result<Option
                 return Ok(max_update);
             };
 
-            let update_time = raw_update.parse::<f64>()? / 1_000_000.0;
+            let update_time = whole_update_unit.to_display(raw_update.parse::<f64>()?);
 
             Ok(Some(
                 max_update.map_or(update_time, |max: f64| max.max(update_time)),
@@ -192,6 +343,218 @@ pub fn max_whole_update_ms_excluding_first_tick(csv_data: &str) -> Result<Option
         })
 }
 
+/// The ticks of a run that count: those after the settle ticks, optionally only a range of them
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TickWindow {
+    pub settle_ticks: u32,
+    pub range: Option<TickRange>,
+}
+
+impl TickWindow {
+    /// Whether every tick Factorio ran counts
+    pub fn is_whole_run(&self) -> bool {
+        self.settle_ticks == 0 && self.range.is_none()
+    }
+
+    /// Factorio's verbose CSV with only the ticks of the window
+    pub fn apply<'a>(&self, csv_data: &'a str) -> Cow<'a, str> {
+        if self.is_whole_run() {
+            return Cow::Borrowed(csv_data);
+        }
+
+        let skip = self.settle_ticks + self.range.map_or(0, |range| range.start);
+        let take = self.range.map_or(usize::MAX, |range| range.len() as usize);
+        let mut lines = csv_data.lines();
+        let header = lines.next().unwrap_or_default();
+        Cow::Owned(
+            std::iter::once(header)
+                .chain(lines.skip(skip as usize).take(take))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+}
+
+/// Mean and minimum `wholeUpdate` in milliseconds, `None` without the column or any ticks
+pub fn whole_update_mean_min_ms(csv_data: &str) -> Result<Option<(f64, f64)>> {
+    let mut reader = csv::Reader::from_reader(csv_data.as_bytes());
+    let headers = reader.headers()?;
+    let Some(whole_update_index) = headers.iter().position(|header| header == "wholeUpdate") else {
+        return Ok(None);
+    };
+    let whole_update_unit = describe_metric("wholeUpdate").unit;
+
+    let mut update_times = Vec::new();
+    for record in reader.records() {
+        if let Some(raw_update) = record?.get(whole_update_index) {
+            update_times.push(whole_update_unit.to_display(raw_update.parse::<f64>()?));
+        }
+    }
+
+    let min = update_times.iter().copied().reduce(f64::min);
+    Ok(utils::mean(&update_times).zip(min))
+}
+
+/// Split the `wholeUpdate` column into segments of `segment_ticks` ticks; the last one holds the
+/// remainder. Empty without the column or any ticks.
+pub fn segments(csv_data: &str, segment_ticks: u32) -> Result<Vec<Segment>> {
+    let mut reader = csv::Reader::from_reader(csv_data.as_bytes());
+    let headers = reader.headers()?;
+    let Some(whole_update_index) = headers.iter().position(|header| header == "wholeUpdate") else {
+        return Ok(Vec::new());
+    };
+    let whole_update_unit = describe_metric("wholeUpdate").unit;
+
+    let mut update_times = Vec::new();
+    for record in reader.records() {
+        if let Some(raw_update) = record?.get(whole_update_index) {
+            update_times.push(whole_update_unit.to_display(raw_update.parse::<f64>()?));
+        }
+    }
+
+    Ok(update_times
+        .chunks(segment_ticks.max(1) as usize)
+        .enumerate()
+        .map(|(position, times)| Segment {
+            start_tick: position as u32 * segment_ticks,
+            ticks: times.len() as u32,
+            avg_ms: utils::mean(times).unwrap_or_default(),
+            min_ms: times.iter().copied().fold(f64::INFINITY, f64::min),
+            max_ms: times.iter().copied().fold(0.0, f64::max),
+        })
+        .collect())
+}
+
+/// Time of the periodic engine tasks and `wholeUpdate` of every tick, in milliseconds. `None`
+/// without `wholeUpdate` or any of the tasks' columns.
+fn interference_per_tick(csv_data: &str) -> Result<Option<Vec<(f64, f64)>>> {
+    let mut reader = csv::Reader::from_reader(csv_data.as_bytes());
+    let headers = reader.headers()?;
+    let Some(whole_update_index) = headers.iter().position(|header| header == "wholeUpdate") else {
+        return Ok(None);
+    };
+    let task_indices: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .filter(|(_, header)| INTERFERENCE_METRICS.contains(header))
+        .map(|(index, _)| index)
+        .collect();
+    if task_indices.is_empty() {
+        return Ok(None);
+    }
+    let unit = describe_metric("wholeUpdate").unit;
+
+    let mut ticks = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let value = |index: usize| -> Result<f64> {
+            match record.get(index) {
+                Some(raw) => Ok(unit.to_display(raw.parse::<f64>()?)),
+                None => Ok(0.0),
+            }
+        };
+        let mut task_ms = 0.0;
+        for &index in &task_indices {
+            task_ms += value(index)?;
+        }
+        ticks.push((task_ms, value(whole_update_index)?));
+    }
+    Ok(Some(ticks))
+}
+
+fn is_dominated((task_ms, whole_update_ms): (f64, f64)) -> bool {
+    whole_update_ms > 0.0 && task_ms >= whole_update_ms * DOMINATED_TICK_SHARE
+}
+
+/// The time chart refreshes and CRC computations took in Factorio's verbose CSV, `None` without
+/// their columns
+pub fn interference(csv_data: &str) -> Result<Option<Interference>> {
+    Ok(interference_per_tick(csv_data)?.map(|ticks| {
+        let mut interference = Interference::default();
+        for tick in ticks {
+            interference.total_ms += tick.0;
+            if is_dominated(tick) {
+                interference.dominated_ticks += 1;
+                interference.dominated_ms += tick.1;
+            }
+        }
+        interference
+    }))
+}
+
+/// Factorio's verbose CSV without the ticks the periodic engine tasks dominate
+pub fn without_dominated_ticks(csv_data: &str) -> Result<String> {
+    let Some(ticks) = interference_per_tick(csv_data)? else {
+        return Ok(csv_data.to_string());
+    };
+    let mut lines = csv_data.lines();
+    let header = lines.next().unwrap_or_default();
+    Ok(std::iter::once(header)
+        .chain(
+            lines
+                .zip(ticks)
+                .filter(|(_, tick)| !is_dominated(*tick))
+                .map(|(line, _)| line),
+        )
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Execution time measured from the verbose per-tick timestamps, from the start of the first
+/// tick to the end of the last one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimestampTiming {
+    pub ticks: u32,
+    pub execution_time_ms: f64,
+}
+
+/// Measure the run from the verbose `timestamp` column (nanoseconds at the start of each tick)
+/// plus the `wholeUpdate` of the last tick. `None` without both columns or any ticks.
+pub fn timing_from_timestamps(csv_data: &str) -> Result<Option<TimestampTiming>> {
+    let mut reader = csv::Reader::from_reader(csv_data.as_bytes());
+    let headers = reader.headers()?;
+    let (Some(timestamp_index), Some(whole_update_index)) = (
+        headers.iter().position(|header| header == "timestamp"),
+        headers.iter().position(|header| header == "wholeUpdate"),
+    ) else {
+        return Ok(None);
+    };
+    let whole_update_unit = describe_metric("wholeUpdate").unit;
+
+    let mut ticks = 0;
+    let mut first_start = None;
+    let mut last_end = 0.0;
+    for record in reader.records() {
+        let record = record?;
+        let (Some(timestamp), Some(whole_update)) =
+            (record.get(timestamp_index), record.get(whole_update_index))
+        else {
+            continue;
+        };
+
+        let start = timestamp.parse::<f64>()? / 1_000_000.0;
+        ticks += 1;
+        first_start.get_or_insert(start);
+        last_end = start + whole_update_unit.to_display(whole_update.parse::<f64>()?);
+    }
+
+    Ok(first_start.map(|first_start| TimestampTiming {
+        ticks,
+        execution_time_ms: last_end - first_start,
+    }))
+}
+
+/// The metric columns of Factorio's verbose CSV output, without `tick` and `timestamp`
+pub fn verbose_metric_names(csv_data: &str) -> Result<Vec<String>> {
+    let mut reader = csv::Reader::from_reader(csv_data.as_bytes());
+    Ok(reader
+        .headers()?
+        .iter()
+        .filter(|header| *header != "tick" && *header != "timestamp")
+        .map(str::to_string)
+        .collect())
+}
+
 fn get_capture<T>(captures: &Captures, key: &str) -> Result<T>
 where
     T: std::str::FromStr,
@@ -265,6 +628,17 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_verbose_metric_names() {
+        let csv_data = "tick,timestamp,wholeUpdate,gameUpdate,circuitNetworkUpdate\nt0,0,1,2,3\n";
+        let available = verbose_metric_names(csv_data).unwrap();
+
+        assert_eq!(
+            available,
+            vec!["wholeUpdate", "gameUpdate", "circuitNetworkUpdate"]
+        );
+    }
+
     #[test]
     fn test_calculate_base_differences_simple() {
         let mut results = vec![
@@ -303,16 +677,12 @@ mod tests {
 
         let save_path = Path::new("test_save.zip");
 
-        let config = BenchmarkConfig {
-            ticks: 1000,
-            ..Default::default()
-        };
-
-        let result = parse_benchmark_log(FACTORIO_OUTPUT, save_path, &config).unwrap();
+        let result = parse_benchmark_log(FACTORIO_OUTPUT, save_path).unwrap();
 
         // Check misc info
         assert_eq!(result.save_name, "test_save");
         assert_eq!(result.factorio_version, "2.0.55");
+        assert_eq!(result.factorio_build, Some(83138));
 
         // Check actual benchmark info
         assert_eq!(result.execution_time_ms, 2138.223);
@@ -325,6 +695,40 @@ mod tests {
         assert!(difference < 0.001, "Effective UPS calculation is incorrect");
     }
 
+    #[test]
+    fn test_split_benchmark_and_verbose_runs() {
+        const FACTORIO_OUTPUT: &str = r#"0.000 2025-07-09 17:16:57; Factorio 2.0.55 (build 83138, linux64, full, space-age)
+   Performed 100 updates in 200.000 ms
+   avg: 2.000 ms, min: 1.000 ms, max: 5.000 ms
+   Performed 100 updates in 250.000 ms
+   avg: 2.500 ms, min: 1.500 ms, max: 6.000 ms
+   7.737 Goodbye"#;
+
+        let runs: Vec<BenchmarkRun> = split_benchmark_runs(FACTORIO_OUTPUT)
+            .iter()
+            .map(|log| parse_benchmark_log(log, Path::new("save.zip")).unwrap())
+            .collect();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].avg_ms, 2.0);
+        assert_eq!(runs[1].avg_ms, 2.5);
+        assert_eq!(runs[1].execution_time_ms, 250.0);
+        assert_eq!(runs[1].factorio_build, Some(83138));
+        assert_eq!(split_benchmark_runs("Performed 1 updates in 1 ms").len(), 1);
+
+        let header = "tick,timestamp,wholeUpdate";
+        let restarted = format!("{header}\nt0,0,1\nt1,1,2\nt0,5,3\nt1,6,4");
+        let repeated = format!("{header}\nt0,0,1\nt1,1,2\n{header}\nt0,5,3\nt1,6,4");
+        for csv_data in [restarted, repeated] {
+            assert_eq!(
+                split_verbose_runs(&csv_data),
+                vec![
+                    format!("{header}\nt0,0,1\nt1,1,2"),
+                    format!("{header}\nt0,5,3\nt1,6,4")
+                ]
+            );
+        }
+    }
+
     #[test]
     fn test_parse_benchmark_log_extracts_amd_uprof_breadcrumbs() {
         const FACTORIO_OUTPUT: &str = r#"Performed 10 updates in 100.000 ms
@@ -333,12 +737,8 @@ Profiling (data collection) completed
 Generated data files path: /tmp/belt-amduprof-run/session
 Generated report file: /tmp/belt-amduprof-run/session/report.csv"#;
 
-        let result = parse_benchmark_log(
-            FACTORIO_OUTPUT,
-            Path::new("test_save.zip"),
-            &BenchmarkConfig::default(),
-        )
-        .expect("parse benchmark");
+        let result = parse_benchmark_log(FACTORIO_OUTPUT, Path::new("test_save.zip"))
+            .expect("parse benchmark");
 
         let uprof = result.amd_uprof.expect("uProf breadcrumbs");
         assert_eq!(
@@ -351,6 +751,138 @@ Generated report file: /tmp/belt-amduprof-run/session/report.csv"#;
         );
     }
 
+    #[test]
+    fn test_timing_from_timestamps_spans_first_to_last_tick() {
+        let csv = "tick,timestamp,wholeUpdate\n\
+                   t0,1000000000,4000000\n\
+                   t1,1005000000,4000000\n\
+                   t2,1010000000,6000000\n";
+
+        let timing = timing_from_timestamps(csv).unwrap().unwrap();
+
+        assert_eq!(timing.ticks, 3);
+        assert_eq!(timing.execution_time_ms, 16.0);
+        assert_eq!(
+            timing_from_timestamps("tick,wholeUpdate\nt0,1\n").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tick_window_measures_only_settled_ticks_in_range() {
+        let csv = "tick,timestamp,wholeUpdate\n\
+                   t0,1000000000,40000000\n\
+                   t1,1040000000,4000000\n\
+                   t2,1045000000,2000000\n";
+
+        let settled = TickWindow {
+            settle_ticks: 1,
+            range: None,
+        }
+        .apply(csv);
+
+        assert_eq!(
+            settled,
+            "tick,timestamp,wholeUpdate\nt1,1040000000,4000000\nt2,1045000000,2000000"
+        );
+        assert_eq!(timing_from_timestamps(&settled).unwrap().unwrap().ticks, 2);
+        assert_eq!(
+            whole_update_mean_min_ms(&settled).unwrap(),
+            Some((3.0, 2.0))
+        );
+        assert!(matches!(TickWindow::default().apply(csv), Cow::Borrowed(_)));
+
+        let window = TickWindow {
+            settle_ticks: 1,
+            range: Some("1..2".parse().unwrap()),
+        };
+        assert_eq!(
+            window.apply(csv),
+            "tick,timestamp,wholeUpdate\nt2,1045000000,2000000"
+        );
+        assert!("5..5".parse::<TickRange>().is_err());
+        assert!("5-10".parse::<TickRange>().is_err());
+    }
+
+    #[test]
+    fn test_save_newer_than_factorio_is_a_specific_error() {
+        let log = "   0.512 Loading map /saves/base.zip: 1234 bytes.\n\
+                   0.520 Error ServerMultiplayerManager.cpp:94: Map version 2.0.60-0 cannot be loaded because it is higher than the game version (2.0.55-0).\n";
+
+        let error = parse_benchmark_log(log, Path::new("base.zip")).expect_err("newer save");
+        assert!(matches!(
+            error.kind(),
+            BenchmarkErrorKind::SaveVersionTooNew { save_version, game_version }
+                if save_version == "2.0.60-0" && game_version == "2.0.55-0"
+        ));
+        assert!(save_version_too_new("Performed 60 updates in 500 ms").is_none());
+    }
+
+    #[test]
+    fn test_interference_counts_and_drops_dominated_ticks() {
+        let csv = "tick,wholeUpdate,chartRefresh,crcComputation\n\
+                   t0,1000000,0,0\n\
+                   t1,8000000,6000000,0\n\
+                   t2,2000000,500000,0\n\
+                   t3,4000000,1000000,1500000\n";
+
+        let tasks = interference(csv).unwrap().expect("interference");
+        assert_eq!(tasks.total_ms, 9.0);
+        assert_eq!(tasks.dominated_ticks, 2);
+        assert_eq!(tasks.dominated_ms, 12.0);
+
+        let kept = without_dominated_ticks(csv).unwrap();
+        assert_eq!(whole_update_mean_min_ms(&kept).unwrap(), Some((1.5, 1.0)));
+        assert_eq!(
+            interference("tick,wholeUpdate\nt0,1000000\n").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_segments_split_ticks_with_remainder_last() {
+        let csv = "tick,wholeUpdate\n\
+                   t0,1000000\n\
+                   t1,3000000\n\
+                   t2,5000000\n\
+                   t3,7000000\n\
+                   t4,9000000\n";
+
+        let segments = segments(csv, 2).unwrap();
+
+        assert_eq!(
+            segments,
+            vec![
+                Segment {
+                    start_tick: 0,
+                    ticks: 2,
+                    avg_ms: 2.0,
+                    min_ms: 1.0,
+                    max_ms: 3.0,
+                },
+                Segment {
+                    start_tick: 2,
+                    ticks: 2,
+                    avg_ms: 6.0,
+                    min_ms: 5.0,
+                    max_ms: 7.0,
+                },
+                Segment {
+                    start_tick: 4,
+                    ticks: 1,
+                    avg_ms: 9.0,
+                    min_ms: 9.0,
+                    max_ms: 9.0,
+                },
+            ]
+        );
+        assert!(
+            super::segments("tick,gameUpdate\nt0,1\n", 2)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[test]
     fn test_max_whole_update_ms_excluding_first_tick_ignores_first_row() {
         let csv = "tick,timestamp,wholeUpdate,gameUpdate\n\
@@ -0,0 +1,106 @@
+//! Checks of the selected saves before the first run, so a session doesn't spend hours on
+//! comparisons that can't mean anything.
+
+use std::{collections::BTreeSet, path::PathBuf};
+
+use crate::core::{
+    Result, error::BenchmarkErrorKind, save_header, save_header::SaveMod, utils::is_scenario_dir,
+};
+
+/// Compare the mods embedded in every save with those of the first one, warning about each save
+/// saved with different mods or versions, or failing with `strict_mods`. Scenarios and saves
+/// whose header can't be read are skipped.
+pub fn check_mod_sets(save_files: &[PathBuf], strict_mods: bool) -> Result<()> {
+    let mut reference: Option<(String, BTreeSet<String>)> = None;
+    for save_file in save_files.iter().filter(|path| !is_scenario_dir(path)) {
+        let name = save_file
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mods = match save_header::read(save_file) {
+            Ok(header) => mod_set(&header.mods),
+            Err(err) => {
+                tracing::debug!("Not comparing the mods of {name}: {err}");
+                continue;
+            }
+        };
+
+        let Some((reference_name, reference_mods)) = &reference else {
+            reference = Some((name, mods));
+            continue;
+        };
+        let Some(difference) = describe_difference(reference_mods, &mods) else {
+            continue;
+        };
+
+        if strict_mods {
+            return Err(BenchmarkErrorKind::ModSetMismatch {
+                save: name,
+                reference: reference_name.clone(),
+                difference,
+            }
+            .into());
+        }
+        tracing::warn!(
+            "{name} was saved with other mods than {reference_name} ({difference}); their results aren't comparable"
+        );
+    }
+    Ok(())
+}
+
+fn mod_set(mods: &[SaveMod]) -> BTreeSet<String> {
+    mods.iter()
+        .map(|save_mod| format!("{} {}", save_mod.name, save_mod.version))
+        .collect()
+}
+
+/// The mods only one of the sets has, or `None` when they're the same
+fn describe_difference(reference: &BTreeSet<String>, mods: &BTreeSet<String>) -> Option<String> {
+    let list = |mods: Vec<&String>| {
+        mods.into_iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let extra: Vec<_> = mods.difference(reference).collect();
+    let missing: Vec<_> = reference.difference(mods).collect();
+
+    let mut parts = Vec::new();
+    if !extra.is_empty() {
+        parts.push(format!("adds {}", list(extra)));
+    }
+    if !missing.is_empty() {
+        parts.push(format!("lacks {}", list(missing)));
+    }
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_difference_lists_added_and_missing_mods() {
+        let save_mod = |name: &str, version: &str| SaveMod {
+            name: name.to_string(),
+            version: version.to_string(),
+            crc: 0,
+        };
+        let vanilla = mod_set(&[save_mod("base", "2.0.55")]);
+        let modded = mod_set(&[
+            save_mod("base", "2.0.55"),
+            save_mod("bobinserters", "2.0.1"),
+        ]);
+        let updated = mod_set(&[save_mod("base", "2.0.60")]);
+
+        assert_eq!(describe_difference(&vanilla, &vanilla), None);
+        assert_eq!(
+            describe_difference(&vanilla, &modded).as_deref(),
+            Some("adds bobinserters 2.0.1")
+        );
+        assert_eq!(
+            describe_difference(&vanilla, &updated).as_deref(),
+            Some("adds base 2.0.60; lacks base 2.0.55")
+        );
+    }
+}
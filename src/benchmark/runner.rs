@@ -1,22 +1,41 @@
 //! Running and collecting logs of benchmarks on save file(s)
 
+use chrono::Local;
 use indicatif::{ProgressBar, ProgressStyle};
 use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::time::Duration;
+use tempfile::TempPath;
+use tokio::task::JoinSet;
 use tokio::time::Instant;
 
 use super::BenchmarkConfig;
-use crate::benchmark::parser::{self, BenchmarkRun};
+use crate::benchmark::parser::{self, BenchmarkRun, TickWindow};
 use crate::core::Result;
-use crate::core::error::BenchmarkErrorKind;
+use crate::core::derived_metrics::DerivedMetric;
+use crate::core::environment::EnvironmentSnapshot;
+use crate::core::error::{BenchmarkError, BenchmarkErrorKind};
 use crate::core::factorio::FactorioTickRunSpec;
-use crate::core::format_duration;
-use crate::core::{FactorioExecutor, RunOrder};
+use crate::core::instances::{self, FactorioInstance};
+use crate::core::output::raw_log;
+use crate::core::platform::EnergyCounters;
+use crate::core::saves::{DisplayNameRules, SavesManifest};
+use crate::core::settings::{ModSettings, ModSettingsScopeName, ModSettingsValue};
+use crate::core::{CachePolicy, FactorioExecutor, RunOrder, UpsDefinition, platform};
+use crate::core::{
+    confidence_interval_95, format_duration, mean, minimum_detectable_effect, runs_to_detect,
+    sample_std_dev, suggest_similar,
+};
+use crate::core::{strict, utils};
+use crate::sanitize::markers::{self, Marker};
+use crate::sanitize::parser::SanitizerFindings;
 
 /// A job, indicating a single benchmark run, to be used in queues of a specific order
 #[derive(Debug, Clone)]
@@ -25,10 +44,38 @@ struct ExecutionJob {
     run_index: u32,
 }
 
+/// Factorio's verbose CSV of one run, spooled to a temporary file so the runs of a session don't
+/// all stay in memory until they're exported. The file is removed with the last clone.
 #[derive(Debug, Clone)]
 pub struct VerboseData {
     pub save_name: String,
-    pub csv_data: String,
+    file: Arc<TempPath>,
+}
+
+impl VerboseData {
+    pub fn spool(save_name: &str, csv_data: &str) -> Result<Self> {
+        let mut file = tempfile::Builder::new()
+            .prefix("belt-verbose-")
+            .suffix(".csv")
+            .tempfile()?;
+        file.write_all(csv_data.as_bytes())?;
+        Ok(Self {
+            save_name: save_name.to_string(),
+            file: Arc::new(file.into_temp_path()),
+        })
+    }
+
+    /// A reader over the spooled CSV, starting at its header
+    pub fn reader(&self) -> Result<csv::Reader<File>> {
+        Ok(csv::Reader::from_path(self.file.as_ref())?)
+    }
+
+    /// The metric columns of the CSV, without `tick` and `timestamp`
+    fn metric_names(&self) -> Result<Vec<String>> {
+        let mut header = String::new();
+        BufReader::new(File::open(self.file.as_ref())?).read_line(&mut header)?;
+        parser::verbose_metric_names(&header)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,21 +85,85 @@ pub struct CpuFrequencyData {
     pub core_index: usize,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct EnergyUse {
+    pub joules: f64,
+    pub watts: f64,
+}
+
 pub struct FactorioOutput {
     pub summary: String,
     pub verbose_data: Option<String>,
     pub cpu_data: Vec<CpuFrequencyData>,
+    /// Average CPU usage of other processes during the run, in percent of all cores
+    pub background_load: Option<f64>,
+    /// Energy the CPU packages used during the run, when RAPL counters are readable
+    pub energy: Option<EnergyUse>,
+    /// Conditions at the start of the run
+    pub environment: Option<EnvironmentSnapshot>,
+    /// Complete stderr and stdout, before the verbose section is split off
+    pub raw_log: String,
 }
 
+#[derive(Clone)]
 pub struct BenchmarkRunner {
     config: BenchmarkConfig,
     factorio: FactorioExecutor,
+    saves: SavesManifest,
+    names: DisplayNameRules,
+    sanitizer_findings: HashMap<PathBuf, SanitizerFindings>,
+    /// One executor per isolated instance when running with `--jobs`
+    instances: Vec<FactorioExecutor>,
+    /// Run index each save's runs start at, after the runs archived by earlier sessions
+    first_run_indices: Arc<Mutex<HashMap<String, u32>>>,
+    /// Appended to every save name, e.g. ` [mods-off]` for a side of an `--ab-mods` comparison
+    name_suffix: Option<String>,
 }
 
 /// Runs the benchmarks, keeps a progress bar updated and returns results.
 impl BenchmarkRunner {
-    pub fn new(config: BenchmarkConfig, factorio: FactorioExecutor) -> Self {
-        Self { config, factorio }
+    pub fn new(
+        config: BenchmarkConfig,
+        factorio: FactorioExecutor,
+        saves: SavesManifest,
+        names: DisplayNameRules,
+    ) -> Self {
+        Self {
+            config,
+            factorio,
+            saves,
+            names,
+            sanitizer_findings: HashMap::new(),
+            instances: Vec::new(),
+            first_run_indices: Arc::default(),
+            name_suffix: None,
+        }
+    }
+
+    /// Attach sanitize check findings to the results of each save
+    pub fn with_sanitizer_findings(
+        mut self,
+        sanitizer_findings: HashMap<PathBuf, SanitizerFindings>,
+    ) -> Self {
+        self.sanitizer_findings = sanitizer_findings;
+        self
+    }
+
+    /// A runner for one side of an `--ab-mods` comparison: a single run of every save with the
+    /// mods in `mods_dir`, under save names ending in ` [label]`
+    pub fn for_mods_variant(&self, mods_dir: &Path, label: &str) -> Self {
+        let mut runner = self.clone();
+        runner.config.mods_dir = Some(mods_dir.to_path_buf());
+        runner.config.runs = 1;
+        runner.first_run_indices = Arc::default();
+        runner.name_suffix = Some(format!(" [{label}]"));
+        runner
+    }
+
+    /// Distribute the runs over these isolated instances instead of running them one by one
+    pub fn with_instances(mut self, instances: Vec<FactorioExecutor>) -> Self {
+        self.instances = instances;
+        self
     }
 
     /// Run benchmarks for all save files
@@ -61,13 +172,44 @@ impl BenchmarkRunner {
         save_files: Vec<PathBuf>,
         running: &Arc<AtomicBool>,
     ) -> Result<(Vec<BenchmarkRun>, Vec<VerboseData>)> {
-        let execution_schedule = self.create_execution_schedule(&save_files);
-        let total_jobs = execution_schedule.len();
+        if self.instances.len() > 1 {
+            return self.run_parallel(save_files, running).await;
+        }
+
+        // With a time budget, every save is run once first to calibrate its runs count; with
+        // native runs, Factorio makes all of a save's runs in its one job
+        let initial_runs = if self.config.time_budget.is_some() || self.config.native_runs {
+            0..1
+        } else {
+            0..self.config.runs
+        };
+        let initial_plan: Vec<(PathBuf, Range<u32>)> = save_files
+            .iter()
+            .map(|save_file| (save_file.clone(), initial_runs.clone()))
+            .collect();
+        let mut execution_schedule = self.create_execution_schedule(&initial_plan);
+        let mut calibration_durations: HashMap<PathBuf, Duration> = HashMap::new();
+        let mut calibrated = self.config.time_budget.is_none();
+
+        let save_names = self.unique_display_names(&save_files)?;
+
         let start_time = Instant::now();
         let mut all_verbose_data: Vec<VerboseData> = Vec::new();
         let mut results_map: HashMap<String, Vec<BenchmarkRun>> = HashMap::new();
 
-        let progress = ProgressBar::new(total_jobs as u64);
+        // Saves whose outcome against the baseline is already decided
+        let mut concluded: HashSet<String> = HashSet::new();
+        // Saves whose hooks failed, and saves whose pre-save hook already ran
+        let mut failed: HashSet<String> = HashSet::new();
+        let mut prepared: HashSet<PathBuf> = HashSet::new();
+        let baseline_name = match self.config.stop_early_margin {
+            Some(_) => save_files
+                .first()
+                .and_then(|save_file| save_names.get(save_file).cloned()),
+            None => None,
+        };
+
+        let progress = ProgressBar::new(execution_schedule.len() as u64);
         progress.set_style(
             ProgressStyle::with_template(
                 "[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}",
@@ -77,35 +219,59 @@ impl BenchmarkRunner {
         progress.enable_steady_tick(Duration::from_millis(100));
 
         // Execute jobs according to schedule
-        for (job_index, job) in execution_schedule.iter().enumerate() {
+        let mut next_job_index = 0;
+        loop {
+            // Once every save ran once, turn the time budget into a runs count per save. Checked
+            // before taking the next job, so saves skipped at the end of the schedule don't
+            // prevent it.
+            if let Some(budget) = self.config.time_budget
+                && !calibrated
+                && next_job_index == execution_schedule.len()
+            {
+                calibrated = true;
+
+                let plan: Vec<(PathBuf, Range<u32>)> = save_files
+                    .iter()
+                    .filter_map(|save_file| {
+                        let duration = calibration_durations.get(save_file)?;
+                        let runs = runs_within_budget(budget, *duration);
+                        tracing::info!(
+                            "{} takes ~{}, running it {runs} time(s) within the {} budget",
+                            save_file.display(),
+                            format_duration(*duration),
+                            format_duration(budget)
+                        );
+                        Some((save_file.clone(), 1..runs))
+                    })
+                    .collect();
+
+                execution_schedule.extend(self.create_execution_schedule(&plan));
+                progress.set_length(execution_schedule.len() as u64);
+            }
+            if next_job_index >= execution_schedule.len() {
+                break;
+            }
+
+            let job_index = next_job_index;
+            let job = execution_schedule[job_index].clone();
+            next_job_index += 1;
+
             if !running.load(Ordering::SeqCst) {
                 tracing::info!("Shutdown requested. Aborting remaining benchmarks.");
                 break;
             }
 
-            let save_name = job
-                .save_file
-                .file_stem()
-                .ok_or_else(|| BenchmarkErrorKind::InvalidSaveFileName {
-                    path: job.save_file.clone(),
-                })?
-                .to_string_lossy()
-                .to_string();
-
-            let save_name = match self.config.strip_prefix.as_deref() {
-                Some(prefix) => save_name
-                    .strip_prefix(prefix)
-                    .unwrap_or(&save_name)
-                    .to_string(),
-                None => save_name,
-            };
+            let save_name = save_names[&job.save_file].clone();
+            if concluded.contains(&save_name) || failed.contains(&save_name) {
+                continue;
+            }
 
             progress.set_position(job_index as u64);
 
             let eta_message = if job_index > 0 {
                 let elapsed = start_time.elapsed();
                 let avg_time_per_job = elapsed / job_index as u32;
-                let remaining_jobs = total_jobs - job_index;
+                let remaining_jobs = execution_schedule.len() - job_index;
                 let estimated_remaining = avg_time_per_job * remaining_jobs as u32;
 
                 format!(
@@ -120,22 +286,74 @@ impl BenchmarkRunner {
 
             progress.set_message(eta_message);
 
+            if let Some(hook) = self.config.pre_save_hook.as_deref()
+                && prepared.insert(job.save_file.clone())
+                && let Err(error) = self.run_hook("pre-save", hook, &job).await
+            {
+                tracing::error!("Skipping {save_name}: {error}");
+                failed.insert(save_name);
+                continue;
+            }
+
             // Run a single benchmark and get the run data and version
-            let (result_for_run, verbose_data) = match self.run_single_benchmark(job).await {
-                Ok(result) => result,
+            let run_start = Instant::now();
+            let job_results = match self.run_single_benchmark(&job, &save_name).await {
+                Ok(job_results) => job_results,
+                Err(error) if self.skips(&error) => {
+                    tracing::error!("Skipping {save_name}: {error}");
+                    failed.insert(save_name);
+                    continue;
+                }
                 Err(error) => {
                     progress.abandon();
                     return Err(error);
                 }
             };
 
-            results_map
-                .entry(result_for_run.save_name.clone())
-                .or_default()
-                .push(result_for_run);
+            if !calibrated {
+                calibration_durations.insert(job.save_file.clone(), run_start.elapsed());
+            }
+
+            if let Some(hook) = self.config.post_run_hook.as_deref()
+                && let Err(error) = self.run_hook("post-run", hook, &job).await
+            {
+                tracing::error!("Skipping remaining runs of {save_name}: {error}");
+                failed.insert(save_name.clone());
+            }
+
+            for (result_for_run, verbose_data) in job_results {
+                results_map
+                    .entry(result_for_run.save_name.clone())
+                    .or_default()
+                    .push(result_for_run);
+
+                if let Some(data) = verbose_data {
+                    if all_verbose_data.is_empty() {
+                        self.warn_unknown_verbose_metrics(&data);
+                    }
+                    all_verbose_data.push(data);
+                }
+            }
+            if let Err(error) = strict::check() {
+                progress.abandon();
+                return Err(error);
+            }
 
-            if let Some(data) = verbose_data {
-                all_verbose_data.push(data);
+            if let (Some(margin), Some(baseline)) =
+                (self.config.stop_early_margin, baseline_name.as_deref())
+                && let Some(baseline_runs) = results_map.get(baseline)
+            {
+                for (name, runs) in &results_map {
+                    if name != baseline
+                        && !concluded.contains(name)
+                        && is_conclusive(runs, baseline_runs, margin)
+                    {
+                        tracing::info!(
+                            "{name} differs from {baseline} by more than {margin}%, skipping its remaining runs"
+                        );
+                        concluded.insert(name.clone());
+                    }
+                }
             }
         }
 
@@ -145,29 +363,234 @@ impl BenchmarkRunner {
             progress.finish_with_message("Benchmarking complete!");
         }
 
-        let mut groups: Vec<(String, Vec<BenchmarkRun>)> = results_map.into_iter().collect();
+        Ok((sort_by_performance(results_map), all_verbose_data))
+    }
+
+    /// Run the schedule on all instances at once; each instance takes the next job when it's
+    /// done. Pre-save hooks run for every save up front.
+    async fn run_parallel(
+        &self,
+        save_files: Vec<PathBuf>,
+        running: &Arc<AtomicBool>,
+    ) -> Result<(Vec<BenchmarkRun>, Vec<VerboseData>)> {
+        let save_names = Arc::new(self.unique_display_names(&save_files)?);
+        let plan: Vec<(PathBuf, Range<u32>)> = save_files
+            .iter()
+            .map(|save_file| (save_file.clone(), 0..self.config.runs))
+            .collect();
+        let schedule = self.create_execution_schedule(&plan);
+
+        // Saves whose hooks failed
+        let failed: Arc<Mutex<HashSet<String>>> = Arc::new(Mutex::new(HashSet::new()));
+        if let Some(hook) = self.config.pre_save_hook.as_deref() {
+            for save_file in &save_files {
+                let job = ExecutionJob {
+                    save_file: save_file.clone(),
+                    run_index: 0,
+                };
+                if let Err(error) = self.run_hook("pre-save", hook, &job).await {
+                    let save_name = save_names[save_file].clone();
+                    tracing::error!("Skipping {save_name}: {error}");
+                    lock(&failed).insert(save_name);
+                }
+            }
+        }
+
+        let progress = ProgressBar::new(schedule.len() as u64);
+        progress.set_style(
+            ProgressStyle::with_template(
+                "[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}",
+            )?
+            .progress_chars("=="),
+        );
+        progress.enable_steady_tick(Duration::from_millis(100));
+        progress.set_message(format!("{} instances", self.instances.len()));
+
+        let queue = Arc::new(Mutex::new(VecDeque::from(schedule)));
+        // Set when an instance fails, so the others stop taking jobs
+        let aborted = Arc::new(AtomicBool::new(false));
 
-        // Sort by performance
-        groups.sort_by(|(_, runs_a), (_, runs_b)| {
-            avg_effective_ups(runs_a)
-                .partial_cmp(&avg_effective_ups(runs_b))
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        let mut workers = JoinSet::new();
+        for factorio in &self.instances {
+            let runner = BenchmarkRunner {
+                factorio: factorio.clone(),
+                instances: Vec::new(),
+                ..self.clone()
+            };
+            let core_set = factorio.instance().map(FactorioInstance::core_set);
+            let (save_names, failed, queue, aborted, progress, running) = (
+                save_names.clone(),
+                failed.clone(),
+                queue.clone(),
+                aborted.clone(),
+                progress.clone(),
+                running.clone(),
+            );
+
+            workers.spawn(async move {
+                let mut results = Vec::new();
+                let mut verbose_data = Vec::new();
 
-        let all_results = groups.into_iter().flat_map(|(_, runs)| runs).collect();
+                while running.load(Ordering::SeqCst) && !aborted.load(Ordering::SeqCst) {
+                    let Some(job) = lock(&queue).pop_front() else {
+                        break;
+                    };
+                    let save_name = save_names[&job.save_file].clone();
+                    if lock(&failed).contains(&save_name) {
+                        progress.inc(1);
+                        continue;
+                    }
 
-        Ok((all_results, all_verbose_data))
+                    let job_results = match runner.run_single_benchmark(&job, &save_name).await {
+                        Ok(job_results) => job_results,
+                        Err(error) if runner.skips(&error) => {
+                            tracing::error!("Skipping {save_name}: {error}");
+                            lock(&failed).insert(save_name);
+                            progress.inc(1);
+                            continue;
+                        }
+                        Err(error) => {
+                            aborted.store(true, Ordering::SeqCst);
+                            return Err(error);
+                        }
+                    };
+
+                    if let Some(hook) = runner.config.post_run_hook.as_deref()
+                        && let Err(error) = runner.run_hook("post-run", hook, &job).await
+                    {
+                        tracing::error!("Skipping remaining runs of {save_name}: {error}");
+                        lock(&failed).insert(save_name);
+                    }
+
+                    progress.inc(1);
+                    for (mut result, data) in job_results {
+                        result.core_set = core_set.clone();
+                        results.push(result);
+                        verbose_data.extend(data);
+                    }
+                    if let Err(error) = strict::check() {
+                        aborted.store(true, Ordering::SeqCst);
+                        return Err(error);
+                    }
+                }
+
+                Ok((results, verbose_data))
+            });
+        }
+
+        let mut results_map: HashMap<String, Vec<BenchmarkRun>> = HashMap::new();
+        let mut all_verbose_data: Vec<VerboseData> = Vec::new();
+        let mut first_error = None;
+        while let Some(joined) = workers.join_next().await {
+            match joined.map_err(std::io::Error::other)? {
+                Ok((results, verbose_data)) => {
+                    for result in results {
+                        results_map
+                            .entry(result.save_name.clone())
+                            .or_default()
+                            .push(result);
+                    }
+                    all_verbose_data.extend(verbose_data);
+                }
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+
+        if let Some(error) = first_error {
+            progress.abandon();
+            return Err(error);
+        }
+        if !running.load(Ordering::SeqCst) {
+            progress.finish_with_message("Benchmarking interrupted.");
+        } else {
+            progress.finish_with_message("Benchmarking complete!");
+        }
+
+        if let Some(data) = all_verbose_data.first() {
+            self.warn_unknown_verbose_metrics(data);
+        }
+        for runs in results_map.values_mut() {
+            runs.sort_by_key(|run| run.index);
+        }
+
+        Ok((sort_by_performance(results_map), all_verbose_data))
+    }
+
+    /// Whether a failed run only skips its save instead of ending the session: saves newer than
+    /// the Factorio binary can't be benchmarked, but the other saves still can
+    fn skips(&self, error: &BenchmarkError) -> bool {
+        matches!(error.kind(), BenchmarkErrorKind::SaveVersionTooNew { .. })
+            && !self.config.abort_on_newer_saves
+    }
+
+    /// The verbose metrics to export, plus those of the periodic engine tasks to exclude
+    fn requested_verbose_metrics(&self) -> Vec<String> {
+        let mut metrics = self.config.verbose_metrics.clone();
+        if self.config.exclude_interference {
+            metrics.extend(parser::INTERFERENCE_METRICS.map(String::from));
+        }
+        metrics
+    }
+
+    /// The ticks of each run that count towards the results
+    fn tick_window(&self) -> TickWindow {
+        TickWindow {
+            settle_ticks: self.config.settle_ticks,
+            range: self.config.tick_range,
+        }
+    }
+
+    /// The save name as it appears in results: the manifest label if set, otherwise the
+    /// file stem passed through the display name rules
+    fn display_name(&self, save_file: &Path) -> Result<String> {
+        if let Some(label) = self
+            .saves
+            .get(save_file)
+            .and_then(|metadata| metadata.label.clone())
+        {
+            return Ok(label);
+        }
+
+        let stem = save_file
+            .file_stem()
+            .ok_or_else(|| BenchmarkErrorKind::InvalidSaveFileName {
+                path: save_file.to_path_buf(),
+            })?
+            .to_string_lossy();
+
+        Ok(self.names.apply(&stem))
+    }
+
+    /// Display names of all save files, disambiguated where two saves would share a name
+    pub(crate) fn unique_display_names(
+        &self,
+        save_files: &[PathBuf],
+    ) -> Result<HashMap<PathBuf, String>> {
+        let names = save_files
+            .iter()
+            .map(|save_file| Ok((save_file.clone(), self.display_name(save_file)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(disambiguate_save_names(names)
+            .into_iter()
+            .map(|(save_file, name)| match &self.name_suffix {
+                Some(suffix) => (save_file, format!("{name}{suffix}")),
+                None => (save_file, name),
+            })
+            .collect())
     }
 
     /// Create the execution schedule based on the RunOrder
-    fn create_execution_schedule(&self, save_files: &[PathBuf]) -> Vec<ExecutionJob> {
+    fn create_execution_schedule(&self, plan: &[(PathBuf, Range<u32>)]) -> Vec<ExecutionJob> {
         let mut schedule = Vec::new();
 
         match self.config.run_order {
             RunOrder::Grouped => {
                 // Current behavior: A,A,A,B,B,B
-                for save_file in save_files {
-                    for run_index in 0..self.config.runs {
+                for (save_file, runs) in plan {
+                    for run_index in runs.clone() {
                         schedule.push(ExecutionJob {
                             save_file: save_file.clone(),
                             run_index,
@@ -177,18 +600,22 @@ impl BenchmarkRunner {
             }
             RunOrder::Sequential => {
                 // Alternating: A,B,A,B,A,B
-                for run_index in 0..self.config.runs {
-                    for save_file in save_files {
-                        schedule.push(ExecutionJob {
-                            save_file: save_file.clone(),
-                            run_index,
-                        });
+                let first = plan.iter().map(|(_, runs)| runs.start).min().unwrap_or(0);
+                let last = plan.iter().map(|(_, runs)| runs.end).max().unwrap_or(0);
+                for run_index in first..last {
+                    for (save_file, runs) in plan {
+                        if runs.contains(&run_index) {
+                            schedule.push(ExecutionJob {
+                                save_file: save_file.clone(),
+                                run_index,
+                            });
+                        }
                     }
                 }
             }
             RunOrder::Random => {
-                for save_file in save_files {
-                    for run_index in 0..self.config.runs {
+                for (save_file, runs) in plan {
+                    for run_index in runs.clone() {
                         schedule.push(ExecutionJob {
                             save_file: save_file.clone(),
                             run_index,
@@ -210,64 +637,737 @@ impl BenchmarkRunner {
         schedule
     }
 
-    /// Returns the benchmark run and the parsed Factorio version string
+    /// Run Factorio on the job's save and return its run, or with `--native-runs` all the runs
+    /// of the save Factorio made in one go
     async fn run_single_benchmark(
         &self,
         job: &ExecutionJob,
-    ) -> Result<(BenchmarkRun, Option<VerboseData>)> {
+        save_name: &str,
+    ) -> Result<Vec<(BenchmarkRun, Option<VerboseData>)>> {
         // If mods_file is not set, sync mods with the given save file
         if self.config.mods_dir.is_none() {
             self.factorio.sync_mods_for_save(&job.save_file).await?;
         }
 
-        let factorio_output = self
-            .execute_single_factorio_benchmark(&job.save_file)
+        self.apply_cache_policy(&job.save_file);
+
+        if self.config.markers {
+            self.enable_markers()?;
+        }
+
+        let mut factorio_output = self
+            .execute_checking_background_load(job, save_name)
             .await?;
 
-        let mut result =
-            parser::parse_benchmark_log(&factorio_output.summary, &job.save_file, &self.config)?;
+        let runs = parse_runs(
+            &factorio_output.summary,
+            factorio_output.verbose_data.take(),
+            &job.save_file,
+            save_name,
+            job.run_index,
+            self.config.ups_definition,
+            self.tick_window(),
+        )?;
+        let run_count = runs.len() as f64;
 
-        if let Some(csv_data) = factorio_output.verbose_data.as_deref()
-            && let Some(max_ms) = parser::max_whole_update_ms_excluding_first_tick(csv_data)?
-        {
-            result.max_ms = max_ms;
+        let mut results = Vec::new();
+        for (mut result, verbose_data) in runs {
+            if let Some(segment_ticks) = self.config.segment_ticks {
+                match verbose_data.as_deref() {
+                    Some(csv_data) => {
+                        result.segments =
+                            parser::segments(&self.tick_window().apply(csv_data), segment_ticks)?;
+                    }
+                    None => tracing::warn!(
+                        "{save_name} run {}: no per-tick timings, so the run isn't split into segments",
+                        result.index + 1
+                    ),
+                }
+            }
+
+            if let Some(csv_data) = verbose_data.as_deref() {
+                let csv_data = self.tick_window().apply(csv_data);
+                result.interference = parser::interference(&csv_data)?;
+                if self.config.exclude_interference {
+                    exclude_interference(&mut result, &csv_data, self.config.ups_definition)?;
+                }
+            }
+
+            let verbose_data_for_return = if !self.config.verbose_metrics.is_empty() {
+                verbose_data
+                    .map(|csv_data| {
+                        VerboseData::spool(save_name, &self.tick_window().apply(&csv_data))
+                    })
+                    .transpose()?
+            } else {
+                None
+            };
+
+            result.save_metadata = self.saves.get(&job.save_file).cloned();
+            result.sanitizer = self.sanitizer_findings.get(&job.save_file).cloned();
+            result.numa_node = self.factorio.numa_node().map(|node| node.id);
+            result.core_set = self.factorio.cpu_affinity().map(instances::format_core_set);
+            // Measured over the whole invocation, so the CPU frequencies go with its first run
+            // and the energy is split between its runs
+            result.cpu_data = std::mem::take(&mut factorio_output.cpu_data);
+            result.background_load_percent = factorio_output.background_load;
+            result.energy_joules = factorio_output
+                .energy
+                .map(|energy| energy.joules / run_count);
+            result.avg_watts = factorio_output.energy.map(|energy| energy.watts);
+            if let Some(environment) = &factorio_output.environment {
+                result.started_at = Some(environment.started_at.to_rfc3339());
+                result.cpu_temp_c = environment.cpu_temp_c;
+                result.cpu_freq_mhz = environment.cpu_freq_mhz;
+            }
+            if self.config.markers {
+                result.markers = self.take_markers(save_name, job.run_index);
+            }
+
+            results.push((result, verbose_data_for_return));
+        }
+        Ok(results)
+    }
+
+    /// Warn once about requested verbose metrics that Factorio doesn't report, with suggestions
+    fn warn_unknown_verbose_metrics(&self, data: &VerboseData) {
+        let available = match data.metric_names() {
+            Ok(available) => available,
+            Err(err) => {
+                tracing::debug!("Could not read verbose metric header: {err}");
+                return;
+            }
+        };
+
+        let unknown: Vec<&String> = self
+            .config
+            .verbose_metrics
+            .iter()
+            .filter(|metric| *metric != "all" && !metric.contains(['*', '?', '[']))
+            .filter(|metric| !available.contains(metric))
+            .collect();
+        if unknown.is_empty() {
+            return;
         }
 
-        let verbose_data_for_return = if !self.config.verbose_metrics.is_empty() {
-            factorio_output.verbose_data.map(|csv_data| VerboseData {
-                save_name: job
-                    .save_file
-                    .file_stem()
-                    .unwrap()
-                    .to_string_lossy()
-                    .to_string(),
-                csv_data,
+        for metric in unknown {
+            match suggest_similar(metric, &available) {
+                Some(suggestion) => tracing::warn!(
+                    "Unknown verbose metric '{metric}', did you mean '{suggestion}'? It will be exported as N/A"
+                ),
+                None => {
+                    tracing::warn!("Unknown verbose metric '{metric}', it will be exported as N/A")
+                }
+            }
+        }
+        tracing::warn!("Available verbose metrics: {}", available.join(", "));
+    }
+
+    /// Run a user hook through the shell with `{save}` and `{run}` substituted
+    async fn run_hook(&self, hook: &str, template: &str, job: &ExecutionJob) -> Result<()> {
+        let command = template
+            .replace("{save}", &job.save_file.to_string_lossy())
+            .replace("{run}", &(job.run_index + 1).to_string());
+        tracing::debug!("Running {hook} hook: {command}");
+
+        let started_at = Local::now();
+        let mut cmd = platform::shell_command(&command);
+        let output = cmd.output().await?;
+        self.factorio
+            .journal()
+            .record_command(&cmd, started_at, output.status.code());
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let hint = stderr.lines().rev().find(|line| !line.trim().is_empty());
+
+            return Err(BenchmarkError::from(BenchmarkErrorKind::HookFailed {
+                hook: hook.to_string(),
+                command,
+                code: output.status.code().unwrap_or(-1),
             })
+            .with_hint(hint.map(str::trim)));
+        }
+
+        Ok(())
+    }
+
+    /// Turn on the belt-sanitizer mod's event markers and drop markers an interrupted run left
+    /// behind. The setting only goes into session mods directories: with markers on, a given
+    /// mods directory is a copy too (see `copy_mods_dirs_for_markers`).
+    fn enable_markers(&self) -> Result<()> {
+        let mods_dir = self
+            .config
+            .mods_dir
+            .as_deref()
+            .or_else(|| self.factorio.session_mods_dir());
+        if let Some(mods_dir) = mods_dir {
+            let dat_file = mods_dir.join("mod-settings.dat");
+            let mut settings = ModSettings::load_from_file(&dat_file)?;
+            settings.set(
+                ModSettingsScopeName::Startup,
+                markers::MARKERS_SETTING,
+                Some(ModSettingsValue::Bool(true)),
+            );
+            settings.save_to_file(&dat_file)?;
+        }
+
+        if let Some(data_dir) = utils::check_sanitizer() {
+            markers::take_markers(&data_dir)?;
+        }
+        Ok(())
+    }
+
+    /// The markers the mod recorded during the last run; a run without markers is still a result
+    fn take_markers(&self, save_name: &str, run_index: u32) -> Vec<Marker> {
+        let Some(data_dir) = utils::check_sanitizer() else {
+            return Vec::new();
+        };
+
+        markers::take_markers(&data_dir).unwrap_or_else(|err| {
+            tracing::warn!(
+                "Failed to read the markers of {save_name} run {}: {err}",
+                run_index + 1
+            );
+            Vec::new()
+        })
+    }
+
+    /// Bring the save file's page-cache state in line with the configured policy
+    fn apply_cache_policy(&self, save_file: &Path) {
+        let result = match self.config.cache_policy {
+            CachePolicy::Warm => platform::prime_file_cache(save_file),
+            CachePolicy::Cold => platform::drop_file_cache(save_file),
+            CachePolicy::Asis => return,
+        };
+
+        if let Err(err) = result {
+            tracing::warn!(
+                "Failed to apply {:?} cache policy to {}: {err}",
+                self.config.cache_policy,
+                save_file.display()
+            );
+        }
+    }
+
+    /// Execute a run, retrying it up to the configured number of times while other processes
+    /// used more CPU than the background load threshold
+    async fn execute_checking_background_load(
+        &self,
+        job: &ExecutionJob,
+        save_name: &str,
+    ) -> Result<FactorioOutput> {
+        let mut retries = 0;
+        loop {
+            let output = self
+                .execute_single_factorio_benchmark(&job.save_file)
+                .await?;
+
+            let retry = match (
+                self.config.background_load_threshold,
+                output.background_load,
+            ) {
+                (Some(threshold), Some(load)) if load > threshold => {
+                    if retries < self.config.background_load_retries {
+                        tracing::warn!(
+                            "{save_name} run {}: background load was {load:.1}% (threshold {threshold}%), retrying ({}/{})",
+                            job.run_index + 1,
+                            retries + 1,
+                            self.config.background_load_retries
+                        );
+                        true
+                    } else {
+                        tracing::warn!(
+                            "{save_name} run {}: background load was {load:.1}% (threshold {threshold}%), results may be skewed",
+                            job.run_index + 1
+                        );
+                        false
+                    }
+                }
+                _ => false,
+            };
+
+            self.record_attempt(job, save_name, retries, &output, !retry);
+            if !retry {
+                return Ok(output);
+            }
+            retries += 1;
+        }
+    }
+
+    /// Archive the output of an attempt at a run with `--save-raw-logs` and record it in the
+    /// journal. Runs are numbered after those archived by earlier sessions in the output
+    /// directory, so appended and resumed sessions never reuse their names.
+    fn record_attempt(
+        &self,
+        job: &ExecutionJob,
+        save_name: &str,
+        attempt: u32,
+        output: &FactorioOutput,
+        kept: bool,
+    ) {
+        let output_dir = self.config.output.as_deref().unwrap_or(Path::new("."));
+        let first_run_index = *self
+            .first_run_indices
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(save_name.to_string())
+            .or_insert_with(|| raw_log::next_run_index(output_dir, save_name));
+        let run = first_run_index + job.run_index;
+
+        let raw_log = if self.config.save_raw_logs {
+            match raw_log::write_raw_log(output_dir, save_name, run, attempt, &output.raw_log) {
+                Ok(raw_log) => Some(raw_log),
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to save the raw log of {save_name} run {}: {err}",
+                        job.run_index + 1
+                    );
+                    None
+                }
+            }
         } else {
             None
         };
 
-        result.index = job.run_index;
-        result.cpu_data = factorio_output.cpu_data;
-
-        Ok((result, verbose_data_for_return))
+        self.factorio.journal().record_run_attempt(
+            save_name,
+            run,
+            raw_log.as_ref().map_or(attempt, |raw_log| raw_log.attempt),
+            kept,
+            raw_log.as_ref().map(|raw_log| {
+                raw_log
+                    .path
+                    .strip_prefix(output_dir)
+                    .unwrap_or(&raw_log.path)
+            }),
+        );
     }
 
-    /// Execute a single factorio benchmark run
+    /// Execute a single factorio benchmark run, measuring the energy the CPU packages used
+    /// unless other instances run on the same packages
     async fn execute_single_factorio_benchmark(&self, save_file: &Path) -> Result<FactorioOutput> {
-        self.factorio
+        let energy_before = self
+            .factorio
+            .instance()
+            .is_none()
+            .then(EnergyCounters::read)
+            .flatten();
+        let environment = EnvironmentSnapshot::capture();
+        let started = Instant::now();
+
+        let mut output = self
+            .factorio
             .run_for_ticks(FactorioTickRunSpec {
                 save_file,
-                ticks: self.config.ticks,
+                ticks: self.config.ticks + self.config.settle_ticks,
+                runs: if self.config.native_runs {
+                    self.config.runs
+                } else {
+                    1
+                },
                 mods_dir: self.config.mods_dir.as_deref(),
-                verbose_all_metrics: !self.config.verbose_metrics.is_empty(),
+                // Settling, tick ranges and segments need the per-tick timings
+                verbose_metrics: benchmark_verbose_metrics(
+                    &self.requested_verbose_metrics(),
+                    &DerivedMetric::from_config(&self.config.derived_metrics)?,
+                    !self.tick_window().is_whole_run() || self.config.segment_ticks.is_some(),
+                ),
                 headless: self.config.headless,
                 record_cpu: self.config.record_cpu,
+                sample_background_load: self.config.background_load_threshold.is_some(),
             })
-            .await
+            .await?;
+
+        if let Some(before) = energy_before
+            && let Some(after) = EnergyCounters::read()
+        {
+            let joules = after.joules_since(&before);
+            let seconds = started.elapsed().as_secs_f64();
+            output.energy = Some(EnergyUse {
+                joules,
+                watts: if seconds > 0.0 { joules / seconds } else { 0.0 },
+            });
+        }
+
+        output.environment = Some(environment);
+
+        Ok(output)
+    }
+}
+
+/// Parse a run's summary, refining the max tick time and execution time with its verbose data,
+/// and compute its effective UPS under the given definition. Only the ticks in `window` count,
+/// which needs verbose data unless the window is the whole run.
+pub(crate) fn parse_run(
+    summary: &str,
+    verbose_data: Option<&str>,
+    save_file: &Path,
+    save_name: &str,
+    run_index: u32,
+    ups_definition: UpsDefinition,
+    window: TickWindow,
+) -> Result<BenchmarkRun> {
+    let mut result = parser::parse_benchmark_log(summary, save_file)?;
+    result.save_name = save_name.to_string();
+    result.index = run_index;
+
+    let verbose_data = verbose_data.map(|csv_data| window.apply(csv_data));
+    if !window.is_whole_run() {
+        match verbose_data
+            .as_deref()
+            .map(parser::whole_update_mean_min_ms)
+        {
+            Some(Ok(Some((avg_ms, min_ms)))) => {
+                result.avg_ms = avg_ms;
+                result.min_ms = min_ms;
+            }
+            Some(Err(err)) => return Err(err),
+            _ => tracing::warn!(
+                "{save_name} run {}: no per-tick timings, so the results include every tick Factorio ran",
+                run_index + 1
+            ),
+        }
+    }
+
+    if let Some(csv_data) = verbose_data.as_deref() {
+        if let Some(max_ms) = parser::max_whole_update_ms_excluding_first_tick(csv_data)? {
+            result.max_ms = max_ms;
+        }
+        if let Some(timing) = parser::timing_from_timestamps(csv_data)? {
+            apply_timestamp_timing(&mut result, timing, save_name);
+        }
+    }
+    result.effective_ups = ups_definition.effective_ups(&result);
+
+    Ok(result)
+}
+
+/// Parse the runs of one Factorio invocation: one run, or with `--benchmark-runs` one per
+/// "Performed ... updates" block, numbered from `first_run_index`. Each comes with its part of
+/// the verbose data; see [`parse_run`].
+pub(crate) fn parse_runs(
+    summary: &str,
+    verbose_data: Option<String>,
+    save_file: &Path,
+    save_name: &str,
+    first_run_index: u32,
+    ups_definition: UpsDefinition,
+    window: TickWindow,
+) -> Result<Vec<(BenchmarkRun, Option<String>)>> {
+    let summaries = parser::split_benchmark_runs(summary);
+    let verbose_runs = match verbose_data {
+        Some(csv_data) if summaries.len() > 1 => {
+            let verbose_runs = parser::split_verbose_runs(&csv_data);
+            if verbose_runs.len() == summaries.len() {
+                verbose_runs.into_iter().map(Some).collect()
+            } else {
+                tracing::warn!(
+                    "{save_name}: Factorio reported {} runs but per-tick timings of {}, so the timings aren't used",
+                    summaries.len(),
+                    verbose_runs.len()
+                );
+                vec![None; summaries.len()]
+            }
+        }
+        verbose_data => vec![verbose_data],
+    };
+
+    summaries
+        .iter()
+        .zip(verbose_runs)
+        .zip(first_run_index..)
+        .map(|((summary, verbose_data), run_index)| {
+            let result = parse_run(
+                summary,
+                verbose_data.as_deref(),
+                save_file,
+                save_name,
+                run_index,
+                ups_definition,
+                window,
+            )?;
+            Ok((result, verbose_data))
+        })
+        .collect()
+}
+
+/// Drop the ticks dominated by chart refreshes and CRC computations from the run's tick times and
+/// execution time, e.g. to compare saves whose map charting differs
+fn exclude_interference(
+    result: &mut BenchmarkRun,
+    csv_data: &str,
+    ups_definition: UpsDefinition,
+) -> Result<()> {
+    let Some(interference) = result.interference else {
+        tracing::warn!(
+            "{} run {}: no chartRefresh or crcComputation timings, so no ticks are excluded",
+            result.save_name,
+            result.index + 1
+        );
+        return Ok(());
+    };
+    if interference.dominated_ticks == 0 {
+        return Ok(());
+    }
+
+    let kept = parser::without_dominated_ticks(csv_data)?;
+    if let Some((avg_ms, min_ms)) = parser::whole_update_mean_min_ms(&kept)? {
+        result.avg_ms = avg_ms;
+        result.min_ms = min_ms;
+    }
+    if let Some(max_ms) = parser::max_whole_update_ms_excluding_first_tick(&kept)? {
+        result.max_ms = max_ms;
+    }
+    result.ticks = result.ticks.saturating_sub(interference.dominated_ticks);
+    result.execution_time_ms -= interference.dominated_ms;
+    result.effective_ups = ups_definition.effective_ups(result);
+    Ok(())
+}
+
+/// Discrepancy between the timestamp and "Performed" execution times worth a warning, in percent
+const TIMING_DISCREPANCY_WARN_PERCENT: f64 = 2.0;
+
+/// Use the execution time measured from verbose timestamps, keeping Factorio's own measurement
+/// to cross-check. A large difference means time was spent outside the update loop, e.g. hitches.
+fn apply_timestamp_timing(
+    result: &mut BenchmarkRun,
+    timing: parser::TimestampTiming,
+    save_name: &str,
+) {
+    if timing.execution_time_ms <= 0.0 {
+        return;
+    }
+
+    let performed = result.execution_time_ms;
+    if performed > 0.0 {
+        let discrepancy = (timing.execution_time_ms - performed) / performed * 100.0;
+        if discrepancy.abs() > TIMING_DISCREPANCY_WARN_PERCENT {
+            tracing::warn!(
+                "{save_name} run {}: execution time from verbose timestamps ({:.3} ms) differs from Factorio's ({performed:.3} ms) by {discrepancy:+.1}%",
+                result.index + 1,
+                timing.execution_time_ms
+            );
+        }
+    }
+
+    result.performed_execution_time_ms = Some(performed);
+    result.ticks = timing.ticks;
+    result.execution_time_ms = timing.execution_time_ms;
+}
+
+/// Flatten the runs of every save, slowest save first
+pub(crate) fn sort_by_performance<K>(
+    results_map: HashMap<K, Vec<BenchmarkRun>>,
+) -> Vec<BenchmarkRun> {
+    let mut groups: Vec<(K, Vec<BenchmarkRun>)> = results_map.into_iter().collect();
+
+    // Sort by performance
+    groups.sort_by(|(_, runs_a), (_, runs_b)| {
+        avg_effective_ups(runs_a)
+            .partial_cmp(&avg_effective_ups(runs_b))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    groups.into_iter().flat_map(|(_, runs)| runs).collect()
+}
+
+/// Lock a mutex shared between instances, ignoring poisoning since the data stays valid
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Whether the candidate's 95% confidence interval lies entirely outside the baseline's,
+/// widened by `margin_percent`
+fn is_conclusive(
+    candidate: &[BenchmarkRun],
+    baseline: &[BenchmarkRun],
+    margin_percent: f64,
+) -> bool {
+    let ups = |runs: &[BenchmarkRun]| runs.iter().map(|r| r.effective_ups).collect::<Vec<_>>();
+
+    let (Some((candidate_low, candidate_high)), Some((baseline_low, baseline_high))) = (
+        confidence_interval_95(&ups(candidate)),
+        confidence_interval_95(&ups(baseline)),
+    ) else {
+        return false;
+    };
+
+    let margin = margin_percent / 100.0;
+    candidate_low > baseline_high * (1.0 + margin) || candidate_high < baseline_low * (1.0 - margin)
+}
+
+/// How many runs of the given duration fit into the budget, at least one
+fn runs_within_budget(budget: Duration, run_duration: Duration) -> u32 {
+    if run_duration.is_zero() {
+        return 1;
+    }
+    ((budget.as_secs_f64() / run_duration.as_secs_f64()).floor() as u32).max(1)
+}
+
+/// Results are keyed by save name, so saves that share one (e.g. `a/base.zip` and `b/base.zip`,
+/// or two saves that are equal after `--strip-prefix`) would silently merge. Colliding names are
+/// qualified with their parent directory, then numbered if that is not enough.
+fn disambiguate_save_names(names: Vec<(PathBuf, String)>) -> Vec<(PathBuf, String)> {
+    let count = |names: &[(PathBuf, String)], name: &str| {
+        names.iter().filter(|(_, other)| other == name).count()
+    };
+
+    let qualified: Vec<(PathBuf, String)> = names
+        .iter()
+        .map(|(save_file, name)| {
+            let parent = save_file
+                .parent()
+                .and_then(Path::file_name)
+                .map(|parent| parent.to_string_lossy());
+            match parent {
+                Some(parent) if count(&names, name) > 1 => {
+                    (save_file.clone(), format!("{parent}-{name}"))
+                }
+                _ => (save_file.clone(), name.clone()),
+            }
+        })
+        .collect();
+
+    let mut seen: HashMap<String, u32> = HashMap::new();
+    let mut unique = Vec::with_capacity(qualified.len());
+    for (save_file, name) in &qualified {
+        let name = if count(&qualified, name) > 1 {
+            let n = seen.entry(name.clone()).or_insert(0);
+            *n += 1;
+            format!("{name}-{n}")
+        } else {
+            name.clone()
+        };
+        unique.push((save_file.clone(), name));
+    }
+
+    for ((save_file, original), (_, name)) in names.iter().zip(&unique) {
+        if original != name {
+            tracing::warn!(
+                "Save name '{original}' is used by more than one save, reporting {} as '{name}'",
+                save_file.display()
+            );
+        }
+    }
+
+    unique
+}
+
+/// Flag the runs of every save whose mean tick time is below `min_workload_ms`, warning that its
+/// results likely measure engine overhead and cache effects rather than the factory. A threshold
+/// of 0 disables the check.
+pub fn flag_light_workloads(results: &mut [BenchmarkRun], min_workload_ms: f64) {
+    if min_workload_ms <= 0.0 {
+        return;
+    }
+
+    let mut avg_ms_by_save: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for run in results.iter() {
+        avg_ms_by_save
+            .entry(run.save_name.clone())
+            .or_default()
+            .push(run.avg_ms);
+    }
+
+    let mut light_saves = HashSet::new();
+    for (save_name, avg_ms) in avg_ms_by_save {
+        let Some(avg_ms) = mean(&avg_ms) else {
+            continue;
+        };
+        if avg_ms < min_workload_ms {
+            tracing::warn!(
+                "{save_name} takes {avg_ms:.3} ms per tick, below the minimum workload of {min_workload_ms} ms; its results likely measure engine overhead and cache effects rather than the factory"
+            );
+            light_saves.insert(save_name);
+        }
+    }
+
+    for run in results {
+        run.light_workload = Some(light_saves.contains(&run.save_name));
+    }
+}
+
+/// Log each save's run-to-run noise and the smallest UPS difference the session could resolve,
+/// with the runs count needed to resolve a 1% difference
+pub fn log_measurement_sensitivity(results: &[BenchmarkRun]) {
+    const TARGET_EFFECT_PERCENT: f64 = 1.0;
+
+    let mut ups_by_save: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for run in results {
+        ups_by_save
+            .entry(run.save_name.as_str())
+            .or_default()
+            .push(run.effective_ups);
+    }
+
+    let mut worst_cv: Option<f64> = None;
+    for (save_name, ups) in &ups_by_save {
+        let (Some(mean), Some(std_dev)) = (mean(ups), sample_std_dev(ups)) else {
+            continue;
+        };
+        if mean <= 0.0 {
+            continue;
+        }
+
+        let cv_percent = std_dev / mean * 100.0;
+        let Some(effect_percent) = minimum_detectable_effect(cv_percent, ups.len() as u32) else {
+            continue;
+        };
+        tracing::info!(
+            "{save_name}: run-to-run variation {cv_percent:.2}%, differences below {effect_percent:.2}% (~{:.0} UPS) are not detectable with {} runs",
+            mean * effect_percent / 100.0,
+            ups.len()
+        );
+        worst_cv = Some(worst_cv.map_or(cv_percent, |worst: f64| worst.max(cv_percent)));
+    }
+
+    match worst_cv.map(|cv| runs_to_detect(cv, TARGET_EFFECT_PERCENT)) {
+        Some(Some(runs)) => tracing::info!(
+            "Use at least {runs} runs per save to detect {TARGET_EFFECT_PERCENT}% differences"
+        ),
+        Some(None) => tracing::info!(
+            "Run-to-run noise is too high to detect {TARGET_EFFECT_PERCENT}% differences, consider more ticks per run"
+        ),
+        None => tracing::info!(
+            "Use at least 2 runs per save to estimate the smallest detectable difference"
+        ),
     }
 }
 
+/// The metrics to have Factorio report per tick, `None` when neither the export nor the tick
+/// window needs them. Only the requested metrics, the operands of derived metrics and
+/// `wholeUpdate`, which backs the tick times, are asked for; `all` and patterns such as `*Update`
+/// need every column to be resolved against.
+fn benchmark_verbose_metrics(
+    verbose_metrics: &[String],
+    derived_metrics: &[DerivedMetric],
+    needs_tick_times: bool,
+) -> Option<String> {
+    if verbose_metrics.is_empty() && !needs_tick_times {
+        return None;
+    }
+    if verbose_metrics
+        .iter()
+        .any(|metric| metric == "all" || metric.contains(['*', '?', '[']))
+    {
+        return Some("all".to_string());
+    }
+
+    let derived_names: HashSet<&str> = derived_metrics
+        .iter()
+        .map(|metric| metric.name.as_str())
+        .collect();
+    let mut metrics: Vec<&str> = vec!["wholeUpdate"];
+    let requested = verbose_metrics.iter().map(String::as_str);
+    let operands = derived_metrics.iter().flat_map(DerivedMetric::operands);
+    for metric in requested.chain(operands) {
+        if !derived_names.contains(metric) && !metrics.contains(&metric) {
+            metrics.push(metric);
+        }
+    }
+    Some(metrics.join(","))
+}
+
 fn avg_effective_ups(runs: &[BenchmarkRun]) -> f64 {
     if runs.is_empty() {
         return f64::NEG_INFINITY; // or 0.0, depending on what "no runs" should mean
@@ -281,10 +1381,153 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_colliding_save_names_are_disambiguated() {
+        let names = vec![
+            (PathBuf::from("a/base.zip"), "base".to_string()),
+            (PathBuf::from("b/base.zip"), "base".to_string()),
+            (PathBuf::from("a/x/other.zip"), "other".to_string()),
+            (PathBuf::from("b/x/other.zip"), "other".to_string()),
+            (PathBuf::from("a/unique.zip"), "unique".to_string()),
+        ];
+
+        let names: Vec<String> = disambiguate_save_names(names)
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        assert_eq!(
+            names,
+            vec!["a-base", "b-base", "x-other-1", "x-other-2", "unique"]
+        );
+    }
+
+    #[test]
+    fn test_parse_run_applies_ups_definition() {
+        let summary =
+            "Performed 600 updates in 5000.000 ms\navg: 5.000 ms, min: 4.000 ms, max: 9.000 ms\n";
+        let ups = |definition| {
+            parse_run(
+                summary,
+                None,
+                Path::new("a.zip"),
+                "a",
+                0,
+                definition,
+                TickWindow::default(),
+            )
+            .expect("parse run")
+            .effective_ups
+        };
+
+        assert_eq!(ups(UpsDefinition::Total), 120.0);
+        assert_eq!(ups(UpsDefinition::Average), 200.0);
+        assert_eq!(ups(UpsDefinition::Capped), 60.0);
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(Duration::from_secs(59)), "59s");
         assert_eq!(format_duration(Duration::from_secs(61)), "1m1s");
         assert_eq!(format_duration(Duration::from_secs(3661)), "1h1m");
     }
+
+    #[test]
+    fn test_runs_within_budget() {
+        let budget = Duration::from_secs(30 * 60);
+
+        assert_eq!(runs_within_budget(budget, Duration::from_secs(60)), 30);
+        assert_eq!(runs_within_budget(budget, Duration::from_secs(7 * 60)), 4);
+        assert_eq!(runs_within_budget(budget, Duration::from_secs(60 * 60)), 1);
+    }
+
+    fn runs_with_ups(ups: &[f64]) -> Vec<BenchmarkRun> {
+        ups.iter()
+            .map(|&effective_ups| BenchmarkRun {
+                effective_ups,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_benchmark_verbose_metrics_asks_only_for_needed_columns() {
+        let metrics =
+            |names: &[&str]| -> Vec<String> { names.iter().map(|name| name.to_string()).collect() };
+        let derived = DerivedMetric::from_config(&BTreeMap::from([(
+            "otherUpdate".to_string(),
+            "gameUpdate - entityUpdate".to_string(),
+        )]))
+        .unwrap();
+
+        assert_eq!(benchmark_verbose_metrics(&[], &[], false), None);
+        assert_eq!(
+            benchmark_verbose_metrics(&[], &[], true).as_deref(),
+            Some("wholeUpdate")
+        );
+        assert_eq!(
+            benchmark_verbose_metrics(&metrics(&["gameUpdate", "otherUpdate"]), &derived, false)
+                .as_deref(),
+            Some("wholeUpdate,gameUpdate,entityUpdate")
+        );
+        assert_eq!(
+            benchmark_verbose_metrics(&metrics(&["wholeUpdate", "*Update"]), &[], false).as_deref(),
+            Some("all")
+        );
+    }
+
+    #[test]
+    fn test_is_conclusive_when_intervals_are_far_apart() {
+        let baseline = runs_with_ups(&[100.0, 101.0, 99.0]);
+        let candidate = runs_with_ups(&[150.0, 151.0, 149.0]);
+
+        assert!(is_conclusive(&candidate, &baseline, 5.0));
+        assert!(is_conclusive(&baseline, &candidate, 5.0));
+    }
+
+    #[test]
+    fn test_minimum_detectable_effect_shrinks_with_runs() {
+        assert_eq!(minimum_detectable_effect(1.0, 1), None);
+
+        let five_runs = minimum_detectable_effect(1.0, 5).unwrap();
+        let ten_runs = minimum_detectable_effect(1.0, 10).unwrap();
+        assert!((five_runs - 2.306 * (2.0f64 / 5.0).sqrt()).abs() < 1e-9);
+        assert!(ten_runs < five_runs);
+
+        let runs = runs_to_detect(1.0, 1.0).unwrap();
+        assert!(minimum_detectable_effect(1.0, runs).unwrap() <= 1.0);
+        assert!(minimum_detectable_effect(1.0, runs - 1).unwrap() > 1.0);
+    }
+
+    #[test]
+    fn test_is_not_conclusive_within_margin_or_single_run() {
+        let baseline = runs_with_ups(&[100.0, 101.0, 99.0]);
+        let close = runs_with_ups(&[104.0, 105.0, 103.0]);
+        let single = runs_with_ups(&[200.0]);
+
+        assert!(!is_conclusive(&close, &baseline, 5.0));
+        assert!(!is_conclusive(&single, &baseline, 5.0));
+    }
+
+    #[test]
+    fn test_flag_light_workloads_by_save_mean() {
+        let run = |save_name: &str, avg_ms: f64| BenchmarkRun {
+            save_name: save_name.to_string(),
+            avg_ms,
+            ..Default::default()
+        };
+        let mut results = vec![run("light", 0.5), run("light", 1.2), run("heavy", 4.0)];
+
+        flag_light_workloads(&mut results, 1.0);
+        assert_eq!(
+            results
+                .iter()
+                .map(|run| run.light_workload)
+                .collect::<Vec<_>>(),
+            vec![Some(true), Some(true), Some(false)]
+        );
+
+        let mut unchecked = vec![run("light", 0.5)];
+        flag_light_workloads(&mut unchecked, 0.0);
+        assert_eq!(unchecked[0].light_workload, None);
+    }
 }
@@ -0,0 +1,264 @@
+//! Bisect module
+//!
+//! Binary-searches the Factorio versions installed between a good and a bad version for the first
+//! one whose UPS on a save dropped by more than a threshold.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::{
+    benchmark::{self, runner::BenchmarkRunner},
+    core::{
+        FactorioExecutor, Result,
+        config::BenchmarkConfig,
+        error::BenchmarkErrorKind,
+        mods::SessionModsDir,
+        output::ensure_output_dir,
+        saves::{DisplayNameRules, SavesManifest},
+        utils,
+        versions::{self, FactorioVersion, InstalledVersion},
+    },
+};
+
+/// File the measured versions are written to
+const BISECT_CSV_FILENAME: &str = "bisect.csv";
+
+/// What to bisect
+#[derive(Debug, Clone)]
+pub struct BisectOptions {
+    pub good: FactorioVersion,
+    pub bad: FactorioVersion,
+    pub save: PathBuf,
+    /// Directory with one Factorio installation per version
+    pub versions_dir: PathBuf,
+    /// UPS drop from the good version, in percent, that counts as a regression
+    pub threshold_percent: f64,
+}
+
+/// A measured version
+#[derive(Debug, Clone)]
+struct Measurement {
+    version: FactorioVersion,
+    build: Option<u32>,
+    mean_ups: f64,
+}
+
+/// Find the first version between `options.good` and `options.bad` that regressed, benchmarking
+/// the save with `benchmark_config`'s ticks and runs on each version the search visits
+pub async fn run(
+    mut benchmark_config: BenchmarkConfig,
+    options: BisectOptions,
+    running: &Arc<AtomicBool>,
+) -> Result<()> {
+    if options.good >= options.bad {
+        return Err(BenchmarkErrorKind::InvalidBisectRange {
+            good: options.good.to_string(),
+            bad: options.bad.to_string(),
+        }
+        .into());
+    }
+    utils::validate_save_files(std::slice::from_ref(&options.save))?;
+
+    let installed = versions::find_installed(&options.versions_dir)?;
+    let index_of = |version: FactorioVersion| {
+        installed
+            .iter()
+            .position(|installed| installed.version == version)
+            .ok_or_else(|| BenchmarkErrorKind::VersionNotInstalled {
+                version: version.to_string(),
+                directory: options.versions_dir.clone(),
+            })
+    };
+    let (good, bad) = (index_of(options.good)?, index_of(options.bad)?);
+    let candidates = &installed[good..=bad];
+    tracing::info!(
+        "Bisecting {} installed versions from {} to {}",
+        candidates.len(),
+        options.good,
+        options.bad
+    );
+
+    let output_dir = benchmark_config
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("."));
+    ensure_output_dir(&output_dir)?;
+    let _marker_mods_dirs = benchmark::copy_mods_dirs_for_markers(&mut benchmark_config)?;
+
+    let mut measurements = Vec::new();
+    let mut measure = async |installed: &InstalledVersion| -> Result<Option<f64>> {
+        let Some(measurement) =
+            measure_version(&benchmark_config, installed, &options.save, running).await?
+        else {
+            return Ok(None);
+        };
+        let mean_ups = measurement.mean_ups;
+        measurements.push(measurement);
+        Ok(Some(mean_ups))
+    };
+
+    let Some(good_ups) = measure(&candidates[0]).await? else {
+        return Ok(());
+    };
+    let Some(bad_ups) = measure(&candidates[candidates.len() - 1]).await? else {
+        return Ok(());
+    };
+
+    if !is_regression(good_ups, bad_ups, options.threshold_percent) {
+        tracing::warn!(
+            "{} isn't more than {}% slower than {} ({:.0} vs {:.0} UPS); nothing to bisect",
+            options.bad,
+            options.threshold_percent,
+            options.good,
+            bad_ups,
+            good_ups
+        );
+        return write_bisect_csv(&measurements, good_ups, &output_dir);
+    }
+
+    let mut bisection = Bisection::new(candidates.len());
+    while let Some(index) = bisection.next_probe() {
+        let Some(ups) = measure(&candidates[index]).await? else {
+            return write_bisect_csv(&measurements, good_ups, &output_dir);
+        };
+        let regressed = is_regression(good_ups, ups, options.threshold_percent);
+        tracing::info!(
+            "{} is {} ({:.0} UPS)",
+            candidates[index].version,
+            if regressed { "bad" } else { "good" },
+            ups
+        );
+        bisection.record(index, regressed);
+    }
+
+    let (last_good, first_bad) = (
+        &candidates[bisection.good].version,
+        &candidates[bisection.bad].version,
+    );
+    tracing::info!("The regression was introduced in {first_bad} (last good: {last_good})");
+
+    write_bisect_csv(&measurements, good_ups, &output_dir)
+}
+
+/// Benchmark the save on one version; `None` when interrupted
+async fn measure_version(
+    benchmark_config: &BenchmarkConfig,
+    installed: &InstalledVersion,
+    save: &Path,
+    running: &Arc<AtomicBool>,
+) -> Result<Option<Measurement>> {
+    tracing::info!("Benchmarking Factorio {}", installed.version);
+
+    // Sync mods in a throwaway copy of the player's mods folder, like `belt benchmark`
+    let factorio = FactorioExecutor::new(installed.executable.clone());
+    let factorio = match (&benchmark_config.mods_dir, utils::find_mod_directory()) {
+        (None, Some(user_mods_dir)) => {
+            factorio.with_session_mods_dir(Arc::new(SessionModsDir::assemble(&user_mods_dir)?))
+        }
+        _ => factorio,
+    };
+
+    let runner = BenchmarkRunner::new(
+        benchmark_config.clone(),
+        factorio,
+        SavesManifest::default(),
+        DisplayNameRules::default(),
+    );
+    let (results, _) = runner.run_all(vec![save.to_path_buf()], running).await?;
+    if !running.load(Ordering::SeqCst) || results.is_empty() {
+        tracing::info!("Bisect interrupted");
+        return Ok(None);
+    }
+
+    let ups: Vec<f64> = results.iter().map(|run| run.effective_ups).collect();
+    Ok(Some(Measurement {
+        version: installed.version,
+        build: results.iter().find_map(|run| run.factorio_build),
+        mean_ups: utils::mean(&ups).unwrap_or_default(),
+    }))
+}
+
+/// Whether `ups` is more than `threshold_percent` below the good version's UPS
+fn is_regression(good_ups: f64, ups: f64, threshold_percent: f64) -> bool {
+    ups < good_ups * (1.0 - threshold_percent / 100.0)
+}
+
+/// Indexes of the last known good and first known bad candidate
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bisection {
+    good: usize,
+    bad: usize,
+}
+
+impl Bisection {
+    /// The first candidate is good and the last one bad
+    fn new(candidates: usize) -> Self {
+        Self {
+            good: 0,
+            bad: candidates.saturating_sub(1),
+        }
+    }
+
+    /// The candidate to measure next, `None` once the good and bad versions are adjacent
+    fn next_probe(&self) -> Option<usize> {
+        (self.bad - self.good > 1).then(|| self.good + (self.bad - self.good) / 2)
+    }
+
+    fn record(&mut self, index: usize, regressed: bool) {
+        if regressed {
+            self.bad = index;
+        } else {
+            self.good = index;
+        }
+    }
+}
+
+fn write_bisect_csv(measurements: &[Measurement], good_ups: f64, path: &Path) -> Result<()> {
+    let mut measurements = measurements.to_vec();
+    measurements.sort_by_key(|measurement| measurement.version);
+
+    let csv_path = path.join(BISECT_CSV_FILENAME);
+    let mut writer = csv::Writer::from_path(&csv_path)?;
+    writer.write_record(["version", "build", "mean_ups", "change_percent"])?;
+    for measurement in measurements {
+        writer.write_record([
+            measurement.version.to_string(),
+            measurement
+                .build
+                .map(|build| build.to_string())
+                .unwrap_or_default(),
+            measurement.mean_ups.to_string(),
+            ((measurement.mean_ups - good_ups) / good_ups * 100.0).to_string(),
+        ])?;
+    }
+    writer.flush()?;
+
+    tracing::info!("Measurements written to {}", csv_path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bisection_finds_first_bad_candidate() {
+        // Candidates 0..=9, regressed from 6 on
+        let mut bisection = Bisection::new(10);
+        let mut probes = Vec::new();
+        while let Some(index) = bisection.next_probe() {
+            probes.push(index);
+            bisection.record(index, index >= 6);
+        }
+
+        assert_eq!(bisection, Bisection { good: 5, bad: 6 });
+        assert_eq!(probes, vec![4, 6, 5]);
+        assert!(is_regression(100.0, 96.0, 3.0));
+        assert!(!is_regression(100.0, 97.5, 3.0));
+    }
+}
@@ -1,6 +1,7 @@
 //! Blueprint Benchmarking module
 //!
-//! Contains logic for running blueprints, then uses the normal benchmark stuff to report results.
+//! Contains logic for running blueprints, then optionally uses the normal benchmark stuff to report
+//! results.
 
 pub mod runner;
 
@@ -9,12 +10,23 @@ use std::{
     sync::{Arc, atomic::AtomicBool},
 };
 
-use crate::core::{FactorioExecutor, GlobalConfig, Result, config::BlueprintConfig, output, utils};
+use crate::{
+    benchmark,
+    core::{
+        FactorioExecutor, GlobalConfig, Result,
+        config::{BenchmarkConfig, BlueprintConfig},
+        output,
+        saves::SavesManifest,
+        utils,
+    },
+};
 
-/// Run all of the benchmarks, capture the logs and write the results to files.
+/// Generate a save for every blueprint. With a `save_benchmark_config`, the generated saves are
+/// then benchmarked and the results written to the output directory like `belt benchmark` does.
 pub async fn run(
     global_config: GlobalConfig,
     benchmark_config: BlueprintConfig,
+    save_benchmark_config: Option<BenchmarkConfig>,
     running: &Arc<AtomicBool>,
 ) -> Result<()> {
     tracing::info!(
@@ -23,7 +35,7 @@ pub async fn run(
     );
 
     // Find the Factorio binary
-    let factorio = FactorioExecutor::discover(global_config.factorio_path)?;
+    let factorio = FactorioExecutor::discover(global_config.factorio_path.clone())?;
     tracing::info!(
         "Using Factorio at: {}",
         factorio.executable_path().display()
@@ -44,7 +56,26 @@ pub async fn run(
 
     // Run the benchmarks
     let runner = runner::BlueprintRunner::new(benchmark_config.clone(), factorio);
-    runner.run_all(blueprint_files, running).await?;
+    let generated_saves = runner.run_all(blueprint_files, running).await?;
+
+    let Some(mut save_benchmark_config) = save_benchmark_config else {
+        return Ok(());
+    };
+    if generated_saves.is_empty() {
+        tracing::warn!("No saves were generated, nothing to benchmark");
+        return Ok(());
+    }
+
+    save_benchmark_config.saves_dir = output_dir.to_path_buf();
+    save_benchmark_config.output = Some(output_dir.to_path_buf());
+    save_benchmark_config.headless |= benchmark_config.headless;
 
-    Ok(())
+    benchmark::benchmark_saves(
+        global_config,
+        save_benchmark_config,
+        generated_saves,
+        SavesManifest::load(output_dir)?,
+        running,
+    )
+    .await
 }
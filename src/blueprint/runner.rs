@@ -25,12 +25,13 @@ impl BlueprintRunner {
         Self { config, factorio }
     }
 
-    /// Run benchmarks for all blueprint files
+    /// Generate a save for every blueprint file and return the paths of the generated saves
     pub async fn run_all(
         &self,
         blueprint_files: Vec<PathBuf>,
         running: &Arc<AtomicBool>,
-    ) -> Result<()> {
+    ) -> Result<Vec<PathBuf>> {
+        let mut generated_saves = Vec::new();
         for bp_file in &blueprint_files {
             if !running.load(Ordering::SeqCst) {
                 tracing::info!("Shutdown requested. Aborting remaining blueprints.");
@@ -160,18 +161,22 @@ impl BlueprintRunner {
                 tracing::debug!("Found generated save file at: {}", save_file.display());
 
                 if let Some(output_dir) = &self.config.output {
-                    std::fs::rename(&save_file, output_dir.join(format!("{}.zip", &filestem)))?;
+                    let moved = output_dir.join(format!("{}.zip", &filestem));
+                    std::fs::rename(&save_file, &moved)?;
                     tracing::info!(
                         "Moved generated save from: {}, to: {}",
                         save_file.display(),
                         output_dir.display()
                     );
+                    generated_saves.push(moved);
+                } else {
+                    generated_saves.push(save_file);
                 }
             } else {
                 tracing::error!("No generated save file found.");
             }
         }
 
-        Ok(())
+        Ok(generated_saves)
     }
 }
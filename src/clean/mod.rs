@@ -0,0 +1,221 @@
+//! Clean module
+//!
+//! Applies the retention policy of the `[retention]` config section to the session output
+//! directories in `outputs_dir` and to the runs cached by `--use-cache`, so long-running
+//! benchmarking setups don't fill the disk.
+
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::core::{
+    Result, config::RetentionConfig, error::BenchmarkErrorKind, journal::JOURNAL_FILENAME,
+    result_cache,
+};
+
+const BYTES_PER_GB: f64 = 1_000_000_000.0;
+
+/// Something retention may remove
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArtifactKind {
+    /// An output directory of a benchmark session, with its raw logs
+    Session,
+    /// The cached runs of one save
+    CachedRuns,
+}
+
+#[derive(Debug, Clone)]
+struct Artifact {
+    path: PathBuf,
+    kind: ArtifactKind,
+    modified: SystemTime,
+    size: u64,
+}
+
+/// List what the retention policy would remove, and remove it with `apply`
+pub fn run(config: &RetentionConfig, apply: bool) -> Result<()> {
+    if config.keep_last.is_none() && config.max_size_gb.is_none() {
+        return Err(BenchmarkErrorKind::NoRetentionPolicy.into());
+    }
+
+    let mut artifacts = Vec::new();
+    match &config.outputs_dir {
+        Some(outputs_dir) => artifacts.extend(find_sessions(outputs_dir)?),
+        None => {
+            tracing::warn!("No outputs_dir configured; only cached runs are subject to retention")
+        }
+    }
+    if let Some(cache_dir) = result_cache::default_dir()
+        && cache_dir.is_dir()
+    {
+        artifacts.extend(find_cached_runs(&cache_dir)?);
+    }
+
+    let expired = expired(artifacts, config);
+    if expired.is_empty() {
+        tracing::info!("Nothing to clean");
+        return Ok(());
+    }
+
+    let freed: u64 = expired.iter().map(|artifact| artifact.size).sum();
+    for artifact in &expired {
+        if apply {
+            match artifact.kind {
+                ArtifactKind::Session => std::fs::remove_dir_all(&artifact.path)?,
+                ArtifactKind::CachedRuns => std::fs::remove_file(&artifact.path)?,
+            }
+            tracing::info!("Removed {}", artifact.path.display());
+        } else {
+            tracing::info!("Would remove {}", artifact.path.display());
+        }
+    }
+
+    if apply {
+        tracing::info!("Freed {:.2} GB", freed as f64 / BYTES_PER_GB);
+    } else {
+        tracing::info!(
+            "Would free {:.2} GB; run with --apply-retention to remove these",
+            freed as f64 / BYTES_PER_GB
+        );
+    }
+    Ok(())
+}
+
+/// The artifacts to remove: sessions beyond the `keep_last` most recent ones, then the oldest
+/// artifacts until the rest fit in `max_size_gb`
+fn expired(mut artifacts: Vec<Artifact>, config: &RetentionConfig) -> Vec<Artifact> {
+    // Newest first
+    artifacts.sort_by_key(|artifact| std::cmp::Reverse(artifact.modified));
+
+    let mut sessions = 0;
+    let (mut kept, mut expired): (Vec<Artifact>, Vec<Artifact>) =
+        artifacts.into_iter().partition(|artifact| {
+            if artifact.kind != ArtifactKind::Session {
+                return true;
+            }
+            sessions += 1;
+            config
+                .keep_last
+                .is_none_or(|keep_last| sessions <= keep_last)
+        });
+
+    if let Some(max_size_gb) = config.max_size_gb {
+        let max_size = (max_size_gb * BYTES_PER_GB) as u64;
+        let mut size: u64 = kept.iter().map(|artifact| artifact.size).sum();
+        while size > max_size
+            && let Some(oldest) = kept.pop()
+        {
+            size -= oldest.size;
+            expired.push(oldest);
+        }
+    }
+
+    expired
+}
+
+/// Directories directly inside `outputs_dir` that a benchmark session wrote its journal to
+fn find_sessions(outputs_dir: &Path) -> Result<Vec<Artifact>> {
+    if !outputs_dir.is_dir() {
+        return Err(BenchmarkErrorKind::OutputsDirectoryNotFound {
+            path: outputs_dir.to_path_buf(),
+        }
+        .into());
+    }
+
+    let mut sessions = Vec::new();
+    for entry in std::fs::read_dir(outputs_dir)? {
+        let path = entry?.path();
+        let journal = path.join(JOURNAL_FILENAME);
+        if !journal.is_file() {
+            continue;
+        }
+
+        sessions.push(Artifact {
+            modified: std::fs::metadata(&journal)?.modified()?,
+            size: dir_size(&path)?,
+            path,
+            kind: ArtifactKind::Session,
+        });
+    }
+    Ok(sessions)
+}
+
+fn find_cached_runs(cache_dir: &Path) -> Result<Vec<Artifact>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(cache_dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            entries.push(Artifact {
+                path: entry.path(),
+                kind: ArtifactKind::CachedRuns,
+                modified: metadata.modified()?,
+                size: metadata.len(),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+fn dir_size(dir: &Path) -> Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_expired_keeps_recent_sessions_within_size() {
+        let artifact = |name: &str, kind, age_secs: u64, size: u64| Artifact {
+            path: PathBuf::from(name),
+            kind,
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(1_000 - age_secs),
+            size,
+        };
+        let artifacts = vec![
+            artifact("session-1", ArtifactKind::Session, 40, 100),
+            artifact("session-2", ArtifactKind::Session, 30, 100),
+            artifact("cached-1", ArtifactKind::CachedRuns, 35, 10),
+            artifact("session-3", ArtifactKind::Session, 20, 100),
+            artifact("session-4", ArtifactKind::Session, 10, 100),
+        ];
+        let names = |artifacts: Vec<Artifact>| -> Vec<String> {
+            artifacts
+                .into_iter()
+                .map(|artifact| artifact.path.display().to_string())
+                .collect()
+        };
+
+        let keep_last = RetentionConfig {
+            keep_last: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            names(expired(artifacts.clone(), &keep_last)),
+            vec!["session-1"]
+        );
+
+        let max_size = RetentionConfig {
+            keep_last: Some(3),
+            max_size_gb: Some(250.0 / BYTES_PER_GB),
+            ..Default::default()
+        };
+        assert_eq!(
+            names(expired(artifacts, &max_size)),
+            vec!["session-1", "cached-1", "session-2"]
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Compare module
+//!
+//! Compares the results of a candidate session against a stored baseline, save by save, and
+//! fails when a save got slower than a threshold allows, for gating changes to maps or mods in CI.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Result,
+    benchmark::parser::BenchmarkRun,
+    core::{error::BenchmarkErrorKind, mean, output::csv::read_benchmark_csv},
+};
+
+/// One save's mean UPS in the baseline and the candidate
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveDelta {
+    pub save_name: String,
+    pub baseline_ups: f64,
+    pub candidate_ups: f64,
+    /// UPS change of the candidate from the baseline, in percent
+    pub change_percent: f64,
+    /// Whether the candidate is more than the threshold slower
+    pub regressed: bool,
+}
+
+/// Compare the results of `candidate` against `baseline`, each an output directory or a
+/// `results.csv`, failing if any save's UPS dropped by more than `threshold_percent`
+pub fn run(baseline: &Path, candidate: &Path, threshold_percent: f64) -> Result<()> {
+    let baseline_results = read_benchmark_csv(&results_path(baseline)?)?;
+    let candidate_results = read_benchmark_csv(&results_path(candidate)?)?;
+
+    let (deltas, unmatched) = compare(&baseline_results, &candidate_results, threshold_percent);
+    for save_name in &unmatched {
+        tracing::warn!("{save_name} is only in one of the results; not compared");
+    }
+
+    let width = deltas
+        .iter()
+        .map(|delta| delta.save_name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Save".len());
+    println!(
+        "{:<width$}  {:>12}  {:>13}  {:>9}",
+        "Save", "Baseline UPS", "Candidate UPS", "Change"
+    );
+    for delta in &deltas {
+        println!(
+            "{:<width$}  {:>12.2}  {:>13.2}  {:>+8.2}%{}",
+            delta.save_name,
+            delta.baseline_ups,
+            delta.candidate_ups,
+            delta.change_percent,
+            if delta.regressed { "  REGRESSION" } else { "" }
+        );
+    }
+
+    let regressions = deltas.iter().filter(|delta| delta.regressed).count();
+    if regressions > 0 {
+        return Err(BenchmarkErrorKind::PerformanceRegression {
+            regressions,
+            threshold_percent,
+        }
+        .into());
+    }
+
+    tracing::info!(
+        "No save regressed by more than {threshold_percent}% over {} save(s)",
+        deltas.len()
+    );
+    Ok(())
+}
+
+/// `results.csv` of an output directory, or `path` itself if it's a file
+fn results_path(path: &Path) -> Result<PathBuf> {
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+
+    let csv_path = path.join("results.csv");
+    if !csv_path.is_file() {
+        return Err(BenchmarkErrorKind::ResultsNotFound {
+            path: path.to_path_buf(),
+        }
+        .into());
+    }
+    Ok(csv_path)
+}
+
+/// Mean UPS per save of `results`
+fn mean_ups(results: &[BenchmarkRun]) -> BTreeMap<&str, f64> {
+    let mut ups_by_save: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for run in results {
+        ups_by_save
+            .entry(&run.save_name)
+            .or_default()
+            .push(run.effective_ups);
+    }
+
+    ups_by_save
+        .into_iter()
+        .filter_map(|(save_name, ups)| Some((save_name, mean(&ups)?)))
+        .collect()
+}
+
+/// The delta of every save in both results, and the names of the saves in only one of them
+fn compare(
+    baseline: &[BenchmarkRun],
+    candidate: &[BenchmarkRun],
+    threshold_percent: f64,
+) -> (Vec<SaveDelta>, Vec<String>) {
+    let baseline = mean_ups(baseline);
+    let candidate = mean_ups(candidate);
+
+    let unmatched = baseline
+        .keys()
+        .filter(|save_name| !candidate.contains_key(*save_name))
+        .chain(
+            candidate
+                .keys()
+                .filter(|save_name| !baseline.contains_key(*save_name)),
+        )
+        .map(|save_name| save_name.to_string())
+        .collect();
+
+    let deltas = baseline
+        .iter()
+        .filter_map(|(&save_name, &baseline_ups)| {
+            let candidate_ups = *candidate.get(save_name)?;
+            let change_percent = if baseline_ups > 0.0 {
+                (candidate_ups - baseline_ups) / baseline_ups * 100.0
+            } else {
+                0.0
+            };
+            Some(SaveDelta {
+                save_name: save_name.to_string(),
+                baseline_ups,
+                candidate_ups,
+                change_percent,
+                regressed: change_percent < -threshold_percent,
+            })
+        })
+        .collect();
+
+    (deltas, unmatched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_flags_saves_slower_than_threshold() {
+        let run = |save_name: &str, ups: f64| BenchmarkRun {
+            save_name: save_name.to_string(),
+            effective_ups: ups,
+            ..Default::default()
+        };
+        let baseline = vec![
+            run("fast", 100.0),
+            run("fast", 100.0),
+            run("slow", 200.0),
+            run("removed", 50.0),
+        ];
+        let candidate = vec![
+            run("fast", 98.0),
+            run("fast", 100.0),
+            run("slow", 180.0),
+            run("added", 50.0),
+        ];
+
+        let (deltas, unmatched) = compare(&baseline, &candidate, 3.0);
+
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].save_name, "fast");
+        assert!((deltas[0].change_percent + 1.0).abs() < 1e-9);
+        assert!(!deltas[0].regressed);
+        assert_eq!(deltas[1].save_name, "slow");
+        assert!((deltas[1].change_percent + 10.0).abs() < 1e-9);
+        assert!(deltas[1].regressed);
+        assert_eq!(unmatched, vec!["removed".to_string(), "added".to_string()]);
+    }
+}
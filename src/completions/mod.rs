@@ -0,0 +1,221 @@
+//! Completions module
+//!
+//! Prints shell completion scripts that ask BELT itself for candidates, so they can offer what's
+//! on disk and in the config: the saves in the saves directory for `--pattern` and the known
+//! metrics for `--verbose-metrics`, besides subcommands and flags.
+
+use std::path::{Path, PathBuf};
+
+use clap::Command;
+
+use crate::core::{error::BenchmarkErrorKind, metrics::known_metric_names, utils};
+
+/// Shells `belt completions` has a script for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Get a Shell from a string
+impl std::str::FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(BenchmarkErrorKind::InvalidShell {
+                input: s.to_string(),
+            }
+            .to_string()),
+        }
+    }
+}
+
+/// The completion script of `shell`, calling the hidden `belt __complete` for candidates
+pub fn script(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => {
+            r#"_belt() {
+    local IFS=$'\n'
+    COMPREPLY=($(belt __complete --index "$COMP_CWORD" -- "${COMP_WORDS[@]}" 2>/dev/null))
+}
+complete -o default -F _belt belt
+"#
+        }
+        Shell::Zsh => {
+            r#"#compdef belt
+_belt() {
+    local -a candidates
+    candidates=("${(@f)$(belt __complete --index $((CURRENT - 1)) -- "${words[@]}" 2>/dev/null)}")
+    compadd -a candidates || _files
+}
+compdef _belt belt
+"#
+        }
+        Shell::Fish => {
+            r#"complete -c belt -f -a '(belt __complete --index (count (commandline -opc)) -- (commandline -opc) (commandline -ct) 2>/dev/null)'
+"#
+        }
+    }
+}
+
+/// Candidates for `words[index]` of a `belt` command line. `saves_dir` gives the configured saves
+/// directory of a subcommand, used for `--pattern` unless the command line names one.
+pub fn complete(
+    command: &Command,
+    words: &[String],
+    index: usize,
+    saves_dir: impl Fn(&str) -> Option<PathBuf>,
+) -> Vec<String> {
+    let current = words.get(index).map(String::as_str).unwrap_or_default();
+    let previous = index
+        .checked_sub(1)
+        .and_then(|previous| words.get(previous))
+        .map(String::as_str)
+        .unwrap_or_default();
+
+    // Walk the words before the cursor down to the subcommand being completed
+    let mut subcommand = command;
+    let mut positionals: Vec<&str> = Vec::new();
+    let mut words_before = words.iter().take(index).skip(1).map(String::as_str);
+    while let Some(word) = words_before.next() {
+        if let Some(long) = word.strip_prefix("--") {
+            let takes_value = subcommand
+                .get_arguments()
+                .chain(command.get_arguments())
+                .find(|arg| arg.get_long() == Some(long))
+                .is_some_and(|arg| arg.get_action().takes_values());
+            if takes_value {
+                words_before.next();
+            }
+        } else if let Some(found) = subcommand.find_subcommand(word) {
+            subcommand = found;
+            positionals.clear();
+        } else {
+            positionals.push(word);
+        }
+    }
+
+    let candidates: Vec<String> = match previous {
+        "--pattern" if matches!(subcommand.get_name(), "benchmark" | "sanitize") => {
+            let dir = positionals
+                .first()
+                .map(PathBuf::from)
+                .or_else(|| saves_dir(subcommand.get_name()));
+            dir.map(|dir| save_names(&dir)).unwrap_or_default()
+        }
+        "--verbose-metrics" => return metric_candidates(current),
+        _ if current.starts_with('-') => subcommand
+            .get_arguments()
+            .chain(command.get_arguments().filter(|arg| arg.is_global_set()))
+            .filter(|arg| !arg.is_hide_set())
+            .filter_map(|arg| arg.get_long())
+            .map(|long| format!("--{long}"))
+            .collect(),
+        _ if subcommand.has_subcommands() => subcommand
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut candidates: Vec<String> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(current))
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+/// Names of the saves and scenario folders in `dir`, as `--pattern` matches them
+fn save_names(dir: &Path) -> Vec<String> {
+    utils::find_save_files(dir, None)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| match path.extension() {
+            Some(extension) if extension == "zip" => path.file_stem(),
+            _ => path.file_name(),
+        })
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Completions of the last metric in a comma-separated `--verbose-metrics` list, keeping the
+/// metrics before it and leaving out ones already listed
+fn metric_candidates(current: &str) -> Vec<String> {
+    let (listed, last) = match current.rsplit_once(',') {
+        Some((listed, last)) => (Some(listed), last),
+        None => (None, current),
+    };
+    let already: Vec<&str> = listed
+        .map(|listed| listed.split(',').collect())
+        .unwrap_or_default();
+
+    let mut metrics: Vec<&str> = std::iter::once("all")
+        .chain(known_metric_names())
+        .filter(|metric| metric.starts_with(last) && !already.contains(metric))
+        .collect();
+    metrics.sort_unstable();
+    metrics
+        .into_iter()
+        .map(|metric| match listed {
+            Some(listed) => format!("{listed},{metric}"),
+            None => metric.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    #[test]
+    fn test_complete_saves_metrics_and_flags() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        for name in ["belt-weave.zip", "baseline.zip", "notes.txt"] {
+            std::fs::write(temp_dir.path().join(name), "").expect("write save");
+        }
+        let command = Command::new("belt")
+            .arg(Arg::new("verbose").long("verbose").global(true).num_args(0))
+            .subcommand(
+                Command::new("benchmark")
+                    .arg(Arg::new("saves_dir"))
+                    .arg(Arg::new("pattern").long("pattern"))
+                    .arg(Arg::new("verbose_metrics").long("verbose-metrics")),
+            )
+            .subcommand(Command::new("sanitize"));
+        let complete = |line: &[&str], index: usize| {
+            let words: Vec<String> = line.iter().map(|word| word.to_string()).collect();
+            let dir = temp_dir.path().to_path_buf();
+            complete(&command, &words, index, |_| Some(dir.clone()))
+        };
+
+        assert_eq!(complete(&["belt", "b"], 1), vec!["benchmark"]);
+        assert_eq!(
+            complete(&["belt", "benchmark", "--pattern", ""], 3),
+            vec!["baseline", "belt-weave"]
+        );
+        assert_eq!(
+            complete(&["belt", "benchmark", "--pattern", "belt"], 3),
+            vec!["belt-weave"]
+        );
+        assert_eq!(
+            complete(
+                &["belt", "benchmark", "--verbose-metrics", "wholeUpdate,game"],
+                3
+            ),
+            vec!["wholeUpdate,gameUpdate"]
+        );
+        assert_eq!(
+            complete(&["belt", "benchmark", "--ver"], 2),
+            vec!["--verbose", "--verbose-metrics"]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Anonymization of output folders for public sharing (`--anonymize`).
+//!
+//! Replaces the working, home and temporary directories, the user name and the host name in
+//! the report and journal with placeholders. Hardware and platform info is kept.
+
+use std::path::{MAIN_SEPARATOR, Path};
+
+use sysinfo::System;
+
+/// Placeholder for directories outside the working, home and temporary directories
+const DIRECTORY_PLACEHOLDER: &str = "<dir>";
+
+/// Replaces local details in text with placeholders
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+    /// Longest first, so nested directories are replaced before their parents
+    replacements: Vec<(String, String)>,
+}
+
+impl Anonymizer {
+    /// Collect the details of this machine and session to replace
+    pub fn detect() -> Self {
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok();
+
+        Self::new(
+            std::env::current_dir().ok().as_deref(),
+            dirs::home_dir().as_deref(),
+            Some(&std::env::temp_dir()),
+            user.as_deref(),
+            System::host_name().as_deref(),
+        )
+    }
+
+    fn new(
+        working_dir: Option<&Path>,
+        home_dir: Option<&Path>,
+        temp_dir: Option<&Path>,
+        user: Option<&str>,
+        host: Option<&str>,
+    ) -> Self {
+        let mut replacements = Vec::new();
+        let mut add_dir = |dir: Option<&Path>, placeholder: &str| {
+            if let Some(dir) = dir.map(|dir| dir.to_string_lossy())
+                && dir.len() > 1
+            {
+                let dir = dir.trim_end_matches(['/', '\\']).to_string();
+                replacements.push((dir, placeholder.to_string()));
+            }
+        };
+        add_dir(working_dir, ".");
+        add_dir(home_dir, "~");
+        add_dir(temp_dir, "<tmp>");
+
+        // User names only inside paths, where they can't be mistaken for ordinary words
+        if let Some(user) = user.filter(|user| !user.is_empty()) {
+            for separator in ['/', '\\'] {
+                replacements.push((
+                    format!("{separator}{user}{separator}"),
+                    format!("{separator}<user>{separator}"),
+                ));
+            }
+        }
+        if let Some(host) = host.filter(|host| host.len() > 1) {
+            replacements.push((host.to_string(), "<host>".to_string()));
+        }
+
+        replacements.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+        Self { replacements }
+    }
+
+    /// Replace every known local detail in free text
+    pub fn text(&self, text: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(text.to_string(), |text, (from, to)| text.replace(from, to))
+    }
+
+    /// Anonymize a single path or argument; remaining absolute paths keep only their file name
+    pub fn path(&self, path: &str) -> String {
+        let anonymized = self.text(path);
+        let as_path = Path::new(&anonymized);
+        match as_path.file_name() {
+            Some(file_name) if as_path.is_absolute() => format!(
+                "{DIRECTORY_PLACEHOLDER}{MAIN_SEPARATOR}{}",
+                file_name.to_string_lossy()
+            ),
+            _ => anonymized,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_anonymizer_replaces_local_details() {
+        let anonymizer = Anonymizer::new(
+            Some(Path::new("/home/alice/bench")),
+            Some(Path::new("/home/alice")),
+            Some(Path::new("/tmp")),
+            Some("alice"),
+            Some("alice-desktop"),
+        );
+
+        assert_eq!(
+            anonymizer.path("/home/alice/bench/saves/a.zip"),
+            "./saves/a.zip"
+        );
+        assert_eq!(
+            anonymizer.path("/home/alice/.factorio/mods"),
+            "~/.factorio/mods"
+        );
+        assert_eq!(anonymizer.path("/tmp/belt-mods-x"), "<tmp>/belt-mods-x");
+        assert_eq!(
+            anonymizer.path("/opt/factorio/bin/x64/factorio"),
+            "<dir>/factorio"
+        );
+        assert_eq!(anonymizer.path("/data/alice/x.zip"), "<dir>/x.zip");
+        assert_eq!(anonymizer.path("--benchmark"), "--benchmark");
+        assert_eq!(
+            anonymizer.text("Run on alice-desktop by alice"),
+            "Run on <host> by alice"
+        );
+    }
+}
@@ -22,6 +22,9 @@
 //! - `BELT_BENCHMARK__TICKS` → `benchmark.ticks`
 //! - `BELT_BENCHMARK__RUNS` → `benchmark.runs`
 //! - `BELT_GLOBAL__VERBOSE` → `global.verbose`
+//! - `BELT_BENCHMARK__VERBOSE_METRICS=wholeUpdate,gameUpdate` → `benchmark.verbose_metrics`
+//!
+//! List options also take a comma-separated string, like their command line flags.
 //!
 //! # Example Config File
 //!
@@ -29,6 +32,7 @@
 //! [global]
 //! factorio_path = "/opt/factorio/bin/factorio"
 //! verbose = false
+//! machine = "desktop"
 //!
 //! [benchmark]
 //! ticks = 6000
@@ -37,6 +41,8 @@
 //! pattern = "*.zip"
 //! headless = true
 //! record_cpu = true
+//! cache_policy = "warm"
+//! verbose_metrics = ["wholeUpdate", "gameUpdate"]
 //!
 //! [sanitize]
 //! ticks = 3600
@@ -45,15 +51,22 @@
 //! [blueprint]
 //! count = 10
 //! buffer_ticks = 120
+//!
+//! [retention]
+//! outputs_dir = "benchmarks"
+//! keep_last = 10
+//! max_size_gb = 5
 //! ```
 
 use figment::Figment;
 use figment::providers::{Env, Format, Toml};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::time::Duration;
 
-use crate::core::RunOrder;
 use crate::core::error::{BenchmarkErrorKind, Result};
+use crate::core::{CachePolicy, Downsample, NameCase, RunOrder, SortBy, TickRange, UpsDefinition};
 
 /// Default configuration file name
 const CONFIG_FILENAME: &str = "config.toml";
@@ -75,6 +88,8 @@ pub struct GlobalConfig {
     /// Enable verbose logging output
     #[serde(default)]
     pub verbose: bool,
+    /// Name of this machine in the results, to tell apart results measured on several
+    pub machine: Option<String>,
 }
 
 impl GlobalConfig {
@@ -82,6 +97,14 @@ impl GlobalConfig {
     pub fn from_figment(figment: &Figment) -> Result<Self> {
         extract_config(figment, "global")
     }
+
+    /// The configured machine name, falling back to the host name
+    pub fn machine_name(&self) -> String {
+        self.machine
+            .clone()
+            .or_else(sysinfo::System::host_name)
+            .unwrap_or_else(|| "unknown".to_string())
+    }
 }
 
 /// Benchmarking specific configuration
@@ -93,6 +116,15 @@ pub struct BenchmarkConfig {
     /// Number of ticks to run each benchmark
     #[serde(default = "default_ticks")]
     pub ticks: u32,
+    /// Ticks run before the measured ticks of each benchmark, excluded from the results
+    #[serde(default)]
+    pub settle_ticks: u32,
+    /// Only these of the measured ticks count towards the results and verbose exports
+    #[serde(default)]
+    pub tick_range: Option<TickRange>,
+    /// Split the counted ticks of every run into segments of this many ticks in the report
+    #[serde(default)]
+    pub segment_ticks: Option<u32>,
     /// Number of benchmark runs per save file
     #[serde(default = "default_runs")]
     pub runs: u32,
@@ -111,12 +143,30 @@ pub struct BenchmarkConfig {
     /// Execution order for benchmark runs
     #[serde(default)]
     pub run_order: RunOrder,
-    /// Metrics to export as verbose CSV data
-    #[serde(default)]
+    /// Metrics to export as verbose CSV data, used unless `--verbose-metrics` is given
+    #[serde(default, deserialize_with = "list_or_comma_separated")]
     pub verbose_metrics: Vec<String>,
+    /// Metrics computed from the verbose metrics, by name, e.g. `"gameUpdate - entityUpdate"`
+    #[serde(default)]
+    pub derived_metrics: BTreeMap<String, String>,
+    /// Variables for custom report templates, rendered as `{{vars.<key>}}`
+    #[serde(default)]
+    pub extra_vars: HashMap<String, String>,
     /// Prefix to strip from save file names in output
     #[serde(default)]
     pub strip_prefix: Option<String>,
+    /// Suffix to strip from save file names in output
+    #[serde(default)]
+    pub strip_suffix: Option<String>,
+    /// Regex replacements (`PATTERN=>REPLACEMENT`) applied to save names in output, in order
+    #[serde(default)]
+    pub name_replace: Vec<String>,
+    /// Case mapping applied to save names in output
+    #[serde(default)]
+    pub name_case: NameCase,
+    /// Regex whose first capture group in a save's display name is its family of variants
+    #[serde(default)]
+    pub group_regex: Option<String>,
     /// Run Factorio in headless mode
     #[serde(default)]
     pub headless: bool,
@@ -125,6 +175,131 @@ pub struct BenchmarkConfig {
     pub record_cpu: bool,
     #[serde(default)]
     pub append: bool,
+    /// Page-cache handling for save files before each run
+    #[serde(default)]
+    pub cache_policy: CachePolicy,
+    /// Order of the saves in results.csv and the report; by default results.csv lists the
+    /// slowest save first and the report sorts by name
+    #[serde(default)]
+    pub sort_by: Option<SortBy>,
+    /// How effective UPS is computed
+    #[serde(default)]
+    pub ups_definition: UpsDefinition,
+    /// Stop benchmarking a save once it differs from the baseline by more than this percentage
+    #[serde(default)]
+    pub stop_early_margin: Option<f64>,
+    /// Time to spend on each save; turns `runs` into a per-save runs count
+    #[serde(default, with = "optional_duration")]
+    pub time_budget: Option<Duration>,
+    /// Ticks to run scenario folders for before saving the map to benchmark
+    #[serde(default = "default_scenario_ticks")]
+    pub scenario_ticks: u32,
+    /// Only benchmark saves carrying one of these tags in `saves.toml`
+    #[serde(default, deserialize_with = "list_or_comma_separated")]
+    pub tags: Vec<String>,
+    /// Shell command run once per save before its first run; `{save}` is the save path
+    #[serde(default)]
+    pub pre_save_hook: Option<String>,
+    /// Shell command run after every run; `{save}` is the save path, `{run}` the run number
+    #[serde(default)]
+    pub post_run_hook: Option<String>,
+    /// Run each save once with the sanitizer mod before benchmarking it
+    #[serde(default)]
+    pub sanitize_check: bool,
+    /// Skip saves whose sanitize check found benchmark-affecting issues
+    #[serde(default)]
+    pub skip_sanitize_issues: bool,
+    /// Attribute tick time to surfaces by the entity counts of the sanitize check
+    #[serde(default)]
+    pub surface_stats: bool,
+    /// Record the belt-sanitizer mod's event markers and line them up with the tick times
+    #[serde(default)]
+    pub markers: bool,
+    /// List every individual run in the report
+    #[serde(default)]
+    pub report_runs: bool,
+    /// Take over the session locks of other running sessions
+    #[serde(default)]
+    pub force: bool,
+    /// Write the distribution of every verbose metric to `<save>_metric_summary.csv`
+    #[serde(default)]
+    pub metric_summary: bool,
+    /// Export the aggregates and downsampled verbose metrics to `chartdata.json`
+    #[serde(default)]
+    pub chart_data: bool,
+    /// How the per-tick series of `chartdata.json` are downsampled
+    #[serde(default)]
+    pub downsample: Downsample,
+    /// Export the aggregates and runs to `metrics.prom` in the Prometheus text format
+    #[serde(default)]
+    pub prometheus: bool,
+    /// Pushgateway URL to push `metrics.prom` to, e.g. `http://localhost:9091/metrics/job/belt`
+    #[serde(default)]
+    pub push_url: Option<String>,
+    /// Mean tick time below which a save is flagged as too light to measure the factory; 0
+    /// disables the check
+    #[serde(default = "default_min_workload_ms")]
+    pub min_workload_ms: f64,
+    /// Replace local paths, the user name and the host name in the report and journal
+    #[serde(default)]
+    pub anonymize: bool,
+    /// Store each run's complete Factorio output, compressed, in `raw_logs/`
+    #[serde(default)]
+    pub save_raw_logs: bool,
+    /// Reuse the runs of earlier sessions for saves, builds, ticks and mods that didn't change
+    #[serde(default)]
+    pub use_cache: bool,
+    /// Flag runs during which other processes used more than this percentage of all cores
+    #[serde(default)]
+    pub background_load_threshold: Option<f64>,
+    /// Times to retry a run flagged for background load
+    #[serde(default)]
+    pub background_load_retries: u32,
+    /// Set the CPU governor to performance and disable turbo for the session (Linux, root)
+    #[serde(default)]
+    pub lock_frequency: bool,
+    /// Abort the session on the first warning instead of reporting around it
+    #[serde(default)]
+    pub strict: bool,
+    /// Leave ticks dominated by chart refreshes and CRC computations out of the results
+    #[serde(default)]
+    pub exclude_interference: bool,
+    /// Fail the session on a save newer than the Factorio binary instead of skipping the save
+    #[serde(default)]
+    pub abort_on_newer_saves: bool,
+    /// Abort before the first run when the saves were saved with different mods
+    #[serde(default)]
+    pub strict_mods: bool,
+    /// Two mods directories to benchmark every save with in alternating runs and compare
+    #[serde(default)]
+    pub ab_mods: Option<[PathBuf; 2]>,
+    /// Let Factorio make all runs of a save in one invocation with `--benchmark-runs`
+    #[serde(default)]
+    pub native_runs: bool,
+    /// Number of Factorio instances to run concurrently, each pinned to its own cores
+    #[serde(default = "default_jobs")]
+    pub jobs: u32,
+    /// NUMA node to bind Factorio's cores and memory to
+    #[serde(default)]
+    pub numa_node: Option<u32>,
+    /// CPU cores to pin Factorio to, e.g. `0-7` for the performance cores of a hybrid CPU
+    #[serde(default)]
+    pub cpu_affinity: Option<String>,
+    /// Decimal places of millisecond values in the report (default 3)
+    #[serde(default)]
+    pub ms_decimals: Option<usize>,
+    /// Decimal places of UPS values in the report (default 0)
+    #[serde(default)]
+    pub ups_decimals: Option<usize>,
+    /// Decimal places of percentages in the report (default 2)
+    #[serde(default)]
+    pub percent_decimals: Option<usize>,
+    /// Separator between groups of thousands in report numbers
+    #[serde(default)]
+    pub thousands_separator: Option<char>,
+    /// Decimal separator of report numbers (default `.`)
+    #[serde(default)]
+    pub decimal_separator: Option<char>,
 }
 
 impl Default for BenchmarkConfig {
@@ -132,6 +307,9 @@ impl Default for BenchmarkConfig {
         Self {
             saves_dir: PathBuf::new(),
             ticks: default_ticks(),
+            settle_ticks: 0,
+            tick_range: None,
+            segment_ticks: None,
             runs: default_runs(),
             pattern: None,
             output: None,
@@ -139,10 +317,57 @@ impl Default for BenchmarkConfig {
             mods_dir: None,
             run_order: RunOrder::default(),
             verbose_metrics: Vec::new(),
+            derived_metrics: BTreeMap::new(),
+            extra_vars: HashMap::new(),
             strip_prefix: None,
+            strip_suffix: None,
+            name_replace: Vec::new(),
+            name_case: NameCase::default(),
+            group_regex: None,
             headless: false,
             record_cpu: default_record_cpu(),
             append: false,
+            cache_policy: CachePolicy::default(),
+            sort_by: None,
+            ups_definition: UpsDefinition::default(),
+            stop_early_margin: None,
+            time_budget: None,
+            scenario_ticks: default_scenario_ticks(),
+            tags: Vec::new(),
+            pre_save_hook: None,
+            post_run_hook: None,
+            sanitize_check: false,
+            skip_sanitize_issues: false,
+            surface_stats: false,
+            markers: false,
+            report_runs: false,
+            force: false,
+            metric_summary: false,
+            chart_data: false,
+            downsample: Downsample::default(),
+            prometheus: false,
+            push_url: None,
+            min_workload_ms: default_min_workload_ms(),
+            anonymize: false,
+            save_raw_logs: false,
+            use_cache: false,
+            background_load_threshold: None,
+            background_load_retries: 0,
+            lock_frequency: false,
+            strict: false,
+            strict_mods: false,
+            ab_mods: None,
+            native_runs: false,
+            abort_on_newer_saves: false,
+            exclude_interference: false,
+            jobs: default_jobs(),
+            numa_node: None,
+            cpu_affinity: None,
+            ms_decimals: None,
+            ups_decimals: None,
+            percent_decimals: None,
+            thousands_separator: None,
+            decimal_separator: None,
         }
     }
 }
@@ -159,6 +384,40 @@ fn default_record_cpu() -> bool {
     true
 }
 
+fn default_scenario_ticks() -> u32 {
+    60
+}
+
+fn default_jobs() -> u32 {
+    1
+}
+
+fn default_min_workload_ms() -> f64 {
+    1.0
+}
+
+/// A list, or a comma-separated string as environment variables give it
+fn list_or_comma_separated<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ListOrString {
+        List(Vec<String>),
+        String(String),
+    }
+
+    Ok(match ListOrString::deserialize(deserializer)? {
+        ListOrString::List(list) => list,
+        ListOrString::String(string) => string
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
 impl BenchmarkConfig {
     /// Load configuration from figment
     pub fn from_figment(figment: &Figment) -> Result<Self> {
@@ -190,6 +449,12 @@ pub struct SanitizeConfig {
     /// Fluids to preserve during sanitization (comma-separated)
     #[serde(default)]
     pub fluids: Option<String>,
+    /// Report entity counts per surface
+    #[serde(default)]
+    pub surface_stats: bool,
+    /// Query the map over RCON from a headless server instead of running the belt-sanitizer mod
+    #[serde(default)]
+    pub rcon: bool,
     /// Run Factorio in headless mode
     #[serde(default)]
     pub headless: bool,
@@ -209,6 +474,8 @@ impl Default for SanitizeConfig {
             data_dir: None,
             items: None,
             fluids: None,
+            surface_stats: false,
+            rcon: false,
             headless: false,
         }
     }
@@ -260,6 +527,9 @@ pub struct BlueprintConfig {
     /// Number of construction bots to use
     #[serde(default)]
     pub bot_count: Option<u32>,
+    /// Benchmark the generated saves with the `[benchmark]` settings
+    #[serde(default)]
+    pub benchmark: bool,
 }
 
 impl Default for BlueprintConfig {
@@ -277,6 +547,7 @@ impl Default for BlueprintConfig {
             prefix: None,
             headless: false,
             bot_count: None,
+            benchmark: false,
         }
     }
 }
@@ -296,6 +567,53 @@ impl BlueprintConfig {
     }
 }
 
+/// Retention of benchmark artifacts, applied by `belt clean --apply-retention`
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Directory whose session output directories are subject to retention
+    #[serde(default)]
+    pub outputs_dir: Option<PathBuf>,
+    /// Number of most recent sessions to keep
+    #[serde(default)]
+    pub keep_last: Option<usize>,
+    /// Total size in GB the sessions and cached runs may take up
+    #[serde(default)]
+    pub max_size_gb: Option<f64>,
+}
+
+impl RetentionConfig {
+    /// Load configuration from figment
+    pub fn from_figment(figment: &Figment) -> Result<Self> {
+        extract_config(figment, "retention")
+    }
+}
+
+/// (De)serialize an optional duration as a human readable string such as "30m"
+mod optional_duration {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    use crate::core::utils::{format_duration, parse_duration};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => serializer.serialize_some(&format_duration(*duration)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Option<Duration>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| parse_duration(&value).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
 // Figment Configuration
 // =============================================================================
 
@@ -376,23 +694,89 @@ pub fn init_config_dir() -> Result<PathBuf> {
 # Path to Factorio executable
 # factorio_path = "/opt/factorio/bin/factorio"
 # verbose = false
+# Name of this machine in the results [default: the host name]
+# machine = "desktop"
 
 [benchmark]
 # ticks = 6000
+# settle_ticks = 0  # Ticks run before measuring, while bots re-path and machines refill
+# tick_range = "1000..5000"  # Only these of the measured ticks count
+# segment_ticks = 36000  # Report the tick times of every 10 minutes of game time separately
 # runs = 5
 # run_order = "sequential"  # Options: "sequential", "random", "grouped"
 # pattern = "*.zip"
 # headless = false
 # record_cpu = true
+# verbose_metrics = ["wholeUpdate", "gameUpdate"]  # Used unless --verbose-metrics is given
+# cache_policy = "asis"  # Options: "warm", "cold", "asis"
+# sort_by = "input-order"  # Options: "ups", "name", "input-order"
+# ups_definition = "total"  # Options: "total", "average", "capped"
+# time_budget = "30m"
+# scenario_ticks = 60  # Ticks to run scenario folders before saving the map to benchmark
+# strip_prefix = "test-"
+# strip_suffix = "-final"
+# name_replace = ["_=> "]  # Regex replacements applied to save names, PATTERN=>REPLACEMENT
+# name_case = "asis"  # Options: "asis", "lower", "upper", "title"
+# group_regex = "^(.+)-v[0-9]+$"  # Family of variants, e.g. "weave" for "weave-v2"
+# tags = ["weave"]  # Filter saves by tags from saves.toml
+# pre_save_hook = "./prepare.sh {save}"
+# post_run_hook = "./collect.sh {save} {run}"
+# sanitize_check = false
+# skip_sanitize_issues = false
+# surface_stats = false
+# markers = false  # Needs the belt-sanitizer mod
+# report_runs = false
+# metric_summary = false  # Mean, median, p95, p99, stddev and max of every verbose metric
+# chart_data = false  # Write chartdata.json for embedding charts in websites
+# downsample = "mean"  # Options: "lttb", "minmax", "mean"
+# anonymize = false
+# save_raw_logs = false
+# use_cache = false
+# background_load_threshold = 10.0  # Flag runs where other processes used more than 10% of all cores
+# background_load_retries = 0
+# lock_frequency = false  # Needs root; Linux only
+# strict = false  # Abort on the first warning
+# strict_mods = false  # Abort when the saves were saved with different mods
+# ab_mods = ["./mods-on", "./mods-off"]  # Compare every save with two mods directories
+# native_runs = false  # Run each save's runs in one Factorio process, loading it only once
+# prometheus = false  # Write metrics.prom in the Prometheus text format
+# push_url = "http://localhost:9091/metrics/job/belt"  # Push metrics.prom to a Pushgateway
+# min_workload_ms = 1.0  # Flag saves faster than this per tick as measuring engine overhead; 0 disables
+# abort_on_newer_saves = false  # Fail instead of skipping saves newer than the Factorio binary
+# exclude_interference = false  # Leave out ticks dominated by chart refreshes and CRC computations
+# jobs = 1
+# numa_node = 0  # Bind to the cores and memory of this NUMA node (Linux)
+# cpu_affinity = "0-7"  # Pin Factorio to these cores, e.g. the performance cores (Linux, Windows)
+# ms_decimals = 3
+# ups_decimals = 0
+# percent_decimals = 2
+# thousands_separator = ","
+# decimal_separator = "."
+
+# Extra verbose metrics computed from Factorio's, exported with --verbose-metrics
+# [benchmark.derived_metrics]
+# gameUpdateOther = "gameUpdate - transportLinesUpdate - electricNetworkUpdate - entityUpdate"
+
+# Variables for custom report templates, rendered as {{vars.<key>}}
+# [benchmark.extra_vars]
+# description = "Belt weaves compared on the test rig"
 
 [sanitize]
 # ticks = 3600
 # headless = false
+# surface_stats = false
+# rcon = false  # Query the map over RCON instead of running the belt-sanitizer mod
 
 [blueprint]
 # count = 10
 # buffer_ticks = 120
 # headless = false
+# benchmark = false
+
+[retention]
+# outputs_dir = "benchmarks"  # Holds one output directory per session, e.g. --output "benchmarks/{timestamp}"
+# keep_last = 10
+# max_size_gb = 5
 "#;
         std::fs::write(&config_file, example_config)
             .map_err(|e| BenchmarkErrorKind::ConfigLoadError(e.to_string()))?;
@@ -0,0 +1,199 @@
+//! Metrics derived from the verbose metrics, configured as sums and differences of their columns:
+//!
+//! ```toml
+//! [benchmark.derived_metrics]
+//! gameUpdateOther = "gameUpdate - transportLinesUpdate - electricNetworkUpdate - entityUpdate"
+//! ```
+//!
+//! They're exported as extra columns of the verbose metrics CSV, like the metrics Factorio reports.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::core::{
+    error::{BenchmarkErrorKind, Result},
+    metrics::{MetricCategory, MetricDescriptor, MetricUnit},
+};
+
+/// A metric computed from other metrics of the same tick
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivedMetric {
+    pub name: String,
+    pub expression: String,
+    terms: Vec<Term>,
+}
+
+/// `factor * metric`, or a constant without a metric
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    factor: f64,
+    metric: Option<String>,
+}
+
+impl DerivedMetric {
+    /// Parse `expression`: metrics and numbers joined by `+` and `-`, where a metric can be
+    /// scaled like `0.5 * gameUpdate`
+    pub fn parse(name: &str, expression: &str) -> Result<Self> {
+        let invalid = |reason: String| BenchmarkErrorKind::InvalidDerivedMetric {
+            name: name.to_string(),
+            reason,
+        };
+
+        if name.is_empty() || !name.chars().all(is_metric_char) {
+            return Err(
+                invalid("the name may only contain letters, digits and _".to_string()).into(),
+            );
+        }
+
+        let mut terms = Vec::new();
+        let mut sign = 1.0;
+        let mut rest = expression.trim();
+        loop {
+            let end = rest.find(['+', '-']).unwrap_or(rest.len());
+            let term = rest[..end].trim();
+            if term.is_empty() {
+                return Err(invalid(format!("missing operand in '{expression}'")).into());
+            }
+            terms.push(parse_term(term, sign).map_err(invalid)?);
+
+            if end == rest.len() {
+                break;
+            }
+            sign = if rest[end..].starts_with('-') {
+                -1.0
+            } else {
+                1.0
+            };
+            rest = &rest[end + 1..];
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            expression: expression.trim().to_string(),
+            terms,
+        })
+    }
+
+    /// Every `name = expression` of the config, in name order
+    pub fn from_config(derived_metrics: &BTreeMap<String, String>) -> Result<Vec<Self>> {
+        derived_metrics
+            .iter()
+            .map(|(name, expression)| Self::parse(name, expression))
+            .collect()
+    }
+
+    /// The metrics the expression reads
+    pub fn operands(&self) -> impl Iterator<Item = &str> {
+        self.terms.iter().filter_map(|term| term.metric.as_deref())
+    }
+
+    /// The value for one tick, `None` if one of the metrics has no numeric value
+    pub fn evaluate(&self, value_of: impl Fn(&str) -> Option<f64>) -> Option<f64> {
+        self.terms.iter().try_fold(0.0, |sum, term| {
+            let value = match &term.metric {
+                Some(metric) => value_of(metric)?,
+                None => 1.0,
+            };
+            Some(sum + term.factor * value)
+        })
+    }
+
+    /// Derived metrics are measured in the unit of the timing metrics they're built from
+    pub fn descriptor(&self) -> MetricDescriptor {
+        MetricDescriptor {
+            name: self.name.clone(),
+            unit: MetricUnit::Nanoseconds,
+            category: MetricCategory::Derived,
+            description: format!("Derived: {}", self.expression),
+        }
+    }
+
+    /// The value for one row of Factorio's verbose CSV, as exported
+    pub fn evaluate_record(
+        &self,
+        record: &csv::StringRecord,
+        header_map: &HashMap<String, usize>,
+    ) -> String {
+        self.evaluate(|metric| {
+            header_map
+                .get(metric)
+                .and_then(|&index| record.get(index))
+                .and_then(|value| value.parse().ok())
+        })
+        .map_or_else(
+            || "N/A".to_string(),
+            |value: f64| {
+                if value.fract() == 0.0 {
+                    format!("{value:.0}")
+                } else {
+                    value.to_string()
+                }
+            },
+        )
+    }
+}
+
+fn is_metric_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn parse_term(term: &str, sign: f64) -> std::result::Result<Term, String> {
+    let (factor, metric) = match term.split_once('*') {
+        Some((factor, metric)) => (
+            factor
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("'{}' is not a number", factor.trim()))?,
+            metric.trim(),
+        ),
+        None => match term.parse::<f64>() {
+            Ok(constant) => {
+                return Ok(Term {
+                    factor: sign * constant,
+                    metric: None,
+                });
+            }
+            Err(_) => (1.0, term),
+        },
+    };
+
+    if metric.is_empty() || !metric.chars().all(is_metric_char) {
+        return Err(format!("'{metric}' is not a metric name"));
+    }
+    Ok(Term {
+        factor: sign * factor,
+        metric: Some(metric.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_evaluate_derived_metric() {
+        let metric = DerivedMetric::parse(
+            "gameUpdateOther",
+            "gameUpdate - transportLinesUpdate - 0.5 * entityUpdate + 10",
+        )
+        .expect("parse");
+        assert_eq!(
+            metric.operands().collect::<Vec<_>>(),
+            vec!["gameUpdate", "transportLinesUpdate", "entityUpdate"]
+        );
+
+        let values = HashMap::from([
+            ("gameUpdate", 1000.0),
+            ("transportLinesUpdate", 200.0),
+            ("entityUpdate", 400.0),
+        ]);
+        assert_eq!(
+            metric.evaluate(|name| values.get(name).copied()),
+            Some(610.0)
+        );
+        assert_eq!(metric.evaluate(|_| None), None);
+
+        assert!(DerivedMetric::parse("x", "gameUpdate -").is_err());
+        assert!(DerivedMetric::parse("x", "a * gameUpdate").is_err());
+        assert!(DerivedMetric::parse("x y", "gameUpdate").is_err());
+    }
+}
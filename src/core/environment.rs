@@ -0,0 +1,172 @@
+//! Snapshots of the machine recorded in `results.csv`: its conditions at the start of each run,
+//! to correlate slow runs with e.g. thermal throttling, and its hardware and system at the start
+//! of the session, to compare results measured on different machines.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Components, CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+
+/// Sensor labels of CPU temperatures across drivers, e.g. `coretemp Package id 0` (Intel) or
+/// `k10temp Tctl` (AMD)
+const CPU_SENSOR_LABELS: [&str; 5] = ["cpu", "package", "tctl", "tdie", "core"];
+
+/// Conditions at the start of a run
+#[derive(Debug, Clone)]
+pub struct EnvironmentSnapshot {
+    pub started_at: DateTime<Local>,
+    /// Highest CPU sensor temperature in °C, when sensors are readable
+    pub cpu_temp_c: Option<f64>,
+    /// Mean frequency of all cores in MHz
+    pub cpu_freq_mhz: Option<u64>,
+}
+
+impl EnvironmentSnapshot {
+    pub fn capture() -> Self {
+        let components = Components::new_with_refreshed_list();
+        let cpu_temp_c = components
+            .iter()
+            .filter(|component| {
+                let label = component.label().to_lowercase();
+                CPU_SENSOR_LABELS.iter().any(|cpu| label.contains(cpu))
+            })
+            .filter_map(|component| component.temperature())
+            .filter(|temperature| temperature.is_finite())
+            .map(f64::from)
+            .max_by(f64::total_cmp);
+
+        let sys = System::new_with_specifics(
+            RefreshKind::nothing().with_cpu(CpuRefreshKind::nothing().with_frequency()),
+        );
+        let frequencies: Vec<u64> = sys
+            .cpus()
+            .iter()
+            .map(|cpu| cpu.frequency())
+            .filter(|&frequency| frequency > 0)
+            .collect();
+        let cpu_freq_mhz = (!frequencies.is_empty())
+            .then(|| frequencies.iter().sum::<u64>() / frequencies.len() as u64);
+
+        Self {
+            started_at: Local::now(),
+            cpu_temp_c,
+            cpu_freq_mhz,
+        }
+    }
+}
+
+/// Hardware and system of the machine at the start of a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SystemSnapshot {
+    pub cpu_model: Option<String>,
+    /// Logical cores
+    pub cpu_cores: Option<usize>,
+    /// Total memory in GiB
+    pub memory_gb: Option<f64>,
+    /// CPU frequency governor on Linux, active power plan on Windows
+    pub power_profile: Option<String>,
+    pub os_version: Option<String>,
+}
+
+impl SystemSnapshot {
+    pub fn capture() -> Self {
+        let sys = System::new_with_specifics(
+            RefreshKind::nothing()
+                .with_cpu(CpuRefreshKind::nothing())
+                .with_memory(MemoryRefreshKind::nothing().with_ram()),
+        );
+
+        let cpu_model = sys
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string())
+            .filter(|brand| !brand.is_empty());
+        let memory_gb = (sys.total_memory() > 0)
+            .then(|| (sys.total_memory() as f64 / (1u64 << 30) as f64 * 10.0).round() / 10.0);
+
+        Self {
+            cpu_model,
+            cpu_cores: (!sys.cpus().is_empty()).then(|| sys.cpus().len()),
+            memory_gb,
+            power_profile: power_profile(),
+            os_version: System::long_os_version(),
+        }
+    }
+
+    /// One line for the log, e.g. `AMD Ryzen 7 7800X3D, 16 cores, 31.2 GiB, performance`
+    pub fn summary(&self) -> String {
+        [
+            self.cpu_model.clone(),
+            self.cpu_cores.map(|cores| format!("{cores} cores")),
+            self.memory_gb.map(|gb| format!("{gb} GiB")),
+            self.power_profile.clone(),
+            self.os_version.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+/// The frequency governor of the first core
+#[cfg(target_os = "linux")]
+fn power_profile() -> Option<String> {
+    std::fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor")
+        .ok()
+        .map(|governor| governor.trim().to_string())
+        .filter(|governor| !governor.is_empty())
+}
+
+/// The name of the active power plan
+#[cfg(windows)]
+fn power_profile() -> Option<String> {
+    let output = std::process::Command::new("powercfg")
+        .arg("/getactivescheme")
+        .output()
+        .ok()?;
+    parse_power_scheme(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn power_profile() -> Option<String> {
+    None
+}
+
+/// The plan name from `powercfg /getactivescheme`, e.g.
+/// `Power Scheme GUID: 381b4222-f694-41f0-9685-ff5bb260df2e  (Balanced)`
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_power_scheme(output: &str) -> Option<String> {
+    let (_, name) = output.trim().rsplit_once('(')?;
+    name.strip_suffix(')')
+        .map(str::to_string)
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_power_scheme() {
+        assert_eq!(
+            parse_power_scheme(
+                "Power Scheme GUID: 381b4222-f694-41f0-9685-ff5bb260df2e  (Balanced)\r\n"
+            )
+            .as_deref(),
+            Some("Balanced")
+        );
+        assert_eq!(parse_power_scheme(""), None);
+
+        let snapshot = SystemSnapshot {
+            cpu_model: Some("AMD Ryzen 7 7800X3D".to_string()),
+            cpu_cores: Some(16),
+            memory_gb: Some(31.2),
+            power_profile: None,
+            os_version: Some("Linux 6.9 Arch Linux".to_string()),
+        };
+        assert_eq!(
+            snapshot.summary(),
+            "AMD Ryzen 7 7800X3D, 16 cores, 31.2 GiB, Linux 6.9 Arch Linux"
+        );
+    }
+}
@@ -31,6 +31,9 @@ pub enum BenchmarkErrorKind {
     #[error("No save files found matching pattern '{pattern}' in {directory}")]
     NoSaveFilesFound { pattern: String, directory: PathBuf },
 
+    #[error("No save files tagged with any of [{tags}] in {directory}")]
+    NoSaveFilesWithTags { tags: String, directory: PathBuf },
+
     #[error("Invalid save file: {path} - {reason}")]
     InvalidSaveFile { path: PathBuf, reason: String },
 
@@ -49,12 +52,30 @@ pub enum BenchmarkErrorKind {
     #[error("Factorio process failed with exit code {code}.")]
     FactorioProcessFailed { code: i32 },
 
+    #[error(
+        "The save was written by Factorio {save_version}, newer than this Factorio {game_version}"
+    )]
+    SaveVersionTooNew {
+        save_version: String,
+        game_version: String,
+    },
+
+    #[error("{hook} hook `{command}` failed with exit code {code}")]
+    HookFailed {
+        hook: String,
+        command: String,
+        code: i32,
+    },
+
     #[error("Template render error: {0}")]
     TemplateRenderError(#[from] handlebars::RenderError),
 
     #[error("Template error: {0}")]
     TemplateError(#[from] handlebars::TemplateError),
 
+    #[error("Template {path} failed the check: {reason}")]
+    InvalidTemplate { path: PathBuf, reason: String },
+
     #[error("CSV error: {0}")]
     CsvError(#[from] csv::Error),
 
@@ -73,6 +94,88 @@ pub enum BenchmarkErrorKind {
     #[error("Invalid run order: {input}. Valid options: sequential, random, grouped")]
     InvalidRunOrder { input: String },
 
+    #[error("Invalid cache policy: {input}. Valid options: warm, cold, asis")]
+    InvalidCachePolicy { input: String },
+
+    #[error("Invalid group regex '{input}': {reason}")]
+    InvalidGroupRegex { input: String, reason: String },
+
+    #[error("Invalid sort order: {input}. Valid options: ups, name, input-order")]
+    InvalidSortBy { input: String },
+
+    #[error("Invalid downsampling strategy: {input}. Valid options: lttb, minmax, mean")]
+    InvalidDownsample { input: String },
+
+    #[error("Unsupported shell: {input}. Valid options: bash, zsh, fish")]
+    InvalidShell { input: String },
+
+    #[error("Invalid name case: {input}. Valid options: asis, lower, upper, title")]
+    InvalidNameCase { input: String },
+
+    #[error("Invalid name replacement '{input}': {reason}")]
+    InvalidNameReplace { input: String, reason: String },
+
+    #[error("Invalid UPS definition: {input}. Valid options: total, average, capped")]
+    InvalidUpsDefinition { input: String },
+
+    #[error("Invalid percentage: {input}. Expected e.g. 3% or 2.5")]
+    InvalidPercent { input: String },
+
+    #[error("Invalid Factorio version: {input}. Expected major.minor.patch, e.g. 2.0.55")]
+    InvalidFactorioVersion { input: String },
+
+    #[error("Versions directory does not exist: {path}")]
+    VersionsDirectoryNotFound { path: PathBuf },
+
+    #[error("Factorio {version} is not installed in {directory}")]
+    VersionNotInstalled { version: String, directory: PathBuf },
+
+    #[error("The good version {good} must be older than the bad version {bad}")]
+    InvalidBisectRange { good: String, bad: String },
+
+    #[error("Invalid duration: {input}. Expected a value like 90s, 30m or 1h30m")]
+    InvalidDuration { input: String },
+
+    #[error("--jobs cannot be combined with {option}")]
+    JobsIncompatible { option: String },
+
+    #[error("--ab-mods cannot be combined with {option}")]
+    AbModsIncompatible { option: String },
+
+    #[error("--native-runs cannot be combined with {option}")]
+    NativeRunsIncompatible { option: String },
+
+    #[error("--jobs {jobs} needs at least {jobs} CPU cores, found {available}")]
+    NotEnoughCores { jobs: usize, available: usize },
+
+    #[error("NUMA node {node} is not available: {reason}")]
+    NumaNodeUnavailable { node: u32, reason: String },
+
+    #[error("Aborted by --strict: {message}")]
+    StrictModeWarning { message: String },
+
+    #[error("Invalid CPU affinity '{input}': {reason}")]
+    InvalidCpuAffinity { input: String, reason: String },
+
+    #[error("Failed to pin to CPU cores: {reason}")]
+    CpuAffinityFailed { reason: String },
+
+    #[error("{path} is in use by the BELT session of process {pid}; pass --force to take it over")]
+    SessionLocked { path: PathBuf, pid: u32 },
+
+    #[error("{save} was saved with other mods than {reference}: {difference}")]
+    ModSetMismatch {
+        save: String,
+        reference: String,
+        difference: String,
+    },
+
+    #[error("Failed to lock the CPU frequency: {reason}")]
+    FrequencyLockFailed { reason: String },
+
+    #[error("Scenario {path} did not produce a save")]
+    ScenarioSaveNotFound { path: PathBuf },
+
     #[error("Invalid WriteData")]
     InvalidWriteData,
 
@@ -117,10 +220,82 @@ pub enum BenchmarkErrorKind {
 
     #[error("Configuration file not found: {0}")]
     ConfigNotFound(PathBuf),
+
+    #[error("No verbose metric header found in output of {path}")]
+    VerboseHeaderNotFound { path: PathBuf },
+
+    #[error("No results.csv found in {path}")]
+    ResultsNotFound { path: PathBuf },
+
+    #[error("No raw logs found in {path}")]
+    NoRawLogsFound { path: PathBuf },
+
+    #[error("{path} failed verification with {issues} issue(s)")]
+    VerificationFailed { path: PathBuf, issues: usize },
+
+    #[error("{regressions} save(s) regressed by more than {threshold_percent}%")]
+    PerformanceRegression {
+        regressions: usize,
+        threshold_percent: f64,
+    },
+
+    #[error("Invalid mod settings file {path}: {reason}")]
+    InvalidModSettings { path: PathBuf, reason: String },
+
+    #[error("Invalid saves manifest {path}: {reason}")]
+    InvalidSavesManifest { path: PathBuf, reason: String },
+
+    #[error("Could not find a cache directory for --use-cache")]
+    CacheDirectoryNotFound,
+
+    #[error("Invalid tick range '{input}', expected START..END with START < END, e.g. 1000..5000")]
+    InvalidTickRange { input: String },
+
+    #[error("The tick range {range} ends after the {ticks} measured ticks")]
+    TickRangeOutOfBounds { range: String, ticks: u32 },
+
+    #[error("Invalid template variable '{input}': expected KEY=VALUE")]
+    InvalidTemplateVar { input: String },
+
+    #[error("Invalid derived metric '{name}': {reason}")]
+    InvalidDerivedMetric { name: String, reason: String },
+
+    #[error("Could not migrate {path}: {reason}")]
+    MigrationFailed { path: PathBuf, reason: String },
+
+    #[error(
+        "No retention policy configured; set keep_last or max_size_gb in the [retention] section"
+    )]
+    NoRetentionPolicy,
+
+    #[error("Outputs directory not found: {path}")]
+    OutputsDirectoryNotFound { path: PathBuf },
+
+    #[error("No outputs directory given; pass one or set outputs_dir in the [retention] config")]
+    NoOutputsDirectory,
+
+    #[error("Invalid setting assignment '{input}': {reason}")]
+    InvalidSettingAssignment { input: String, reason: String },
+
+    #[error("RCON connection to {address} failed: {reason}")]
+    RconConnectionFailed { address: String, reason: String },
+
+    #[error("Invalid Pushgateway URL {url}; expected e.g. http://localhost:9091/metrics/job/belt")]
+    InvalidPushUrl { url: String },
+
+    #[error("Pushing metrics to {url} failed: {reason}")]
+    PushFailed { url: String, reason: String },
+
+    #[error("The Factorio server rejected the RCON password")]
+    RconAuthenticationFailed,
 }
 
 /// Get a hint for the FactorioProcessFailed error, if it exists
 impl BenchmarkError {
+    pub fn kind(&self) -> &BenchmarkErrorKind {
+        &self.kind
+    }
+
     /// Attaches a hint to the error
     pub fn with_hint(mut self, hint: Option<impl Into<String>>) -> Self {
         if let Some(hint) = hint {
@@ -1,5 +1,6 @@
 //! The wrapper for the Factorio binary.
 
+use chrono::Local;
 use std::{
     path::{Path, PathBuf},
     process::Stdio,
@@ -9,31 +10,58 @@ use std::{
     },
     time::{Duration, SystemTime},
 };
-use sysinfo::System;
-use tokio::process::Command;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tokio::{process::Command, task::JoinHandle};
 
 use crate::{
-    benchmark::runner::{CpuFrequencyData, FactorioOutput},
+    benchmark::{
+        parser,
+        runner::{CpuFrequencyData, FactorioOutput},
+    },
     core::{
         Result,
         error::{BenchmarkError, BenchmarkErrorKind},
-        is_executable, utils,
+        instances::FactorioInstance,
+        is_executable,
+        journal::Journal,
+        mods::SessionModsDir,
+        rcon::FactorioServer,
+        utils,
     },
 };
 
-use super::platform;
+use super::platform::{self, NumaNode, affinity};
 
+#[derive(Clone)]
 pub struct FactorioExecutor {
     executable_path: PathBuf,
+    journal: Arc<Journal>,
+    session_mods_dir: Option<Arc<SessionModsDir>>,
+    instance: Option<Arc<FactorioInstance>>,
+    numa_node: Option<Arc<NumaNode>>,
+    cpu_affinity: Option<Arc<Vec<usize>>>,
 }
 
 pub struct FactorioTickRunSpec<'a> {
     pub save_file: &'a Path,
     pub ticks: u32,
+    /// Value of `--benchmark-runs`: runs Factorio makes of the save in one go
+    pub runs: u32,
     pub mods_dir: Option<&'a Path>,
-    pub verbose_all_metrics: bool,
+    /// Value of `--benchmark-verbose`: `all` or a comma-separated list of metrics
+    pub verbose_metrics: Option<String>,
     pub headless: bool,
     pub record_cpu: bool,
+    /// Sample the CPU usage of other processes while Factorio runs
+    pub sample_background_load: bool,
+}
+
+pub struct FactorioScenarioRunSpec<'a> {
+    pub scenario_dir: &'a Path,
+    pub ticks: u32,
+    pub mods_dir: Option<&'a Path>,
+    /// Where to put the save the scenario produced
+    pub save_file: &'a Path,
 }
 
 pub struct FactorioSaveRunSpec<'a> {
@@ -45,7 +73,70 @@ pub struct FactorioSaveRunSpec<'a> {
 
 impl FactorioExecutor {
     pub fn new(executable_path: PathBuf) -> Self {
-        Self { executable_path }
+        Self {
+            executable_path,
+            journal: Arc::new(Journal::disabled()),
+            session_mods_dir: None,
+            instance: None,
+            numa_node: None,
+            cpu_affinity: None,
+        }
+    }
+
+    /// Record every spawned command in the given journal
+    pub fn with_journal(mut self, journal: Arc<Journal>) -> Self {
+        self.journal = journal;
+        self
+    }
+
+    /// Getter for the journal
+    pub fn journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    /// Sync and run with this mods directory whenever no explicit one is given
+    pub fn with_session_mods_dir(mut self, session_mods_dir: Arc<SessionModsDir>) -> Self {
+        self.session_mods_dir = Some(session_mods_dir);
+        self
+    }
+
+    /// The session mods directory, if one is used
+    pub fn session_mods_dir(&self) -> Option<&Path> {
+        self.session_mods_dir.as_deref().map(SessionModsDir::path)
+    }
+
+    /// Run every command as this isolated instance: its own config and write-data directory,
+    /// pinned to its cores
+    pub fn with_instance(mut self, instance: Arc<FactorioInstance>) -> Self {
+        self.instance = Some(instance);
+        self
+    }
+
+    /// The instance commands run as, if any
+    pub fn instance(&self) -> Option<&FactorioInstance> {
+        self.instance.as_deref()
+    }
+
+    /// Bind every command to the cores and memory of this NUMA node
+    pub fn with_numa_node(mut self, numa_node: Arc<NumaNode>) -> Self {
+        self.numa_node = Some(numa_node);
+        self
+    }
+
+    /// The NUMA node commands are bound to, if any
+    pub fn numa_node(&self) -> Option<&NumaNode> {
+        self.numa_node.as_deref()
+    }
+
+    /// Pin every command to these cores
+    pub fn with_cpu_affinity(mut self, cores: Arc<Vec<usize>>) -> Self {
+        self.cpu_affinity = Some(cores);
+        self
+    }
+
+    /// The cores commands are pinned to with `--cpu-affinity`, if any
+    pub fn cpu_affinity(&self) -> Option<&[usize]> {
+        self.cpu_affinity.as_deref().map(Vec::as_slice)
     }
 
     /// Find the binary and create a FactorioExecutor with that path
@@ -95,7 +186,62 @@ impl FactorioExecutor {
 
     /// Public API for creating a command
     pub fn create_command(&self) -> Command {
-        Command::new(&self.executable_path)
+        let mut cmd = Command::new(&self.executable_path);
+        // Don't leave Factorio running when a run is abandoned, e.g. by another `--jobs` instance
+        cmd.kill_on_drop(true);
+        if let Some(instance) = &self.instance {
+            cmd.arg("--config").arg(instance.config_path());
+        }
+
+        // An instance's cores are a subset of the affinity's, which are a subset of the NUMA node's
+        let cores = match (&self.instance, &self.cpu_affinity, &self.numa_node) {
+            (Some(instance), _, _) => Some(instance.cores()),
+            (None, Some(cores), _) => Some(cores.as_slice()),
+            (None, None, Some(numa_node)) => Some(numa_node.cores.as_slice()),
+            (None, None, None) => None,
+        };
+        if let Some(cores) = cores {
+            affinity::pin_to_cores(&mut cmd, cores);
+        }
+        if let Some(numa_node) = &self.numa_node {
+            platform::bind_memory_to_numa_node(&mut cmd, numa_node.id);
+        }
+        cmd
+    }
+
+    /// Pass `--mod-directory` with the given mods directory, falling back to the session one
+    fn add_mods_dir_arg(&self, cmd: &mut Command, mods_dir: Option<&Path>) -> Result<()> {
+        if let Some(mods_dir) = mods_dir.or(self.session_mods_dir()) {
+            cmd.arg("--mod-directory");
+            cmd.arg(
+                mods_dir
+                    .to_str()
+                    .ok_or_else(|| BenchmarkErrorKind::InvalidModsFileName {
+                        path: mods_dir.to_path_buf(),
+                    })?,
+            );
+        }
+        Ok(())
+    }
+
+    /// The first line of `factorio --version`, e.g.
+    /// `Version: 2.0.55 (build 83138, linux64, full, space-age)`
+    pub async fn version_line(&self) -> Result<String> {
+        let mut cmd = self.create_command();
+        cmd.arg("--version");
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        let output = cmd.spawn()?.wait_with_output().await?;
+        if !output.status.success() {
+            return Err(process_failed(&output));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string())
     }
 
     /// Sync Factorio's mods to the given save
@@ -111,35 +257,20 @@ impl FactorioExecutor {
                 })?,
         ]);
 
+        self.add_mods_dir_arg(&mut cmd, None)?;
+
         cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
         tracing::debug!("Syncing mods to: {}", save_file.display());
 
+        let started_at = Local::now();
         let child = cmd.spawn()?;
         let output = child.wait_with_output().await?;
+        self.journal
+            .record_command(&cmd, started_at, output.status.code());
 
         if !output.status.success() {
-            let stdout_str = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr_str = String::from_utf8_lossy(&output.stderr).to_string();
-
-            let hint = if stdout_str.contains("already running")
-                || stderr_str.contains("already running")
-            {
-                Some(
-                    "Factorio might already be running. Please close any open Factorio instances."
-                        .to_string(),
-                )
-            } else {
-                None
-            };
-
-            return Err(
-                BenchmarkError::from(BenchmarkErrorKind::FactorioProcessFailed {
-                    code: output.status.code().unwrap_or(-1),
-                })
-                .with_process_output(&stdout_str, &stderr_str)
-                .with_hint(hint),
-            );
+            return Err(process_failed(&output));
         }
 
         tracing::debug!("Mod sync completed successfully");
@@ -159,7 +290,7 @@ impl FactorioExecutor {
             "--benchmark-ticks",
             &spec.ticks.to_string(),
             "--benchmark-runs",
-            "1", // Always run single benchmark
+            &spec.runs.max(1).to_string(),
         ]);
 
         if spec.headless {
@@ -168,25 +299,17 @@ impl FactorioExecutor {
             cmd.arg("--disable-audio");
         }
 
-        if spec.verbose_all_metrics {
+        if let Some(metrics) = &spec.verbose_metrics {
             cmd.arg("--benchmark-verbose");
-            cmd.arg("all");
+            cmd.arg(metrics);
         }
 
         // Run with the argument --mod-directory if a mod-directory was given
-        if let Some(mods_dir) = spec.mods_dir {
-            cmd.arg("--mod-directory");
-            cmd.arg(
-                mods_dir
-                    .to_str()
-                    .ok_or_else(|| BenchmarkErrorKind::InvalidModsFileName {
-                        path: mods_dir.to_path_buf(),
-                    })?,
-            );
-        }
+        self.add_mods_dir_arg(&mut cmd, spec.mods_dir)?;
 
         cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
+        let started_at = Local::now();
         let child = cmd.spawn()?;
 
         let cpu_freqs = Arc::new(Mutex::new(Vec::<CpuFrequencyData>::new()));
@@ -217,13 +340,32 @@ impl FactorioExecutor {
             None
         };
 
+        let load_samples = Arc::new(Mutex::new(Vec::<f64>::new()));
+        let load_sampler = match child.id() {
+            Some(pid) if spec.sample_background_load => Some(spawn_background_load_sampler(
+                pid,
+                Arc::clone(&load_samples),
+            )),
+            _ => None,
+        };
+
         let output = child.wait_with_output().await?;
+        self.journal
+            .record_command(&cmd, started_at, output.status.code());
 
         if let Some(cpu_logger) = cpu_logger {
             cpu_logger.abort();
             // Wait for cpu_logger to die
             let _ = cpu_logger.await;
         }
+        if let Some(load_sampler) = load_sampler {
+            load_sampler.abort();
+            let _ = load_sampler.await;
+        }
+        let background_load = load_samples
+            .lock()
+            .ok()
+            .and_then(|samples| crate::core::mean(&samples));
         // Get rid of the Arc and Mutex
         let cpu_frequency_data = Arc::into_inner(cpu_freqs)
             .and_then(|mutex| mutex.into_inner().ok())
@@ -233,53 +375,116 @@ impl FactorioExecutor {
             });
 
         if !output.status.success() {
-            let stdout_str = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr_str = String::from_utf8_lossy(&output.stderr).to_string();
-
-            let hint = if stdout_str.contains("already running")
-                || stderr_str.contains("already running")
-            {
-                Some(
-                    "Factorio might already be running. Please close any open Factorio instances."
-                        .to_string(),
-                )
-            } else {
-                None
-            };
-
-            tracing::debug!("Out: {stdout_str}");
-            tracing::debug!("Err: {stderr_str}");
+            tracing::debug!("Out: {}", String::from_utf8_lossy(&output.stdout));
+            tracing::debug!("Err: {}", String::from_utf8_lossy(&output.stderr));
 
-            return Err(
-                BenchmarkError::from(BenchmarkErrorKind::FactorioProcessFailed {
-                    code: output.status.code().unwrap_or(-1),
-                })
-                .with_process_output(&stdout_str, &stderr_str)
-                .with_hint(hint),
-            );
+            return Err(process_failed(&output));
         }
 
-        let summary = String::from_utf8_lossy(&output.stderr).to_string()
+        let raw_log = String::from_utf8_lossy(&output.stderr).to_string()
             + String::from_utf8_lossy(&output.stdout).as_ref();
+        let (summary, verbose_data) = split_output(&raw_log);
+
+        Ok(FactorioOutput {
+            summary,
+            verbose_data,
+            cpu_data: cpu_frequency_data,
+            background_load,
+            energy: None,
+            environment: None,
+            raw_log,
+        })
+    }
 
-        const VERBOSE_HEADER: &str = "tick,timestamp,wholeUpdate";
+    /// Start the scenario as a server in a throwaway write-data directory, stop it after the given
+    /// ticks and copy the map it saved to `spec.save_file`
+    pub async fn run_scenario(&self, spec: FactorioScenarioRunSpec<'_>) -> Result<()> {
+        let name = spec
+            .scenario_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| BenchmarkErrorKind::InvalidSaveFileName {
+                path: spec.scenario_dir.to_path_buf(),
+            })?;
+
+        // Factorio only looks for scenarios in the write-data directory
+        let instance = Arc::new(FactorioInstance::create(Vec::new())?);
+        utils::copy_dir_all(
+            spec.scenario_dir,
+            &instance.write_dir().join("scenarios").join(name),
+        )?;
+        let factorio = self.clone().with_instance(instance.clone());
+
+        let mut cmd = factorio.create_command();
+        cmd.args([
+            "--start-server-load-scenario",
+            name,
+            "--until-tick",
+            &spec.ticks.to_string(),
+        ]);
+        factorio.add_mods_dir_arg(&mut cmd, spec.mods_dir)?;
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
-        if let Some(index) = summary.find(VERBOSE_HEADER) {
-            let (summary, verbose_part) = summary.split_at(index);
-            let (summary, cleaned_verbose_data) = split_verbose_output(summary, verbose_part);
+        let started_at = Local::now();
+        let output = cmd.spawn()?.wait_with_output().await?;
+        self.journal
+            .record_command(&cmd, started_at, output.status.code());
 
-            Ok(FactorioOutput {
-                summary,
-                verbose_data: Some(cleaned_verbose_data),
-                cpu_data: cpu_frequency_data,
-            })
-        } else {
-            Ok(FactorioOutput {
-                summary,
-                verbose_data: None,
-                cpu_data: cpu_frequency_data,
-            })
+        if !output.status.success() {
+            return Err(process_failed(&output));
         }
+
+        let saved =
+            utils::newest_save_file(&instance.write_dir().join("saves")).ok_or_else(|| {
+                BenchmarkError::from(BenchmarkErrorKind::ScenarioSaveNotFound {
+                    path: spec.scenario_dir.to_path_buf(),
+                })
+                .with_process_output(
+                    &String::from_utf8_lossy(&output.stdout),
+                    &String::from_utf8_lossy(&output.stderr),
+                )
+            })?;
+        std::fs::copy(saved, spec.save_file)?;
+
+        Ok(())
+    }
+
+    /// Start the save as a headless server with RCON on a free local port, in a throwaway
+    /// write-data directory. The server runs until stopped or dropped.
+    pub fn start_rcon_server(
+        &self,
+        save_file: &Path,
+        mods_dir: Option<&Path>,
+    ) -> Result<FactorioServer> {
+        let instance = Arc::new(FactorioInstance::create(Vec::new())?);
+        let factorio = self.clone().with_instance(instance.clone());
+        let address = FactorioServer::free_address()?;
+        let password = format!("{:016x}", rand::random::<u64>());
+
+        let mut cmd = factorio.create_command();
+        cmd.args([
+            "--start-server",
+            save_file
+                .to_str()
+                .ok_or_else(|| BenchmarkErrorKind::InvalidSaveFileName {
+                    path: save_file.to_path_buf(),
+                })?,
+            "--rcon-bind",
+            &address.to_string(),
+            "--rcon-password",
+            &password,
+        ]);
+        factorio.add_mods_dir_arg(&mut cmd, mods_dir)?;
+        cmd.stdout(Stdio::null()).stderr(Stdio::null());
+
+        tracing::debug!("Starting {} as a server on {address}", save_file.display());
+        self.journal.record_command(&cmd, Local::now(), None);
+        Ok(FactorioServer {
+            child: cmd.spawn()?,
+            address,
+            password,
+            _instance: instance,
+        })
     }
 
     pub async fn run_for_save(
@@ -305,19 +510,11 @@ impl FactorioExecutor {
             cmd.arg("--disable-audio");
         }
 
-        if let Some(mods_dir) = spec.mods_dir {
-            cmd.arg("--mod-directory");
-            cmd.arg(
-                mods_dir
-                    .to_str()
-                    .ok_or_else(|| BenchmarkErrorKind::InvalidModsFileName {
-                        path: mods_dir.to_path_buf(),
-                    })?,
-            );
-        }
+        self.add_mods_dir_arg(&mut cmd, spec.mods_dir)?;
 
         cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
+        let started_at = Local::now();
         let mut child = cmd.spawn()?;
         let poll_duration = Duration::from_secs(1);
 
@@ -349,35 +546,85 @@ impl FactorioExecutor {
         }
 
         let output = child.wait_with_output().await?;
+        self.journal
+            .record_command(&cmd, started_at, output.status.code());
 
         if !output.status.success() && output.status.code().is_some() {
-            let stdout_str = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr_str = String::from_utf8_lossy(&output.stderr).to_string();
-
-            let hint = if stdout_str.contains("already running")
-                || stderr_str.contains("already running")
-            {
-                Some(
-                    "Factorio might already be running. Please close any open Factorio instances."
-                        .to_string(),
-                )
-            } else {
-                None
-            };
-
-            return Err(
-                BenchmarkError::from(BenchmarkErrorKind::FactorioProcessFailed {
-                    code: output.status.code().unwrap_or(-1),
-                })
-                .with_process_output(&stdout_str, &stderr_str)
-                .with_hint(hint),
-            );
+            return Err(process_failed(&output));
         }
 
         Ok(())
     }
 }
 
+/// Error for a failed Factorio process, with its output and a hint when another instance is running
+fn process_failed(output: &std::process::Output) -> BenchmarkError {
+    let stdout_str = String::from_utf8_lossy(&output.stdout);
+    let stderr_str = String::from_utf8_lossy(&output.stderr);
+
+    if let Some(kind) = parser::save_version_too_new(&stdout_str)
+        .or_else(|| parser::save_version_too_new(&stderr_str))
+    {
+        return BenchmarkError::from(kind).with_process_output(&stdout_str, &stderr_str);
+    }
+
+    let hint = (stdout_str.contains("already running") || stderr_str.contains("already running"))
+        .then_some("Factorio might already be running. Please close any open Factorio instances.");
+
+    BenchmarkError::from(BenchmarkErrorKind::FactorioProcessFailed {
+        code: output.status.code().unwrap_or(-1),
+    })
+    .with_process_output(&stdout_str, &stderr_str)
+    .with_hint(hint)
+}
+
+/// Interval between background load samples, well above sysinfo's minimum CPU update interval
+const BACKGROUND_LOAD_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Sample the CPU usage of everything but the Factorio process `pid`, in percent of the total
+/// capacity of all cores
+fn spawn_background_load_sampler(pid: u32, samples: Arc<Mutex<Vec<f64>>>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let pid = Pid::from_u32(pid);
+        let mut sys = System::new();
+        let mut interval = tokio::time::interval(BACKGROUND_LOAD_SAMPLE_INTERVAL);
+
+        // Usage is measured between refreshes, so the first refresh only sets the baseline
+        interval.tick().await;
+        sys.refresh_cpu_usage();
+        sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+
+        loop {
+            interval.tick().await;
+            sys.refresh_cpu_usage();
+            sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+
+            let cores = sys.cpus().len().max(1) as f64;
+            let factorio = sys
+                .process(pid)
+                .map_or(0.0, |process| process.cpu_usage() as f64 / cores);
+            let background = (sys.global_cpu_usage() as f64 - factorio).max(0.0);
+            if let Ok(mut samples) = samples.lock() {
+                samples.push(background);
+            }
+        }
+    })
+}
+
+/// Split Factorio's combined output into the summary and the verbose CSV section, if any
+pub fn split_output(log: &str) -> (String, Option<String>) {
+    const VERBOSE_HEADER: &str = "tick,timestamp,wholeUpdate";
+
+    match log.find(VERBOSE_HEADER) {
+        Some(index) => {
+            let (summary, verbose_part) = log.split_at(index);
+            let (summary, verbose_data) = split_verbose_output(summary, verbose_part);
+            (summary, Some(verbose_data))
+        }
+        None => (log.to_string(), None),
+    }
+}
+
 fn split_verbose_output(summary: &str, verbose_part: &str) -> (String, String) {
     let cleaned_verbose_data = verbose_part
         .lines()
@@ -407,6 +654,27 @@ fn split_verbose_output(summary: &str, verbose_part: &str) -> (String, String) {
 mod tests {
     use super::*;
 
+    #[cfg(unix)]
+    #[test]
+    fn process_failed_hints_at_running_instance() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let output = |stderr: &str| std::process::Output {
+            status: std::process::ExitStatus::from_raw(1 << 8),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+
+        let error = process_failed(&output("Error: Factorio is already running")).to_string();
+        assert!(error.contains("exit code 1"));
+        assert!(error.contains("might already be running"));
+        assert!(
+            !process_failed(&output("crash"))
+                .to_string()
+                .contains("might already")
+        );
+    }
+
     #[test]
     fn split_verbose_output_keeps_uprof_breadcrumbs_after_csv() {
         let (summary, verbose_data) = split_verbose_output(
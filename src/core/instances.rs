@@ -0,0 +1,109 @@
+//! Isolated Factorio instances for running benchmarks in parallel (`--jobs`).
+//!
+//! Each instance gets its own write-data directory, so instances don't contend for Factorio's
+//! lock file, and a disjoint set of CPU cores, so they don't contend for cores.
+
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+use crate::core::{Result, error::BenchmarkErrorKind};
+
+/// A Factorio config and write-data directory for one parallel instance, removed when dropped
+#[derive(Debug)]
+pub struct FactorioInstance {
+    cores: Vec<usize>,
+    dir: TempDir,
+}
+
+impl FactorioInstance {
+    /// Create the write-data directory and a `config.ini` pointing Factorio at it
+    pub fn create(cores: Vec<usize>) -> Result<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix("belt-instance-")
+            .tempdir()?;
+
+        let config = format!(
+            "; Generated by BELT for a parallel benchmark instance\n\
+             [path]\n\
+             read-data=__PATH__system-read-data__\n\
+             write-data={}\n",
+            dir.path().display()
+        );
+        std::fs::write(dir.path().join("config.ini"), config)?;
+
+        Ok(Self { cores, dir })
+    }
+
+    /// The write-data directory, holding `saves`, `scenarios` and the lock file
+    pub fn write_dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// The `config.ini` to pass with `--config`
+    pub fn config_path(&self) -> PathBuf {
+        self.dir.path().join("config.ini")
+    }
+
+    pub fn cores(&self) -> &[usize] {
+        &self.cores
+    }
+
+    /// The cores in a compact form such as `0-3` or `0,2,4`
+    pub fn core_set(&self) -> String {
+        format_core_set(&self.cores)
+    }
+}
+
+/// Split the available cores into `jobs` disjoint, contiguous sets of equal size
+pub fn partition_cores(available: &[usize], jobs: usize) -> Result<Vec<Vec<usize>>> {
+    let per_job = available.len() / jobs.max(1);
+    if per_job == 0 {
+        return Err(BenchmarkErrorKind::NotEnoughCores {
+            jobs,
+            available: available.len(),
+        }
+        .into());
+    }
+
+    Ok(available
+        .chunks_exact(per_job)
+        .take(jobs)
+        .map(<[usize]>::to_vec)
+        .collect())
+}
+
+/// Format cores compactly, e.g. `0-3` for contiguous cores and `0,2,4` otherwise
+pub fn format_core_set(cores: &[usize]) -> String {
+    let contiguous = cores.windows(2).all(|pair| pair[1] == pair[0] + 1);
+    match (cores.first(), cores.last()) {
+        (Some(first), Some(last)) if contiguous && first != last => format!("{first}-{last}"),
+        _ => cores
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>()
+            .join(","),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_cores() {
+        let cores: Vec<usize> = (0..10).collect();
+        let sets = partition_cores(&cores, 3).expect("enough cores");
+        assert_eq!(sets, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8]]);
+        assert_eq!(format_core_set(&sets[1]), "3-5");
+        assert_eq!(format_core_set(&[0, 2]), "0,2");
+        assert_eq!(format_core_set(&[7]), "7");
+
+        let node = [8, 9, 10, 11];
+        assert_eq!(
+            partition_cores(&node, 2).expect("enough cores"),
+            vec![vec![8, 9], vec![10, 11]]
+        );
+        assert!(partition_cores(&cores[..2], 3).is_err());
+    }
+}
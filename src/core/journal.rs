@@ -0,0 +1,306 @@
+//! Execution journal (`journal.jsonl`) recording every spawned command and produced artifact.
+
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::process::Command;
+
+use crate::{
+    benchmark::parser::BenchmarkRun,
+    core::{Result, anonymize::Anonymizer, lock::LOCK_FILENAME, platform::FrequencyLock},
+};
+
+/// File name of the journal inside the output directory
+pub const JOURNAL_FILENAME: &str = "journal.jsonl";
+
+/// A single line of the journal
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEntry {
+    Command {
+        command: Vec<String>,
+        started_at: String,
+        ended_at: String,
+        exit_code: Option<i32>,
+    },
+    /// A produced file, relative to the output directory
+    Artifact {
+        path: String,
+        /// SHA-256 of the file's contents, absent in journals of older versions
+        #[serde(default)]
+        sha256: Option<String>,
+    },
+    /// A system setting changed for the session
+    Setting {
+        name: String,
+        original: String,
+        applied: String,
+    },
+    /// The machine the session runs on
+    Machine { name: String },
+    /// The number of runs a session added for a save
+    Runs { save_name: String, runs: u32 },
+    /// An execution of a run; of a retried run only the last attempt is kept
+    RunAttempt {
+        save_name: String,
+        run: u32,
+        attempt: u32,
+        kept: bool,
+        /// Archived output, relative to the output directory
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        raw_log: Option<String>,
+    },
+}
+
+/// Appends JSON lines to the journal file, or does nothing when disabled
+#[derive(Debug, Default)]
+pub struct Journal {
+    file: Option<Mutex<File>>,
+    anonymizer: Option<Anonymizer>,
+}
+
+impl Journal {
+    /// A journal that records nothing
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Open `journal.jsonl` in the output directory, truncating it unless appending
+    pub fn open(output_dir: &Path, append: bool) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(output_dir.join(JOURNAL_FILENAME))?;
+
+        Ok(Self {
+            file: Some(Mutex::new(file)),
+            anonymizer: None,
+        })
+    }
+
+    /// Record paths without local directories, the user name or the host name
+    pub fn anonymized(mut self, anonymizer: Anonymizer) -> Self {
+        self.anonymizer = Some(anonymizer);
+        self
+    }
+
+    fn path(&self, path: &str) -> String {
+        match &self.anonymizer {
+            Some(anonymizer) => anonymizer.path(path),
+            None => path.to_string(),
+        }
+    }
+
+    /// Record a finished command with its start time and exit code
+    pub fn record_command(
+        &self,
+        cmd: &Command,
+        started_at: DateTime<Local>,
+        exit_code: Option<i32>,
+    ) {
+        let cmd = cmd.as_std();
+        let command = std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|arg| self.path(&arg.to_string_lossy()))
+            .collect();
+
+        self.record(&JournalEntry::Command {
+            command,
+            started_at: started_at.to_rfc3339(),
+            ended_at: Local::now().to_rfc3339(),
+            exit_code,
+        });
+    }
+
+    /// Record the name of the machine the session runs on
+    pub fn record_machine(&self, name: &str) {
+        self.record(&JournalEntry::Machine {
+            name: name.to_string(),
+        });
+    }
+
+    /// Record how many runs of each save the session produced
+    pub fn record_runs(&self, results: &[BenchmarkRun]) {
+        let mut runs: BTreeMap<&str, u32> = BTreeMap::new();
+        for result in results {
+            *runs.entry(result.save_name.as_str()).or_default() += 1;
+        }
+
+        for (save_name, runs) in runs {
+            self.record(&JournalEntry::Runs {
+                save_name: save_name.to_string(),
+                runs,
+            });
+        }
+    }
+
+    /// Record an attempt at a run and where its output was archived
+    pub fn record_run_attempt(
+        &self,
+        save_name: &str,
+        run: u32,
+        attempt: u32,
+        kept: bool,
+        raw_log: Option<&Path>,
+    ) {
+        self.record(&JournalEntry::RunAttempt {
+            save_name: save_name.to_string(),
+            run,
+            attempt,
+            kept,
+            raw_log: raw_log.map(|path| self.path(&path.to_string_lossy().replace('\\', "/"))),
+        });
+    }
+
+    /// Record the CPU frequency settings applied for the session, one entry per distinct change
+    pub fn record_frequency_lock(&self, lock: &FrequencyLock) {
+        let mut recorded: Vec<(&str, &str, &str)> = Vec::new();
+        for setting in lock.settings() {
+            let change = (
+                setting.name,
+                setting.original.as_str(),
+                setting.applied.as_str(),
+            );
+            if recorded.contains(&change) {
+                continue;
+            }
+            recorded.push(change);
+
+            self.record(&JournalEntry::Setting {
+                name: setting.name.to_string(),
+                original: setting.original.clone(),
+                applied: setting.applied.clone(),
+            });
+        }
+    }
+
+    /// Record every file below `dir` modified since `since` with its checksum, except the journal
+    /// itself and the session lock
+    pub fn record_artifacts_since(&self, dir: &Path, since: SystemTime) {
+        if self.file.is_none() {
+            return;
+        }
+
+        let mut artifacts = Vec::new();
+        collect_files_modified_since(dir, since, &mut artifacts);
+        artifacts.sort();
+
+        for path in artifacts.iter().filter(|path| {
+            path.file_name()
+                .is_none_or(|name| name != JOURNAL_FILENAME && name != LOCK_FILENAME)
+        }) {
+            let relative = path.strip_prefix(dir).unwrap_or(path);
+            self.record(&JournalEntry::Artifact {
+                path: relative.to_string_lossy().replace('\\', "/"),
+                sha256: sha256_file(path).ok(),
+            });
+        }
+    }
+
+    fn record(&self, entry: &JournalEntry) {
+        let Some(file) = &self.file else {
+            return;
+        };
+
+        let result = serde_json::to_string(entry)
+            .map_err(std::io::Error::other)
+            .and_then(|line| match file.lock() {
+                Ok(mut file) => writeln!(file, "{line}"),
+                Err(_) => Err(std::io::Error::other("journal lock poisoned")),
+            });
+
+        if let Err(err) = result {
+            tracing::warn!("Failed to write to the execution journal: {err}");
+        }
+    }
+}
+
+/// Every entry of the journal in `output_dir`; lines of unknown events are skipped
+pub fn read_journal(output_dir: &Path) -> Result<Vec<JournalEntry>> {
+    let contents = std::fs::read_to_string(output_dir.join(JOURNAL_FILENAME))?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Hex-encoded SHA-256 of a file's contents
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+fn collect_files_modified_since(dir: &Path, since: SystemTime, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+
+        if metadata.is_dir() {
+            collect_files_modified_since(&path, since, files);
+        } else if metadata.modified().is_ok_and(|modified| modified >= since) {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_records_commands_and_artifacts() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+        let since = SystemTime::now() - std::time::Duration::from_secs(1);
+
+        let journal = Journal::open(dir, false).expect("open journal");
+        let mut cmd = Command::new("factorio");
+        cmd.args(["--benchmark", "save.zip"]);
+        journal.record_command(&cmd, Local::now(), Some(0));
+
+        std::fs::write(dir.join("results.csv"), "save_name\n").expect("write artifact");
+        journal.record_artifacts_since(dir, since);
+
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(dir.join(JOURNAL_FILENAME))
+            .expect("read journal")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("valid json"))
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "command");
+        assert_eq!(
+            lines[0]["command"],
+            serde_json::json!(["factorio", "--benchmark", "save.zip"])
+        );
+        assert_eq!(lines[0]["exit_code"], 0);
+        assert_eq!(lines[1]["event"], "artifact");
+        assert_eq!(lines[1]["path"], "results.csv");
+        assert_eq!(
+            lines[1]["sha256"],
+            "f517ced8e2b51263ccd5a8c9ebc2b66a24b8cf1b7505f543d6dd4ba4c79845e5"
+        );
+    }
+}
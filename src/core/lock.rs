@@ -0,0 +1,109 @@
+//! Session locks (`belt.lock`), so two BELT sessions started by accident don't interleave their
+//! Factorio launches in the same output directory or rewrite the same `mod-settings.dat`.
+//!
+//! A lock holds the id of the process that took it. Locks of processes that are gone, e.g. after
+//! a crash, are taken over with a warning; `--force` takes over the locks of running ones too.
+
+use std::{
+    fs::OpenOptions,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use crate::core::error::{BenchmarkErrorKind, Result};
+
+/// File name of the lock inside the locked directory
+pub const LOCK_FILENAME: &str = "belt.lock";
+
+/// A held lock on a directory, released when dropped
+#[derive(Debug)]
+pub struct SessionLock {
+    path: PathBuf,
+}
+
+impl SessionLock {
+    /// Lock `dir` for this process. Fails if another running BELT session holds it, unless
+    /// `force` is set.
+    pub fn acquire(dir: &Path, force: bool) -> Result<Self> {
+        let path = dir.join(LOCK_FILENAME);
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    write!(file, "{}", std::process::id())?;
+                    tracing::debug!("Locked {}", dir.display());
+                    return Ok(Self { path });
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+                Err(err) => return Err(err.into()),
+            }
+
+            let holder = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| contents.trim().parse::<u32>().ok());
+            match holder {
+                Some(pid) if is_running(pid) && !force => {
+                    return Err(BenchmarkErrorKind::SessionLocked {
+                        path: dir.to_path_buf(),
+                        pid,
+                    }
+                    .into());
+                }
+                Some(pid) if is_running(pid) => tracing::warn!(
+                    "Taking over the lock on {} from the running BELT process {pid} (--force); their Factorio launches may interleave",
+                    dir.display()
+                ),
+                _ => tracing::warn!(
+                    "Taking over the stale lock on {} left by a BELT session that ended",
+                    dir.display()
+                ),
+            }
+            std::fs::remove_file(&path)?;
+        }
+    }
+}
+
+impl Drop for SessionLock {
+    fn drop(&mut self) {
+        // A forced takeover may have replaced the lock; only release our own
+        let ours = std::fs::read_to_string(&self.path)
+            .is_ok_and(|contents| contents.trim() == std::process::id().to_string());
+        if ours && let Err(err) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed to remove {}: {err}", self.path.display());
+        }
+    }
+}
+
+fn is_running(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    sys.process(pid).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_blocks_running_sessions_and_takes_over_stale_ones() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+
+        let lock = SessionLock::acquire(dir, false).expect("lock");
+        assert!(dir.join(LOCK_FILENAME).is_file());
+        // This process is running, so a second session is refused unless forced
+        assert!(SessionLock::acquire(dir, false).is_err());
+        drop(lock);
+        assert!(!dir.join(LOCK_FILENAME).exists());
+
+        // No process has this id, as ids are well below it
+        std::fs::write(dir.join(LOCK_FILENAME), u32::MAX.to_string()).expect("write lock");
+        let lock = SessionLock::acquire(dir, false).expect("take over stale lock");
+        let forced = SessionLock::acquire(dir, true).expect("force");
+        drop(forced);
+        drop(lock);
+        assert!(!dir.join(LOCK_FILENAME).exists());
+    }
+}
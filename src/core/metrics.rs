@@ -0,0 +1,333 @@
+//! Descriptors of the per-tick metrics reported by `--benchmark-verbose`: unit, category and a
+//! short description, so consumers don't have to assume every column is a duration.
+
+use serde::Serialize;
+
+/// Unit of the raw values Factorio reports for a metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricUnit {
+    /// Durations in nanoseconds
+    Nanoseconds,
+    /// Not a known timing metric; values are passed through unconverted
+    Unknown,
+}
+
+impl MetricUnit {
+    /// Unit of the raw values
+    pub fn raw_label(self) -> &'static str {
+        match self {
+            MetricUnit::Nanoseconds => "ns",
+            MetricUnit::Unknown => "",
+        }
+    }
+
+    /// Unit of the values [`Self::to_display`] returns
+    pub fn display_label(self) -> &'static str {
+        match self {
+            MetricUnit::Nanoseconds => "ms",
+            MetricUnit::Unknown => "",
+        }
+    }
+
+    /// Convert a raw value for display: nanoseconds become milliseconds, others are unchanged
+    pub fn to_display(self, raw: f64) -> f64 {
+        match self {
+            MetricUnit::Nanoseconds => raw / 1_000_000.0,
+            MetricUnit::Unknown => raw,
+        }
+    }
+}
+
+/// Grouping of metrics by the part of the game they measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricCategory {
+    /// Whole-tick totals
+    Tick,
+    /// Simulation steps inside `gameUpdate`
+    Simulation,
+    /// Map generation phases
+    MapGeneration,
+    /// Per entity type update time (Factorio 2.1+)
+    Entity,
+    /// Engine work outside the simulation, like scripts or charting
+    Engine,
+    /// Planet and space platform updates of Space Age
+    SpaceAge,
+    /// Derived metrics from the config
+    Derived,
+    /// Metrics BELT doesn't know
+    Other,
+}
+
+impl MetricCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            MetricCategory::Tick => "tick",
+            MetricCategory::Simulation => "simulation",
+            MetricCategory::MapGeneration => "map_generation",
+            MetricCategory::Entity => "entity",
+            MetricCategory::Engine => "engine",
+            MetricCategory::SpaceAge => "space_age",
+            MetricCategory::Derived => "derived",
+            MetricCategory::Other => "other",
+        }
+    }
+}
+
+/// What BELT knows about a verbose metric
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricDescriptor {
+    pub name: String,
+    pub unit: MetricUnit,
+    pub category: MetricCategory,
+    pub description: String,
+}
+
+/// Known timing metrics with their category and a short description
+const KNOWN_METRICS: &[(&str, MetricCategory, &str)] = &[
+    (
+        "wholeUpdate",
+        MetricCategory::Tick,
+        "Total time of the tick",
+    ),
+    (
+        "latencyUpdate",
+        MetricCategory::Tick,
+        "Latency hiding / multiplayer input handling",
+    ),
+    (
+        "gameUpdate",
+        MetricCategory::Simulation,
+        "Game state update, the sum of the simulation steps",
+    ),
+    (
+        "planetsUpdate",
+        MetricCategory::SpaceAge,
+        "Per-surface planet updates",
+    ),
+    (
+        "controlBehaviorUpdate",
+        MetricCategory::Simulation,
+        "Circuit conditions of entities",
+    ),
+    (
+        "transportLinesUpdate",
+        MetricCategory::Simulation,
+        "Belts and other transport lines",
+    ),
+    (
+        "electricHeatFluidCircuitUpdate",
+        MetricCategory::Simulation,
+        "Electric, heat, fluid and circuit networks combined",
+    ),
+    (
+        "electricNetworkUpdate",
+        MetricCategory::Simulation,
+        "Electric networks",
+    ),
+    (
+        "heatNetworkUpdate",
+        MetricCategory::Simulation,
+        "Heat networks",
+    ),
+    (
+        "fluidFlowUpdate",
+        MetricCategory::Simulation,
+        "Fluid systems",
+    ),
+    (
+        "entityUpdate",
+        MetricCategory::Simulation,
+        "Active entities, broken down by type in 2.1+",
+    ),
+    (
+        "turretTargetAcquisition",
+        MetricCategory::Simulation,
+        "Turret target search",
+    ),
+    (
+        "lightningUpdate",
+        MetricCategory::SpaceAge,
+        "Lightning strikes",
+    ),
+    (
+        "tileHeatingUpdate",
+        MetricCategory::SpaceAge,
+        "Tile heating on frozen surfaces",
+    ),
+    (
+        "pollutionUpdate",
+        MetricCategory::Simulation,
+        "Pollution spreading and absorption",
+    ),
+    ("particleUpdate", MetricCategory::Simulation, "Particles"),
+    (
+        "mapGenerator",
+        MetricCategory::MapGeneration,
+        "Map generation, the sum of its phases",
+    ),
+    (
+        "spacePlatforms",
+        MetricCategory::SpaceAge,
+        "Space platform updates",
+    ),
+    (
+        "collectorNavMesh",
+        MetricCategory::Simulation,
+        "Navigation mesh updates for units",
+    ),
+    (
+        "collectorNavMeshPathfinding",
+        MetricCategory::Simulation,
+        "Navigation mesh pathfinding",
+    ),
+    (
+        "collectorNavMeshRaycast",
+        MetricCategory::Simulation,
+        "Navigation mesh raycasts",
+    ),
+    (
+        "crcComputation",
+        MetricCategory::Engine,
+        "Multiplayer checksum computation",
+    ),
+    (
+        "consistencyScraper",
+        MetricCategory::Engine,
+        "Consistency checks",
+    ),
+    (
+        "logisticManagerUpdate",
+        MetricCategory::Simulation,
+        "Logistic network management",
+    ),
+    (
+        "constructionManagerUpdate",
+        MetricCategory::Simulation,
+        "Construction network management",
+    ),
+    (
+        "pathFinder",
+        MetricCategory::Simulation,
+        "Unit and character pathfinding",
+    ),
+    (
+        "trains",
+        MetricCategory::Simulation,
+        "Train movement and logic",
+    ),
+    (
+        "trainPathFinder",
+        MetricCategory::Simulation,
+        "Train pathfinding",
+    ),
+    (
+        "commander",
+        MetricCategory::Simulation,
+        "Enemy unit group commands",
+    ),
+    ("chartRefresh", MetricCategory::Engine, "Map chart refresh"),
+    (
+        "luaGarbageIncremental",
+        MetricCategory::Engine,
+        "Incremental Lua garbage collection",
+    ),
+    ("chartUpdate", MetricCategory::Engine, "Map chart updates"),
+    (
+        "scriptUpdate",
+        MetricCategory::Engine,
+        "Mod scripts (on_tick and events)",
+    ),
+];
+
+/// Space Age entity types of Factorio 2.1+, reported in their own section next to the
+/// [`MetricCategory::SpaceAge`] metrics
+const SPACE_AGE_ENTITIES: &[&str] = &[
+    "AgriculturalTower",
+    "Asteroid",
+    "AsteroidCollector",
+    "CargoPod",
+    "FusionGenerator",
+    "FusionReactor",
+    "Thruster",
+];
+
+/// Names of the metrics BELT knows, for completing `--verbose-metrics`
+pub fn known_metric_names() -> impl Iterator<Item = &'static str> {
+    KNOWN_METRICS
+        .iter()
+        .map(|(name, _, _)| *name)
+        .chain(SPACE_AGE_ENTITIES.iter().copied())
+}
+
+/// Whether a metric measures planets, space platforms or their entities
+pub fn is_space_age_metric(name: &str) -> bool {
+    SPACE_AGE_ENTITIES.contains(&name) || describe_metric(name).category == MetricCategory::SpaceAge
+}
+
+/// Look up the descriptor of a verbose metric; unknown metrics get [`MetricUnit::Unknown`]
+pub fn describe_metric(name: &str) -> MetricDescriptor {
+    let descriptor = |unit, category, description: String| MetricDescriptor {
+        name: name.to_string(),
+        unit,
+        category,
+        description,
+    };
+
+    if let Some((_, category, description)) =
+        KNOWN_METRICS.iter().find(|(metric, _, _)| *metric == name)
+    {
+        return descriptor(MetricUnit::Nanoseconds, *category, description.to_string());
+    }
+
+    if let Some(phase) = name.strip_prefix("mapGenerator") {
+        return descriptor(
+            MetricUnit::Nanoseconds,
+            MetricCategory::MapGeneration,
+            format!("Map generation phase: {phase}"),
+        );
+    }
+
+    // Factorio 2.1+ reports entity update time per entity type, e.g. `Inserter`
+    if name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return descriptor(
+            MetricUnit::Nanoseconds,
+            MetricCategory::Entity,
+            format!("Update time of {name} entities"),
+        );
+    }
+
+    descriptor(MetricUnit::Unknown, MetricCategory::Other, String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_metric_units_and_categories() {
+        let whole = describe_metric("wholeUpdate");
+        assert_eq!(whole.unit, MetricUnit::Nanoseconds);
+        assert_eq!(whole.category, MetricCategory::Tick);
+        assert_eq!(whole.unit.to_display(2_500_000.0), 2.5);
+
+        assert_eq!(
+            describe_metric("mapGeneratorVariations").category,
+            MetricCategory::MapGeneration
+        );
+        assert_eq!(describe_metric("Inserter").category, MetricCategory::Entity);
+        assert_eq!(
+            describe_metric("spacePlatforms").category,
+            MetricCategory::SpaceAge
+        );
+        assert!(is_space_age_metric("lightningUpdate"));
+        assert!(is_space_age_metric("Thruster"));
+        assert!(!is_space_age_metric("Inserter"));
+
+        let unknown = describe_metric("someNewCounter");
+        assert_eq!(unknown.unit, MetricUnit::Unknown);
+        assert_eq!(unknown.unit.to_display(42.0), 42.0);
+    }
+}
@@ -2,13 +2,29 @@
 //!
 //! Provides configuration, error types, Factorio process management, output handling, and platform utilities.
 
+pub mod anonymize;
 pub mod config;
+pub mod derived_metrics;
+pub mod environment;
 pub mod error;
 pub mod factorio;
+pub mod instances;
+pub mod journal;
+pub mod lock;
+pub mod metrics;
+pub mod mods;
 pub mod output;
 pub mod platform;
+pub mod propertytree;
+pub mod rcon;
+pub mod result_cache;
+pub mod save_header;
+pub mod saves;
+pub mod scenarios;
 pub mod settings;
+pub mod strict;
 pub mod utils;
+pub mod versions;
 
 pub use config::GlobalConfig;
 pub use error::Result;
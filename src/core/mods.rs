@@ -0,0 +1,84 @@
+//! Throwaway mods directory assembled per benchmark session.
+//!
+//! Syncing mods to a save rewrites `mod-list.json`, and the sanitizer writes `mod-settings.dat`.
+//! Pointing Factorio at a session copy instead of the player's mods folder guarantees BELT never
+//! changes the player's real configuration.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use tempfile::TempDir;
+
+use crate::core::{Result, platform};
+
+/// Files Factorio or BELT write to, which are copied instead of linked
+const MUTABLE_FILES: [&str; 2] = ["mod-list.json", "mod-settings.dat"];
+
+/// A temporary mods directory, removed when dropped
+#[derive(Debug)]
+pub struct SessionModsDir {
+    /// Removes the copy when dropped
+    _dir: TempDir,
+    path: PathBuf,
+}
+
+impl SessionModsDir {
+    /// Link every mod of `source` into a new temporary directory and copy its mod list and
+    /// settings
+    pub fn assemble(source: &Path) -> Result<Self> {
+        let dir = tempfile::Builder::new().prefix("belt-mods-").tempdir()?;
+        // Keep the source's name, which labels the sides of an --ab-mods comparison
+        let path = dir
+            .path()
+            .join(source.file_name().unwrap_or(OsStr::new("mods")));
+        std::fs::create_dir(&path)?;
+
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            let target = path.join(entry.file_name());
+
+            if MUTABLE_FILES.iter().any(|name| entry.file_name() == *name) {
+                std::fs::copy(entry.path(), &target)?;
+            } else {
+                platform::link_or_copy(&entry.path(), &target)?;
+            }
+        }
+
+        tracing::debug!(
+            "Assembled session mods directory {} from {}",
+            path.display(),
+            source.display()
+        );
+        Ok(Self { _dir: dir, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_mods_dir_isolates_mutable_files() {
+        let source = tempfile::tempdir().expect("temp dir");
+        std::fs::write(source.path().join("mod-list.json"), "{}").expect("write mod list");
+        std::fs::write(source.path().join("some-mod_1.0.0.zip"), "zip").expect("write mod");
+
+        let session = SessionModsDir::assemble(source.path()).expect("assemble");
+        std::fs::write(session.path().join("mod-list.json"), "changed").expect("write copy");
+
+        let original = std::fs::read_to_string(source.path().join("mod-list.json")).unwrap();
+        assert_eq!(original, "{}");
+        assert!(session.path().join("some-mod_1.0.0.zip").exists());
+        assert_eq!(session.path().file_name(), source.path().file_name());
+
+        let path = session.path().to_path_buf();
+        drop(session);
+        assert!(!path.exists());
+    }
+}
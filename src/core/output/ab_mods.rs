@@ -0,0 +1,88 @@
+//! Mods A/B comparisons (`--ab-mods`): the UPS change of each save with the second mods directory
+//! against the first, paired by round and tested for significance.
+
+use serde::Serialize;
+
+use crate::{
+    benchmark::{ab_mods, parser::BenchmarkRun},
+    core::output::NumberFormat,
+};
+
+/// Build the report section from the runs of an `--ab-mods` comparison
+pub(crate) fn build_section(results: &[BenchmarkRun], numbers: &NumberFormat) -> Vec<AbModsView> {
+    ab_mods::compare(results)
+        .into_iter()
+        .map(|comparison| AbModsView {
+            significant: if comparison.is_significant() {
+                "yes"
+            } else {
+                "no"
+            },
+            confidence_interval: comparison.confidence_interval.map_or_else(
+                || "-".to_string(),
+                |(low, high)| format!("{} to {}", signed(numbers, low), signed(numbers, high)),
+            ),
+            change: signed(numbers, comparison.change_percent),
+            ups_a: numbers.ups(comparison.ups[0]),
+            ups_b: numbers.ups(comparison.ups[1]),
+            pairs: comparison.pairs,
+            save_name: comparison.save_name,
+            labels: comparison.labels,
+        })
+        .collect()
+}
+
+fn signed(numbers: &NumberFormat, percent: f64) -> String {
+    let sign = if percent > 0.0 { "+" } else { "" };
+    format!("{sign}{}", numbers.percent(percent))
+}
+
+/// One save's comparison as rendered in the report
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AbModsView {
+    save_name: String,
+    /// Labels of the first and second mods directory
+    labels: [String; 2],
+    pairs: usize,
+    ups_a: String,
+    ups_b: String,
+    /// Mean UPS change of the second mods directory from the first
+    change: String,
+    confidence_interval: String,
+    significant: &'static str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::ab_mods::ModsVariant;
+
+    #[test]
+    fn test_ab_mods_section_formats_comparisons() {
+        let run = |side: usize, round: u32, ups: f64| BenchmarkRun {
+            index: round,
+            effective_ups: ups,
+            mods_variant: Some(ModsVariant {
+                side,
+                label: ["on", "off"][side].to_string(),
+                save_name: "base".to_string(),
+            }),
+            ..Default::default()
+        };
+        let results = vec![
+            run(0, 0, 100.0),
+            run(1, 0, 95.0),
+            run(0, 1, 100.0),
+            run(1, 1, 105.0),
+        ];
+
+        let section = build_section(&results, &NumberFormat::default());
+
+        assert_eq!(section.len(), 1);
+        assert_eq!(section[0].labels, ["on".to_string(), "off".to_string()]);
+        assert_eq!(section[0].pairs, 2);
+        assert_eq!(section[0].change, "0.00%");
+        assert_eq!(section[0].significant, "no");
+        assert!(section[0].confidence_interval.starts_with('-'));
+    }
+}
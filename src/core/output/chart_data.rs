@@ -0,0 +1,350 @@
+//! Compact chart data (`--chart-data`): `chartdata.json` with the aggregates of every save and its
+//! exported verbose metrics downsampled per tick, for static sites to embed without shipping or
+//! parsing the full CSVs.
+
+use std::{collections::BTreeMap, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    Result,
+    benchmark::parser::BenchmarkRun,
+    core::{Downsample, mean, metrics::describe_metric, output::csv::read_benchmark_csv},
+};
+
+/// File in the output directory holding the chart data
+pub const CHART_DATA_FILENAME: &str = "chartdata.json";
+
+/// Points each per-tick series is downsampled to at most
+const MAX_POINTS: usize = 500;
+
+/// Metric whose shape LTTB preserves, when exported
+const PRIMARY_METRIC: &str = "wholeUpdate";
+
+/// Write `chartdata.json` from the `results.csv` and `<save>_verbose_metrics.csv` files in
+/// `output_dir`, so appended sessions are included too
+pub fn write_chart_data(output_dir: &Path, downsample: Downsample) -> Result<()> {
+    let runs = read_benchmark_csv(&output_dir.join("results.csv"))?;
+    let data = chart_data(&runs, output_dir, downsample)?;
+
+    let path = output_dir.join(CHART_DATA_FILENAME);
+    std::fs::write(&path, serde_json::to_string(&data)?)?;
+    tracing::info!("Chart data written to {}", path.display());
+    Ok(())
+}
+
+fn chart_data(
+    runs: &[BenchmarkRun],
+    output_dir: &Path,
+    downsample: Downsample,
+) -> Result<ChartData> {
+    // Saves keep the order of the results, which is sorted by performance
+    let mut runs_by_save: Vec<(&str, Vec<&BenchmarkRun>)> = Vec::new();
+    for run in runs {
+        match runs_by_save
+            .iter_mut()
+            .find(|(save_name, _)| *save_name == run.save_name)
+        {
+            Some((_, save_runs)) => save_runs.push(run),
+            None => runs_by_save.push((&run.save_name, vec![run])),
+        }
+    }
+
+    let mut saves = Vec::new();
+    for (save_name, save_runs) in runs_by_save {
+        let csv_path = output_dir.join(format!("{save_name}_verbose_metrics.csv"));
+        let series = if csv_path.is_file() {
+            Some(read_series(&csv_path, downsample)?)
+        } else {
+            None
+        };
+
+        let mean_of = |value: fn(&BenchmarkRun) -> f64| {
+            round(mean(&save_runs.iter().map(|run| value(run)).collect::<Vec<_>>()).unwrap_or(0.0))
+        };
+        saves.push(ChartSave {
+            name: save_name.to_string(),
+            family: save_runs.iter().find_map(|run| run.family.clone()),
+            runs: save_runs.len(),
+            ups: mean_of(|run| run.effective_ups),
+            avg_ms: mean_of(|run| run.avg_ms),
+            min_ms: round(
+                save_runs
+                    .iter()
+                    .map(|run| run.min_ms)
+                    .fold(f64::INFINITY, f64::min),
+            ),
+            max_ms: round(save_runs.iter().map(|run| run.max_ms).fold(0.0, f64::max)),
+            base_diff: mean_of(|run| run.base_diff),
+            series,
+        });
+    }
+
+    Ok(ChartData {
+        factorio_version: runs
+            .first()
+            .map(|run| run.factorio_version.clone())
+            .unwrap_or_default(),
+        ticks: runs.first().map_or(0, |run| run.ticks),
+        downsample,
+        saves,
+    })
+}
+
+/// Every metric of a verbose metrics CSV averaged over its runs per tick, in display units, and
+/// downsampled to at most [`MAX_POINTS`]
+fn read_series(path: &Path, downsample: Downsample) -> Result<ChartSeries> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let metrics: Vec<String> = reader
+        .headers()?
+        .iter()
+        .skip(2)
+        .map(str::to_string)
+        .collect();
+    let units: Vec<_> = metrics
+        .iter()
+        .map(|metric| describe_metric(metric).unit)
+        .collect();
+
+    // Sums and counts of every metric per tick, over the runs
+    let mut ticks: BTreeMap<u64, (Vec<f64>, u32)> = BTreeMap::new();
+    for record in reader.records() {
+        let record = record?;
+        let Some(tick) = record.get(0).and_then(|tick| tick.parse::<u64>().ok()) else {
+            continue;
+        };
+
+        let (sums, count) = ticks
+            .entry(tick)
+            .or_insert_with(|| (vec![0.0; metrics.len()], 0));
+        for (position, unit) in units.iter().enumerate() {
+            let value = record
+                .get(position + 2)
+                .and_then(|value| value.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            sums[position] += unit.to_display(value);
+        }
+        *count += 1;
+    }
+
+    let per_tick: Vec<(u64, Vec<f64>)> = ticks
+        .into_iter()
+        .map(|(tick, (sums, count))| {
+            let means = sums.iter().map(|sum| sum / count as f64).collect();
+            (tick, means)
+        })
+        .collect();
+
+    Ok(match downsample {
+        Downsample::Lttb => {
+            let primary = metrics
+                .iter()
+                .position(|metric| metric == PRIMARY_METRIC)
+                .unwrap_or(0);
+            let points: Vec<(f64, f64)> = per_tick
+                .iter()
+                .map(|(tick, means)| (*tick as f64, means.get(primary).copied().unwrap_or(0.0)))
+                .collect();
+            let selected = lttb(&points, MAX_POINTS);
+            ChartSeries {
+                tick: selected.iter().map(|&index| per_tick[index].0).collect(),
+                metrics: metrics
+                    .iter()
+                    .enumerate()
+                    .map(|(position, metric)| {
+                        let values = selected
+                            .iter()
+                            .map(|&index| round(per_tick[index].1[position]))
+                            .collect();
+                        (metric.clone(), values)
+                    })
+                    .collect(),
+            }
+        }
+        Downsample::MinMax | Downsample::Mean => {
+            bucketed(&per_tick, &metrics, downsample == Downsample::MinMax)
+        }
+    })
+}
+
+/// Average buckets of consecutive ticks, starting each point at the first tick of its bucket.
+/// With `extremes`, each metric also gets `<metric>_min` and `<metric>_max` columns.
+fn bucketed(per_tick: &[(u64, Vec<f64>)], metrics: &[String], extremes: bool) -> ChartSeries {
+    let bucket = per_tick.len().div_ceil(MAX_POINTS).max(1);
+    let mut series = ChartSeries {
+        tick: Vec::new(),
+        metrics: BTreeMap::new(),
+    };
+    for chunk in per_tick.chunks(bucket) {
+        series.tick.push(chunk[0].0);
+        for (position, metric) in metrics.iter().enumerate() {
+            let values: Vec<f64> = chunk.iter().map(|(_, means)| means[position]).collect();
+            let mut push = |name: String, value: f64| {
+                series.metrics.entry(name).or_default().push(round(value));
+            };
+            push(metric.clone(), mean(&values).unwrap_or(0.0));
+            if extremes {
+                push(
+                    format!("{metric}_min"),
+                    values.iter().copied().fold(f64::INFINITY, f64::min),
+                );
+                push(
+                    format!("{metric}_max"),
+                    values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                );
+            }
+        }
+    }
+    series
+}
+
+/// Indices of at most `threshold` of `points` chosen with Largest Triangle Three Buckets: the
+/// first and last point, and from each bucket in between the point spanning the largest
+/// triangle with the previously chosen point and the average of the next bucket
+fn lttb(points: &[(f64, f64)], threshold: usize) -> Vec<usize> {
+    if points.len() <= threshold || threshold < 3 {
+        return (0..points.len()).collect();
+    }
+
+    let every = (points.len() - 2) as f64 / (threshold - 2) as f64;
+    let mut selected = vec![0];
+    let mut previous = 0;
+    for bucket in 0..threshold - 2 {
+        let next_start = ((bucket + 1) as f64 * every) as usize + 1;
+        let next_end = (((bucket + 2) as f64 * every) as usize + 1).min(points.len());
+        let next = &points[next_start..next_end];
+        let average_x = next.iter().map(|point| point.0).sum::<f64>() / next.len() as f64;
+        let average_y = next.iter().map(|point| point.1).sum::<f64>() / next.len() as f64;
+
+        let start = (bucket as f64 * every) as usize + 1;
+        let end = next_start.min(points.len() - 1);
+        let (previous_x, previous_y) = points[previous];
+        let area = |index: usize| {
+            let (x, y) = points[index];
+            ((previous_x - average_x) * (y - previous_y)
+                - (previous_x - x) * (average_y - previous_y))
+                .abs()
+        };
+        previous = (start..end)
+            .max_by(|&a, &b| area(a).total_cmp(&area(b)))
+            .unwrap_or(start);
+        selected.push(previous);
+    }
+    selected.push(points.len() - 1);
+    selected
+}
+
+/// Round to what charts can show, keeping the file small
+fn round(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+#[derive(Debug, Serialize)]
+struct ChartData {
+    factorio_version: String,
+    /// Ticks each run measured
+    ticks: u32,
+    /// How the series were downsampled
+    downsample: Downsample,
+    saves: Vec<ChartSave>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChartSave {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    family: Option<String>,
+    runs: usize,
+    ups: f64,
+    avg_ms: f64,
+    min_ms: f64,
+    max_ms: f64,
+    /// Percentage difference from the slowest save
+    base_diff: f64,
+    /// Downsampled per-tick values, when verbose metrics were exported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series: Option<ChartSeries>,
+}
+
+/// Columns of points: the tick of each point and a value per exported metric, plus its bucket's
+/// minimum and maximum when downsampled with [`Downsample::MinMax`]
+#[derive(Debug, Serialize)]
+struct ChartSeries {
+    tick: Vec<u64>,
+    #[serde(flatten)]
+    metrics: BTreeMap<String, Vec<f64>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chart_data_aggregates_saves_and_downsamples_series() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+        let mut csv = "tick,run,wholeUpdate\n".to_string();
+        for run in 0..2 {
+            for tick in 0..1000 {
+                csv.push_str(&format!("{tick},{run},{}\n", (run + 1) * 1_000_000));
+            }
+        }
+        std::fs::write(dir.join("base_verbose_metrics.csv"), csv).expect("write verbose metrics");
+
+        let run = |save_name: &str, avg_ms: f64| BenchmarkRun {
+            save_name: save_name.to_string(),
+            ticks: 1000,
+            avg_ms,
+            min_ms: avg_ms / 2.0,
+            max_ms: avg_ms * 2.0,
+            effective_ups: 1000.0 / avg_ms,
+            ..Default::default()
+        };
+        let runs = vec![run("base", 1.0), run("base", 2.0), run("other", 4.0)];
+
+        let data = chart_data(&runs, dir, Downsample::Mean).expect("chart data");
+
+        assert_eq!(data.ticks, 1000);
+        let names: Vec<&str> = data.saves.iter().map(|save| save.name.as_str()).collect();
+        assert_eq!(names, vec!["base", "other"]);
+        let base = &data.saves[0];
+        assert_eq!(base.runs, 2);
+        assert_eq!(base.avg_ms, 1.5);
+        assert_eq!(base.min_ms, 0.5);
+        assert_eq!(base.max_ms, 4.0);
+        assert_eq!(base.ups, 750.0);
+
+        let series = base.series.as_ref().expect("series");
+        assert_eq!(series.tick.len(), MAX_POINTS);
+        assert_eq!(series.tick[1], 2);
+        assert_eq!(series.metrics["wholeUpdate"][0], 1.5);
+        assert!(data.saves[1].series.is_none());
+    }
+
+    #[test]
+    fn test_downsampling_strategies_keep_spikes() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path().join("base_verbose_metrics.csv");
+        let mut csv = "tick,run,wholeUpdate\n".to_string();
+        for tick in 0..2000 {
+            let ns = if tick == 1234 { 50_000_000 } else { 1_000_000 };
+            csv.push_str(&format!("{tick},0,{ns}\n"));
+        }
+        std::fs::write(&path, csv).expect("write verbose metrics");
+
+        let lttb = read_series(&path, Downsample::Lttb).expect("lttb");
+        assert_eq!(lttb.tick.len(), MAX_POINTS);
+        assert_eq!((lttb.tick[0], lttb.tick[MAX_POINTS - 1]), (0, 1999));
+        assert!(lttb.tick.contains(&1234));
+        assert!(lttb.metrics["wholeUpdate"].contains(&50.0));
+
+        let min_max = read_series(&path, Downsample::MinMax).expect("minmax");
+        assert_eq!(min_max.tick.len(), MAX_POINTS);
+        assert_eq!(min_max.metrics["wholeUpdate_max"][1234 / 4], 50.0);
+        assert_eq!(min_max.metrics["wholeUpdate_min"][1234 / 4], 1.0);
+        assert_eq!(min_max.metrics["wholeUpdate"][1234 / 4], 13.25);
+
+        let mean = read_series(&path, Downsample::Mean).expect("mean");
+        assert_eq!(mean.metrics.len(), 1);
+    }
+}
@@ -8,9 +8,19 @@ use std::{
 use crate::{
     benchmark::{parser::BenchmarkRun, runner::VerboseData},
     core::{
+        confidence_interval_95,
+        derived_metrics::DerivedMetric,
+        environment::SystemSnapshot,
         error::{BenchmarkErrorKind, Result},
-        output::{ResultWriter, WriteData, ensure_output_dir},
+        mean,
+        metrics::{MetricDescriptor, describe_metric},
+        output::{
+            MARKERS_CSV_FILENAME, ResultWriter, SCALING_CSV_FILENAME, SEGMENTS_CSV_FILENAME,
+            WriteData, ensure_output_dir, scaling,
+        },
+        percentile, sample_std_dev,
     },
+    sanitize::parser::SanitizerFindings,
 };
 
 pub struct CsvWriter {}
@@ -34,7 +44,15 @@ impl ResultWriter for CsvWriter {
             WriteData::Verbose {
                 data,
                 metrics_to_export,
-            } => write_verbose_csv(data, metrics_to_export, path),
+                derived_metrics,
+                metric_summary,
+            } => {
+                write_verbose_csv(data, metrics_to_export, derived_metrics, path)?;
+                if *metric_summary && let Some(first) = data.first() {
+                    write_metric_summary_csv(&first.save_name, derived_metrics, path)?;
+                }
+                Ok(())
+            }
             _ => Err(BenchmarkErrorKind::InvalidWriteData.into()),
         }
     }
@@ -45,7 +63,15 @@ impl ResultWriter for CsvWriter {
             WriteData::Verbose {
                 data,
                 metrics_to_export,
-            } => append_verbose_csv(data, metrics_to_export, path),
+                derived_metrics,
+                metric_summary,
+            } => {
+                append_verbose_csv(data, metrics_to_export, derived_metrics, path)?;
+                if *metric_summary && let Some(first) = data.first() {
+                    write_metric_summary_csv(&first.save_name, derived_metrics, path)?;
+                }
+                Ok(())
+            }
             _ => Err(BenchmarkErrorKind::InvalidWriteData.into()),
         }
     }
@@ -59,46 +85,32 @@ fn write_benchmark_csv(results: &[BenchmarkRun], path: &Path) -> Result<()> {
 
     let mut writer = csv::Writer::from_path(&csv_path)?;
 
-    writer.write_record([
-        "save_name",
-        "run_index",
-        "execution_time_ms",
-        "avg_ms",
-        "min_ms",
-        "max_ms",
-        "effective_ups",
-        "percentage_improvement",
-        "ticks",
-        "factorio_version",
-        "platform",
-    ])?;
+    let columns = OptionalColumns::of(results);
+    writer.write_record(benchmark_header(columns))?;
 
     for result in results {
-        writer.write_record([
-            &result.save_name,
-            &result.index.to_string(),
-            &result.execution_time_ms.to_string(),
-            &result.avg_ms.to_string(),
-            &result.min_ms.to_string(),
-            &result.max_ms.to_string(),
-            &result.effective_ups.to_string(),
-            &result.base_diff.to_string(),
-            &result.ticks.to_string(),
-            &result.factorio_version,
-            &result.platform,
-        ])?;
+        writer.write_record(benchmark_record(result, columns))?;
     }
 
     writer.flush()?;
     tracing::info!("Results written to {}", csv_path.display());
 
     write_cpu_freq_csv(results, path)?;
+    write_markers_csv(results, path, false)?;
+    write_segments_csv(results, path, false)?;
+    write_scaling_csv(results, path)?;
+    write_summary_csv(path)?;
 
     Ok(())
 }
 
 /// Write factorio's verbose output to a CSV file
-fn write_verbose_csv(data: &[VerboseData], metrics: &[String], path: &Path) -> Result<()> {
+fn write_verbose_csv(
+    data: &[VerboseData],
+    metrics: &[String],
+    derived_metrics: &[DerivedMetric],
+    path: &Path,
+) -> Result<()> {
     ensure_output_dir(path)?;
 
     if data.is_empty() {
@@ -108,8 +120,7 @@ fn write_verbose_csv(data: &[VerboseData], metrics: &[String], path: &Path) -> R
     let csv_path = path.join(format!("{}_verbose_metrics.csv", data[0].save_name));
     let mut writer = csv::Writer::from_path(&csv_path)?;
 
-    let first_run_csv_data = &data[0].csv_data;
-    let mut reader = csv::Reader::from_reader(first_run_csv_data.as_bytes());
+    let mut reader = data[0].reader()?;
     let headers_from_factorio: Vec<String> =
         reader.headers()?.iter().map(|s| s.to_string()).collect();
     let header_map: HashMap<String, usize> = headers_from_factorio
@@ -119,21 +130,16 @@ fn write_verbose_csv(data: &[VerboseData], metrics: &[String], path: &Path) -> R
         .map(|(i, h)| (h, i))
         .collect();
 
-    let metrics_to_export: Vec<String> = if metrics.contains(&"all".to_string()) {
-        headers_from_factorio
-            .into_iter()
-            .filter(|h| h != "tick" && h != "timestamp")
-            .collect()
-    } else {
-        metrics.to_vec()
-    };
+    let metrics_to_export = select_verbose_metrics(&headers_from_factorio, metrics);
+    warn_missing_operands(derived_metrics, &header_map);
 
     let mut header_row = vec!["tick".to_string(), "run".to_string()];
     header_row.extend(metrics_to_export.iter().cloned());
+    header_row.extend(derived_metrics.iter().map(|metric| metric.name.clone()));
     writer.write_record(header_row)?;
 
     for (run_idx, run_data) in data.iter().enumerate() {
-        let mut inner_reader = csv::Reader::from_reader(run_data.csv_data.as_bytes());
+        let mut inner_reader = run_data.reader()?;
         // Skip headers
         let _ = inner_reader.headers()?;
 
@@ -144,19 +150,17 @@ fn write_verbose_csv(data: &[VerboseData], metrics: &[String], path: &Path) -> R
             let tick_value = tick_str.trim_start_matches('t');
 
             let mut data_row = vec![tick_value.to_string(), run_idx.to_string()];
-
-            for metric_name in &metrics_to_export {
-                if let Some(&colum_index) = header_map.get(metric_name) {
-                    let value = record.get(colum_index).unwrap_or("0");
-                    data_row.push(value.to_string());
-                } else {
-                    data_row.push("N/A".to_string());
-                }
-            }
+            data_row.extend(verbose_values(
+                &record,
+                &header_map,
+                &metrics_to_export,
+                derived_metrics,
+            ));
             writer.write_record(data_row)?;
         }
     }
     writer.flush()?;
+    write_verbose_metric_units(&metrics_to_export, derived_metrics, path)?;
     tracing::debug!(
         "Verbose metrics for {} exported to {}",
         data[0].save_name,
@@ -165,6 +169,163 @@ fn write_verbose_csv(data: &[VerboseData], metrics: &[String], path: &Path) -> R
     Ok(())
 }
 
+/// The exported metrics of one row of Factorio's verbose CSV, followed by the derived metrics
+fn verbose_values(
+    record: &csv::StringRecord,
+    header_map: &HashMap<String, usize>,
+    metrics_to_export: &[String],
+    derived_metrics: &[DerivedMetric],
+) -> Vec<String> {
+    let exported = metrics_to_export.iter().map(|metric_name| {
+        header_map
+            .get(metric_name)
+            .map_or("N/A", |&column_index| {
+                record.get(column_index).unwrap_or("0")
+            })
+            .to_string()
+    });
+    let derived = derived_metrics
+        .iter()
+        .map(|metric| metric.evaluate_record(record, header_map));
+
+    exported.chain(derived).collect()
+}
+
+fn warn_missing_operands(derived_metrics: &[DerivedMetric], header_map: &HashMap<String, usize>) {
+    for metric in derived_metrics {
+        for operand in metric.operands() {
+            if !header_map.contains_key(operand) {
+                tracing::warn!(
+                    "Derived metric {} uses {operand}, which this Factorio version doesn't report",
+                    metric.name
+                );
+            }
+        }
+    }
+}
+
+/// Resolve the requested metrics against Factorio's verbose header. `all` selects every metric,
+/// glob patterns such as `*Update` expand to matching columns in header order, and plain names
+/// are kept as given.
+fn select_verbose_metrics(headers: &[String], requested: &[String]) -> Vec<String> {
+    let available = headers
+        .iter()
+        .filter(|h| h.as_str() != "tick" && h.as_str() != "timestamp");
+
+    if requested.iter().any(|metric| metric == "all") {
+        return available.cloned().collect();
+    }
+
+    let mut selected: Vec<String> = Vec::new();
+    for metric in requested {
+        let is_pattern = metric.contains(['*', '?', '[']);
+        match glob::Pattern::new(metric) {
+            Ok(pattern) if is_pattern => {
+                let matches: Vec<&String> =
+                    available.clone().filter(|h| pattern.matches(h)).collect();
+                if matches.is_empty() {
+                    tracing::warn!("Verbose metric pattern '{metric}' matched no metrics");
+                }
+                for header in matches {
+                    if !selected.contains(header) {
+                        selected.push(header.clone());
+                    }
+                }
+            }
+            _ => {
+                if !selected.contains(metric) {
+                    selected.push(metric.clone());
+                }
+            }
+        }
+    }
+
+    selected
+}
+
+const METRIC_SUMMARY_HEADER: [&str; 8] = [
+    "metric", "unit", "mean", "median", "p95", "p99", "stddev", "max",
+];
+
+/// Write `<save>_metric_summary.csv` with the distribution of every column of the save's verbose
+/// metrics CSV over all its ticks and runs, in display units, so the update step that dominates
+/// shows without post-processing the whole CSV. Computed from the file, so appended runs count.
+fn write_metric_summary_csv(
+    save_name: &str,
+    derived_metrics: &[DerivedMetric],
+    path: &Path,
+) -> Result<()> {
+    let mut reader = csv::Reader::from_path(path.join(format!("{save_name}_verbose_metrics.csv")))?;
+    let metrics: Vec<MetricDescriptor> = reader
+        .headers()?
+        .iter()
+        .skip(2)
+        .map(|name| {
+            derived_metrics
+                .iter()
+                .find(|metric| metric.name == name)
+                .map_or_else(|| describe_metric(name), DerivedMetric::descriptor)
+        })
+        .collect();
+
+    let mut values: Vec<Vec<f64>> = vec![Vec::new(); metrics.len()];
+    for record in reader.records() {
+        let record = record?;
+        for (position, metric) in metrics.iter().enumerate() {
+            // Metrics this Factorio version doesn't report are `N/A`
+            if let Some(value) = record
+                .get(position + 2)
+                .and_then(|value| value.parse::<f64>().ok())
+            {
+                values[position].push(metric.unit.to_display(value));
+            }
+        }
+    }
+
+    let csv_path = path.join(format!("{save_name}_metric_summary.csv"));
+    let mut writer = csv::Writer::from_path(&csv_path)?;
+    writer.write_record(METRIC_SUMMARY_HEADER)?;
+
+    let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+    for (metric, values) in metrics.iter().zip(&values) {
+        writer.write_record([
+            metric.name.clone(),
+            metric.unit.display_label().to_string(),
+            optional(mean(values)),
+            optional(percentile(values, 50.0)),
+            optional(percentile(values, 95.0)),
+            optional(percentile(values, 99.0)),
+            optional(sample_std_dev(values)),
+            optional(values.iter().copied().reduce(f64::max)),
+        ])?;
+    }
+
+    writer.flush()?;
+    tracing::debug!(
+        "Metric summary of {save_name} written to {}",
+        csv_path.display()
+    );
+    Ok(())
+}
+
+/// Describe the unit and category of every exported verbose metric, so consumers don't have to
+/// assume every column is a duration in nanoseconds
+fn write_verbose_metric_units(
+    metrics: &[String],
+    derived_metrics: &[DerivedMetric],
+    path: &Path,
+) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path.join("verbose_metrics_units.csv"))?;
+    for metric in metrics {
+        writer.serialize(describe_metric(metric))?;
+    }
+    for metric in derived_metrics {
+        writer.serialize(metric.descriptor())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
 fn write_cpu_freq_csv(data: &[BenchmarkRun], path: &Path) -> Result<()> {
     if data.is_empty() {
         return Ok(());
@@ -208,6 +369,133 @@ fn write_cpu_freq_csv(data: &[BenchmarkRun], path: &Path) -> Result<()> {
     Ok(())
 }
 
+const MARKERS_HEADER: [&str; 4] = ["save_name", "run_index", "tick", "event"];
+
+/// Write the markers recorded with `--markers` to `markers.csv`, one row per marker
+fn write_markers_csv(data: &[BenchmarkRun], path: &Path, append: bool) -> Result<()> {
+    if data.iter().all(|run| run.markers.is_empty()) {
+        return Ok(());
+    }
+
+    let csv_path = path.join(MARKERS_CSV_FILENAME);
+    let mut writer = if append && csv_path.exists() {
+        validate_csv_header(&csv_path, &MARKERS_HEADER)?;
+        let file = OpenOptions::new().append(true).open(&csv_path)?;
+        csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(file)
+    } else {
+        let mut writer = csv::WriterBuilder::new().from_path(&csv_path)?;
+        writer.write_record(MARKERS_HEADER)?;
+        writer
+    };
+
+    for result in data {
+        for marker in &result.markers {
+            writer.write_record([
+                &result.save_name,
+                &result.index.to_string(),
+                &marker.tick.to_string(),
+                &marker.event,
+            ])?;
+        }
+    }
+
+    writer.flush()?;
+    tracing::info!("Markers written to {}", csv_path.display());
+
+    Ok(())
+}
+
+const SEGMENTS_HEADER: [&str; 7] = [
+    "save_name",
+    "run_index",
+    "start_tick",
+    "ticks",
+    "avg_ms",
+    "min_ms",
+    "max_ms",
+];
+
+/// Write the segments measured with `--segment-ticks` to `segments.csv`, one row per segment
+fn write_segments_csv(data: &[BenchmarkRun], path: &Path, append: bool) -> Result<()> {
+    if data.iter().all(|run| run.segments.is_empty()) {
+        return Ok(());
+    }
+
+    let csv_path = path.join(SEGMENTS_CSV_FILENAME);
+    let mut writer = if append && csv_path.exists() {
+        validate_csv_header(&csv_path, &SEGMENTS_HEADER)?;
+        let file = OpenOptions::new().append(true).open(&csv_path)?;
+        csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(file)
+    } else {
+        let mut writer = csv::WriterBuilder::new().from_path(&csv_path)?;
+        writer.write_record(SEGMENTS_HEADER)?;
+        writer
+    };
+
+    for result in data {
+        for segment in &result.segments {
+            writer.write_record([
+                &result.save_name,
+                &result.index.to_string(),
+                &segment.start_tick.to_string(),
+                &segment.ticks.to_string(),
+                &segment.avg_ms.to_string(),
+                &segment.min_ms.to_string(),
+                &segment.max_ms.to_string(),
+            ])?;
+        }
+    }
+
+    writer.flush()?;
+    tracing::info!("Segments written to {}", csv_path.display());
+
+    Ok(())
+}
+
+const SCALING_HEADER: [&str; 6] = [
+    "series",
+    "save_name",
+    "clones",
+    "mean_ups",
+    "mean_avg_ms",
+    "fitted_avg_ms",
+];
+
+/// Write the points of every scaling series with their fitted tick times to `scaling.csv`. The
+/// fit covers the session's runs, so appending replaces the file like the report.
+fn write_scaling_csv(data: &[BenchmarkRun], path: &Path) -> Result<()> {
+    let series = scaling::fit_series(data);
+    if series.is_empty() {
+        return Ok(());
+    }
+
+    let csv_path = path.join(SCALING_CSV_FILENAME);
+    let mut writer = csv::Writer::from_path(&csv_path)?;
+    writer.write_record(SCALING_HEADER)?;
+
+    for series in &series {
+        for point in &series.points {
+            writer.write_record([
+                series.name.clone(),
+                point.save_name.clone(),
+                point.clones.to_string(),
+                point.ups.to_string(),
+                point.avg_ms.to_string(),
+                series.fit.at(point.clones as f64).to_string(),
+            ])?;
+        }
+    }
+
+    writer.flush()?;
+    tracing::info!("Scaling series written to {}", csv_path.display());
+
+    Ok(())
+}
+
 const BENCHMARK_HEADER: [&str; 11] = [
     "save_name",
     "run_index",
@@ -222,6 +510,414 @@ const BENCHMARK_HEADER: [&str; 11] = [
     "platform",
 ];
 
+/// Environment columns added when a sanitize check ran
+const SANITIZER_HEADER: [&str; 3] = ["total_pollution", "evolution_factor", "enemy_count"];
+
+/// Column added when runs were pinned to core sets with `--jobs`
+const CORE_SET_HEADER: &str = "core_set";
+
+/// Column added when runs were bound to a NUMA node
+const NUMA_NODE_HEADER: &str = "numa_node";
+
+/// Column added when execution times were measured from verbose timestamps
+const PERFORMED_EXECUTION_TIME_HEADER: &str = "performed_execution_time_ms";
+
+/// Column added when the background load was sampled
+const BACKGROUND_LOAD_HEADER: &str = "background_load_percent";
+
+/// Columns added when RAPL energy counters were readable
+const ENERGY_HEADER: [&str; 2] = ["energy_joules", "avg_watts"];
+
+/// Columns describing the conditions at the start of each run
+const ENVIRONMENT_HEADER: [&str; 3] = ["started_at", "cpu_temp_c", "cpu_freq_mhz"];
+
+/// Column added when Factorio reported its build number
+const FACTORIO_BUILD_HEADER: &str = "factorio_build";
+
+/// Column naming the machine each run was measured on
+const MACHINE_HEADER: &str = "machine";
+
+/// Columns describing the machine's hardware and system at the start of the session
+const SYSTEM_HEADER: [&str; 5] = [
+    "cpu_model",
+    "cpu_cores",
+    "memory_gb",
+    "power_profile",
+    "os_version",
+];
+
+/// Column naming the family of variants each save belongs to
+const FAMILY_HEADER: &str = "family";
+
+/// Column added when the saves were checked against `--min-workload-ms`
+const LIGHT_WORKLOAD_HEADER: &str = "light_workload";
+
+/// Columns of `results.csv` that are only written when some run has the data
+#[derive(Debug, Clone, Copy)]
+struct OptionalColumns {
+    sanitizer: bool,
+    core_set: bool,
+    numa_node: bool,
+    performed_execution_time: bool,
+    background_load: bool,
+    energy: bool,
+    environment: bool,
+    machine: bool,
+    factorio_build: bool,
+    system: bool,
+    family: bool,
+    light_workload: bool,
+}
+
+impl OptionalColumns {
+    fn of(results: &[BenchmarkRun]) -> Self {
+        Self {
+            sanitizer: results.iter().any(|result| result.sanitizer.is_some()),
+            core_set: results.iter().any(|result| result.core_set.is_some()),
+            numa_node: results.iter().any(|result| result.numa_node.is_some()),
+            performed_execution_time: results
+                .iter()
+                .any(|result| result.performed_execution_time_ms.is_some()),
+            background_load: results
+                .iter()
+                .any(|result| result.background_load_percent.is_some()),
+            energy: results.iter().any(|result| result.energy_joules.is_some()),
+            environment: results.iter().any(|result| result.started_at.is_some()),
+            machine: results.iter().any(|result| result.machine.is_some()),
+            factorio_build: results.iter().any(|result| result.factorio_build.is_some()),
+            system: results.iter().any(|result| result.system.is_some()),
+            family: results.iter().any(|result| result.family.is_some()),
+            light_workload: results.iter().any(|result| result.light_workload.is_some()),
+        }
+    }
+}
+
+fn benchmark_header(columns: OptionalColumns) -> Vec<&'static str> {
+    let mut header = BENCHMARK_HEADER.to_vec();
+    if columns.sanitizer {
+        header.extend(SANITIZER_HEADER);
+    }
+    if columns.core_set {
+        header.push(CORE_SET_HEADER);
+    }
+    if columns.numa_node {
+        header.push(NUMA_NODE_HEADER);
+    }
+    if columns.performed_execution_time {
+        header.push(PERFORMED_EXECUTION_TIME_HEADER);
+    }
+    if columns.background_load {
+        header.push(BACKGROUND_LOAD_HEADER);
+    }
+    if columns.energy {
+        header.extend(ENERGY_HEADER);
+    }
+    if columns.environment {
+        header.extend(ENVIRONMENT_HEADER);
+    }
+    if columns.machine {
+        header.push(MACHINE_HEADER);
+    }
+    if columns.factorio_build {
+        header.push(FACTORIO_BUILD_HEADER);
+    }
+    if columns.system {
+        header.extend(SYSTEM_HEADER);
+    }
+    if columns.family {
+        header.push(FAMILY_HEADER);
+    }
+    if columns.light_workload {
+        header.push(LIGHT_WORKLOAD_HEADER);
+    }
+    header
+}
+
+fn benchmark_record(result: &BenchmarkRun, columns: OptionalColumns) -> Vec<String> {
+    let mut record = vec![
+        result.save_name.clone(),
+        result.index.to_string(),
+        result.execution_time_ms.to_string(),
+        result.avg_ms.to_string(),
+        result.min_ms.to_string(),
+        result.max_ms.to_string(),
+        result.effective_ups.to_string(),
+        result.base_diff.to_string(),
+        result.ticks.to_string(),
+        result.factorio_version.clone(),
+        result.platform.clone(),
+    ];
+
+    if columns.sanitizer {
+        let sanitizer = result.sanitizer.as_ref();
+        let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        record.extend([
+            optional(sanitizer.and_then(|s| s.total_pollution)),
+            optional(sanitizer.and_then(|s| s.evolution_factor)),
+            sanitizer
+                .map(|s| s.enemy_count.to_string())
+                .unwrap_or_default(),
+        ]);
+    }
+
+    if columns.core_set {
+        record.push(result.core_set.clone().unwrap_or_default());
+    }
+
+    if columns.numa_node {
+        record.push(
+            result
+                .numa_node
+                .map(|node| node.to_string())
+                .unwrap_or_default(),
+        );
+    }
+
+    if columns.performed_execution_time {
+        record.push(
+            result
+                .performed_execution_time_ms
+                .map(|ms| ms.to_string())
+                .unwrap_or_default(),
+        );
+    }
+
+    if columns.background_load {
+        record.push(
+            result
+                .background_load_percent
+                .map(|load| load.to_string())
+                .unwrap_or_default(),
+        );
+    }
+
+    if columns.energy {
+        let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        record.extend([optional(result.energy_joules), optional(result.avg_watts)]);
+    }
+
+    if columns.environment {
+        record.extend([
+            result.started_at.clone().unwrap_or_default(),
+            result
+                .cpu_temp_c
+                .map(|temp| temp.to_string())
+                .unwrap_or_default(),
+            result
+                .cpu_freq_mhz
+                .map(|mhz| mhz.to_string())
+                .unwrap_or_default(),
+        ]);
+    }
+
+    if columns.machine {
+        record.push(result.machine.clone().unwrap_or_default());
+    }
+
+    if columns.factorio_build {
+        record.push(
+            result
+                .factorio_build
+                .map(|build| build.to_string())
+                .unwrap_or_default(),
+        );
+    }
+
+    if columns.system {
+        let system = result.system.clone().unwrap_or_default();
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        record.extend([
+            optional(system.cpu_model),
+            optional(system.cpu_cores.map(|cores| cores.to_string())),
+            optional(system.memory_gb.map(|gb| gb.to_string())),
+            optional(system.power_profile),
+            optional(system.os_version),
+        ]);
+    }
+
+    if columns.family {
+        record.push(result.family.clone().unwrap_or_default());
+    }
+
+    if columns.light_workload {
+        record.push(
+            result
+                .light_workload
+                .map(|light| light.to_string())
+                .unwrap_or_default(),
+        );
+    }
+
+    record
+}
+
+/// Read the runs back from a `results.csv`, including the optional columns it has. Data that
+/// isn't part of the file, such as CPU frequencies and save metadata, is left empty.
+pub fn read_benchmark_csv(csv_path: &Path) -> Result<Vec<BenchmarkRun>> {
+    let mut reader = csv::Reader::from_path(csv_path)?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+
+    for name in BENCHMARK_HEADER {
+        if column(name).is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{} is missing the {name} column", csv_path.display()),
+            )
+            .into());
+        }
+    }
+
+    let mut results = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let field = |name: &str| {
+            column(name)
+                .and_then(|index| record.get(index))
+                .filter(|value| !value.is_empty())
+        };
+        let text = |name: &str| field(name).unwrap_or_default().to_string();
+
+        let sanitizer = match field(SANITIZER_HEADER[2]) {
+            Some(enemy_count) => Some(SanitizerFindings {
+                total_pollution: field(SANITIZER_HEADER[0]).map(str::parse).transpose()?,
+                evolution_factor: field(SANITIZER_HEADER[1]).map(str::parse).transpose()?,
+                enemy_count: enemy_count.parse()?,
+                ..Default::default()
+            }),
+            None => None,
+        };
+
+        let system = SYSTEM_HEADER
+            .iter()
+            .any(|name| field(name).is_some())
+            .then(|| -> Result<SystemSnapshot> {
+                Ok(SystemSnapshot {
+                    cpu_model: field(SYSTEM_HEADER[0]).map(str::to_string),
+                    cpu_cores: field(SYSTEM_HEADER[1]).map(str::parse).transpose()?,
+                    memory_gb: field(SYSTEM_HEADER[2]).map(str::parse).transpose()?,
+                    power_profile: field(SYSTEM_HEADER[3]).map(str::to_string),
+                    os_version: field(SYSTEM_HEADER[4]).map(str::to_string),
+                })
+            })
+            .transpose()?;
+
+        results.push(BenchmarkRun {
+            save_name: text("save_name"),
+            index: text("run_index").parse()?,
+            execution_time_ms: text("execution_time_ms").parse()?,
+            avg_ms: text("avg_ms").parse()?,
+            min_ms: text("min_ms").parse()?,
+            max_ms: text("max_ms").parse()?,
+            effective_ups: text("effective_ups").parse()?,
+            base_diff: text("percentage_improvement").parse()?,
+            ticks: text("ticks").parse()?,
+            factorio_version: text("factorio_version"),
+            platform: text("platform"),
+            sanitizer,
+            core_set: field(CORE_SET_HEADER).map(str::to_string),
+            numa_node: field(NUMA_NODE_HEADER).map(str::parse).transpose()?,
+            performed_execution_time_ms: field(PERFORMED_EXECUTION_TIME_HEADER)
+                .map(str::parse)
+                .transpose()?,
+            background_load_percent: field(BACKGROUND_LOAD_HEADER).map(str::parse).transpose()?,
+            energy_joules: field(ENERGY_HEADER[0]).map(str::parse).transpose()?,
+            avg_watts: field(ENERGY_HEADER[1]).map(str::parse).transpose()?,
+            started_at: field(ENVIRONMENT_HEADER[0]).map(str::to_string),
+            cpu_temp_c: field(ENVIRONMENT_HEADER[1]).map(str::parse).transpose()?,
+            cpu_freq_mhz: field(ENVIRONMENT_HEADER[2]).map(str::parse).transpose()?,
+            machine: field(MACHINE_HEADER).map(str::to_string),
+            factorio_build: field(FACTORIO_BUILD_HEADER).map(str::parse).transpose()?,
+            system,
+            family: field(FAMILY_HEADER).map(str::to_string),
+            light_workload: field(LIGHT_WORKLOAD_HEADER).map(|light| light == "true"),
+            ..Default::default()
+        });
+    }
+
+    Ok(results)
+}
+
+const SUMMARY_HEADER: [&str; 12] = [
+    "save_name",
+    "runs",
+    "mean_ups",
+    "median_ups",
+    "stddev_ups",
+    "ci95_low_ups",
+    "ci95_high_ups",
+    "p10_ups",
+    "p90_ups",
+    "mean_avg_ms",
+    "median_avg_ms",
+    "stddev_avg_ms",
+];
+
+/// Write `summary.csv` with one row per save, computed from every run in `results.csv` so
+/// appended sessions are included
+fn write_summary_csv(path: &Path) -> Result<()> {
+    let mut reader = csv::Reader::from_path(path.join("results.csv"))?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| {
+        headers.iter().position(|h| h == name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("results.csv is missing the {name} column"),
+            )
+        })
+    };
+    let (save_col, ups_col, avg_col) = (
+        column("save_name")?,
+        column("effective_ups")?,
+        column("avg_ms")?,
+    );
+
+    // (save_name, ups per run, avg_ms per run) in order of first appearance
+    let mut saves: Vec<(String, Vec<f64>, Vec<f64>)> = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let save_name = record.get(save_col).unwrap_or_default();
+        let ups: f64 = record.get(ups_col).unwrap_or_default().parse()?;
+        let avg_ms: f64 = record.get(avg_col).unwrap_or_default().parse()?;
+
+        match saves.iter_mut().find(|(name, _, _)| name == save_name) {
+            Some((_, ups_values, avg_values)) => {
+                ups_values.push(ups);
+                avg_values.push(avg_ms);
+            }
+            None => saves.push((save_name.to_string(), vec![ups], vec![avg_ms])),
+        }
+    }
+
+    let csv_path = path.join("summary.csv");
+    let mut writer = csv::Writer::from_path(&csv_path)?;
+    writer.write_record(SUMMARY_HEADER)?;
+
+    let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+    for (save_name, ups, avg_ms) in &saves {
+        let ci = confidence_interval_95(ups);
+        writer.write_record([
+            save_name.clone(),
+            ups.len().to_string(),
+            optional(mean(ups)),
+            optional(percentile(ups, 50.0)),
+            optional(sample_std_dev(ups)),
+            optional(ci.map(|(low, _)| low)),
+            optional(ci.map(|(_, high)| high)),
+            optional(percentile(ups, 10.0)),
+            optional(percentile(ups, 90.0)),
+            optional(mean(avg_ms)),
+            optional(percentile(avg_ms, 50.0)),
+            optional(sample_std_dev(avg_ms)),
+        ])?;
+    }
+
+    writer.flush()?;
+    tracing::info!("Summary written to {}", csv_path.display());
+
+    Ok(())
+}
+
 const CPU_FREQ_HEADER: [&str; 5] = [
     "save_name",
     "run_index",
@@ -238,7 +934,10 @@ fn append_benchmark_csv(results: &[BenchmarkRun], path: &Path) -> Result<()> {
         return write_benchmark_csv(results, path);
     }
 
-    validate_csv_header(&csv_path, &BENCHMARK_HEADER)?;
+    // Sessions with other flags fill other optional columns, so records follow the file's header
+    let columns = OptionalColumns::of(results);
+    let header = benchmark_header(columns);
+    let file_header = widen_benchmark_csv(&csv_path, &header)?;
 
     let next_indexes = next_benchmark_run_indexes(&csv_path)?;
     let adjusted_results = offset_benchmark_run_indexes(results, &next_indexes);
@@ -249,30 +948,86 @@ fn append_benchmark_csv(results: &[BenchmarkRun], path: &Path) -> Result<()> {
         .from_writer(file);
 
     for result in &adjusted_results {
-        writer.write_record([
-            &result.save_name,
-            &result.index.to_string(),
-            &result.execution_time_ms.to_string(),
-            &result.avg_ms.to_string(),
-            &result.min_ms.to_string(),
-            &result.max_ms.to_string(),
-            &result.effective_ups.to_string(),
-            &result.base_diff.to_string(),
-            &result.ticks.to_string(),
-            &result.factorio_version,
-            &result.platform,
-        ])?;
+        let mut values: HashMap<&str, String> = header
+            .iter()
+            .copied()
+            .zip(benchmark_record(result, columns))
+            .collect();
+        writer.write_record(
+            file_header
+                .iter()
+                .map(|name| values.remove(name.as_str()).unwrap_or_default()),
+        )?;
     }
 
     writer.flush()?;
     tracing::info!("Results appended to {}", csv_path.display());
 
     append_cpu_freq_csv(&adjusted_results, path)?;
+    write_markers_csv(&adjusted_results, path, true)?;
+    write_segments_csv(&adjusted_results, path, true)?;
+    write_scaling_csv(&adjusted_results, path)?;
+    write_summary_csv(path)?;
 
     Ok(())
 }
 
-fn append_verbose_csv(data: &[VerboseData], metrics: &[String], path: &Path) -> Result<()> {
+/// Add the optional columns of `header` that the `results.csv` at `csv_path` lacks, leaving them
+/// empty in its rows, and return the file's header. A file missing a column every session writes
+/// predates the current schema and needs `belt migrate` first.
+fn widen_benchmark_csv(csv_path: &Path, header: &[&str]) -> Result<Vec<String>> {
+    let mut reader = csv::Reader::from_path(csv_path)?;
+    let mut file_header: Vec<String> = reader.headers()?.iter().map(str::to_string).collect();
+
+    if let Some(name) = BENCHMARK_HEADER
+        .iter()
+        .find(|name| !file_header.iter().any(|column| column == *name))
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Cannot append to {}: it has no {name} column. Run `belt migrate` on it first",
+                csv_path.display()
+            ),
+        )
+        .into());
+    }
+
+    let missing: Vec<&str> = header
+        .iter()
+        .copied()
+        .filter(|name| !file_header.iter().any(|column| column == name))
+        .collect();
+    if missing.is_empty() {
+        return Ok(file_header);
+    }
+
+    let records = reader
+        .records()
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    file_header.extend(missing.iter().map(|name| name.to_string()));
+
+    let mut writer = csv::Writer::from_path(csv_path)?;
+    writer.write_record(&file_header)?;
+    for record in &records {
+        writer.write_record(record.iter().chain(missing.iter().map(|_| "")))?;
+    }
+    writer.flush()?;
+    tracing::debug!(
+        "Added the {} column(s) to {}",
+        missing.join(", "),
+        csv_path.display()
+    );
+
+    Ok(file_header)
+}
+
+fn append_verbose_csv(
+    data: &[VerboseData],
+    metrics: &[String],
+    derived_metrics: &[DerivedMetric],
+    path: &Path,
+) -> Result<()> {
     ensure_output_dir(path)?;
 
     if data.is_empty() {
@@ -281,11 +1036,10 @@ fn append_verbose_csv(data: &[VerboseData], metrics: &[String], path: &Path) ->
 
     let csv_path = path.join(format!("{}_verbose_metrics.csv", data[0].save_name));
     if !csv_path.exists() {
-        return write_verbose_csv(data, metrics, path);
+        return write_verbose_csv(data, metrics, derived_metrics, path);
     }
 
-    let first_run_csv_data = &data[0].csv_data;
-    let mut reader = csv::Reader::from_reader(first_run_csv_data.as_bytes());
+    let mut reader = data[0].reader()?;
     let headers_from_factorio: Vec<String> =
         reader.headers()?.iter().map(|s| s.to_string()).collect();
 
@@ -296,17 +1050,11 @@ fn append_verbose_csv(data: &[VerboseData], metrics: &[String], path: &Path) ->
         .map(|(i, h)| (h, i))
         .collect();
 
-    let metrics_to_export: Vec<String> = if metrics.contains(&"all".to_string()) {
-        headers_from_factorio
-            .into_iter()
-            .filter(|h| h != "tick" && h != "timestamp")
-            .collect()
-    } else {
-        metrics.to_vec()
-    };
+    let metrics_to_export = select_verbose_metrics(&headers_from_factorio, metrics);
 
     let mut expected_header = vec!["tick".to_string(), "run".to_string()];
     expected_header.extend(metrics_to_export.iter().cloned());
+    expected_header.extend(derived_metrics.iter().map(|metric| metric.name.clone()));
 
     validate_csv_header(&csv_path, &expected_header)?;
 
@@ -318,7 +1066,7 @@ fn append_verbose_csv(data: &[VerboseData], metrics: &[String], path: &Path) ->
         .from_writer(file);
 
     for (local_run_idx, run_data) in data.iter().enumerate() {
-        let mut inner_reader = csv::Reader::from_reader(run_data.csv_data.as_bytes());
+        let mut inner_reader = run_data.reader()?;
         let _ = inner_reader.headers()?;
 
         let run_index = next_run_index + local_run_idx as u32;
@@ -330,15 +1078,12 @@ fn append_verbose_csv(data: &[VerboseData], metrics: &[String], path: &Path) ->
             let tick_value = tick_str.trim_start_matches('t');
 
             let mut data_row = vec![tick_value.to_string(), run_index.to_string()];
-
-            for metric_name in &metrics_to_export {
-                if let Some(&column_index) = header_map.get(metric_name) {
-                    let value = record.get(column_index).unwrap_or("0");
-                    data_row.push(value.to_string());
-                } else {
-                    data_row.push("N/A".to_string());
-                }
-            }
+            data_row.extend(verbose_values(
+                &record,
+                &header_map,
+                &metrics_to_export,
+                derived_metrics,
+            ));
 
             writer.write_record(data_row)?;
         }
@@ -472,6 +1217,250 @@ mod tests {
     use super::*;
     use crate::benchmark::runner::CpuFrequencyData;
 
+    #[test]
+    fn test_summary_csv_covers_appended_runs() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let run = |save_name: &str, ups: f64| BenchmarkRun {
+            save_name: save_name.to_string(),
+            effective_ups: ups,
+            avg_ms: 1000.0 / ups,
+            ..Default::default()
+        };
+
+        write_benchmark_csv(&[run("alpha", 100.0), run("beta", 50.0)], path).unwrap();
+        append_benchmark_csv(&[run("alpha", 200.0), run("alpha", 300.0)], path).unwrap();
+
+        let mut reader = csv::Reader::from_path(path.join("summary.csv")).unwrap();
+        assert_eq!(reader.headers().unwrap(), SUMMARY_HEADER.as_slice());
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[0][0], "alpha");
+        assert_eq!(&rows[0][1], "3");
+        assert_eq!(&rows[0][2], "200");
+        assert_eq!(&rows[0][3], "200");
+        assert_eq!(&rows[0][4], "100");
+        assert_eq!(&rows[0][7], "120");
+        assert_eq!(&rows[1][0], "beta");
+        assert_eq!(&rows[1][1], "1");
+        assert_eq!(&rows[1][4], "");
+    }
+
+    #[test]
+    fn test_append_jobs_session_to_plain_session() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let plain = BenchmarkRun {
+            save_name: "alpha".to_string(),
+            performed_execution_time_ms: Some(10.0),
+            ..Default::default()
+        };
+        let jobs = BenchmarkRun {
+            save_name: "alpha".to_string(),
+            core_set: Some("4-7".to_string()),
+            numa_node: Some(1),
+            ..Default::default()
+        };
+
+        write_benchmark_csv(std::slice::from_ref(&plain), path).expect("write results csv");
+        append_benchmark_csv(std::slice::from_ref(&jobs), path).expect("append jobs session");
+        append_benchmark_csv(std::slice::from_ref(&plain), path).expect("append plain session");
+
+        let csv = std::fs::read_to_string(path.join("results.csv")).expect("read results csv");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].ends_with(",platform,performed_execution_time_ms,core_set,numa_node"));
+        assert!(lines[1].ends_with(",10,,"));
+        assert!(lines[2].ends_with(",,4-7,1"));
+        assert!(lines[3].ends_with(",10,,"));
+
+        let read = read_benchmark_csv(&path.join("results.csv")).expect("read results csv");
+        let indexes: Vec<u32> = read.iter().map(|run| run.index).collect();
+        assert_eq!(indexes, [0, 1, 2]);
+        assert_eq!(read[1].core_set.as_deref(), Some("4-7"));
+        assert_eq!(read[2].core_set, None);
+    }
+
+    #[test]
+    fn test_scaling_csv_lists_fitted_points() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let run = |save_name: &str, clones: u32, avg_ms: f64| BenchmarkRun {
+            save_name: save_name.to_string(),
+            avg_ms,
+            save_metadata: Some(crate::core::saves::SaveMetadata {
+                tags: vec![format!("clones:{clones}")],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        write_benchmark_csv(&[run("x1", 1, 2.0)], path).expect("write results csv");
+        assert!(!path.join(SCALING_CSV_FILENAME).exists());
+
+        write_benchmark_csv(&[run("x1", 1, 2.0), run("x2", 2, 3.0)], path)
+            .expect("write results csv");
+        let csv = std::fs::read_to_string(path.join(SCALING_CSV_FILENAME)).expect("read csv");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2], "clones,x2,2,0,3,3");
+    }
+
+    #[test]
+    fn test_benchmark_csv_adds_sanitizer_columns_when_available() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+
+        let data = vec![BenchmarkRun {
+            save_name: "alpha".to_string(),
+            sanitizer: Some(crate::sanitize::parser::SanitizerFindings {
+                total_pollution: Some(12.5),
+                evolution_factor: None,
+                enemy_count: 3,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }];
+
+        write_benchmark_csv(&data, path).expect("write results csv");
+
+        let csv = std::fs::read_to_string(path.join("results.csv")).expect("read results csv");
+        let mut lines = csv.lines();
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .ends_with(",platform,total_pollution,evolution_factor,enemy_count")
+        );
+        assert!(lines.next().unwrap().ends_with(",12.5,,3"));
+    }
+
+    #[test]
+    fn test_benchmark_csv_records_core_set_and_numa_node() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+
+        let data = vec![BenchmarkRun {
+            save_name: "alpha".to_string(),
+            core_set: Some("4-7".to_string()),
+            numa_node: Some(1),
+            ..Default::default()
+        }];
+
+        write_benchmark_csv(&data, path).expect("write results csv");
+
+        let csv = std::fs::read_to_string(path.join("results.csv")).expect("read results csv");
+        let mut lines = csv.lines();
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .ends_with(",platform,core_set,numa_node")
+        );
+        assert!(lines.next().unwrap().ends_with(",4-7,1"));
+
+        let read = read_benchmark_csv(&path.join("results.csv")).expect("read results csv");
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].save_name, "alpha");
+        assert_eq!(read[0].core_set.as_deref(), Some("4-7"));
+        assert_eq!(read[0].numa_node, Some(1));
+        assert!(read[0].sanitizer.is_none());
+    }
+
+    #[test]
+    fn test_select_verbose_metrics_expands_globs() {
+        let headers: Vec<String> = [
+            "tick",
+            "timestamp",
+            "wholeUpdate",
+            "gameUpdate",
+            "mapGenerator",
+            "mapGeneratorBasicTilesSupportCompute",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let selected = select_verbose_metrics(
+            &headers,
+            &[
+                "mapGenerator*".to_string(),
+                "*Update".to_string(),
+                "gameUpdate".to_string(),
+            ],
+        );
+        assert_eq!(
+            selected,
+            vec![
+                "mapGenerator",
+                "mapGeneratorBasicTilesSupportCompute",
+                "wholeUpdate",
+                "gameUpdate",
+            ]
+        );
+
+        let all = select_verbose_metrics(&headers, &["all".to_string()]);
+        assert_eq!(all.len(), 4);
+    }
+
+    #[test]
+    fn test_verbose_csv_exports_derived_metrics() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let data = vec![
+            VerboseData::spool(
+                "base",
+                "tick,timestamp,wholeUpdate,gameUpdate,entityUpdate\n\
+                 t0,0,1000,800,300\n\
+                 t1,1,1100,N/A,300\n",
+            )
+            .expect("spool verbose data"),
+        ];
+        let derived = vec![
+            DerivedMetric::parse("gameUpdateOther", "gameUpdate - entityUpdate")
+                .expect("parse derived metric"),
+        ];
+
+        write_verbose_csv(&data, &["wholeUpdate".to_string()], &derived, path)
+            .expect("write verbose csv");
+
+        let csv = std::fs::read_to_string(path.join("base_verbose_metrics.csv")).expect("read");
+        assert_eq!(
+            csv,
+            "tick,run,wholeUpdate,gameUpdateOther\n0,0,1000,500\n1,0,1100,N/A\n"
+        );
+        let units =
+            std::fs::read_to_string(path.join("verbose_metrics_units.csv")).expect("read units");
+        assert!(
+            units
+                .contains("gameUpdateOther,nanoseconds,derived,Derived: gameUpdate - entityUpdate")
+        );
+    }
+
+    #[test]
+    fn test_metric_summary_csv_covers_every_tick_of_every_run() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let mut csv = "tick,run,wholeUpdate,gameUpdate\n".to_string();
+        for run in 0..2 {
+            for tick in 0..50 {
+                let whole_update = (run * 50 + tick + 1) * 1_000_000;
+                csv.push_str(&format!("{tick},{run},{whole_update},N/A\n"));
+            }
+        }
+        std::fs::write(path.join("base_verbose_metrics.csv"), csv).expect("write verbose csv");
+
+        write_metric_summary_csv("base", &[], path).expect("write metric summary");
+
+        let summary =
+            std::fs::read_to_string(path.join("base_metric_summary.csv")).expect("read summary");
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], "metric,unit,mean,median,p95,p99,stddev,max");
+        assert!(lines[1].starts_with("wholeUpdate,ms,50.5,50.5,95.05,99.01,"));
+        assert!(lines[1].ends_with(",100"));
+        assert_eq!(lines[2], "gameUpdate,ms,,,,,,");
+    }
+
     #[test]
     fn test_cpu_freq_csv_uses_shared_filename_for_all_saves() {
         let temp_dir = tempfile::tempdir().expect("temp dir");
@@ -0,0 +1,103 @@
+//! Periodic engine tasks of each save: how much time chart refreshes and CRC computations took
+//! per run and how many ticks they dominated, as they can drown out what a benchmark compares.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    benchmark::parser::BenchmarkRun,
+    core::{mean, output::NumberFormat},
+};
+
+/// Build the report section from the runs whose verbose data had the tasks' columns
+pub(crate) fn build_section(
+    results: &[BenchmarkRun],
+    numbers: &NumberFormat,
+) -> Vec<InterferenceView> {
+    let mut runs_by_save: BTreeMap<&str, Vec<&BenchmarkRun>> = BTreeMap::new();
+    for run in results.iter().filter(|run| run.interference.is_some()) {
+        runs_by_save.entry(&run.save_name).or_default().push(run);
+    }
+
+    runs_by_save
+        .into_iter()
+        .map(|(save_name, runs)| {
+            let per_run = |value: &dyn Fn(&BenchmarkRun) -> f64| {
+                mean(&runs.iter().map(|run| value(run)).collect::<Vec<_>>()).unwrap_or(0.0)
+            };
+            let task_ms = per_run(&|run| run.interference.map_or(0.0, |i| i.total_ms));
+            let execution_time_ms = per_run(&|run| run.execution_time_ms);
+            InterferenceView {
+                save_name: save_name.to_string(),
+                runs: runs.len(),
+                task_ms: numbers.ms(task_ms),
+                share: if execution_time_ms > 0.0 {
+                    numbers.percent(task_ms / execution_time_ms * 100.0)
+                } else {
+                    "-".to_string()
+                },
+                dominated_ticks: numbers.format(
+                    per_run(&|run| run.interference.map_or(0.0, |i| i.dominated_ticks as f64)),
+                    1,
+                ),
+                dominated_ms: numbers.ms(per_run(&|run| {
+                    run.interference.map_or(0.0, |i| i.dominated_ms)
+                })),
+            }
+        })
+        .collect()
+}
+
+/// The periodic engine tasks of one save as rendered in the report, averaged over its runs
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct InterferenceView {
+    save_name: String,
+    runs: usize,
+    /// Time of the tasks per run
+    task_ms: String,
+    /// Of the execution time of the runs
+    share: String,
+    /// Ticks per run the tasks took at least half of
+    dominated_ticks: String,
+    /// Tick time of the dominated ticks per run
+    dominated_ms: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::parser::Interference;
+
+    #[test]
+    fn test_interference_section_averages_runs() {
+        let run = |total_ms: f64, dominated_ticks: u32| BenchmarkRun {
+            save_name: "base".to_string(),
+            execution_time_ms: 1000.0,
+            interference: Some(Interference {
+                total_ms,
+                dominated_ticks,
+                dominated_ms: dominated_ticks as f64 * 20.0,
+            }),
+            ..Default::default()
+        };
+        let results = vec![
+            run(40.0, 2),
+            run(60.0, 1),
+            BenchmarkRun {
+                save_name: "no-verbose".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let section = build_section(&results, &NumberFormat::default());
+
+        assert_eq!(section.len(), 1);
+        assert_eq!(section[0].save_name, "base");
+        assert_eq!(section[0].runs, 2);
+        assert_eq!(section[0].task_ms, "50.000");
+        assert_eq!(section[0].share, "5.00%");
+        assert_eq!(section[0].dominated_ticks, "1.5");
+        assert_eq!(section[0].dominated_ms, "30.000");
+    }
+}
@@ -0,0 +1,218 @@
+//! Event markers of each save (`--markers`): per kind of event the belt-sanitizer mod recorded,
+//! the tick time at the marked tick and the peak of the ticks right after it, from the
+//! `wholeUpdate` column of its verbose metrics CSV.
+
+use std::{collections::BTreeMap, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    Result,
+    benchmark::parser::BenchmarkRun,
+    core::{mean, metrics::describe_metric, output::NumberFormat},
+};
+
+/// File in the output directory listing every recorded marker
+pub const MARKERS_CSV_FILENAME: &str = "markers.csv";
+
+/// Ticks from a marker on whose peak tick time is attributed to its event
+const MARKER_WINDOW_TICKS: u64 = 60;
+
+/// Build the report section from the runs' markers and the `<save>_verbose_metrics.csv` files in
+/// `output_dir`
+pub(crate) fn build_section(
+    results: &[BenchmarkRun],
+    numbers: &NumberFormat,
+    output_dir: &Path,
+) -> Vec<MarkerSave> {
+    let mut runs_by_save: BTreeMap<&str, Vec<&BenchmarkRun>> = BTreeMap::new();
+    for run in results {
+        runs_by_save.entry(&run.save_name).or_default().push(run);
+    }
+
+    runs_by_save
+        .into_iter()
+        .filter(|(_, runs)| runs.iter().any(|run| !run.markers.is_empty()))
+        .map(|(save_name, mut runs)| {
+            // The verbose CSV numbers the runs of a save in the order they ran
+            runs.sort_by_key(|run| run.index);
+
+            let csv_path = output_dir.join(format!("{save_name}_verbose_metrics.csv"));
+            let tick_times = if csv_path.is_file() {
+                read_tick_times(&csv_path).unwrap_or_else(|err| {
+                    tracing::warn!("Failed to read {}: {err}", csv_path.display());
+                    Vec::new()
+                })
+            } else {
+                Vec::new()
+            };
+
+            save_view(save_name, &runs, &tick_times, numbers)
+        })
+        .collect()
+}
+
+/// `wholeUpdate` in ms by tick, for every run of the verbose metrics CSV
+fn read_tick_times(path: &Path) -> Result<Vec<BTreeMap<u64, f64>>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let Some(column) = reader
+        .headers()?
+        .iter()
+        .position(|header| header == "wholeUpdate")
+    else {
+        return Ok(Vec::new());
+    };
+
+    let unit = describe_metric("wholeUpdate").unit;
+    let mut runs: Vec<BTreeMap<u64, f64>> = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let parsed = (
+            record.get(0).and_then(|tick| tick.parse::<u64>().ok()),
+            record.get(1).and_then(|run| run.parse::<usize>().ok()),
+            record
+                .get(column)
+                .and_then(|value| value.parse::<f64>().ok()),
+        );
+        let (Some(tick), Some(run), Some(value)) = parsed else {
+            continue;
+        };
+
+        if runs.len() <= run {
+            runs.resize_with(run + 1, BTreeMap::new);
+        }
+        runs[run].insert(tick, unit.to_display(value));
+    }
+    Ok(runs)
+}
+
+fn save_view(
+    save_name: &str,
+    runs: &[&BenchmarkRun],
+    tick_times: &[BTreeMap<u64, f64>],
+    numbers: &NumberFormat,
+) -> MarkerSave {
+    #[derive(Default)]
+    struct EventTimes {
+        count: usize,
+        at_marker: Vec<f64>,
+        peaks: Vec<f64>,
+        peak_vs_mean: Vec<f64>,
+    }
+
+    let mut events: BTreeMap<&str, EventTimes> = BTreeMap::new();
+    for (position, run) in runs.iter().enumerate() {
+        let ticks = tick_times.get(position);
+        let run_mean = ticks.and_then(|ticks| mean(&ticks.values().copied().collect::<Vec<_>>()));
+
+        for marker in &run.markers {
+            let times = events.entry(&marker.event).or_default();
+            times.count += 1;
+
+            let Some(ticks) = ticks else {
+                continue;
+            };
+            if let Some(at_marker) = ticks.get(&marker.tick) {
+                times.at_marker.push(*at_marker);
+            }
+            let peak = ticks
+                .range(marker.tick..marker.tick + MARKER_WINDOW_TICKS)
+                .map(|(_, ms)| *ms)
+                .reduce(f64::max);
+            if let Some(peak) = peak {
+                times.peaks.push(peak);
+                if let Some(run_mean) = run_mean.filter(|run_mean| *run_mean > 0.0) {
+                    times.peak_vs_mean.push((peak / run_mean - 1.0) * 100.0);
+                }
+            }
+        }
+    }
+
+    let ms = |values: &[f64]| mean(values).map_or_else(|| "-".to_string(), |ms| numbers.ms(ms));
+    MarkerSave {
+        save_name: save_name.to_string(),
+        events: events
+            .into_iter()
+            .map(|(event, times)| MarkerEventView {
+                event: event.to_string(),
+                count: times.count,
+                at_marker_ms: ms(&times.at_marker),
+                peak_ms: ms(&times.peaks),
+                peak_vs_mean: mean(&times.peak_vs_mean).map_or_else(
+                    || "-".to_string(),
+                    |change| {
+                        let sign = if change > 0.0 { "+" } else { "" };
+                        format!("{sign}{}", numbers.percent(change))
+                    },
+                ),
+            })
+            .collect(),
+    }
+}
+
+/// The markers of one save as rendered in the report
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct MarkerSave {
+    save_name: String,
+    events: Vec<MarkerEventView>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct MarkerEventView {
+    event: String,
+    count: usize,
+    /// Mean tick time at the marked ticks
+    at_marker_ms: String,
+    /// Mean of the peak tick time within the window after each marker
+    peak_ms: String,
+    /// How much that peak exceeds the mean tick time of its run
+    peak_vs_mean: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sanitize::markers::Marker;
+
+    #[test]
+    fn test_markers_section_aligns_events_with_tick_times() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+        let mut csv = "tick,run,wholeUpdate\n".to_string();
+        for tick in 0..100 {
+            let ns = if tick == 11 { 4_000_000 } else { 1_000_000 };
+            csv.push_str(&format!("{tick},0,{ns}\n"));
+        }
+        std::fs::write(dir.join("base_verbose_metrics.csv"), csv).expect("write verbose metrics");
+
+        let marker = |tick: u64, event: &str| Marker {
+            tick,
+            event: event.to_string(),
+        };
+        let results = vec![
+            BenchmarkRun {
+                save_name: "base".to_string(),
+                markers: vec![marker(10, "rocket-launched"), marker(80, "train-departed")],
+                ..Default::default()
+            },
+            BenchmarkRun {
+                save_name: "quiet".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let section = build_section(&results, &NumberFormat::default(), dir);
+
+        assert_eq!(section.len(), 1);
+        assert_eq!(section[0].save_name, "base");
+        let events = &section[0].events;
+        assert_eq!(events[0].event, "rocket-launched");
+        assert_eq!(events[0].count, 1);
+        assert_eq!(events[0].at_marker_ms, "1.000");
+        assert_eq!(events[0].peak_ms, "4.000");
+        assert_eq!(events[0].peak_vs_mean, "+288.35%");
+        assert_eq!(events[1].event, "train-departed");
+        assert_eq!(events[1].peak_ms, "1.000");
+        assert_eq!(events[1].peak_vs_mean, "-2.91%");
+    }
+}
@@ -5,13 +5,27 @@ use std::path::Path;
 use crate::{
     Result,
     benchmark::{parser::BenchmarkRun, runner::VerboseData},
+    core::derived_metrics::DerivedMetric,
 };
 
 // Re-export submodules
+mod ab_mods;
+pub mod chart_data;
 pub mod csv;
+mod interference;
+mod markers;
+pub mod prometheus;
+pub mod raw_log;
 pub mod report;
+mod scaling;
+mod segments;
+mod space_age;
 mod uprof;
 pub use csv::CsvWriter;
+pub use markers::MARKERS_CSV_FILENAME;
+pub use report::{NumberFormat, ReportOptions};
+pub use scaling::SCALING_CSV_FILENAME;
+pub use segments::SEGMENTS_CSV_FILENAME;
 
 // Simple data holder
 #[derive(Debug)]
@@ -21,11 +35,15 @@ pub enum WriteData<'a> {
     Verbose {
         data: Vec<VerboseData>,
         metrics_to_export: Vec<String>,
+        derived_metrics: Vec<DerivedMetric>,
+        /// Also write `<save>_metric_summary.csv` with the distribution of every metric
+        metric_summary: bool,
     },
 
     Report {
         data: Vec<BenchmarkRun>,
         template_path: Option<&'a Path>,
+        options: ReportOptions,
     },
 }
 
@@ -0,0 +1,288 @@
+//! Prometheus export (`--prometheus`, `--push-url`): `metrics.prom` with the aggregates of every
+//! save and the measurements of every run in the text exposition format, optionally pushed to a
+//! Pushgateway, so benchmark rigs can be graphed without glue scripts.
+
+use std::{collections::BTreeMap, fmt::Write as _, path::Path, time::Duration};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    Result,
+    benchmark::parser::BenchmarkRun,
+    core::{error::BenchmarkErrorKind, mean, output::csv::read_benchmark_csv},
+};
+
+/// File in the output directory holding the metrics
+pub const PROMETHEUS_FILENAME: &str = "metrics.prom";
+
+/// How long pushing to a Pushgateway may take
+const PUSH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Write `metrics.prom` from the `results.csv` in `output_dir`, so appended sessions are included
+/// too, and return its contents
+pub fn write_metrics(output_dir: &Path) -> Result<String> {
+    let runs = read_benchmark_csv(&output_dir.join("results.csv"))?;
+    let metrics = exposition(&runs);
+
+    let path = output_dir.join(PROMETHEUS_FILENAME);
+    std::fs::write(&path, &metrics)?;
+    tracing::info!("Prometheus metrics written to {}", path.display());
+    Ok(metrics)
+}
+
+/// One metric family: its help text and its samples as labels and value
+struct Family {
+    name: &'static str,
+    help: &'static str,
+    samples: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+impl Family {
+    fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            samples: Vec::new(),
+        }
+    }
+}
+
+/// `runs` in the text exposition format: a gauge per save and measurement, and one per run
+fn exposition(runs: &[BenchmarkRun]) -> String {
+    let mut runs_by_save: BTreeMap<&str, Vec<&BenchmarkRun>> = BTreeMap::new();
+    for run in runs {
+        runs_by_save.entry(&run.save_name).or_default().push(run);
+    }
+
+    let mut info = Family::new(
+        "belt_info",
+        "Factorio version the saves were benchmarked on",
+    );
+    let mut ups = Family::new("belt_ups", "Mean effective UPS of the save over its runs");
+    let mut avg_ms = Family::new("belt_tick_avg_ms", "Mean average tick time of the save");
+    let mut min_ms = Family::new("belt_tick_min_ms", "Shortest tick time of the save");
+    let mut max_ms = Family::new("belt_tick_max_ms", "Longest tick time of the save");
+    let mut base_diff = Family::new(
+        "belt_base_diff_percent",
+        "UPS of the save relative to the slowest save",
+    );
+    let mut run_count = Family::new("belt_runs", "Runs of the save");
+    let mut run_ups = Family::new("belt_run_ups", "Effective UPS of a run");
+    let mut run_avg_ms = Family::new("belt_run_tick_avg_ms", "Average tick time of a run");
+    let mut run_execution_ms = Family::new(
+        "belt_run_execution_time_ms",
+        "Time Factorio took for the ticks of a run",
+    );
+
+    let mut versions: Vec<&str> = runs
+        .iter()
+        .map(|run| run.factorio_version.as_str())
+        .collect();
+    versions.sort_unstable();
+    versions.dedup();
+    for version in versions {
+        info.samples
+            .push((vec![("factorio_version", version.to_string())], 1.0));
+    }
+
+    for (save_name, save_runs) in runs_by_save {
+        let save = vec![("save", save_name.to_string())];
+        let mean_of = |value: fn(&BenchmarkRun) -> f64| {
+            mean(&save_runs.iter().map(|run| value(run)).collect::<Vec<_>>()).unwrap_or(0.0)
+        };
+        ups.samples
+            .push((save.clone(), mean_of(|run| run.effective_ups)));
+        avg_ms
+            .samples
+            .push((save.clone(), mean_of(|run| run.avg_ms)));
+        min_ms.samples.push((
+            save.clone(),
+            save_runs
+                .iter()
+                .map(|run| run.min_ms)
+                .fold(f64::INFINITY, f64::min),
+        ));
+        max_ms.samples.push((
+            save.clone(),
+            save_runs.iter().map(|run| run.max_ms).fold(0.0, f64::max),
+        ));
+        base_diff
+            .samples
+            .push((save.clone(), mean_of(|run| run.base_diff)));
+        run_count.samples.push((save, save_runs.len() as f64));
+
+        // Numbered by position, as appended sessions repeat run indices
+        for (position, run) in save_runs.into_iter().enumerate() {
+            let labels = vec![
+                ("save", save_name.to_string()),
+                ("run", (position + 1).to_string()),
+            ];
+            run_ups.samples.push((labels.clone(), run.effective_ups));
+            run_avg_ms.samples.push((labels.clone(), run.avg_ms));
+            run_execution_ms
+                .samples
+                .push((labels, run.execution_time_ms));
+        }
+    }
+
+    let mut text = String::new();
+    for family in [
+        info,
+        ups,
+        avg_ms,
+        min_ms,
+        max_ms,
+        base_diff,
+        run_count,
+        run_ups,
+        run_avg_ms,
+        run_execution_ms,
+    ] {
+        if family.samples.is_empty() {
+            continue;
+        }
+        let _ = writeln!(text, "# HELP {} {}", family.name, family.help);
+        let _ = writeln!(text, "# TYPE {} gauge", family.name);
+        for (labels, value) in family.samples {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(name, value)| format!("{name}=\"{}\"", escape_label(value)))
+                .collect();
+            let _ = writeln!(text, "{}{{{}}} {value}", family.name, labels.join(","));
+        }
+    }
+    text
+}
+
+/// Escape a label value as the exposition format requires
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Host, port and path of an `http://` Pushgateway URL, e.g.
+/// `http://localhost:9091/metrics/job/belt`. IPv6 hosts are bracketed, as in `http://[::1]:9091/`,
+/// and returned without the brackets.
+pub fn parse_push_url(url: &str) -> Result<(String, u16, String)> {
+    let invalid = || BenchmarkErrorKind::InvalidPushUrl {
+        url: url.to_string(),
+    };
+    let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, "/"),
+    };
+    // IPv6 hosts contain colons themselves, so the port follows the closing bracket
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(bracketed) => bracketed.split_once(']').ok_or_else(invalid)?,
+        None => authority.split_at(authority.find(':').unwrap_or(authority.len())),
+    };
+    let port = match port.strip_prefix(':') {
+        Some(port) => port.parse().map_err(|_| invalid())?,
+        None if port.is_empty() => 80,
+        None => return Err(invalid().into()),
+    };
+    if host.is_empty() {
+        return Err(invalid().into());
+    }
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+/// Replace the metrics of the Pushgateway group at `url` with `metrics`
+pub async fn push(url: &str, metrics: &str) -> Result<()> {
+    let (host, port, path) = parse_push_url(url)?;
+    let failed = |reason: String| BenchmarkErrorKind::PushFailed {
+        url: url.to_string(),
+        reason,
+    };
+
+    let request = async {
+        let mut stream = TcpStream::connect((host.as_str(), port)).await?;
+        let authority = if host.contains(':') {
+            format!("[{host}]:{port}")
+        } else {
+            format!("{host}:{port}")
+        };
+        let head = format!(
+            "PUT {path} HTTP/1.1\r\nHost: {authority}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            metrics.len()
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(metrics.as_bytes()).await?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        std::io::Result::Ok(response)
+    };
+    let response = tokio::time::timeout(PUSH_TIMEOUT, request)
+        .await
+        .map_err(|_| failed("timed out".to_string()))?
+        .map_err(|err| failed(err.to_string()))?;
+
+    let status_line = response.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(failed(format!("the Pushgateway answered \"{status_line}\"")).into());
+    }
+
+    tracing::info!("Prometheus metrics pushed to {url}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exposition_has_save_and_run_gauges() {
+        let run = |save_name: &str, index: u32, ups: f64| BenchmarkRun {
+            save_name: save_name.to_string(),
+            index,
+            factorio_version: "2.0.55".to_string(),
+            effective_ups: ups,
+            ..Default::default()
+        };
+        let runs = vec![
+            run("base", 0, 100.0),
+            run("base", 1, 110.0),
+            run("say \"hi\"", 0, 50.0),
+        ];
+
+        let text = exposition(&runs);
+
+        assert!(text.contains("# TYPE belt_ups gauge\n"));
+        assert!(text.contains("belt_info{factorio_version=\"2.0.55\"} 1\n"));
+        assert!(text.contains("belt_ups{save=\"base\"} 105\n"));
+        assert!(text.contains("belt_runs{save=\"base\"} 2\n"));
+        assert!(text.contains("belt_run_ups{save=\"base\",run=\"2\"} 110\n"));
+        assert!(text.contains("belt_ups{save=\"say \\\"hi\\\"\"} 50\n"));
+    }
+
+    #[test]
+    fn test_parse_push_url() {
+        assert_eq!(
+            parse_push_url("http://localhost:9091/metrics/job/belt").unwrap(),
+            (
+                "localhost".to_string(),
+                9091,
+                "/metrics/job/belt".to_string()
+            )
+        );
+        assert_eq!(
+            parse_push_url("http://[::1]:9091/metrics/job/belt").unwrap(),
+            ("::1".to_string(), 9091, "/metrics/job/belt".to_string())
+        );
+        assert_eq!(
+            parse_push_url("http://[::1]").unwrap(),
+            ("::1".to_string(), 80, "/".to_string())
+        );
+        assert!(parse_push_url("https://localhost:9091/metrics/job/belt").is_err());
+        assert!(parse_push_url("http://::1:9091/metrics/job/belt").is_err());
+        assert!(parse_push_url("http://[::1/metrics/job/belt").is_err());
+    }
+}
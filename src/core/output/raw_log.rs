@@ -0,0 +1,191 @@
+//! Compressed copies of Factorio's complete output per run (`--save-raw-logs`), so results can
+//! be re-parsed by newer BELT versions without re-running the benchmarks.
+
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::Result;
+
+/// Directory inside the output directory holding the raw logs
+pub const RAW_LOGS_DIR: &str = "raw_logs";
+
+/// Extension of a compressed raw log
+pub const RAW_LOG_EXTENSION: &str = "log.zst";
+
+/// zstd level; logs are mostly repetitive CSV, higher levels gain little
+const COMPRESSION_LEVEL: i32 = 9;
+
+/// Compress the output of an attempt at a run to `raw_logs/<save>_run<N>_attempt<A>.log.zst`.
+/// Existing logs, e.g. of a session that was resumed, are never overwritten; the attempt number
+/// is bumped instead.
+pub fn write_raw_log(
+    output_dir: &Path,
+    save_name: &str,
+    run_index: u32,
+    attempt: u32,
+    log: &str,
+) -> Result<RawLog> {
+    let dir = output_dir.join(RAW_LOGS_DIR);
+    std::fs::create_dir_all(&dir)?;
+
+    let mut attempt = attempt;
+    let path = loop {
+        let path = dir.join(format!(
+            "{save_name}_run{run_index}_attempt{attempt}.{RAW_LOG_EXTENSION}"
+        ));
+        if !path.exists() {
+            break path;
+        }
+        attempt += 1;
+    };
+
+    let mut encoder = zstd::Encoder::new(File::create(&path)?, COMPRESSION_LEVEL)?;
+    encoder.write_all(log.as_bytes())?;
+    encoder.finish()?;
+
+    tracing::debug!("Saved raw log to {}", path.display());
+    Ok(RawLog {
+        path,
+        save_name: save_name.to_string(),
+        run_index,
+        attempt,
+    })
+}
+
+/// The run index the next session appending to `output_dir` starts `save_name` at, after the
+/// runs whose raw logs are already there
+pub fn next_run_index(output_dir: &Path, save_name: &str) -> u32 {
+    let dir = output_dir.join(RAW_LOGS_DIR);
+    if !dir.is_dir() {
+        return 0;
+    }
+    find_all_raw_logs(&dir)
+        .unwrap_or_default()
+        .iter()
+        .filter(|log| log.save_name == save_name)
+        .map(|log| log.run_index + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// A raw log found in a `raw_logs/` directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawLog {
+    pub path: PathBuf,
+    pub save_name: String,
+    pub run_index: u32,
+    /// Attempts before the last one of a run were retried, e.g. for background load
+    pub attempt: u32,
+}
+
+impl RawLog {
+    /// Decompress the log
+    pub fn read(&self) -> Result<String> {
+        let bytes = zstd::decode_all(File::open(&self.path)?)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// The last attempt of every run with a raw log in `dir`, or in its `raw_logs/` subdirectory,
+/// ordered by save and run
+pub fn find_raw_logs(dir: &Path) -> Result<Vec<RawLog>> {
+    let raw_logs_dir = dir.join(RAW_LOGS_DIR);
+    let dir = if raw_logs_dir.is_dir() {
+        raw_logs_dir.as_path()
+    } else {
+        dir
+    };
+
+    let mut logs: Vec<RawLog> = Vec::new();
+    for log in find_all_raw_logs(dir)? {
+        match logs.last_mut() {
+            Some(last) if (&last.save_name, last.run_index) == (&log.save_name, log.run_index) => {
+                *last = log;
+            }
+            _ => logs.push(log),
+        }
+    }
+    Ok(logs)
+}
+
+/// Every attempt's raw log in `dir`, ordered by save, run and attempt. Logs of older versions,
+/// named without an attempt, count as the first attempt.
+fn find_all_raw_logs(dir: &Path) -> Result<Vec<RawLog>> {
+    let mut logs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().map(|name| name.to_string_lossy()) else {
+            continue;
+        };
+        let Some(stem) = file_name.strip_suffix(&format!(".{RAW_LOG_EXTENSION}")) else {
+            continue;
+        };
+        let (stem, attempt) = stem
+            .rsplit_once("_attempt")
+            .and_then(|(stem, attempt)| Some((stem, attempt.parse().ok()?)))
+            .unwrap_or((stem, 0));
+        let Some((save_name, run_index)) = stem
+            .rsplit_once("_run")
+            .and_then(|(save_name, index)| Some((save_name.to_string(), index.parse().ok()?)))
+        else {
+            continue;
+        };
+
+        logs.push(RawLog {
+            path,
+            save_name,
+            run_index,
+            attempt,
+        });
+    }
+
+    logs.sort_by(|a, b| {
+        (&a.save_name, a.run_index, a.attempt).cmp(&(&b.save_name, b.run_index, b.attempt))
+    });
+    Ok(logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_logs_are_written_and_found_again() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let log = "Performed 60 updates in 12.000 ms\ntick,timestamp,wholeUpdate\n0,1,2\n";
+
+        let dir = temp_dir.path();
+
+        let first = write_raw_log(dir, "base", 0, 0, log).expect("write log");
+        // A resumed session writing the same attempt again doesn't overwrite it
+        let retried = write_raw_log(dir, "base", 0, 0, "retried").expect("write log");
+        let second = write_raw_log(dir, "base", 1, 0, "other").expect("write log");
+
+        assert!(first.path.ends_with("raw_logs/base_run0_attempt0.log.zst"));
+        assert!(
+            retried
+                .path
+                .ends_with("raw_logs/base_run0_attempt1.log.zst")
+        );
+        assert_eq!(retried.attempt, 1);
+        assert!(second.path.ends_with("raw_logs/base_run1_attempt0.log.zst"));
+        assert_eq!(next_run_index(dir, "base"), 2);
+        assert_eq!(next_run_index(dir, "other"), 0);
+
+        let raw_logs_dir = dir.join(RAW_LOGS_DIR);
+        std::fs::write(raw_logs_dir.join("notes.txt"), "").expect("write unrelated file");
+        std::fs::copy(&first.path, raw_logs_dir.join("old_run3.log.zst")).expect("copy log");
+        let logs = find_raw_logs(dir).expect("find logs");
+        assert_eq!(
+            logs.iter()
+                .map(|log| (log.save_name.as_str(), log.run_index, log.attempt))
+                .collect::<Vec<_>>(),
+            vec![("base", 0, 1), ("base", 1, 0), ("old", 3, 0)]
+        );
+        assert_eq!(logs[0].read().expect("read log"), "retried");
+        assert_eq!(logs[2].read().expect("read log"), log);
+    }
+}
@@ -9,13 +9,22 @@ use serde_json::json;
 
 use crate::{
     benchmark::{
-        parser::{BenchmarkRun, MimallocStats},
+        ab_mods::ModsVariant,
+        parser::{BenchmarkRun, Interference, MimallocStats, Segment},
         uprof,
     },
     core::{
+        anonymize::Anonymizer,
         calculate_base_differences,
+        environment::SystemSnapshot,
         error::{BenchmarkErrorKind, Result},
-        output::{self, ResultWriter, WriteData, ensure_output_dir},
+        output::{self, ResultWriter, WriteData, csv::read_benchmark_csv, ensure_output_dir},
+        saves::SaveMetadata,
+        utils::{SortBy, TickRange, UpsDefinition},
+    },
+    sanitize::{
+        markers::Marker,
+        parser::{SanitizerFindings, SurfaceStatistics},
     },
 };
 
@@ -33,13 +42,132 @@ impl ReportWriter {
     }
 }
 
+/// Optional report sections and formatting
+#[derive(Debug, Clone, Default)]
+pub struct ReportOptions {
+    /// List every individual run per save, collapsible in HTML reports
+    pub include_runs: bool,
+    /// Rounding and separators of the numbers in the report
+    pub number_format: NumberFormat,
+    /// Replace local paths, the user name and the host name
+    pub anonymize: bool,
+    /// How the effective UPS in the results was computed
+    pub ups_definition: UpsDefinition,
+    /// Ticks run before the measured ticks of each run
+    pub settle_ticks: u32,
+    /// Measured ticks the results are limited to
+    pub tick_range: Option<TickRange>,
+    /// Keep the saves in the order of the results instead of sorting them by name
+    pub sort_by: Option<SortBy>,
+    /// Ticks dominated by chart refreshes and CRC computations were left out of the results
+    pub exclude_interference: bool,
+    /// Variables from `--var`, rendered as `{{vars.<key>}}`
+    pub extra_vars: HashMap<String, String>,
+}
+
+/// Rounding and separators applied to every UPS, millisecond and percentage value in the report
+#[derive(Debug, Clone, Copy)]
+pub struct NumberFormat {
+    pub ms_decimals: usize,
+    pub ups_decimals: usize,
+    pub percent_decimals: usize,
+    /// Inserted between groups of three integer digits, e.g. `,` or a space
+    pub thousands_separator: Option<char>,
+    pub decimal_separator: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            ms_decimals: 3,
+            ups_decimals: 0,
+            percent_decimals: 2,
+            thousands_separator: None,
+            decimal_separator: '.',
+        }
+    }
+}
+
+impl NumberFormat {
+    pub fn ms(&self, value: f64) -> String {
+        self.format(value, self.ms_decimals)
+    }
+
+    pub fn ups(&self, value: f64) -> String {
+        self.format(value, self.ups_decimals)
+    }
+
+    pub fn percent(&self, value: f64) -> String {
+        format!("{}%", self.format(value, self.percent_decimals))
+    }
+
+    pub(crate) fn format(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{value:.decimals$}");
+        let (sign, digits) = match formatted.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", formatted.as_str()),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+        let mut grouped = String::with_capacity(formatted.len() + integer.len() / 3);
+        for (i, digit) in integer.chars().enumerate() {
+            if let Some(separator) = self.thousands_separator
+                && i > 0
+                && (integer.len() - i) % 3 == 0
+            {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+
+        if fraction.is_empty() {
+            format!("{sign}{grouped}")
+        } else {
+            format!("{sign}{grouped}{}{fraction}", self.decimal_separator)
+        }
+    }
+}
+
+/// Markup of the rendered report, derived from the output file name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("html" | "htm") => ReportFormat::Html,
+            _ => ReportFormat::Markdown,
+        }
+    }
+
+    fn bold(self, text: &str) -> String {
+        match self {
+            ReportFormat::Markdown => format!("**{text}**"),
+            ReportFormat::Html => format!("<strong>{text}</strong>"),
+        }
+    }
+
+    /// Exposed to templates as `format`, so they can branch on `format.is_html`
+    fn context(self) -> serde_json::Value {
+        let name = match self {
+            ReportFormat::Markdown => "markdown",
+            ReportFormat::Html => "html",
+        };
+        json!({ "name": name, "is_html": self == ReportFormat::Html })
+    }
+}
+
 impl ResultWriter for ReportWriter {
     fn write(&self, data: &WriteData, path: &Path) -> Result<()> {
         match data {
             WriteData::Report {
                 data,
                 template_path,
-            } => write_report(data, *template_path, path),
+                options,
+            } => write_report(data, *template_path, options, path),
             _ => Err(BenchmarkErrorKind::InvalidWriteData.into()),
         }
     }
@@ -49,15 +177,21 @@ impl ResultWriter for ReportWriter {
             WriteData::Report {
                 data,
                 template_path,
-            } => append_report(data, *template_path, path),
+                options,
+            } => append_report(data, *template_path, options, path),
             _ => Err(BenchmarkErrorKind::InvalidWriteData.into()),
         }
     }
 }
 
 /// Write the results to a Handlebars file
-fn write_report(results: &[BenchmarkRun], template_path: Option<&Path>, path: &Path) -> Result<()> {
-    const TPL_STR: &str = "# Factorio Benchmark Results\n\n**Platform:** {{platform}}\n**Factorio Version:** {{factorio_version}}\n**Date:** {{date}}\n\n## Scenario\n* Each save was tested for {{ticks}} tick(s) and {{runs}} run(s)\n\n## Results\n| Metric            | Description                           |\n| ----------------- | ------------------------------------- |\n| **Mean UPS**      | Updates per second – higher is better |\n| **Mean Avg (ms)** | Average frame time – lower is better  |\n| **Mean Min (ms)** | Minimum frame time – lower is better  |\n| **Mean Max (ms)** | Maximum frame time – lower is better  |\n\n| Save | Avg (ms) | Min (ms) | Max (ms) | UPS | Execution Time (ms) | % Difference from base |\n|------|----------|----------|----------|-----|---------------------|------------------------|\n{{#each results}}\n| {{save_name}} | {{avg_ms}} | {{min_ms}} | {{max_ms}} | {{{avg_effective_ups}}} | {{total_execution_time_ms}} | {{percentage_improvement}} |\n{{/each}}\n\n{{#if results.0.mimalloc}}\n## Memory (mimalloc)\n\n### What these numbers mean (practical interpretation)\n| Field | What it roughly indicates |\n|------|----------------------------|\n| **Committed (peak)** | Highest amount of memory backed by the OS during the run (best \"memory footprint\" trend metric). |\n| **Reserved (peak)** | Highest virtual address space reserved by the allocator. **If Committed > Reserved, the application uses direct `mmap`/`VirtualAlloc` outside the allocator** (e.g., for memory-mapped files or custom pools). |\n| **Peak RSS** | Highest resident set size (what was actually in RAM). Large gaps between Committed and RSS indicate sparse memory usage (hugepages, memory-mapped files, or reserved-but-untouched arenas). |\n| **Commit Efficiency** | `(Peak RSS / Committed Peak)` as percentage. <10% = sparse allocation (mostly reserved, not touched); >80% = dense working set. |\n| **Committed/Reserved (current)** | What the allocator still held at process exit. Not automatically a leak—mimalloc retains arenas for reuse. **Trend this across multiple runs; growth between identical runs indicates leaks.** |\n| **Pages / Abandoned (current + status)** | \"Not all freed\" is **normal**—the allocator caches pages for reuse. Abandoned blocks indicate thread-local heap fragments from terminated threads. Flag only if these numbers grow across benchmark iterations. |\n| **Thread Churn** | `(Threads Peak - Current)`. Values >0 indicate short-lived worker threads spawned during initialization (explains Abandoned blocks). |\n| **Threads (peak)** | Peak allocator thread count observed. If Peak > Current, expect elevated Abandoned blocks. |\n| **mmaps** | Number of OS allocation calls. Low counts (<50) with high memory usage indicate efficient arena reuse. High counts indicate frequent allocation pressure or fragmentation. |\n| **purges / resets** | Memory returned to OS. Usually 0 in benchmarks—non-zero indicates aggressive memory trimming or constrained environments. |\n\n### Summary (end-of-run heap stats)\n| Save | Committed Peak | Peak RSS | Commit Efficiency | Reserved Peak | Committed Current | Reserved Current | Pages Current | Pages Status | Abandoned Current | Abandoned Status | Thread Churn | Threads Peak | mmaps | purges | resets |\n|------|----------------|----------|-------------------|---------------|-------------------|------------------|---------------|-------------|-------------------|------------------|--------------|-------------|-------|--------|--------|\n{{#each results}}\n{{#each mimalloc}}\n| {{../save_name}} | {{committed_peak}} | {{peak_rss}} | {{commit_efficiency}} | {{reserved_peak}} | {{committed_current}} | {{reserved_current}} | {{pages_current}} | {{pages_status}} | {{abandoned_current}} | {{abandoned_status}} | {{thread_churn}} | {{threads_peak}} | {{mmaps}} | {{purges}} | {{resets}} |\n{{/each}}\n{{/each}}\n\n{{/if}}\n{{#if amd_uprof.summary_rows}}\n## AMD uProf\n\n| Save | Run | Profile | View | Duration | Threads | Session | Report |\n|------|-----|---------|------|----------|---------|---------|--------|\n{{#each amd_uprof.summary_rows}}\n| {{{save}}} | {{run}} | {{{profile}}} | {{{view}}} | {{{duration}}} | {{{threads}}} | {{{session}}} | {{{report}}} |\n{{/each}}\n\n{{#each amd_uprof.reports}}\n### {{{title}}}\n\n{{#if copy_error}}\nReport archive warning: {{{copy_error}}}\n\n{{/if}}\n{{#if parse_error}}\nReport parse warning: {{{parse_error}}}. Full CSV: `{{{report_path}}}`\n\n{{/if}}\n{{#if metadata_rows}}\n| Field | Value |\n|-------|-------|\n{{#each metadata_rows}}\n| {{{field}}} | {{{value}}} |\n{{/each}}\n\n{{/if}}\n{{#if cache_rows}}\n#### Estimated L1 Data Cache Summary\n\nEstimated from `L1_DC_ACCESSES_ALL.USER` and demand refill source counters.\n\n| Table | Item | Accesses | Est Hits | Est Misses | Est Miss Rate | L2 Refills | Cache Refills | External Cache Refills | DRAM Refills |\n|-------|------|----------|----------|------------|---------------|------------|---------------|------------------------|--------------|\n{{#each cache_rows}}\n| {{{table}}} | {{{item}}} | {{{accesses}}} | {{{hits}}} | {{{misses}}} | {{{miss_rate}}} | {{{local_l2}}} | {{{local_cache}}} | {{{external_cache}}} | {{{local_dram}}} |\n{{/each}}\n\n{{/if}}\n{{#if ibs_load_rows}}\n#### IBS Load Cache Summary\n\nReported by AMD IBS load views such as `ibs_op_ld` and `ibs_op_ld_lat`.\n\n| Table | Item | Loads | L1 Hit Rate | L1 Miss Rate | L2 Hit Rate | Local Cache Hit Rate | Peer Cache Hit Rate | Remote Cache Hit Rate | DRAM Hit Rate | Avg L1 Miss Latency |\n|-------|------|-------|-------------|--------------|-------------|----------------------|---------------------|-----------------------|---------------|---------------------|\n{{#each ibs_load_rows}}\n| {{{table}}} | {{{item}}} | {{{loads}}} | {{{l1_hit_rate}}} | {{{l1_miss_rate}}} | {{{l2_hit_rate}}} | {{{local_cache_hit_rate}}} | {{{peer_cache_hit_rate}}} | {{{remote_cache_hit_rate}}} | {{{dram_hit_rate}}} | {{{l1_miss_latency}}} |\n{{/each}}\n\n{{/if}}\n{{#each tables}}\n#### {{{title}}}\n\n|{{#each headers}} {{{this}}} |{{/each}}\n|{{#each headers}}------|{{/each}}\n{{#each rows}}\n|{{#each this}} {{{this}}} |{{/each}}\n{{/each}}\n\n{{#if truncated}}\nThis AMD uProf table was truncated in Markdown. Full CSV: `{{{../report_path}}}`\n\n{{/if}}\n{{/each}}\n{{#if truncated}}\nThis AMD uProf report was truncated in Markdown. Full CSV: `{{{report_path}}}`\n\n{{/if}}\n{{/each}}\n{{/if}}\n## Conclusion";
+fn write_report(
+    results: &[BenchmarkRun],
+    template_path: Option<&Path>,
+    options: &ReportOptions,
+    path: &Path,
+) -> Result<()> {
+    const TPL_STR: &str = "# Factorio Benchmark Results\n\n**Platform:** {{platform}}\n**Factorio Version:** {{factorio_version}}\n**Date:** {{date}}\n{{#if machines}}\n**Machines:** {{machines}}\n{{/if}}\n{{#each report_notes}}\n\n> **Note:** {{this}}\n{{/each}}\n\n{{#if system_details}}\n## System\n| Machine | CPU | Cores | Memory | Power profile | OS | Factorio |\n|---------|-----|-------|--------|---------------|----|----------|\n{{#each system_details}}\n| {{machine}} | {{cpu_model}} | {{cpu_cores}} | {{memory_gb}} | {{power_profile}} | {{os_version}} | {{factorio_version}} |\n{{/each}}\n\n{{/if}}\n## Scenario\n* Each save was tested for {{ticks}} tick(s) and {{runs}} run(s)\n{{#if settle_ticks}}\n* Each run settled for {{settle_ticks}} tick(s) before measuring\n{{/if}}\n{{#if tick_range}}\n* Results only count measured ticks {{tick_range}} of each run\n{{/if}}\n{{#if exclude_interference}}\n* Ticks dominated by chart refreshes and CRC computations are left out of the results\n{{/if}}\n* Effective UPS: {{ups_definition}}\n\n## Results\n| Metric            | Description                           |\n| ----------------- | ------------------------------------- |\n| **Mean UPS**      | Updates per second – higher is better |\n| **Mean Avg (ms)** | Average frame time – lower is better  |\n| **Mean Min (ms)** | Minimum frame time – lower is better  |\n| **Mean Max (ms)** | Maximum frame time – lower is better  |\n\n| Save | Avg (ms) | Min (ms) | Max (ms) | UPS | Execution Time (ms) | % Difference from base |\n|------|----------|----------|----------|-----|---------------------|------------------------|\n{{#each results}}\n| {{save_name}} | {{avg_ms}} | {{min_ms}} | {{max_ms}} | {{{avg_effective_ups}}} | {{total_execution_time_ms}} | {{percentage_improvement}} |\n{{/each}}\n\n{{#each light_workloads}}\n> **Warning:** {{save_name}} takes {{avg_ms}} ms per tick, so its results likely measure engine overhead and cache effects rather than the factory.\n\n{{/each}}\n{{#if run_details}}\n## Individual Runs\n{{#if format.is_html}}\n<details>\n<summary>Show all runs</summary>\n\n{{/if}}\n{{#each run_details}}\n### {{save_name}}\n| Run | UPS | Avg (ms) | Min (ms) | Max (ms) | Execution Time (ms) |\n|-----|-----|----------|----------|----------|---------------------|\n{{#each runs}}\n| {{run}} | {{ups}} | {{avg_ms}} | {{min_ms}} | {{max_ms}} | {{execution_time_ms}} |\n{{/each}}\n\n{{/each}}\n{{#if format.is_html}}\n</details>\n\n{{/if}}\n{{/if}}\n{{#if save_details}}\n## Saves\n| Save | Family | Description | Author | Expected | Tags |\n|------|--------|-------------|--------|----------|------|\n{{#each save_details}}\n| {{save_name}} | {{family}} | {{description}} | {{author}} | {{expected}} | {{tags}} |\n{{/each}}\n\n{{/if}}\n{{#if map_details}}\n## Maps\n| Save | Seed |\n|------|------|\n{{#each map_details}}\n| {{save_name}} | {{seed}} |\n{{/each}}\n\n{{#each map_details}}\n{{#if map_exchange_string}}\n### {{save_name}}\n```\n{{{map_exchange_string}}}\n```\n\n{{/if}}\n{{/each}}\n{{/if}}\n{{#if sanitizer_details}}\n## Sanitizer\n| Save | UPS | Total pollution | Evolution factor | Enemies |\n|------|-----|-----------------|------------------|---------|\n{{#each sanitizer_details}}\n| {{save_name}} | {{ups}} | {{total_pollution}} | {{evolution_factor}} | {{enemy_count}} |\n{{/each}}\n\n{{#each sanitizer_details}}\n### {{save_name}}\n{{#each warnings}}\n* Warning: {{this}}\n{{else}}\n* No benchmark-affecting issues found\n{{/each}}\n{{#each production}}\n* {{this}}\n{{/each}}\n{{#if surfaces}}\n\n| Surface | Kind | Entities | Share | Est. tick time (ms) |\n|---------|------|----------|-------|---------------------|\n{{#each surfaces}}\n| {{name}} | {{kind}} | {{entities}} | {{share}} | {{estimated_ms}} |\n{{/each}}\n{{/if}}\n\n{{/each}}\n{{/if}}\n{{#if space_age}}\n## Space Age\nMean time per tick of the planet, space platform and Space Age entity metrics that were non-zero.\n\n{{#each space_age}}\n### {{save_name}}\n| Metric | Description | Mean (ms) | Share of tick |\n|--------|-------------|-----------|---------------|\n{{#each metrics}}\n| {{name}} | {{description}} | {{mean_ms}} | {{share}} |\n{{/each}}\n\n{{/each}}\n{{/if}}\n{{#if markers}}\n## Markers\nTick time at the events the belt-sanitizer mod recorded, and the peak of the 60 ticks after them compared to the mean tick time of their run. Every marker is listed in `markers.csv`.\n\n{{#each markers}}\n### {{save_name}}\n| Event | Count | At marker (ms) | Peak after (ms) | Peak vs run mean |\n|-------|-------|----------------|-----------------|------------------|\n{{#each events}}\n| {{event}} | {{count}} | {{at_marker_ms}} | {{peak_ms}} | {{peak_vs_mean}} |\n{{/each}}\n\n{{/each}}\n{{/if}}\n{{#if segments}}\n## Segments\nMean tick times of consecutive segments of the measured ticks, then of the whole runs. Drift compares the last segment to the first. Every segment of every run is listed in `segments.csv`.\n\n{{#each segments}}\n### {{save_name}}\nDrift: {{drift}}\n\n| Ticks | UPS | Avg (ms) | Min (ms) | Max (ms) | vs first segment |\n|-------|-----|----------|----------|----------|------------------|\n{{#each segments}}\n| {{ticks}} | {{ups}} | {{avg_ms}} | {{min_ms}} | {{max_ms}} | {{vs_first}} |\n{{/each}}\n\n{{/each}}\n{{/if}}\n{{#if interference}}\n## Periodic Engine Tasks\nTime per run of chart refreshes and CRC computations (`chartRefresh`, `crcComputation`), and the ticks they took at least half of.\n\n| Save | Runs | Task time (ms) | Share of execution time | Dominated ticks | Dominated tick time (ms) |\n|------|------|----------------|-------------------------|-----------------|--------------------------|\n{{#each interference}}\n| {{save_name}} | {{runs}} | {{task_ms}} | {{share}} | {{dominated_ticks}} | {{dominated_ms}} |\n{{/each}}\n\n{{/if}}\n{{#if ab_mods}}\n## Mods A/B\nEach save ran alternately with both mods directories, paired by round. The change is the mean UPS change of the second mods directory from the first over the pairs; it is significant when its 95% confidence interval excludes no change.\n\n| Save | A | B | Pairs | UPS A | UPS B | Change | 95% CI | Significant |\n|------|---|---|-------|-------|-------|--------|--------|-------------|\n{{#each ab_mods}}\n| {{save_name}} | {{labels.[0]}} | {{labels.[1]}} | {{pairs}} | {{ups_a}} | {{ups_b}} | {{change}} | {{confidence_interval}} | {{significant}} |\n{{/each}}\n\n{{/if}}\n{{#if scaling}}\n## Scaling\nTick time fitted linearly against the number of clones of each series.\n\n| Series | Per clone (ms) | Base (ms) | R² | Clones at 60 UPS |\n|--------|----------------|-----------|----|------------------|\n{{#each scaling}}\n| {{name}} | {{ms_per_clone}} | {{base_ms}} | {{r_squared}} | {{clones_at_60_ups}} |\n{{/each}}\n\n{{#each scaling}}\n### {{name}}\n| Save | Clones | UPS | Avg (ms) | Fitted (ms) |\n|------|--------|-----|----------|-------------|\n{{#each points}}\n| {{save_name}} | {{clones}} | {{ups}} | {{avg_ms}} | {{fitted_ms}} |\n{{/each}}\n\n{{#unless ../format.is_html}}\n```mermaid\nxychart-beta\n    title \"{{name}}\"\n    x-axis \"Clones\" [{{chart_clones}}]\n    y-axis \"Avg tick time (ms)\"\n    line [{{chart_measured}}]\n    line [{{chart_fitted}}]\n```\n\n{{/unless}}\n{{/each}}\n{{/if}}\n{{#if energy_details}}\n## Energy\nMeasured with RAPL over the whole Factorio process, including loading the save.\n\n| Save | Energy per run (J) | Avg power (W) |\n|------|--------------------|---------------|\n{{#each energy_details}}\n| {{save_name}} | {{joules}} | {{watts}} |\n{{/each}}\n\n{{/if}}\n{{#if results.0.mimalloc}}\n## Memory (mimalloc)\n\n### What these numbers mean (practical interpretation)\n| Field | What it roughly indicates |\n|------|----------------------------|\n| **Committed (peak)** | Highest amount of memory backed by the OS during the run (best \"memory footprint\" trend metric). |\n| **Reserved (peak)** | Highest virtual address space reserved by the allocator. **If Committed > Reserved, the application uses direct `mmap`/`VirtualAlloc` outside the allocator** (e.g., for memory-mapped files or custom pools). |\n| **Peak RSS** | Highest resident set size (what was actually in RAM). Large gaps between Committed and RSS indicate sparse memory usage (hugepages, memory-mapped files, or reserved-but-untouched arenas). |\n| **Commit Efficiency** | `(Peak RSS / Committed Peak)` as percentage. <10% = sparse allocation (mostly reserved, not touched); >80% = dense working set. |\n| **Committed/Reserved (current)** | What the allocator still held at process exit. Not automatically a leak—mimalloc retains arenas for reuse. **Trend this across multiple runs; growth between identical runs indicates leaks.** |\n| **Pages / Abandoned (current + status)** | \"Not all freed\" is **normal**—the allocator caches pages for reuse. Abandoned blocks indicate thread-local heap fragments from terminated threads. Flag only if these numbers grow across benchmark iterations. |\n| **Thread Churn** | `(Threads Peak - Current)`. Values >0 indicate short-lived worker threads spawned during initialization (explains Abandoned blocks). |\n| **Threads (peak)** | Peak allocator thread count observed. If Peak > Current, expect elevated Abandoned blocks. |\n| **mmaps** | Number of OS allocation calls. Low counts (<50) with high memory usage indicate efficient arena reuse. High counts indicate frequent allocation pressure or fragmentation. |\n| **purges / resets** | Memory returned to OS. Usually 0 in benchmarks—non-zero indicates aggressive memory trimming or constrained environments. |\n\n### Summary (end-of-run heap stats)\n| Save | Committed Peak | Peak RSS | Commit Efficiency | Reserved Peak | Committed Current | Reserved Current | Pages Current | Pages Status | Abandoned Current | Abandoned Status | Thread Churn | Threads Peak | mmaps | purges | resets |\n|------|----------------|----------|-------------------|---------------|-------------------|------------------|---------------|-------------|-------------------|------------------|--------------|-------------|-------|--------|--------|\n{{#each results}}\n{{#each mimalloc}}\n| {{../save_name}} | {{committed_peak}} | {{peak_rss}} | {{commit_efficiency}} | {{reserved_peak}} | {{committed_current}} | {{reserved_current}} | {{pages_current}} | {{pages_status}} | {{abandoned_current}} | {{abandoned_status}} | {{thread_churn}} | {{threads_peak}} | {{mmaps}} | {{purges}} | {{resets}} |\n{{/each}}\n{{/each}}\n\n{{/if}}\n{{#if amd_uprof.summary_rows}}\n## AMD uProf\n\n| Save | Run | Profile | View | Duration | Threads | Session | Report |\n|------|-----|---------|------|----------|---------|---------|--------|\n{{#each amd_uprof.summary_rows}}\n| {{{save}}} | {{run}} | {{{profile}}} | {{{view}}} | {{{duration}}} | {{{threads}}} | {{{session}}} | {{{report}}} |\n{{/each}}\n\n{{#each amd_uprof.reports}}\n### {{{title}}}\n\n{{#if copy_error}}\nReport archive warning: {{{copy_error}}}\n\n{{/if}}\n{{#if parse_error}}\nReport parse warning: {{{parse_error}}}. Full CSV: `{{{report_path}}}`\n\n{{/if}}\n{{#if metadata_rows}}\n| Field | Value |\n|-------|-------|\n{{#each metadata_rows}}\n| {{{field}}} | {{{value}}} |\n{{/each}}\n\n{{/if}}\n{{#if cache_rows}}\n#### Estimated L1 Data Cache Summary\n\nEstimated from `L1_DC_ACCESSES_ALL.USER` and demand refill source counters.\n\n| Table | Item | Accesses | Est Hits | Est Misses | Est Miss Rate | L2 Refills | Cache Refills | External Cache Refills | DRAM Refills |\n|-------|------|----------|----------|------------|---------------|------------|---------------|------------------------|--------------|\n{{#each cache_rows}}\n| {{{table}}} | {{{item}}} | {{{accesses}}} | {{{hits}}} | {{{misses}}} | {{{miss_rate}}} | {{{local_l2}}} | {{{local_cache}}} | {{{external_cache}}} | {{{local_dram}}} |\n{{/each}}\n\n{{/if}}\n{{#if ibs_load_rows}}\n#### IBS Load Cache Summary\n\nReported by AMD IBS load views such as `ibs_op_ld` and `ibs_op_ld_lat`.\n\n| Table | Item | Loads | L1 Hit Rate | L1 Miss Rate | L2 Hit Rate | Local Cache Hit Rate | Peer Cache Hit Rate | Remote Cache Hit Rate | DRAM Hit Rate | Avg L1 Miss Latency |\n|-------|------|-------|-------------|--------------|-------------|----------------------|---------------------|-----------------------|---------------|---------------------|\n{{#each ibs_load_rows}}\n| {{{table}}} | {{{item}}} | {{{loads}}} | {{{l1_hit_rate}}} | {{{l1_miss_rate}}} | {{{l2_hit_rate}}} | {{{local_cache_hit_rate}}} | {{{peer_cache_hit_rate}}} | {{{remote_cache_hit_rate}}} | {{{dram_hit_rate}}} | {{{l1_miss_latency}}} |\n{{/each}}\n\n{{/if}}\n{{#each tables}}\n#### {{{title}}}\n\n|{{#each headers}} {{{this}}} |{{/each}}\n|{{#each headers}}------|{{/each}}\n{{#each rows}}\n|{{#each this}} {{{this}}} |{{/each}}\n{{/each}}\n\n{{#if truncated}}\nThis AMD uProf table was truncated in Markdown. Full CSV: `{{{../report_path}}}`\n\n{{/if}}\n{{/each}}\n{{#if truncated}}\nThis AMD uProf report was truncated in Markdown. Full CSV: `{{{report_path}}}`\n\n{{/if}}\n{{/each}}\n{{/if}}\n## Conclusion";
     ensure_output_dir(path)?;
 
     let mut report_results = results.to_vec();
@@ -65,38 +199,91 @@ fn write_report(results: &[BenchmarkRun], template_path: Option<&Path>, path: &P
         uprof::archive_and_parse_run(run, path);
     }
 
+    // Use the given template, else the legacy path, else the template string. A template that
+    // fails to load or render falls back to the template string, noting why in the report.
     let mut handlebars = Handlebars::new();
-    // Check for legacy path, otherwise use template string
-    let results_path = if let Some(template_path) = template_path {
-        let file_name = if template_path.extension().and_then(|s| s.to_str()) == Some("hbs") {
-            template_path.file_stem().map(PathBuf::from).unwrap()
-        } else {
-            PathBuf::from("results.md")
-        };
+    let mut report_notes = Vec::new();
+    let mut results_path = path.join("results.md");
+    let custom_template = template_path.map(Path::to_path_buf).or_else(|| {
+        let legacy_path = PathBuf::from("templates/results.md.hbs");
+        legacy_path.exists().then_some(legacy_path)
+    });
+    if let Some(custom_template) = &custom_template {
+        match handlebars.register_template_file("benchmark", custom_template) {
+            Ok(()) if template_path.is_some() => {
+                if custom_template.extension().and_then(|s| s.to_str()) == Some("hbs")
+                    && let Some(file_name) = custom_template.file_stem()
+                {
+                    results_path = path.join(file_name);
+                }
+            }
+            Ok(()) => {}
+            Err(err) => report_notes.push(template_failure(custom_template, &err)),
+        }
+    }
+    if !handlebars.has_template("benchmark") {
+        handlebars.register_template_string("benchmark", TPL_STR)?;
+    }
 
-        handlebars.register_template_file("benchmark", template_path)?;
+    let format = ReportFormat::from_path(&results_path);
+    let mut data = report_data(&report_results, options, path, format);
+    data["report_notes"] = json!(report_notes);
+
+    let mut rendered = match (handlebars.render("benchmark", &data), &custom_template) {
+        (Ok(rendered), _) => rendered,
+        (Err(err), Some(custom_template)) if report_notes.is_empty() => {
+            report_notes.push(template_failure(custom_template, &err));
+            results_path = path.join("results.md");
+            data["report_notes"] = json!(report_notes);
+            data["format"] = ReportFormat::Markdown.context();
 
-        path.join(file_name)
-    } else {
-        let legacy_path = PathBuf::from("templates/results.md.hbs");
-        if legacy_path.exists() {
-            handlebars.register_template_file("benchmark", legacy_path)?;
-        } else {
             handlebars.register_template_string("benchmark", TPL_STR)?;
+            handlebars.render("benchmark", &data)?
         }
-        path.join("results.md")
+        (Err(err), _) => return Err(err.into()),
     };
+    if options.anonymize {
+        rendered = Anonymizer::detect().text(&rendered);
+    }
+
+    std::fs::write(&results_path, rendered)?;
+
+    tracing::info!("Report written to {}", results_path.display());
+    Ok(())
+}
 
+/// The values the report template renders, for `results` written to `path` as `format`
+fn report_data(
+    results: &[BenchmarkRun],
+    options: &ReportOptions,
+    path: &Path,
+    format: ReportFormat,
+) -> serde_json::Value {
     // Calculate aggregated metrics for each benchmark result
-    let aggs = aggregate_by_save_name(&report_results);
-    let amd_uprof = output::uprof::build_section(&report_results, path);
+    let aggs = aggregate_by_save_name(results, options.sort_by.is_some());
+    let amd_uprof = output::uprof::build_section(results, path);
+
+    let numbers = &options.number_format;
+    let scaling = output::scaling::build_section(results, numbers);
+    let space_age = output::space_age::build_section(results, numbers, path);
+    let markers = output::markers::build_section(results, numbers, path);
+    let segments = output::segments::build_section(results, numbers);
+    let interference = output::interference::build_section(results, numbers);
+    let ab_mods = output::ab_mods::build_section(results, numbers);
+
+    let mean_ups = |a: &Aggregate| a.effective_ups / a.runs.max(1) as f64;
+    // The highest mean UPS, as displayed, is highlighted
+    let best_ups = aggs
+        .iter()
+        .map(mean_ups)
+        .max_by(f64::total_cmp)
+        .map(|ups| numbers.ups(ups));
 
     let mut table_results = Vec::new();
     for a in &aggs {
         let n = a.runs.max(1) as f64;
 
         let avg_ms = a.avg_ms / n;
-        let avg_effective_ups = a.effective_ups / n;
         let avg_base_diff = a.base_diff / n;
 
         let min_ms = if a.min_ms.is_infinite() {
@@ -110,115 +297,363 @@ fn write_report(results: &[BenchmarkRun], template_path: Option<&Path>, path: &P
             a.max_ms
         };
 
+        let ups = numbers.ups(mean_ups(a));
+        let ups = if best_ups.as_ref() == Some(&ups) {
+            format.bold(&ups)
+        } else {
+            ups
+        };
+
+        // Custom templates can describe each save in its row
+        let metadata = a.save_metadata.clone().unwrap_or_default();
         table_results.push(json!({
-            "save_name": a.save_name,
-            "avg_ms": format!("{:.3}", avg_ms),
-            "min_ms": format!("{:.3}", min_ms),
-            "max_ms": format!("{:.3}", max_ms),
-            "avg_effective_ups": (avg_effective_ups as u64).to_string(),
-            "percentage_improvement": format!("{:.2}%", avg_base_diff),
-            "total_execution_time_ms": a.total_execution_time_ms as u64,
+            "save_name": a.label,
+            "description": metadata.description.unwrap_or_default(),
+            "author": metadata.author.unwrap_or_default(),
+            "expected": metadata.expected.unwrap_or_default(),
+            "tags": metadata.tags,
+            "avg_ms": numbers.ms(avg_ms),
+            "min_ms": numbers.ms(min_ms),
+            "max_ms": numbers.ms(max_ms),
+            "avg_effective_ups": ups,
+            "percentage_improvement": numbers.percent(avg_base_diff),
+            "total_execution_time_ms": numbers.format(a.total_execution_time_ms, 0),
             "mimalloc": a.mimalloc_stats,
         }));
     }
 
-    let bolding_tags = match results_path.extension().and_then(|s| s.to_str()) {
-        Some("html") => ("<strong>", "</strong>"),
-        Some("md") => ("**", "**"),
-        _ => ("**", "**"),
+    let light_workloads: Vec<_> = aggs
+        .iter()
+        .filter(|a| a.light_workload)
+        .map(|a| {
+            json!({
+                "save_name": a.label,
+                "avg_ms": numbers.ms(a.avg_ms / a.runs.max(1) as f64),
+            })
+        })
+        .collect();
+
+    // Saves described in a saves.toml manifest or grouped into families get their own section
+    let save_details: Vec<_> = aggs
+        .iter()
+        .filter(|a| {
+            a.family.is_some()
+                || a.save_metadata.as_ref().is_some_and(|metadata| {
+                    metadata.description.is_some()
+                        || metadata.author.is_some()
+                        || metadata.expected.is_some()
+                        || !metadata.tags.is_empty()
+                })
+        })
+        .map(|a| {
+            let metadata = a.save_metadata.as_ref();
+            json!({
+                "save_name": a.label,
+                "family": a.family.as_deref().unwrap_or_default(),
+                "description": metadata.and_then(|metadata| metadata.description.as_deref()).unwrap_or_default(),
+                "author": metadata.and_then(|metadata| metadata.author.as_deref()).unwrap_or_default(),
+                "expected": metadata.and_then(|metadata| metadata.expected.as_deref()).unwrap_or_default(),
+                "tags": metadata.map(|metadata| metadata.tags.join(", ")).unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    // How to regenerate each map, from the sanitize check or else the saves.toml manifest
+    let map_details: Vec<_> = aggs
+        .iter()
+        .filter_map(|a| {
+            let sanitizer = a.sanitizer.as_ref();
+            let metadata = a.save_metadata.as_ref();
+            let seed = sanitizer
+                .and_then(|findings| findings.map_seed)
+                .or_else(|| metadata.and_then(|metadata| metadata.map_seed));
+            let exchange_string = sanitizer
+                .and_then(|findings| findings.map_exchange_string.as_deref())
+                .or_else(|| metadata.and_then(|metadata| metadata.map_exchange_string.as_deref()));
+
+            (seed.is_some() || exchange_string.is_some()).then(|| {
+                json!({
+                    "save_name": a.label,
+                    "seed": seed.map_or_else(|| "-".to_string(), |seed| seed.to_string()),
+                    "map_exchange_string": exchange_string,
+                })
+            })
+        })
+        .collect();
+
+    // Every individual run, per save
+    let run_details: Vec<_> = if options.include_runs {
+        aggs.iter()
+            .map(|a| {
+                let mut runs: Vec<&BenchmarkRun> = results
+                    .iter()
+                    .filter(|run| run.save_name == a.save_name && run.machine == a.machine)
+                    .collect();
+                runs.sort_by_key(|run| run.index);
+
+                let runs: Vec<_> = runs
+                    .iter()
+                    .map(|run| {
+                        json!({
+                            "run": run.index + 1,
+                            "ups": numbers.ups(run.effective_ups),
+                            "avg_ms": numbers.ms(run.avg_ms),
+                            "min_ms": numbers.ms(run.min_ms),
+                            "max_ms": numbers.ms(run.max_ms),
+                            "execution_time_ms": numbers.format(run.execution_time_ms, 0),
+                        })
+                    })
+                    .collect();
+
+                json!({ "save_name": a.label, "runs": runs })
+            })
+            .collect()
+    } else {
+        Vec::new()
     };
 
-    // Find the highest avg_effective_ups across all benchmarks for highlighting
-    if !table_results.is_empty() {
-        let max_avg_ups = table_results
-            .iter()
-            .map(|r| {
-                r["avg_effective_ups"]
-                    .as_str()
-                    .unwrap_or("0")
-                    .parse::<u64>()
-                    .unwrap_or(0)
+    // Findings of the sanitize check, per save
+    let sanitizer_details: Vec<_> = aggs
+        .iter()
+        .filter_map(|a| {
+            a.sanitizer.as_ref().map(|findings| {
+                let optional = |value: Option<f64>, precision: usize| {
+                    value.map_or_else(|| "-".to_string(), |v| format!("{v:.precision$}"))
+                };
+                // Tick time attributed to each surface by its share of the entities
+                let total_entities: u64 = findings
+                    .surfaces
+                    .iter()
+                    .map(|surface| surface.entity_count)
+                    .sum();
+                let avg_ms = a.avg_ms / a.runs.max(1) as f64;
+                let surfaces: Vec<_> = findings
+                    .surfaces
+                    .iter()
+                    .filter(|_| total_entities > 0)
+                    .map(|surface| {
+                        let share = surface.entity_count as f64 / total_entities as f64;
+                        json!({
+                            "name": surface.name,
+                            "kind": if surface.platform { "platform" } else { "planet" },
+                            "entities": numbers.format(surface.entity_count as f64, 0),
+                            "share": numbers.percent(share * 100.0),
+                            "estimated_ms": numbers.ms(avg_ms * share),
+                        })
+                    })
+                    .collect();
+                json!({
+                    "save_name": a.label,
+                    "ups": numbers.ups(mean_ups(a)),
+                    "total_pollution": optional(findings.total_pollution, 0),
+                    "evolution_factor": optional(findings.evolution_factor, 4),
+                    "enemy_count": findings.enemy_count,
+                    "warnings": findings.warnings,
+                    "production": findings.production,
+                    "surfaces": surfaces,
+                })
             })
-            .max()
-            .unwrap_or(0);
-
-        // Add bold formatting to the highest UPS value
-        for result in &mut table_results {
-            let ups_str = result["avg_effective_ups"].as_str().unwrap_or("0");
-            let ups = ups_str.parse::<u64>().unwrap_or(0);
-            if ups == max_avg_ups {
-                result["avg_effective_ups"] =
-                    json!(format!("{}{}{}", bolding_tags.0, ups, bolding_tags.1));
-            }
-        }
-    }
+        })
+        .collect();
+
+    // Energy and power of the CPU packages, per save
+    let energy_details: Vec<_> = aggs
+        .iter()
+        .filter(|a| a.energy_runs > 0)
+        .map(|a| {
+            let n = a.energy_runs as f64;
+            json!({
+                "save_name": a.label,
+                "joules": numbers.format(a.energy_joules / n, 1),
+                "watts": numbers.format(a.avg_watts / n, 1),
+            })
+        })
+        .collect();
+
+    let system_details = system_details(results);
 
-    let data = json!({
+    json!({
         "platform": results.first().map(|run| run.platform.as_str()),
-        "factorio_version": results.first().map(|run| run.factorio_version.as_str()),
+        "factorio_version": factorio_versions(results).join(", "),
         "results": table_results,
-        "ticks": report_results.first().map(|run| run.ticks).unwrap_or(0),
+        "light_workloads": light_workloads,
+        "vars": options.extra_vars,
+        "save_details": save_details,
+        "map_details": map_details,
+        "run_details": run_details,
+        "format": format.context(),
+        "sanitizer_details": sanitizer_details,
+        "energy_details": energy_details,
+        "scaling": scaling,
+        "space_age": space_age,
+        "markers": markers,
+        "segments": segments,
+        "interference": interference,
+        "ab_mods": ab_mods,
+        "exclude_interference": options.exclude_interference,
+        "ticks": results.first().map(|run| run.ticks).unwrap_or(0),
         "runs": aggs.first().map(|aggregate| aggregate.runs).unwrap_or(0),
         "date": Local::now().date_naive().to_string(),
+        "ups_definition": options.ups_definition.description(),
+        "settle_ticks": options.settle_ticks,
+        "tick_range": options.tick_range.map(|range| range.to_string()),
+        "machines": machines(results).join(", "),
+        "system_details": system_details,
         "amd_uprof": amd_uprof,
-    });
+    })
+}
 
-    let rendered = handlebars.render("benchmark", &data)?;
+/// Render `template` against sample results in strict mode, so a syntax error or a variable the
+/// report doesn't provide fails before hours of benchmarking instead of at report time. The
+/// sample results fill every section except mimalloc, AMD uProf and Space Age, and `vars` holds
+/// `extra_vars`.
+pub fn check_template(template: &Path, extra_vars: &HashMap<String, String>) -> Result<()> {
+    let invalid = |err: &dyn std::fmt::Display| BenchmarkErrorKind::InvalidTemplate {
+        path: template.to_path_buf(),
+        reason: err.to_string(),
+    };
 
-    std::fs::write(&results_path, rendered)?;
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(true);
+    handlebars
+        .register_template_file("benchmark", template)
+        .map_err(|err| invalid(&err))?;
 
-    tracing::info!("Report written to {}", results_path.display());
+    // The report is named after the template, which decides its format
+    let report_path = template
+        .file_stem()
+        .map_or_else(PathBuf::new, PathBuf::from);
+    let options = ReportOptions {
+        include_runs: true,
+        settle_ticks: 60,
+        tick_range: "0..600".parse().ok(),
+        exclude_interference: true,
+        extra_vars: extra_vars.clone(),
+        ..Default::default()
+    };
+    let mut data = report_data(
+        &sample_results(),
+        &options,
+        Path::new(""),
+        ReportFormat::from_path(&report_path),
+    );
+    data["report_notes"] = json!(["A note about the session"]);
+
+    handlebars
+        .render("benchmark", &data)
+        .map_err(|err| invalid(&err))?;
     Ok(())
 }
 
+/// Two runs each of two saves with everything but mimalloc, AMD uProf and verbose metrics
+fn sample_results() -> Vec<BenchmarkRun> {
+    let run = |save_name: &str, clones: u32, index: u32| BenchmarkRun {
+        index,
+        save_name: save_name.to_string(),
+        factorio_version: "2.0.0".to_string(),
+        platform: "linux-x86_64".to_string(),
+        execution_time_ms: 600.0 * clones as f64,
+        ticks: 600,
+        avg_ms: clones as f64,
+        min_ms: 0.5 * clones as f64,
+        max_ms: 2.0 * clones as f64,
+        effective_ups: 1000.0 / clones as f64,
+        save_metadata: Some(SaveMetadata {
+            description: Some("A sample save".to_string()),
+            author: Some("Sample author".to_string()),
+            expected: Some("Scales linearly".to_string()),
+            tags: vec!["scaling:sample".to_string(), format!("clones:{clones}")],
+            map_seed: Some(1),
+            ..Default::default()
+        }),
+        sanitizer: Some(SanitizerFindings {
+            warnings: vec!["Pollution is enabled".to_string()],
+            production: vec!["iron-plate: 100/min".to_string()],
+            total_pollution: Some(0.0),
+            map_exchange_string: Some(">>>sample<<<".to_string()),
+            surfaces: vec![SurfaceStatistics {
+                name: "nauvis".to_string(),
+                platform: false,
+                entity_count: 100,
+            }],
+            ..Default::default()
+        }),
+        energy_joules: Some(100.0),
+        avg_watts: Some(50.0),
+        system: Some(SystemSnapshot {
+            cpu_model: Some("Sample CPU".to_string()),
+            ..Default::default()
+        }),
+        family: Some("sample".to_string()),
+        markers: vec![Marker {
+            tick: 10,
+            event: "rocket-launched".to_string(),
+        }],
+        segments: vec![Segment {
+            start_tick: 0,
+            ticks: 600,
+            avg_ms: clones as f64,
+            min_ms: 0.5 * clones as f64,
+            max_ms: 2.0 * clones as f64,
+        }],
+        interference: Some(Interference {
+            total_ms: 6.0 * clones as f64,
+            dominated_ticks: 1,
+            dominated_ms: 3.0 * clones as f64,
+        }),
+        mods_variant: Some(ModsVariant {
+            side: clones as usize - 1,
+            label: save_name.to_string(),
+            save_name: "sample".to_string(),
+        }),
+        light_workload: Some(clones == 1),
+        ..Default::default()
+    };
+
+    vec![
+        run("sample-1", 1, 0),
+        run("sample-1", 1, 1),
+        run("sample-2", 2, 0),
+        run("sample-2", 2, 1),
+    ]
+}
+
+/// Why `template` wasn't used, logged and noted in the report
+fn template_failure(template: &Path, err: &dyn std::fmt::Display) -> String {
+    let note = format!(
+        "The template {} could not be used ({err}), so this report uses the built-in template",
+        template.display()
+    );
+    tracing::warn!("{note}");
+    note
+}
+
 fn append_report(
     results: &[BenchmarkRun],
     template_path: Option<&Path>,
+    options: &ReportOptions,
     path: &Path,
 ) -> Result<()> {
     let results_csv = path.join("results.csv");
 
     if !results_csv.exists() {
-        return write_report(results, template_path, path);
+        return write_report(results, template_path, options, path);
     }
 
-    let mut combined = read_benchmark_runs_from_csv(&results_csv)?;
+    let mut combined = read_benchmark_csv(&results_csv)?;
     combined.extend_from_slice(results);
 
     calculate_base_differences(&mut combined);
 
-    write_report(results, template_path, path)
-}
-
-fn read_benchmark_runs_from_csv(csv_path: &Path) -> Result<Vec<BenchmarkRun>> {
-    let mut reader = csv::Reader::from_path(csv_path)?;
-    let mut runs = Vec::new();
-
-    for record in reader.records() {
-        let record = record?;
-
-        runs.push(BenchmarkRun {
-            save_name: record.get(0).unwrap_or_default().to_string(),
-            index: record.get(1).unwrap_or("0").parse()?,
-            execution_time_ms: record.get(2).unwrap_or("0").parse()?,
-            avg_ms: record.get(3).unwrap_or("0").parse()?,
-            min_ms: record.get(4).unwrap_or("0").parse()?,
-            max_ms: record.get(5).unwrap_or("0").parse()?,
-            effective_ups: record.get(6).unwrap_or("0").parse()?,
-            base_diff: record.get(7).unwrap_or("0").parse()?,
-            ticks: record.get(8).unwrap_or("0").parse()?,
-            factorio_version: record.get(9).unwrap_or("unknown").to_string(),
-            platform: record.get(10).unwrap_or("unknown").to_string(),
-            ..Default::default()
-        });
-    }
-
-    Ok(runs)
+    write_report(results, template_path, options, path)
 }
 
 #[derive(Debug, Clone)]
 struct Aggregate {
     save_name: String,
+    machine: Option<String>,
+    /// Name in the tables, with the machine when the results span several
+    label: String,
 
     runs: u32,
     total_execution_time_ms: f64,
@@ -229,12 +664,24 @@ struct Aggregate {
     base_diff: f64,
 
     mimalloc_stats: Vec<MimallocStats>,
+    save_metadata: Option<SaveMetadata>,
+    family: Option<String>,
+    sanitizer: Option<SanitizerFindings>,
+    /// Whether the save was flagged as below the minimum workload
+    light_workload: bool,
+
+    /// Runs with an energy measurement, with their summed joules and watts
+    energy_runs: u32,
+    energy_joules: f64,
+    avg_watts: f64,
 }
 
 impl Aggregate {
     fn new(r: &BenchmarkRun) -> Self {
         Self {
             save_name: r.save_name.clone(),
+            machine: r.machine.clone(),
+            label: r.save_name.clone(),
 
             runs: 0,
             total_execution_time_ms: 0.0,
@@ -245,6 +692,14 @@ impl Aggregate {
             base_diff: 0.0,
 
             mimalloc_stats: Vec::new(),
+            save_metadata: r.save_metadata.clone(),
+            family: r.family.clone(),
+            sanitizer: r.sanitizer.clone(),
+            light_workload: r.light_workload == Some(true),
+
+            energy_runs: 0,
+            energy_joules: 0.0,
+            avg_watts: 0.0,
         }
     }
 
@@ -262,26 +717,108 @@ impl Aggregate {
         if let Some(stats) = r.mimalloc_stats.clone() {
             self.mimalloc_stats.push(stats);
         }
+
+        if let (Some(joules), Some(watts)) = (r.energy_joules, r.avg_watts) {
+            self.energy_runs += 1;
+            self.energy_joules += joules;
+            self.avg_watts += watts;
+        }
     }
 }
 
-fn aggregate_by_save_name(runs: &[BenchmarkRun]) -> Vec<Aggregate> {
-    let mut map: HashMap<&str, Aggregate> = HashMap::new();
-
+/// One aggregate per save and machine, sorted by name unless `keep_order` keeps the order the
+/// saves first appear in
+fn aggregate_by_save_name(runs: &[BenchmarkRun], keep_order: bool) -> Vec<Aggregate> {
+    let mut aggs: Vec<Aggregate> = Vec::new();
     for run in runs {
-        map.entry(run.save_name.as_str())
-            .or_insert_with(|| Aggregate::new(run))
-            .push(run);
+        match aggs
+            .iter_mut()
+            .find(|a| a.save_name == run.save_name && a.machine == run.machine)
+        {
+            Some(aggregate) => aggregate.push(run),
+            None => {
+                let mut aggregate = Aggregate::new(run);
+                aggregate.push(run);
+                aggs.push(aggregate);
+            }
+        }
+    }
+
+    if !keep_order {
+        aggs.sort_by(|a, b| (&a.save_name, &a.machine).cmp(&(&b.save_name, &b.machine)));
     }
 
-    let mut aggs: Vec<Aggregate> = map.into_values().collect();
-    aggs.sort_by(|a, b| a.save_name.cmp(&b.save_name));
+    if machines(runs).len() > 1 {
+        for a in &mut aggs {
+            if let Some(machine) = &a.machine {
+                a.label = format!("{} ({machine})", a.save_name);
+            }
+        }
+    }
     aggs
 }
 
+/// Distinct Factorio versions of the runs with their build numbers, in order of appearance
+fn factorio_versions<'a>(runs: impl IntoIterator<Item = &'a BenchmarkRun>) -> Vec<String> {
+    let mut versions: Vec<String> = Vec::new();
+    for run in runs {
+        let version = match run.factorio_build {
+            Some(build) => format!("{} (build {build})", run.factorio_version),
+            None => run.factorio_version.clone(),
+        };
+        if !versions.contains(&version) {
+            versions.push(version);
+        }
+    }
+    versions
+}
+
+/// Hardware and system of each machine with a snapshot, in order of appearance
+fn system_details(runs: &[BenchmarkRun]) -> Vec<serde_json::Value> {
+    let mut seen: Vec<(Option<&str>, &SystemSnapshot)> = Vec::new();
+    let mut details = Vec::new();
+    for run in runs {
+        let Some(system) = &run.system else {
+            continue;
+        };
+        let key = (run.machine.as_deref(), system);
+        if seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+
+        let machine_runs = runs
+            .iter()
+            .filter(|other| other.machine == run.machine && other.system.as_ref() == Some(system));
+        let text = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+        details.push(json!({
+            "machine": run.machine.as_deref().unwrap_or("-"),
+            "cpu_model": text(&system.cpu_model),
+            "cpu_cores": system.cpu_cores.map_or_else(|| "-".to_string(), |cores| cores.to_string()),
+            "memory_gb": system.memory_gb.map_or_else(|| "-".to_string(), |gb| format!("{gb} GiB")),
+            "power_profile": text(&system.power_profile),
+            "os_version": text(&system.os_version),
+            "factorio_version": factorio_versions(machine_runs).join(", "),
+        }));
+    }
+    details
+}
+
+/// Distinct machines the runs were measured on, sorted
+fn machines(runs: &[BenchmarkRun]) -> Vec<&str> {
+    let mut machines: Vec<&str> = runs
+        .iter()
+        .filter_map(|run| run.machine.as_deref())
+        .collect();
+    machines.sort_unstable();
+    machines.dedup();
+    machines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sanitize::parser::SurfaceStatistics;
 
     #[test]
     fn test_report_uses_runs_per_save_in_scenario() {
@@ -342,12 +879,266 @@ mod tests {
             },
         ];
 
-        write_report(&results, None, path).expect("write report");
+        write_report(&results, None, &ReportOptions::default(), path).expect("write report");
 
         let report = std::fs::read_to_string(path.join("results.md")).expect("read report");
         assert!(report.contains("Each save was tested for 6000 tick(s) and 2 run(s)"));
     }
 
+    #[test]
+    fn test_report_lists_individual_runs_when_requested() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let results = vec![
+            BenchmarkRun {
+                save_name: "alpha".to_string(),
+                index: 1,
+                avg_ms: 11.0,
+                effective_ups: 54545.0,
+                ..Default::default()
+            },
+            BenchmarkRun {
+                save_name: "alpha".to_string(),
+                index: 0,
+                avg_ms: 10.0,
+                effective_ups: 60000.0,
+                ..Default::default()
+            },
+        ];
+
+        write_report(&results, None, &ReportOptions::default(), path).expect("write report");
+        let report = std::fs::read_to_string(path.join("results.md")).expect("read report");
+        assert!(!report.contains("## Individual Runs"));
+
+        let options = ReportOptions {
+            include_runs: true,
+            ..Default::default()
+        };
+        write_report(&results, None, &options, path).expect("write report");
+        let report = std::fs::read_to_string(path.join("results.md")).expect("read report");
+        assert!(report.contains("## Individual Runs"));
+        assert!(!report.contains("<details>"));
+        let first = report
+            .find("| 1 | 60000 | 10.000 |")
+            .expect("first run row");
+        let second = report
+            .find("| 2 | 54545 | 11.000 |")
+            .expect("second run row");
+        assert!(first < second);
+    }
+
+    #[test]
+    fn test_number_format_rounding_and_separators() {
+        let default = NumberFormat::default();
+        assert_eq!(default.ms(16.66666), "16.667");
+        assert_eq!(default.ups(1234567.6), "1234568");
+        assert_eq!(default.percent(-2.5051), "-2.51%");
+
+        let german = NumberFormat {
+            ms_decimals: 1,
+            thousands_separator: Some('.'),
+            decimal_separator: ',',
+            ..Default::default()
+        };
+        assert_eq!(german.ms(1234.56), "1.234,6");
+        assert_eq!(german.ups(123456.0), "123.456");
+        assert_eq!(german.ups(-999.0), "-999");
+        assert_eq!(german.percent(-1234.5), "-1.234,50%");
+    }
+
+    #[test]
+    fn test_broken_template_falls_back_to_built_in_one() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let results = vec![BenchmarkRun {
+            save_name: "alpha".to_string(),
+            effective_ups: 60000.0,
+            ..Default::default()
+        }];
+
+        let missing = path.join("missing.md.hbs");
+        write_report(&results, Some(&missing), &ReportOptions::default(), path)
+            .expect("write report");
+        let report = std::fs::read_to_string(path.join("results.md")).expect("read report");
+        assert!(report.contains("> **Note:** The template"));
+        assert!(report.contains("| alpha |"));
+
+        let unrenderable = path.join("strict.md.hbs");
+        std::fs::write(
+            &unrenderable,
+            "{{#each results}}{{> missing_partial}}{{/each}}",
+        )
+        .expect("write template");
+        write_report(
+            &results,
+            Some(&unrenderable),
+            &ReportOptions::default(),
+            path,
+        )
+        .expect("write report");
+        let report = std::fs::read_to_string(path.join("results.md")).expect("read report");
+        assert!(report.contains("strict.md.hbs could not be used"));
+        assert!(!path.join("strict.md").exists());
+    }
+
+    #[test]
+    fn test_check_template_reports_missing_variables_and_syntax_errors() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+
+        check_template(Path::new("templates/results.md.hbs"), &HashMap::new())
+            .expect("the shipped template renders");
+        assert!(
+            !Path::new("scaling.csv").exists(),
+            "checking a template writes no files"
+        );
+
+        let typo = path.join("typo.md.hbs");
+        std::fs::write(&typo, "{{#each results}}{{save_nmae}}{{/each}}").expect("write template");
+        let error =
+            check_template(&typo, &HashMap::new()).expect_err("save_nmae is not a variable");
+        assert!(error.to_string().contains("save_nmae"));
+
+        let unclosed = path.join("unclosed.md.hbs");
+        std::fs::write(&unclosed, "{{#each results}}{{save_name}}").expect("write template");
+        assert!(check_template(&unclosed, &HashMap::new()).is_err());
+        assert!(check_template(&path.join("missing.md.hbs"), &HashMap::new()).is_err());
+
+        let vars = path.join("vars.md.hbs");
+        std::fs::write(&vars, "{{vars.description}}").expect("write template");
+        assert!(check_template(&vars, &HashMap::new()).is_err());
+        let extra_vars = HashMap::from([("description".to_string(), "Rig A".to_string())]);
+        check_template(&vars, &extra_vars).expect("description is given with --var");
+    }
+
+    #[test]
+    fn test_html_template_gets_html_context() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let template = path.join("results.html.hbs");
+        std::fs::write(
+            &template,
+            "{{format.name}}{{#each results}} {{{avg_effective_ups}}}{{/each}}",
+        )
+        .expect("write template");
+        let results = vec![BenchmarkRun {
+            save_name: "alpha".to_string(),
+            effective_ups: 60000.0,
+            ..Default::default()
+        }];
+
+        write_report(&results, Some(&template), &ReportOptions::default(), path)
+            .expect("write report");
+        let report = std::fs::read_to_string(path.join("results.html")).expect("read report");
+        assert_eq!(report, "html <strong>60000</strong>");
+    }
+
+    #[test]
+    fn test_report_renders_sanitizer_findings() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let results = vec![
+            BenchmarkRun {
+                save_name: "alpha".to_string(),
+                effective_ups: 60000.0,
+                avg_ms: 4.0,
+                sanitizer: Some(SanitizerFindings {
+                    warnings: vec!["Pollution is enabled/present".to_string()],
+                    production: vec!["produced: normal-iron-plate (12)".to_string()],
+                    total_pollution: Some(1500.0),
+                    evolution_factor: Some(0.125),
+                    enemy_count: 7,
+                    map_seed: Some(123456789),
+                    map_exchange_string: Some(">>>eNpjYBBgAAAAAP//<<<".to_string()),
+                    surfaces: vec![
+                        SurfaceStatistics {
+                            name: "nauvis".to_string(),
+                            platform: false,
+                            entity_count: 750,
+                        },
+                        SurfaceStatistics {
+                            name: "platform-1".to_string(),
+                            platform: true,
+                            entity_count: 250,
+                        },
+                    ],
+                }),
+                ..Default::default()
+            },
+            BenchmarkRun {
+                save_name: "beta".to_string(),
+                effective_ups: 50000.0,
+                sanitizer: Some(SanitizerFindings::default()),
+                ..Default::default()
+            },
+        ];
+
+        write_report(&results, None, &ReportOptions::default(), path).expect("write report");
+
+        let report = std::fs::read_to_string(path.join("results.md")).expect("read report");
+        assert!(report.contains("## Sanitizer"));
+        assert!(report.contains("| alpha | 60000 | 1500 | 0.1250 | 7 |"));
+        assert!(report.contains("| beta | 50000 | - | - | 0 |"));
+        assert!(report.contains("* Warning: Pollution is enabled/present"));
+        assert!(report.contains("* produced: normal-iron-plate (12)\n\n| Surface |"));
+        assert!(report.contains("| nauvis | planet | 750 | 75.00% | 3.000 |"));
+        assert!(report.contains("| platform-1 | platform | 250 | 25.00% | 1.000 |"));
+        assert!(report.contains("### beta\n* No benchmark-affecting issues found"));
+        assert!(report.contains("## Maps"));
+        assert!(report.contains("| alpha | 123456789 |"));
+        assert!(report.contains("```\n>>>eNpjYBBgAAAAAP//<<<\n```"));
+        assert!(!report.contains("| beta | - |"));
+    }
+
+    #[test]
+    fn test_report_renders_energy_per_save() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let run = |save_name: &str, energy: Option<(f64, f64)>| BenchmarkRun {
+            save_name: save_name.to_string(),
+            energy_joules: energy.map(|(joules, _)| joules),
+            avg_watts: energy.map(|(_, watts)| watts),
+            ..Default::default()
+        };
+        let results = vec![
+            run("alpha", Some((100.0, 40.0))),
+            run("alpha", Some((110.0, 44.0))),
+            run("beta", None),
+        ];
+
+        write_report(&results, None, &ReportOptions::default(), path).expect("write report");
+
+        let report = std::fs::read_to_string(path.join("results.md")).expect("read report");
+        assert!(report.contains("## Energy"));
+        assert!(report.contains("| alpha | 105.0 | 42.0 |"));
+        assert!(!report.contains("| beta | 0.0 |"));
+    }
+
+    #[test]
+    fn test_report_groups_saves_by_machine() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path();
+        let run = |machine: &str, ups: f64| BenchmarkRun {
+            save_name: "alpha".to_string(),
+            machine: Some(machine.to_string()),
+            effective_ups: ups,
+            ..Default::default()
+        };
+        let results = vec![
+            run("desktop", 100.0),
+            run("laptop", 40.0),
+            run("desktop", 120.0),
+        ];
+
+        write_report(&results, None, &ReportOptions::default(), path).expect("write report");
+
+        let report = std::fs::read_to_string(path.join("results.md")).expect("read report");
+        assert!(report.contains("**Machines:** desktop, laptop"));
+        assert!(report.contains("| alpha (desktop) |"));
+        assert!(report.contains("| alpha (laptop) |"));
+        assert!(report.contains("| **110** |"));
+    }
+
     #[test]
     fn test_report_archives_and_renders_amd_uprof_report() {
         let temp_dir = tempfile::tempdir().expect("temp dir");
@@ -399,7 +1190,7 @@ foo,200.0000,80.0000,20.0000,10.0000,7.0000,1.0000,0.0000,2.0000,42.5000,libfoo.
             ..Default::default()
         }];
 
-        write_report(&results, None, path).expect("write report");
+        write_report(&results, None, &ReportOptions::default(), path).expect("write report");
 
         let copied = path.join("uprof/alpha/run_0/report_0.csv");
         assert!(copied.exists(), "report.csv should be copied");
@@ -0,0 +1,220 @@
+//! Scaling analysis of saves tagged as clones of the same build (`clones:<N>` in `saves.toml`):
+//! fits the tick time against the clone count to estimate the marginal cost of every clone.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    benchmark::parser::BenchmarkRun,
+    core::{
+        output::NumberFormat,
+        utils::{self, LinearFit},
+    },
+};
+
+/// Points and fitted tick times of every series, written next to `results.csv`
+pub const SCALING_CSV_FILENAME: &str = "scaling.csv";
+
+/// Tick time at which UPS drops below 60
+const MS_AT_60_UPS: f64 = 1000.0 / 60.0;
+
+/// Mean results of one clone save
+#[derive(Debug, Clone)]
+pub(crate) struct ClonePoint {
+    pub save_name: String,
+    pub clones: u32,
+    pub avg_ms: f64,
+    pub ups: f64,
+}
+
+/// A scaling series with the line fitted through its points
+#[derive(Debug, Clone)]
+pub(crate) struct FittedSeries {
+    pub name: String,
+    pub points: Vec<ClonePoint>,
+    pub fit: LinearFit,
+}
+
+/// Fit every scaling series with saves of at least two different clone counts
+pub(crate) fn fit_series(results: &[BenchmarkRun]) -> Vec<FittedSeries> {
+    let mut fitted = Vec::new();
+    for (name, points) in collect_series(results) {
+        let xy: Vec<(f64, f64)> = points
+            .iter()
+            .map(|point| (point.clones as f64, point.avg_ms))
+            .collect();
+        match utils::linear_fit(&xy) {
+            Some(fit) => fitted.push(FittedSeries { name, points, fit }),
+            None => tracing::debug!(
+                "Scaling series {name} needs saves with at least two different clone counts"
+            ),
+        }
+    }
+    fitted
+}
+
+/// Build the report section for every scaling series
+pub(crate) fn build_section(
+    results: &[BenchmarkRun],
+    numbers: &NumberFormat,
+) -> Vec<ScalingSeries> {
+    fit_series(results)
+        .iter()
+        .map(|series| series_view(series, numbers))
+        .collect()
+}
+
+/// Mean results per clone save, grouped by series and ordered by clone count
+fn collect_series(results: &[BenchmarkRun]) -> BTreeMap<String, Vec<ClonePoint>> {
+    let mut by_save: BTreeMap<&str, (&BenchmarkRun, Vec<&BenchmarkRun>)> = BTreeMap::new();
+    for run in results {
+        by_save
+            .entry(run.save_name.as_str())
+            .or_insert_with(|| (run, Vec::new()))
+            .1
+            .push(run);
+    }
+
+    let mut series: BTreeMap<String, Vec<ClonePoint>> = BTreeMap::new();
+    for (save_name, (first, runs)) in by_save {
+        let Some(metadata) = &first.save_metadata else {
+            continue;
+        };
+        let (Some(clones), Some(name)) = (metadata.clones(), metadata.scaling_series()) else {
+            continue;
+        };
+
+        let avg_ms: Vec<f64> = runs.iter().map(|run| run.avg_ms).collect();
+        let ups: Vec<f64> = runs.iter().map(|run| run.effective_ups).collect();
+        series
+            .entry(name.to_string())
+            .or_default()
+            .push(ClonePoint {
+                save_name: save_name.to_string(),
+                clones,
+                avg_ms: utils::mean(&avg_ms).unwrap_or_default(),
+                ups: utils::mean(&ups).unwrap_or_default(),
+            });
+    }
+
+    for points in series.values_mut() {
+        points.sort_by_key(|point| point.clones);
+    }
+    series
+}
+
+fn series_view(series: &FittedSeries, numbers: &NumberFormat) -> ScalingSeries {
+    let FittedSeries { name, points, fit } = series;
+    // Clones that still run at 60 UPS, extrapolated from the fit
+    let clones_at_60_ups = if fit.slope > 0.0 && fit.intercept < MS_AT_60_UPS {
+        ((MS_AT_60_UPS - fit.intercept) / fit.slope)
+            .floor()
+            .to_string()
+    } else {
+        "-".to_string()
+    };
+
+    let chart_values = |values: Vec<f64>| {
+        values
+            .iter()
+            .map(|value| format!("{value:.3}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    ScalingSeries {
+        name: name.to_string(),
+        ms_per_clone: numbers.ms(fit.slope),
+        base_ms: numbers.ms(fit.intercept),
+        r_squared: format!("{:.3}", fit.r_squared),
+        clones_at_60_ups,
+        points: points
+            .iter()
+            .map(|point| ScalingPointView {
+                save_name: point.save_name.clone(),
+                clones: point.clones,
+                ups: numbers.ups(point.ups),
+                avg_ms: numbers.ms(point.avg_ms),
+                fitted_ms: numbers.ms(fit.at(point.clones as f64)),
+            })
+            .collect(),
+        chart_clones: points
+            .iter()
+            .map(|point| point.clones.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        chart_measured: chart_values(points.iter().map(|point| point.avg_ms).collect()),
+        chart_fitted: chart_values(
+            points
+                .iter()
+                .map(|point| fit.at(point.clones as f64))
+                .collect(),
+        ),
+    }
+}
+
+/// A fitted scaling series as rendered in the report
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ScalingSeries {
+    name: String,
+    /// Marginal tick time of one more clone
+    ms_per_clone: String,
+    /// Tick time without any clones, the fit's intercept
+    base_ms: String,
+    r_squared: String,
+    clones_at_60_ups: String,
+    points: Vec<ScalingPointView>,
+    chart_clones: String,
+    chart_measured: String,
+    chart_fitted: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ScalingPointView {
+    save_name: String,
+    clones: u32,
+    ups: String,
+    avg_ms: String,
+    fitted_ms: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::saves::SaveMetadata;
+
+    #[test]
+    fn test_scaling_fits_tick_time_per_clone() {
+        let run = |save_name: &str, tags: &[&str], avg_ms: f64| BenchmarkRun {
+            save_name: save_name.to_string(),
+            avg_ms,
+            effective_ups: 1000.0 / avg_ms,
+            save_metadata: Some(SaveMetadata {
+                tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let results = vec![
+            run("x4", &["clones:4"], 5.0),
+            run("x1", &["clones:1"], 2.0),
+            run("x2", &["clones:2"], 2.9),
+            run("x2", &["clones:2"], 3.1),
+            run("lonely", &["clones:1", "scaling:other"], 1.0),
+            run("untagged", &[], 10.0),
+        ];
+
+        let section = build_section(&results, &NumberFormat::default());
+
+        assert_eq!(section.len(), 1);
+        let series = &section[0];
+        assert_eq!(series.name, "clones");
+        assert_eq!(series.ms_per_clone, "1.000");
+        assert_eq!(series.base_ms, "1.000");
+        assert_eq!(series.r_squared, "1.000");
+        assert_eq!(series.clones_at_60_ups, "15");
+        assert_eq!(series.chart_clones, "1, 2, 4");
+        assert_eq!(series.chart_measured, "2.000, 3.000, 5.000");
+    }
+}
@@ -0,0 +1,170 @@
+//! Long-run segments of each save (`--segment-ticks`): the tick times of every fixed-size part of
+//! the runs next to the whole runs, so drift within a run, e.g. biters re-pathing or fluids
+//! settling, shows without verbose metrics.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    benchmark::parser::BenchmarkRun,
+    core::{mean, output::NumberFormat},
+};
+
+/// File in the output directory listing the segments of every run
+pub const SEGMENTS_CSV_FILENAME: &str = "segments.csv";
+
+/// Build the report section from the segments of the runs, averaging each segment over the runs
+/// of its save
+pub(crate) fn build_section(results: &[BenchmarkRun], numbers: &NumberFormat) -> Vec<SegmentSave> {
+    let mut runs_by_save: BTreeMap<&str, Vec<&BenchmarkRun>> = BTreeMap::new();
+    for run in results.iter().filter(|run| !run.segments.is_empty()) {
+        runs_by_save.entry(&run.save_name).or_default().push(run);
+    }
+
+    runs_by_save
+        .into_iter()
+        .map(|(save_name, runs)| save_view(save_name, &runs, numbers))
+        .collect()
+}
+
+fn save_view(save_name: &str, runs: &[&BenchmarkRun], numbers: &NumberFormat) -> SegmentSave {
+    #[derive(Default)]
+    struct SegmentTimes {
+        ticks: u32,
+        avg: Vec<f64>,
+        min: Vec<f64>,
+        max: Vec<f64>,
+    }
+
+    let mut segments: BTreeMap<u32, SegmentTimes> = BTreeMap::new();
+    for segment in runs.iter().flat_map(|run| &run.segments) {
+        let times = segments.entry(segment.start_tick).or_default();
+        times.ticks = times.ticks.max(segment.ticks);
+        times.avg.push(segment.avg_ms);
+        times.min.push(segment.min_ms);
+        times.max.push(segment.max_ms);
+    }
+
+    let first_avg = segments.values().next().and_then(|times| mean(&times.avg));
+    let vs_first = |avg: Option<f64>| match (avg, first_avg) {
+        (Some(avg), Some(first)) if first > 0.0 => {
+            let change = (avg / first - 1.0) * 100.0;
+            let sign = if change > 0.0 { "+" } else { "" };
+            format!("{sign}{}", numbers.percent(change))
+        }
+        _ => "-".to_string(),
+    };
+    let row = |label: String, avg: Option<f64>, min: Option<f64>, max: Option<f64>| SegmentView {
+        ticks: label,
+        ups: avg
+            .filter(|avg| *avg > 0.0)
+            .map_or_else(|| "-".to_string(), |avg| numbers.ups(1000.0 / avg)),
+        avg_ms: avg.map_or_else(|| "-".to_string(), |avg| numbers.ms(avg)),
+        min_ms: min.map_or_else(|| "-".to_string(), |min| numbers.ms(min)),
+        max_ms: max.map_or_else(|| "-".to_string(), |max| numbers.ms(max)),
+        vs_first: vs_first(avg),
+    };
+
+    let drift = vs_first(segments.values().last().and_then(|times| mean(&times.avg)));
+    let mut rows: Vec<SegmentView> = segments
+        .iter()
+        .map(|(start_tick, times)| {
+            row(
+                format!("{start_tick}–{}", start_tick + times.ticks),
+                mean(&times.avg),
+                mean(&times.min),
+                mean(&times.max),
+            )
+        })
+        .collect();
+
+    let whole = |value: fn(&BenchmarkRun) -> f64| {
+        mean(&runs.iter().map(|run| value(run)).collect::<Vec<_>>())
+    };
+    rows.push(row(
+        "All".to_string(),
+        whole(|run| run.avg_ms),
+        whole(|run| run.min_ms),
+        whole(|run| run.max_ms),
+    ));
+
+    SegmentSave {
+        save_name: save_name.to_string(),
+        drift,
+        segments: rows,
+    }
+}
+
+/// The segments of one save as rendered in the report
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SegmentSave {
+    save_name: String,
+    /// Mean tick time of the last segment compared to the first
+    drift: String,
+    /// One row per segment, then the whole runs
+    segments: Vec<SegmentView>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SegmentView {
+    /// Measured ticks of the segment, e.g. `0–36000`
+    ticks: String,
+    ups: String,
+    avg_ms: String,
+    min_ms: String,
+    max_ms: String,
+    /// Mean tick time compared to the first segment
+    vs_first: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::parser::Segment;
+
+    #[test]
+    fn test_segments_section_averages_runs_and_shows_drift() {
+        let segment = |start_tick: u32, avg_ms: f64| Segment {
+            start_tick,
+            ticks: 100,
+            avg_ms,
+            min_ms: avg_ms - 1.0,
+            max_ms: avg_ms + 1.0,
+        };
+        let run = |segments: Vec<Segment>| BenchmarkRun {
+            save_name: "base".to_string(),
+            avg_ms: 5.0,
+            min_ms: 3.0,
+            max_ms: 8.0,
+            segments,
+            ..Default::default()
+        };
+        let results = vec![
+            run(vec![segment(0, 4.0), segment(100, 6.0)]),
+            run(vec![segment(0, 4.0), segment(100, 4.0)]),
+            BenchmarkRun {
+                save_name: "short".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let section = build_section(&results, &NumberFormat::default());
+
+        assert_eq!(section.len(), 1);
+        assert_eq!(section[0].save_name, "base");
+        assert_eq!(section[0].drift, "+25.00%");
+        let rows = &section[0].segments;
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].ticks, "0–100");
+        assert_eq!(rows[0].ups, "250");
+        assert_eq!(rows[0].vs_first, "0.00%");
+        assert_eq!(rows[1].ticks, "100–200");
+        assert_eq!(rows[1].avg_ms, "5.000");
+        assert_eq!(rows[1].min_ms, "4.000");
+        assert_eq!(rows[1].vs_first, "+25.00%");
+        assert_eq!(rows[2].ticks, "All");
+        assert_eq!(rows[2].ups, "200");
+        assert_eq!(rows[2].max_ms, "8.000");
+    }
+}
@@ -0,0 +1,160 @@
+//! Space Age metrics of each save: the planet, space platform and Space Age entity metrics of its
+//! verbose metrics CSV that were non-zero, as mean time per tick.
+
+use std::{collections::BTreeSet, path::Path};
+
+use serde::Serialize;
+
+use crate::{
+    Result,
+    benchmark::parser::BenchmarkRun,
+    core::{
+        metrics::{describe_metric, is_space_age_metric},
+        output::NumberFormat,
+    },
+};
+
+/// Build the report section from the `<save>_verbose_metrics.csv` files in `output_dir`
+pub(crate) fn build_section(
+    results: &[BenchmarkRun],
+    numbers: &NumberFormat,
+    output_dir: &Path,
+) -> Vec<SpaceAgeSave> {
+    let save_names: BTreeSet<&str> = results.iter().map(|run| run.save_name.as_str()).collect();
+
+    save_names
+        .into_iter()
+        .filter_map(|save_name| {
+            let csv_path = output_dir.join(format!("{save_name}_verbose_metrics.csv"));
+            if !csv_path.is_file() {
+                return None;
+            }
+
+            match read_means(&csv_path) {
+                Ok(means) => save_view(save_name, &means, numbers),
+                Err(err) => {
+                    tracing::warn!("Failed to read {}: {err}", csv_path.display());
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Mean raw value of every column after `tick` and `run`, in column order
+fn read_means(path: &Path) -> Result<Vec<(String, f64)>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let metrics: Vec<String> = reader
+        .headers()?
+        .iter()
+        .skip(2)
+        .map(str::to_string)
+        .collect();
+
+    let mut sums = vec![0.0; metrics.len()];
+    let mut rows = 0usize;
+    for record in reader.records() {
+        let record = record?;
+        for (sum, value) in sums.iter_mut().zip(record.iter().skip(2)) {
+            // Metrics the Factorio version doesn't report are `N/A`
+            *sum += value.parse::<f64>().unwrap_or(0.0);
+        }
+        rows += 1;
+    }
+
+    let rows = rows.max(1) as f64;
+    Ok(metrics
+        .into_iter()
+        .zip(sums)
+        .map(|(metric, sum)| (metric, sum / rows))
+        .collect())
+}
+
+fn save_view(
+    save_name: &str,
+    means: &[(String, f64)],
+    numbers: &NumberFormat,
+) -> Option<SpaceAgeSave> {
+    let to_ms = |metric: &str, raw: f64| describe_metric(metric).unit.to_display(raw);
+    let whole_update_ms = means
+        .iter()
+        .find(|(metric, _)| metric == "wholeUpdate")
+        .map(|(metric, raw)| to_ms(metric, *raw));
+
+    let metrics: Vec<SpaceAgeMetricView> = means
+        .iter()
+        .filter(|(metric, raw)| is_space_age_metric(metric) && *raw > 0.0)
+        .map(|(metric, raw)| {
+            let mean_ms = to_ms(metric, *raw);
+            SpaceAgeMetricView {
+                name: metric.clone(),
+                description: describe_metric(metric).description,
+                mean_ms: numbers.ms(mean_ms),
+                share: whole_update_ms.filter(|whole| *whole > 0.0).map_or_else(
+                    || "-".to_string(),
+                    |whole| numbers.percent(mean_ms / whole * 100.0),
+                ),
+            }
+        })
+        .collect();
+
+    (!metrics.is_empty()).then(|| SpaceAgeSave {
+        save_name: save_name.to_string(),
+        metrics,
+    })
+}
+
+/// The non-zero Space Age metrics of one save as rendered in the report
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct SpaceAgeSave {
+    save_name: String,
+    metrics: Vec<SpaceAgeMetricView>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SpaceAgeMetricView {
+    name: String,
+    description: String,
+    mean_ms: String,
+    /// Share of the whole tick, when `wholeUpdate` was exported
+    share: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_space_age_section_lists_non_zero_metrics() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join("gleba_verbose_metrics.csv"),
+            "tick,run,wholeUpdate,planetsUpdate,spacePlatforms,lightningUpdate,Inserter\n\
+             0,0,4000000,1000000,0,N/A,500\n\
+             1,0,4000000,3000000,0,N/A,500\n",
+        )
+        .expect("write verbose metrics");
+        std::fs::write(
+            dir.join("nauvis_verbose_metrics.csv"),
+            "tick,run,wholeUpdate,planetsUpdate\n0,0,1000000,0\n",
+        )
+        .expect("write verbose metrics");
+
+        let run = |save_name: &str| BenchmarkRun {
+            save_name: save_name.to_string(),
+            ..Default::default()
+        };
+        let results = vec![run("gleba"), run("nauvis"), run("no-verbose")];
+
+        let section = build_section(&results, &NumberFormat::default(), dir);
+
+        assert_eq!(section.len(), 1);
+        assert_eq!(section[0].save_name, "gleba");
+        assert_eq!(section[0].metrics.len(), 1);
+        let metric = &section[0].metrics[0];
+        assert_eq!(metric.name, "planetsUpdate");
+        assert_eq!(metric.mean_ms, "2.000");
+        assert_eq!(metric.share, "50.00%");
+    }
+}
@@ -0,0 +1,129 @@
+//! Pinning Factorio to CPU cores, so the scheduler can't migrate it between cores mid-run, e.g.
+//! between the performance and efficiency cores of a hybrid CPU.
+//!
+//! On Linux every spawned command is pinned with `sched_setaffinity` before `exec`, like
+//! `taskset`. Windows can't pin a command before it starts, so BELT pins itself with
+//! `SetProcessAffinityMask` and the Factorio processes it spawns inherit its mask.
+
+use tokio::process::Command;
+
+use crate::core::{Result, error::BenchmarkErrorKind};
+
+/// Parse a `--cpu-affinity` core list such as `0-7,16`
+pub fn parse_core_list(input: &str) -> Result<Vec<usize>> {
+    let invalid = |reason: &str| BenchmarkErrorKind::InvalidCpuAffinity {
+        input: input.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let mut cores = parse_cpu_list(input)
+        .ok_or_else(|| invalid("expected core numbers and ranges such as 0-7,16"))?;
+    if cores.is_empty() {
+        return Err(invalid("no cores given").into());
+    }
+    cores.sort_unstable();
+    cores.dedup();
+    Ok(cores)
+}
+
+/// Parse a kernel CPU list such as `0-7,16-23`
+pub(crate) fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cores = Vec::new();
+    for part in list.trim().split(',').filter(|part| !part.is_empty()) {
+        match part.split_once('-') {
+            Some((first, last)) => cores.extend(first.parse::<usize>().ok()?..=last.parse().ok()?),
+            None => cores.push(part.parse().ok()?),
+        }
+    }
+    Some(cores)
+}
+
+/// Restrict the process spawned by `cmd` to the given CPU cores
+///
+/// Only supported on Linux, where it uses `sched_setaffinity` in the child before `exec`.
+#[cfg(target_os = "linux")]
+pub fn pin_to_cores(cmd: &mut Command, cores: &[usize]) {
+    if cores.is_empty() {
+        return;
+    }
+    let cores = cores.to_vec();
+    // SAFETY: the closure only calls async-signal-safe functions and doesn't allocate
+    unsafe {
+        cmd.pre_exec(move || {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            for &core in &cores {
+                libc::CPU_SET(core, &mut set);
+            }
+            if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Restrict the process spawned by `cmd` to the given CPU cores
+#[cfg(not(target_os = "linux"))]
+pub fn pin_to_cores(_cmd: &mut Command, _cores: &[usize]) {}
+
+/// Whether [`pin_to_cores`] has an effect on this platform
+pub fn supports_core_pinning() -> bool {
+    cfg!(target_os = "linux")
+}
+
+/// Restrict BELT, and every process it spawns from now on, to the given CPU cores
+///
+/// For platforms without [`pin_to_cores`]; only supported on Windows, for the cores of the
+/// first processor group.
+#[cfg(windows)]
+pub fn pin_current_process(cores: &[usize]) -> Result<()> {
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, SetProcessAffinityMask};
+
+    let mut mask = 0usize;
+    for &core in cores {
+        if core >= usize::BITS as usize {
+            return Err(BenchmarkErrorKind::CpuAffinityFailed {
+                reason: format!("core {core} is outside the first processor group"),
+            }
+            .into());
+        }
+        mask |= 1 << core;
+    }
+
+    // SAFETY: a plain Win32 call on the pseudo handle of our own process
+    if unsafe { SetProcessAffinityMask(GetCurrentProcess(), mask) } == 0 {
+        return Err(BenchmarkErrorKind::CpuAffinityFailed {
+            reason: std::io::Error::last_os_error().to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Restrict BELT, and every process it spawns from now on, to the given CPU cores
+#[cfg(not(windows))]
+pub fn pin_current_process(_cores: &[usize]) -> Result<()> {
+    Err(BenchmarkErrorKind::CpuAffinityFailed {
+        reason: "pinning to cores is only supported on Linux and Windows".to_string(),
+    }
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(
+            parse_cpu_list("0-3,8,10-11\n"),
+            Some(vec![0, 1, 2, 3, 8, 10, 11])
+        );
+        assert_eq!(parse_cpu_list(""), Some(vec![]));
+        assert_eq!(parse_cpu_list("0-x"), None);
+
+        assert_eq!(parse_core_list("4,0-2,2").expect("valid"), vec![0, 1, 2, 4]);
+        assert!(parse_core_list("").is_err());
+        assert!(parse_core_list("P-cores").is_err());
+    }
+}
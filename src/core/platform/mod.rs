@@ -0,0 +1,488 @@
+//! Platform-specific helpers for BELT.
+//!
+//! Provides OS detection, default Factorio installation path discovery, page-cache control,
+//! shell invocation, file linking, CPU pinning (in [`affinity`]), NUMA binding, RAPL energy
+//! counters and process cleanup on shutdown.
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use tokio::process::Command;
+
+use crate::core::{
+    Result,
+    error::{BenchmarkError, BenchmarkErrorKind},
+};
+
+pub mod affinity;
+
+/// Get all reasonable Factorio paths based on the user's operating system
+pub fn get_default_factorio_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    if cfg!(target_os = "windows") {
+        // Check all drives A: through Z: for Steam installation
+        for drive_letter in b'A'..=b'Z' {
+            let drive = char::from(drive_letter);
+
+            // Steam
+            paths.push(PathBuf::from(format!(
+                r"{drive}:\Program Files (x86)\Steam\steamapps\common\Factorio\bin\x64\factorio.exe",
+            )));
+
+            // Standalone
+            paths.push(PathBuf::from(format!(
+                r"{drive}:\Program Files\Factorio\bin\x64\factorio.exe",
+            )));
+        }
+
+        // User steam library (uncommon)
+        if let Some(home) = dirs::home_dir() {
+            paths.push(
+                home.join(r"AppData\Local\Steam\steamapps\common\Factorio\bin\x64\factorio.exe"),
+            );
+        }
+    } else if cfg!(target_os = "linux") {
+        // User bin (symlinked, personally have this)
+        if let Some(home) = dirs::home_dir() {
+            paths.push(home.join(".local/bin/factorio"));
+        }
+        // System wide
+        paths.push(PathBuf::from("/usr/local/bin/factorio"));
+        paths.push(PathBuf::from("/usr/bin/factorio"));
+        paths.push(PathBuf::from("/opt/factorio/bin/x64/factorio"));
+        // Steam on Linux
+        if let Some(home) = dirs::home_dir() {
+            paths.push(home.join(".steam/steam/steamapps/common/Factorio/bin/x64/factorio"));
+            paths.push(home.join(".local/share/Steam/steamapps/common/Factorio/bin/x64/factorio"));
+        }
+    } else if cfg!(target_os = "macos") {
+        if let Some(home) = dirs::home_dir() {
+            paths.push(home.join(
+                "Library/Application Support/Steam/steamapps/common/Factorio/factorio.app/Contents/MacOS/factorio",
+            ));
+        }
+        paths.push(PathBuf::from(
+            "/Applications/factorio.app/Contents/MacOS/factorio",
+        ));
+    }
+
+    paths
+}
+
+/// Read a file to the end so its pages end up in the OS page cache
+pub fn prime_file_cache(path: &Path) -> std::io::Result<()> {
+    let mut file = File::open(path)?;
+    std::io::copy(&mut file, &mut std::io::sink())?;
+    Ok(())
+}
+
+/// Ask the OS to evict a file's pages from the page cache
+///
+/// Only supported on Linux, where it uses `posix_fadvise(POSIX_FADV_DONTNEED)`, which does not
+/// require elevated privileges for files the user can open.
+#[cfg(target_os = "linux")]
+pub fn drop_file_cache(path: &Path) -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let file = File::open(path)?;
+    let ret = unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+    if ret != 0 {
+        return Err(std::io::Error::from_raw_os_error(ret));
+    }
+    Ok(())
+}
+
+/// Ask the OS to evict a file's pages from the page cache
+#[cfg(not(target_os = "linux"))]
+pub fn drop_file_cache(_path: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "dropping the page cache is only supported on Linux",
+    ))
+}
+
+/// Build a command that runs `command_line` through the platform's shell
+pub fn shell_command(command_line: &str) -> Command {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    cmd.arg(command_line);
+    cmd
+}
+
+/// Make sure every Factorio process BELT spawns dies with it
+///
+/// On Windows, BELT joins a job object that kills all of its processes once BELT exits, since
+/// Factorio doesn't receive the console's Ctrl+C and would keep running after a console close.
+/// On Unix, children share BELT's process group and receive the same signals.
+#[cfg(windows)]
+pub fn kill_children_on_exit() {
+    use windows_sys::Win32::System::{
+        JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JobObjectExtendedLimitInformation,
+            SetInformationJobObject,
+        },
+        Threading::GetCurrentProcess,
+    };
+
+    // SAFETY: plain Win32 calls on a handle we own. The job handle is deliberately never
+    // closed: closing it is what kills the processes, which the OS does when BELT exits.
+    let joined = unsafe {
+        let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+        if job.is_null() {
+            false
+        } else {
+            let mut limits = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+            limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                (&raw const limits).cast(),
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            ) != 0
+                && AssignProcessToJobObject(job, GetCurrentProcess()) != 0
+        }
+    };
+
+    if !joined {
+        tracing::warn!(
+            "Failed to set up a job object ({}); Factorio may keep running if BELT is closed",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+/// Make sure every Factorio process BELT spawns dies with it
+#[cfg(not(windows))]
+pub fn kill_children_on_exit() {}
+
+/// Wait until the user asks BELT to stop: Ctrl+C or SIGTERM on Unix; Ctrl+C, Ctrl+Break or
+/// closing the console on Windows
+#[cfg(unix)]
+pub async fn shutdown_signal() -> std::io::Result<()> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        _ = terminate.recv() => Ok(()),
+    }
+}
+
+/// Wait until the user asks BELT to stop: Ctrl+C or SIGTERM on Unix; Ctrl+C, Ctrl+Break or
+/// closing the console on Windows
+#[cfg(windows)]
+pub async fn shutdown_signal() -> std::io::Result<()> {
+    use tokio::signal::windows::{ctrl_break, ctrl_c, ctrl_close};
+
+    let (mut ctrl_c, mut ctrl_break, mut ctrl_close) = (ctrl_c()?, ctrl_break()?, ctrl_close()?);
+    tokio::select! {
+        _ = ctrl_c.recv() => {}
+        _ = ctrl_break.recv() => {}
+        _ = ctrl_close.recv() => {}
+    }
+    Ok(())
+}
+
+/// Wait until the user asks BELT to stop with Ctrl+C
+#[cfg(not(any(unix, windows)))]
+pub async fn shutdown_signal() -> std::io::Result<()> {
+    tokio::signal::ctrl_c().await
+}
+
+/// A NUMA node and the CPU cores that belong to it
+#[derive(Debug, Clone)]
+pub struct NumaNode {
+    pub id: u32,
+    pub cores: Vec<usize>,
+}
+
+impl NumaNode {
+    /// Look up the cores of a NUMA node in sysfs
+    #[cfg(target_os = "linux")]
+    pub fn load(id: u32) -> Result<Self> {
+        let unavailable =
+            |reason: String| BenchmarkErrorKind::NumaNodeUnavailable { node: id, reason };
+        if id as usize >= NUMA_MASK_BITS {
+            return Err(unavailable(format!("node ids go up to {}", NUMA_MASK_BITS - 1)).into());
+        }
+
+        let path = format!("/sys/devices/system/node/node{id}/cpulist");
+        let cpulist =
+            std::fs::read_to_string(&path).map_err(|e| unavailable(format!("{path}: {e}")))?;
+        let cores = affinity::parse_cpu_list(&cpulist)
+            .filter(|cores| !cores.is_empty())
+            .ok_or_else(|| unavailable(format!("no CPUs in {path}")))?;
+
+        Ok(Self { id, cores })
+    }
+
+    /// Look up the cores of a NUMA node; only supported on Linux
+    #[cfg(not(target_os = "linux"))]
+    pub fn load(id: u32) -> Result<Self> {
+        Err(BenchmarkErrorKind::NumaNodeUnavailable {
+            node: id,
+            reason: "NUMA binding is only supported on Linux".to_string(),
+        }
+        .into())
+    }
+}
+
+/// Directory of the Linux powercap interface exposing RAPL energy counters
+#[cfg(target_os = "linux")]
+const POWERCAP_DIR: &str = "/sys/class/powercap";
+
+/// RAPL energy counters of every CPU package
+#[derive(Debug, Clone)]
+pub struct EnergyCounters {
+    /// Energy and counter range in microjoules, per package
+    packages: Vec<(u64, u64)>,
+}
+
+impl EnergyCounters {
+    /// Read the package counters from powercap. `None` when RAPL isn't available or readable,
+    /// which needs root on most kernels.
+    #[cfg(target_os = "linux")]
+    pub fn read() -> Option<Self> {
+        let mut zones: Vec<PathBuf> = std::fs::read_dir(POWERCAP_DIR)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            // `intel-rapl:0` is a package, `intel-rapl:0:0` one of its subzones
+            .filter(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy())
+                    .is_some_and(|name| {
+                        name.starts_with("intel-rapl:") && name.matches(':').count() == 1
+                    })
+            })
+            .collect();
+        zones.sort();
+
+        let read = |zone: &Path, file: &str| {
+            std::fs::read_to_string(zone.join(file))
+                .ok()?
+                .trim()
+                .parse::<u64>()
+                .ok()
+        };
+        let packages = zones
+            .iter()
+            .map(|zone| Some((read(zone, "energy_uj")?, read(zone, "max_energy_range_uj")?)))
+            .collect::<Option<Vec<_>>>()?;
+
+        (!packages.is_empty()).then_some(Self { packages })
+    }
+
+    /// Read the package counters; only supported on Linux
+    #[cfg(not(target_os = "linux"))]
+    pub fn read() -> Option<Self> {
+        None
+    }
+
+    /// Joules used by all packages since `earlier`, accounting for counters wrapping around
+    pub fn joules_since(&self, earlier: &Self) -> f64 {
+        self.packages
+            .iter()
+            .zip(&earlier.packages)
+            .map(|(&(now, range), &(before, _))| {
+                let microjoules = if now >= before {
+                    now - before
+                } else {
+                    range - before + now
+                };
+                microjoules as f64 / 1_000_000.0
+            })
+            .sum()
+    }
+}
+
+/// A CPU frequency setting in sysfs: the governor of one core, or the turbo switch
+#[derive(Debug, Clone)]
+pub struct FrequencySetting {
+    pub name: &'static str,
+    pub path: PathBuf,
+    pub original: String,
+    pub applied: String,
+}
+
+/// CPU governors set to `performance` and turbo disabled for the session (`--lock-frequency`).
+/// The original settings are restored when the lock is dropped.
+#[derive(Debug, Default)]
+pub struct FrequencyLock {
+    settings: Vec<FrequencySetting>,
+}
+
+impl FrequencyLock {
+    /// Apply the settings; needs root. Settings already changed are restored on failure.
+    #[cfg(target_os = "linux")]
+    pub fn apply() -> Result<Self> {
+        let mut targets: Vec<(&'static str, PathBuf, &str)> =
+            glob::glob("/sys/devices/system/cpu/cpu[0-9]*/cpufreq/scaling_governor")?
+                .flatten()
+                .map(|path| ("scaling_governor", path, "performance"))
+                .collect();
+        if targets.is_empty() {
+            return Err(BenchmarkErrorKind::FrequencyLockFailed {
+                reason: "no cpufreq governors found".to_string(),
+            }
+            .into());
+        }
+
+        // intel_pstate has its own switch, other drivers share the generic one
+        let intel_no_turbo = PathBuf::from("/sys/devices/system/cpu/intel_pstate/no_turbo");
+        let boost = PathBuf::from("/sys/devices/system/cpu/cpufreq/boost");
+        if intel_no_turbo.exists() {
+            targets.push(("no_turbo", intel_no_turbo, "1"));
+        } else if boost.exists() {
+            targets.push(("boost", boost, "0"));
+        } else {
+            tracing::warn!("No turbo switch found; only setting the CPU governor");
+        }
+
+        let mut lock = Self::default();
+        for (name, path, applied) in targets {
+            let original = std::fs::read_to_string(&path)?.trim().to_string();
+            if let Err(err) = std::fs::write(&path, applied) {
+                // Dropping the partial lock restores what was already changed
+                drop(lock);
+                return Err(
+                    BenchmarkError::from(BenchmarkErrorKind::FrequencyLockFailed {
+                        reason: format!("{}: {err}", path.display()),
+                    })
+                    .with_hint(Some("--lock-frequency needs root, e.g. run BELT with sudo")),
+                );
+            }
+            lock.settings.push(FrequencySetting {
+                name,
+                path,
+                original,
+                applied: applied.to_string(),
+            });
+        }
+
+        Ok(lock)
+    }
+
+    /// Apply the settings; only supported on Linux
+    #[cfg(not(target_os = "linux"))]
+    pub fn apply() -> Result<Self> {
+        Err(BenchmarkErrorKind::FrequencyLockFailed {
+            reason: "locking the CPU frequency is only supported on Linux".to_string(),
+        }
+        .into())
+    }
+
+    /// The settings that were changed, with their original values
+    pub fn settings(&self) -> &[FrequencySetting] {
+        &self.settings
+    }
+}
+
+impl Drop for FrequencyLock {
+    fn drop(&mut self) {
+        for setting in &self.settings {
+            if let Err(err) = std::fs::write(&setting.path, &setting.original) {
+                tracing::error!(
+                    "Failed to restore {} to '{}': {err}",
+                    setting.path.display(),
+                    setting.original
+                );
+            }
+        }
+    }
+}
+
+/// Number of nodes the memory policy mask covers
+const NUMA_MASK_BITS: usize = 1024;
+
+/// Bind the memory of the process spawned by `cmd` to a NUMA node
+///
+/// Only supported on Linux, where it uses `set_mempolicy(MPOL_BIND)` in the child before `exec`.
+#[cfg(target_os = "linux")]
+pub fn bind_memory_to_numa_node(cmd: &mut Command, node: u32) {
+    const MPOL_BIND: libc::c_int = 2;
+    const WORD_BITS: usize = libc::c_ulong::BITS as usize;
+
+    let mut mask = [0 as libc::c_ulong; NUMA_MASK_BITS / WORD_BITS];
+    let node = node as usize;
+    mask[node / WORD_BITS] |= 1 << (node % WORD_BITS);
+
+    // SAFETY: the closure only makes a syscall on memory it owns and doesn't allocate
+    unsafe {
+        cmd.pre_exec(move || {
+            if libc::syscall(
+                libc::SYS_set_mempolicy,
+                MPOL_BIND,
+                mask.as_ptr(),
+                NUMA_MASK_BITS + 1,
+            ) != 0
+            {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Bind the memory of the process spawned by `cmd` to a NUMA node
+#[cfg(not(target_os = "linux"))]
+pub fn bind_memory_to_numa_node(_cmd: &mut Command, _node: u32) {}
+
+/// Symlink `src` at `dst`
+#[cfg(unix)]
+pub fn link_or_copy(src: &Path, dst: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(src, dst)
+}
+
+/// Symlink `src` at `dst`, copying instead when symlinks are not permitted (Windows requires
+/// developer mode or elevated privileges for them)
+#[cfg(windows)]
+pub fn link_or_copy(src: &Path, dst: &Path) -> std::io::Result<()> {
+    let linked = if src.is_dir() {
+        std::os::windows::fs::symlink_dir(src, dst)
+    } else {
+        std::os::windows::fs::symlink_file(src, dst)
+    };
+    if linked.is_ok() {
+        return Ok(());
+    }
+
+    if src.is_dir() {
+        std::fs::create_dir_all(dst)?;
+        for entry in std::fs::read_dir(src)? {
+            let entry = entry?;
+            link_or_copy(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(src, dst).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_energy_counters_handle_wraparound() {
+        let before = EnergyCounters {
+            packages: vec![(1_000_000, 10_000_000), (9_000_000, 10_000_000)],
+        };
+        let after = EnergyCounters {
+            packages: vec![(3_500_000, 10_000_000), (500_000, 10_000_000)],
+        };
+
+        assert_eq!(after.joules_since(&before), 4.0);
+    }
+}
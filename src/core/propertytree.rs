@@ -0,0 +1,399 @@
+//! Factorio's property trees: the typed, nested values its binary formats are built from, e.g.
+//! `mod-settings.dat`, which is a [`MapVersion`] followed by one tree.
+//!
+//! Readers of other formats use [`read`] and [`write`] on the part of their file that is a
+//! property tree, and [`MapVersion::from_reader`] on the version most of them start with.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    io::{self, Read},
+    path::Path,
+};
+
+use serde_json::Value;
+
+/// A property tree value
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyTreeData {
+    None,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    List(Vec<PropertyTreeData>),
+    Dictionary(HashMap<String, PropertyTreeData>),
+    SignedInteger(i64),
+    UnsignedInteger(u64),
+}
+
+impl PropertyTreeData {
+    /// The tree as JSON, with dictionary keys in alphabetical order
+    pub fn to_json(&self) -> Value {
+        match self {
+            PropertyTreeData::None => Value::Null,
+            PropertyTreeData::Bool(b) => Value::from(*b),
+            PropertyTreeData::Number(n) => Value::from(*n),
+            PropertyTreeData::String(s) => Value::from(s.as_str()),
+            PropertyTreeData::List(values) => {
+                Value::Array(values.iter().map(PropertyTreeData::to_json).collect())
+            }
+            PropertyTreeData::Dictionary(dict) => Value::Object(
+                dict.iter()
+                    .collect::<BTreeMap<_, _>>()
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), value.to_json()))
+                    .collect(),
+            ),
+            PropertyTreeData::SignedInteger(i) => Value::from(*i),
+            PropertyTreeData::UnsignedInteger(u) => Value::from(*u),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+enum PropertyTreeType {
+    None = 0,
+    Bool = 1,
+    Number = 2,
+    String = 3,
+    List = 4,
+    Dictionary = 5,
+    SignedInteger = 6,
+    UnsignedInteger = 7,
+}
+
+impl TryFrom<u8> for PropertyTreeType {
+    type Error = io::Error;
+
+    fn try_from(value: u8) -> io::Result<Self> {
+        match value {
+            0 => Ok(PropertyTreeType::None),
+            1 => Ok(PropertyTreeType::Bool),
+            2 => Ok(PropertyTreeType::Number),
+            3 => Ok(PropertyTreeType::String),
+            4 => Ok(PropertyTreeType::List),
+            5 => Ok(PropertyTreeType::Dictionary),
+            6 => Ok(PropertyTreeType::SignedInteger),
+            7 => Ok(PropertyTreeType::UnsignedInteger),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid PropertyTreeType: {value}"),
+            )),
+        }
+    }
+}
+
+/// The version of the game that wrote a file, at the start of most of its binary formats
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapVersion {
+    pub(crate) data: [u8; 9],
+}
+
+impl MapVersion {
+    pub fn from_reader<R: Read>(r: &mut R) -> io::Result<Self> {
+        let mut data = [0u8; 9];
+        r.read_exact(&mut data)?;
+        Ok(Self { data })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 9] {
+        self.data
+    }
+
+    /// `major`, `minor`, `patch` and `build`
+    pub fn parts(&self) -> [u16; 4] {
+        [0, 2, 4, 6].map(|i| u16::from_le_bytes([self.data[i], self.data[i + 1]]))
+    }
+}
+
+/// `major.minor.patch-build` of the game that wrote the file
+impl fmt::Display for MapVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [major, minor, patch, build] = self.parts();
+        write!(f, "{major}.{minor}.{patch}-{build}")
+    }
+}
+
+/// A file that is a [`MapVersion`] followed by a property tree, like `mod-settings.dat`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionedTree {
+    pub version: MapVersion,
+    pub tree: PropertyTreeData,
+}
+
+impl VersionedTree {
+    pub fn from_reader<R: Read>(r: R) -> io::Result<Self> {
+        let mut br = io::BufReader::new(r);
+        Ok(Self {
+            version: MapVersion::from_reader(&mut br)?,
+            tree: read(&mut br)?,
+        })
+    }
+
+    pub fn load_from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(fs::File::open(path)?)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&self.version.to_bytes());
+        out.extend_from_slice(&write(&self.tree));
+        out
+    }
+
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+}
+
+pub trait BufferStream: Read {
+    fn read_u8(&mut self) -> io::Result<u8> {
+        let mut buf = [0u8; 1];
+        self.read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn read_u32_le(&mut self) -> io::Result<u32> {
+        let mut buf = [0u8; 4];
+        self.read_exact(&mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_i64_le(&mut self) -> io::Result<i64> {
+        let mut buf = [0u8; 8];
+        self.read_exact(&mut buf)?;
+        Ok(i64::from_le_bytes(buf))
+    }
+
+    fn read_u64_le(&mut self) -> io::Result<u64> {
+        let mut buf = [0u8; 8];
+        self.read_exact(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn read_f64_le(&mut self) -> io::Result<f64> {
+        let mut buf = [0u8; 8];
+        self.read_exact(&mut buf)?;
+        Ok(f64::from_le_bytes(buf))
+    }
+
+    fn read_packed_uint_8_32(&mut self) -> io::Result<u32> {
+        let first = self.read_u8()?;
+        if first < 255 {
+            Ok(first as u32)
+        } else {
+            self.read_u32_le()
+        }
+    }
+
+    fn read_string(&mut self, size: u32) -> io::Result<String> {
+        let mut buf = vec![0u8; size as usize];
+        self.read_exact(&mut buf)?;
+        String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl<R: Read> BufferStream for R {}
+
+fn read_ptree_string<R: BufferStream>(b: &mut R) -> io::Result<String> {
+    let empty = b.read_u8()? != 0;
+    if empty {
+        Ok(String::new())
+    } else {
+        let size = b.read_packed_uint_8_32()?;
+        b.read_string(size)
+    }
+}
+
+/// Read one property tree
+pub fn read<R: BufferStream>(b: &mut R) -> io::Result<PropertyTreeData> {
+    let type_val = b.read_u8()?;
+    let _is_any_type = b.read_u8()?;
+
+    let tree_type = PropertyTreeType::try_from(type_val)?;
+
+    match tree_type {
+        PropertyTreeType::None => Ok(PropertyTreeData::None),
+
+        PropertyTreeType::Bool => {
+            let value = b.read_u8()? != 0;
+            Ok(PropertyTreeData::Bool(value))
+        }
+
+        PropertyTreeType::Number => {
+            let value = b.read_f64_le()?;
+            Ok(PropertyTreeData::Number(value))
+        }
+
+        PropertyTreeType::String => {
+            let value = read_ptree_string(b)?;
+            Ok(PropertyTreeData::String(value))
+        }
+
+        PropertyTreeType::List => {
+            let count = b.read_u32_le()?;
+            let mut arr = Vec::with_capacity(count as usize);
+
+            for _ in 0..count {
+                let _key = read_ptree_string(b)?; // discard key for list items
+                let value = read(b)?;
+                arr.push(value);
+            }
+
+            Ok(PropertyTreeData::List(arr))
+        }
+
+        PropertyTreeType::Dictionary => {
+            let count = b.read_u32_le()?;
+            let mut dict = HashMap::with_capacity(count as usize);
+
+            for _ in 0..count {
+                let key = read_ptree_string(b)?;
+                if key.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Missing key in PropertyTree Dictionary",
+                    ));
+                }
+                let value = read(b)?;
+                dict.insert(key, value);
+            }
+
+            Ok(PropertyTreeData::Dictionary(dict))
+        }
+
+        PropertyTreeType::SignedInteger => {
+            let value = b.read_i64_le()?;
+            Ok(PropertyTreeData::SignedInteger(value))
+        }
+
+        PropertyTreeType::UnsignedInteger => {
+            let value = b.read_u64_le()?;
+            Ok(PropertyTreeData::UnsignedInteger(value))
+        }
+    }
+}
+
+fn save_string(s: &str) -> Vec<u8> {
+    if s.is_empty() {
+        vec![1] // no string
+    } else {
+        let str_bytes = s.as_bytes();
+        let mut result = vec![0];
+
+        if str_bytes.len() < 255 {
+            result.push(str_bytes.len() as u8);
+        } else {
+            result.push(255);
+            result.extend_from_slice(&(str_bytes.len() as u32).to_le_bytes());
+        }
+
+        result.extend_from_slice(str_bytes);
+        result
+    }
+}
+
+fn type_tag(tree_type: PropertyTreeType) -> Vec<u8> {
+    vec![tree_type as u8, 0]
+}
+
+/// Serialize one property tree
+pub fn write(tree: &PropertyTreeData) -> Vec<u8> {
+    match tree {
+        PropertyTreeData::String(value) => {
+            let mut result = type_tag(PropertyTreeType::String);
+            result.extend_from_slice(&save_string(value));
+            result
+        }
+
+        PropertyTreeData::Bool(value) => {
+            let mut result = type_tag(PropertyTreeType::Bool);
+            result.push(if *value { 1 } else { 0 });
+            result
+        }
+
+        PropertyTreeData::Number(value) => {
+            let mut result = type_tag(PropertyTreeType::Number);
+            result.extend_from_slice(&value.to_le_bytes());
+            result
+        }
+
+        PropertyTreeData::SignedInteger(value) => {
+            let mut result = type_tag(PropertyTreeType::SignedInteger);
+            result.extend_from_slice(&value.to_le_bytes());
+            result
+        }
+
+        PropertyTreeData::UnsignedInteger(value) => {
+            let mut result = type_tag(PropertyTreeType::UnsignedInteger);
+            result.extend_from_slice(&value.to_le_bytes());
+            result
+        }
+
+        PropertyTreeData::None => type_tag(PropertyTreeType::None),
+
+        PropertyTreeData::List(values) => {
+            let mut result = type_tag(PropertyTreeType::List);
+            result.extend_from_slice(&(values.len() as u32).to_le_bytes());
+
+            for value in values {
+                result.extend_from_slice(&save_string("")); // no key for list items
+                result.extend_from_slice(&write(value));
+            }
+
+            result
+        }
+
+        PropertyTreeData::Dictionary(dict) => {
+            let mut result = type_tag(PropertyTreeType::Dictionary);
+            result.extend_from_slice(&(dict.len() as u32).to_le_bytes());
+
+            for (key, value) in dict {
+                result.extend_from_slice(&save_string(key));
+                result.extend_from_slice(&write(value));
+            }
+
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_versioned_tree_round_trip() {
+        let tree = PropertyTreeData::Dictionary(HashMap::from([
+            (
+                "list".to_string(),
+                PropertyTreeData::List(vec![
+                    PropertyTreeData::None,
+                    PropertyTreeData::UnsignedInteger(7),
+                ]),
+            ),
+            (
+                "name".to_string(),
+                PropertyTreeData::String("x".repeat(300)),
+            ),
+            ("empty".to_string(), PropertyTreeData::String(String::new())),
+            ("ratio".to_string(), PropertyTreeData::Number(0.5)),
+            ("offset".to_string(), PropertyTreeData::SignedInteger(-2)),
+        ]));
+        let file = VersionedTree {
+            version: MapVersion {
+                data: [2, 0, 0, 0, 55, 0, 1, 0, 0],
+            },
+            tree,
+        };
+
+        let loaded = VersionedTree::from_reader(file.to_bytes().as_slice()).expect("read tree");
+
+        assert_eq!(loaded, file);
+        assert_eq!(loaded.version.to_string(), "2.0.55-1");
+        assert_eq!(loaded.tree.to_json()["list"], json!([null, 7]));
+        assert!(read(&mut [9u8, 0].as_slice()).is_err());
+    }
+}
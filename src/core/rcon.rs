@@ -0,0 +1,247 @@
+//! RCON client for a headless Factorio server started by [`FactorioExecutor::start_rcon_server`],
+//! to query the running game with Lua, e.g. its pollution, enemies and evolution for the
+//! sanitize check without the belt-sanitizer mod.
+//!
+//! Factorio speaks the Source RCON protocol: little-endian length-prefixed packets with a request
+//! id, a type and a NUL-terminated body.
+//!
+//! [`FactorioExecutor::start_rcon_server`]: crate::core::FactorioExecutor::start_rcon_server
+
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    process::Child,
+};
+
+use crate::core::{
+    error::{BenchmarkErrorKind, Result},
+    instances::FactorioInstance,
+};
+
+const SERVERDATA_AUTH: i32 = 3;
+const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+const SERVERDATA_EXECCOMMAND: i32 = 2;
+
+/// Sanity limit on packet sizes; Factorio answers a command in one packet even when it's larger
+/// than the protocol's 4096 bytes
+const MAX_PACKET_SIZE: i32 = 16 * 1024 * 1024;
+
+/// How long a server gets to load its save and open the RCON port
+const SERVER_START_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Lua printing the map's state in the shape of the belt-sanitizer's `snapshot`, so
+/// [`crate::sanitize::parser`] reads both the same way
+pub const SNAPSHOT_LUA: &str = r#"local enemy = game.forces.enemy
+local surfaces = {}
+local total_pollution = 0
+for _, surface in pairs(game.surfaces) do
+  total_pollution = total_pollution + surface.get_total_pollution()
+  local ungenerated_chunks = 0
+  for chunk in surface.get_chunks() do
+    if not surface.is_chunk_generated(chunk) then ungenerated_chunks = ungenerated_chunks + 1 end
+  end
+  surfaces[#surfaces + 1] = {
+    name = surface.name,
+    seed = surface.map_gen_settings.seed,
+    platform = surface.platform ~= nil,
+    entity_count = surface.count_entities_filtered{},
+    enemy_units = surface.count_entities_filtered{force = enemy, type = "unit"},
+    enemy_spawners = surface.count_entities_filtered{force = enemy, type = "unit-spawner"},
+    enemy_worms = surface.count_entities_filtered{force = enemy, type = "turret"},
+    evolution_factor = enemy.get_evolution_factor(surface),
+    ungenerated_chunks = ungenerated_chunks,
+  }
+end
+rcon.print(helpers.table_to_json({snapshot = {
+  pollution_enabled = game.map_settings.pollution.enabled,
+  enemy_expansion_enabled = game.map_settings.enemy_expansion.enabled,
+  total_pollution = total_pollution,
+  map_exchange_string = game.get_map_exchange_string(),
+  surfaces = surfaces,
+}}))"#;
+
+/// An authenticated RCON connection
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl RconClient {
+    pub async fn connect(address: SocketAddr, password: &str) -> Result<Self> {
+        let stream = TcpStream::connect(address).await.map_err(|err| {
+            BenchmarkErrorKind::RconConnectionFailed {
+                address: address.to_string(),
+                reason: err.to_string(),
+            }
+        })?;
+        let mut client = Self { stream, next_id: 1 };
+
+        let id = client.send(SERVERDATA_AUTH, password).await?;
+        // Some servers send an empty response value before the auth response
+        loop {
+            let (response_id, kind, _) = client.receive().await?;
+            if kind != SERVERDATA_AUTH_RESPONSE {
+                continue;
+            }
+            if response_id != id {
+                return Err(BenchmarkErrorKind::RconAuthenticationFailed.into());
+            }
+            return Ok(client);
+        }
+    }
+
+    /// Run a console command and return what it printed
+    pub async fn command(&mut self, command: &str) -> Result<String> {
+        let id = self.send(SERVERDATA_EXECCOMMAND, command).await?;
+        loop {
+            let (response_id, _, body) = self.receive().await?;
+            if response_id == id {
+                return Ok(body);
+            }
+        }
+    }
+
+    /// Run Lua with `/silent-command`, joined into the single line the console accepts, and
+    /// return what it printed with `rcon.print`
+    pub async fn lua(&mut self, lua: &str) -> Result<String> {
+        let line = lua.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+        self.command(&format!("/silent-command {line}")).await
+    }
+
+    async fn send(&mut self, kind: i32, body: &str) -> Result<i32> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.stream
+            .write_all(&encode_packet(id, kind, body))
+            .await?;
+        Ok(id)
+    }
+
+    async fn receive(&mut self) -> Result<(i32, i32, String)> {
+        let size = self.stream.read_i32_le().await?;
+        if !(10..=MAX_PACKET_SIZE).contains(&size) {
+            return Err(BenchmarkErrorKind::RconConnectionFailed {
+                address: self.stream.peer_addr()?.to_string(),
+                reason: format!("invalid packet size {size}"),
+            }
+            .into());
+        }
+
+        let mut packet = vec![0u8; size as usize];
+        self.stream.read_exact(&mut packet).await?;
+        Ok(decode_packet(&packet))
+    }
+}
+
+fn encode_packet(id: i32, kind: i32, body: &str) -> Vec<u8> {
+    // id, type, body and its terminator, and the empty string terminating the packet
+    let size = 4 + 4 + body.len() + 2;
+    let mut packet = Vec::with_capacity(4 + size);
+    packet.extend_from_slice(&(size as i32).to_le_bytes());
+    packet.extend_from_slice(&id.to_le_bytes());
+    packet.extend_from_slice(&kind.to_le_bytes());
+    packet.extend_from_slice(body.as_bytes());
+    packet.extend_from_slice(&[0, 0]);
+    packet
+}
+
+/// The id, type and body of a packet without its size prefix
+fn decode_packet(packet: &[u8]) -> (i32, i32, String) {
+    let id = i32::from_le_bytes([packet[0], packet[1], packet[2], packet[3]]);
+    let kind = i32::from_le_bytes([packet[4], packet[5], packet[6], packet[7]]);
+    let body = &packet[8..packet.len().saturating_sub(2).max(8)];
+    (id, kind, String::from_utf8_lossy(body).into_owned())
+}
+
+/// A headless server with RCON enabled, killed when dropped
+pub struct FactorioServer {
+    pub(crate) child: Child,
+    pub(crate) address: SocketAddr,
+    pub(crate) password: String,
+    /// The throwaway write-data directory the server runs in
+    pub(crate) _instance: Arc<FactorioInstance>,
+}
+
+impl FactorioServer {
+    /// A local port that was free a moment ago, for `--rcon-port`
+    pub(crate) fn free_address() -> Result<SocketAddr> {
+        let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        Ok(listener.local_addr()?)
+    }
+
+    /// Connect once the server has loaded its save and opened the RCON port
+    pub async fn connect(&mut self) -> Result<RconClient> {
+        let started = tokio::time::Instant::now();
+        loop {
+            match RconClient::connect(self.address, &self.password).await {
+                Ok(client) => return Ok(client),
+                Err(err) if started.elapsed() > SERVER_START_TIMEOUT => return Err(err),
+                Err(err) => tracing::trace!("RCON not up yet: {err}"),
+            }
+
+            if let Some(status) = self.child.try_wait()? {
+                return Err(BenchmarkErrorKind::RconConnectionFailed {
+                    address: self.address.to_string(),
+                    reason: format!("the server exited with {status}"),
+                }
+                .into());
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    pub async fn stop(mut self) -> Result<()> {
+        self.child.kill().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_client_authenticates_and_runs_commands() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .expect("bind");
+        let address = listener.local_addr().expect("address");
+
+        async fn read(stream: &mut TcpStream) -> (i32, i32, String) {
+            let size = stream.read_i32_le().await.expect("size");
+            let mut packet = vec![0u8; size as usize];
+            stream.read_exact(&mut packet).await.expect("packet");
+            decode_packet(&packet)
+        }
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+
+            let (id, kind, password) = read(&mut stream).await;
+            assert_eq!((kind, password.as_str()), (SERVERDATA_AUTH, "secret"));
+            let mut replies = encode_packet(id, 0, "");
+            replies.extend(encode_packet(id, SERVERDATA_AUTH_RESPONSE, ""));
+            stream.write_all(&replies).await.expect("write");
+
+            let (id, kind, command) = read(&mut stream).await;
+            assert_eq!((kind, command.as_str()), (SERVERDATA_EXECCOMMAND, "/time"));
+            stream
+                .write_all(&encode_packet(id, 0, "1 hour"))
+                .await
+                .expect("write");
+        });
+
+        let mut client = RconClient::connect(address, "secret")
+            .await
+            .expect("connect");
+        assert_eq!(client.command("/time").await.expect("command"), "1 hour");
+        server.await.expect("server");
+    }
+}
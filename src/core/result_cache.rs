@@ -0,0 +1,258 @@
+//! Runs of earlier sessions, keyed by everything that determines their results: the save's
+//! contents, the Factorio build, the tick counts and the mods. With `--use-cache`, saves whose key
+//! is cached aren't benchmarked again.
+
+use std::{
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    Result,
+    benchmark::parser::BenchmarkRun,
+    core::{
+        FactorioExecutor, config::BenchmarkConfig, error::BenchmarkErrorKind, journal,
+        lock::LOCK_FILENAME, utils,
+    },
+};
+
+/// Directory inside the user's cache directory
+const CACHE_DIRNAME: &str = "belt/results";
+
+/// Files of a mods directory that change without the mods changing
+const VOLATILE_MOD_FILES: [&str; 2] = ["mod-list.json", LOCK_FILENAME];
+
+pub struct ResultCache {
+    dir: PathBuf,
+    /// Hash of the Factorio build, settings and mods shared by every save of the session
+    session_key: String,
+}
+
+impl ResultCache {
+    /// The cache in the user's cache directory, for the runs `factorio` does with `config`
+    pub async fn open(factorio: &FactorioExecutor, config: &BenchmarkConfig) -> Result<Self> {
+        let dir = default_dir().ok_or(BenchmarkErrorKind::CacheDirectoryNotFound)?;
+        std::fs::create_dir_all(&dir)?;
+
+        let version = factorio.version_line().await?;
+        let mods_dir = config
+            .mods_dir
+            .clone()
+            .or_else(|| factorio.session_mods_dir().map(Path::to_path_buf))
+            .or_else(utils::find_mod_directory);
+        let mods = match mods_dir {
+            Some(mods_dir) => mods_hash(&mods_dir)?,
+            None => String::new(),
+        };
+        tracing::debug!("Result cache for {version}, mods {mods}");
+
+        Ok(Self::with_dir(
+            dir,
+            &[
+                version,
+                config.ticks.to_string(),
+                config.settle_ticks.to_string(),
+                format!("{:?}", config.tick_range),
+                format!("{:?}", config.segment_ticks),
+                config.exclude_interference.to_string(),
+                format!("{:?}", config.ups_definition),
+                mods,
+            ],
+        ))
+    }
+
+    fn with_dir(dir: PathBuf, session_parts: &[String]) -> Self {
+        Self {
+            dir,
+            session_key: hash_parts(session_parts),
+        }
+    }
+
+    fn entry_path(&self, save_file: &Path) -> Result<PathBuf> {
+        let save_hash = journal::sha256_file(save_file)?;
+        let key = hash_parts(&[self.session_key.clone(), save_hash]);
+        Ok(self.dir.join(format!("{key}.json")))
+    }
+
+    /// The first `runs` cached runs of `save_file`, if at least that many are cached
+    pub fn get(&self, save_file: &Path, runs: u32) -> Result<Option<Vec<BenchmarkRun>>> {
+        let path = self.entry_path(save_file)?;
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        // Entries written by other versions of BELT may not parse; they're run again
+        let cached: Vec<BenchmarkRun> = match serde_json::from_slice(&std::fs::read(&path)?) {
+            Ok(cached) => cached,
+            Err(err) => {
+                tracing::debug!("Ignoring cache entry {}: {err}", path.display());
+                return Ok(None);
+            }
+        };
+
+        Ok((cached.len() >= runs as usize)
+            .then(|| cached.into_iter().take(runs as usize).collect()))
+    }
+
+    /// Cache the runs of `save_file`, replacing earlier ones
+    pub fn store(&self, save_file: &Path, runs: &[BenchmarkRun]) -> Result<()> {
+        let path = self.entry_path(save_file)?;
+        std::fs::write(path, serde_json::to_vec(runs)?)?;
+        Ok(())
+    }
+}
+
+/// Where the cache is kept, inside the user's cache directory
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join(CACHE_DIRNAME))
+}
+
+/// Hash of the mods and `mod-settings.dat`: the contents of mod zips and other files, and of
+/// unpacked mods see [`unpacked_mod_hash`]
+fn mods_hash(mods_dir: &Path) -> Result<String> {
+    if !mods_dir.is_dir() {
+        return Ok(String::new());
+    }
+
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(mods_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if VOLATILE_MOD_FILES.contains(&name.as_str()) {
+            continue;
+        }
+
+        let path = entry.path();
+        if path.is_dir() {
+            entries.push(format!("{name}:{}", unpacked_mod_hash(&path)?));
+        } else {
+            entries.push(format!("{name}:{}", journal::sha256_file(&path)?));
+        }
+    }
+
+    entries.sort();
+    Ok(hash_parts(&entries))
+}
+
+/// Hash of an unpacked mod's `info.json` and the size and modification time of each of its files,
+/// which is cheaper than reading every script and sprite
+fn unpacked_mod_hash(mod_dir: &Path) -> Result<String> {
+    let mut parts = Vec::new();
+    let info = mod_dir.join("info.json");
+    if info.is_file() {
+        parts.push(format!("info.json:{}", journal::sha256_file(&info)?));
+    }
+    collect_file_stamps(mod_dir, mod_dir, &mut parts)?;
+
+    parts.sort();
+    Ok(hash_parts(&parts))
+}
+
+fn collect_file_stamps(root: &Path, dir: &Path, stamps: &mut Vec<String>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_file_stamps(root, &path, stamps)?;
+            continue;
+        }
+
+        let metadata = std::fs::metadata(&path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        stamps.push(format!(
+            "{}:{}:{modified}",
+            path.strip_prefix(root).unwrap_or(&path).display(),
+            metadata.len()
+        ));
+    }
+    Ok(())
+}
+
+fn hash_parts(parts: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_hits_only_same_save_and_session() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+        let save = dir.join("a.zip");
+        std::fs::write(&save, "save").expect("write save");
+
+        let cache = ResultCache::with_dir(dir.join("cache"), &["2.0.55".to_string()]);
+        std::fs::create_dir_all(dir.join("cache")).expect("create cache");
+        let runs: Vec<BenchmarkRun> = (0..2)
+            .map(|index| BenchmarkRun {
+                index,
+                effective_ups: 100.0,
+                ..Default::default()
+            })
+            .collect();
+        cache.store(&save, &runs).expect("store runs");
+
+        assert_eq!(
+            cache.get(&save, 1).expect("get").map(|runs| runs.len()),
+            Some(1)
+        );
+        assert!(cache.get(&save, 3).expect("get").is_none());
+
+        let other_build = ResultCache::with_dir(dir.join("cache"), &["2.0.56".to_string()]);
+        assert!(other_build.get(&save, 1).expect("get").is_none());
+
+        std::fs::write(&save, "changed save").expect("change save");
+        assert!(cache.get(&save, 1).expect("get").is_none());
+    }
+
+    #[test]
+    fn test_mods_hash_ignores_session_lock() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let mods_dir = temp_dir.path();
+        std::fs::write(mods_dir.join("some-mod_1.0.0.zip"), "mod").expect("write mod");
+        let unlocked = mods_hash(mods_dir).expect("hash");
+
+        std::fs::write(mods_dir.join(LOCK_FILENAME), "123456").expect("write lock");
+        assert_eq!(mods_hash(mods_dir).expect("hash"), unlocked);
+    }
+
+    #[test]
+    fn test_mods_hash_notices_mods_rebuilt_in_place() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let mods_dir = temp_dir.path();
+        std::fs::write(mods_dir.join("some-mod_1.0.0.zip"), "aaa").expect("write mod");
+        let unpacked = mods_dir.join("other-mod");
+        std::fs::create_dir(&unpacked).expect("create mod");
+        std::fs::write(unpacked.join("info.json"), "{}").expect("write info");
+        std::fs::write(unpacked.join("control.lua"), "x = 1").expect("write script");
+        let original = mods_hash(mods_dir).expect("hash");
+
+        std::fs::write(mods_dir.join("some-mod_1.0.0.zip"), "bbb").expect("rebuild mod");
+        let rebuilt = mods_hash(mods_dir).expect("hash");
+        assert_ne!(rebuilt, original);
+
+        std::fs::write(unpacked.join("control.lua"), "x = 2").expect("edit script");
+        std::fs::File::options()
+            .write(true)
+            .open(unpacked.join("control.lua"))
+            .and_then(|file| file.set_modified(UNIX_EPOCH))
+            .expect("set modification time");
+        assert_ne!(mods_hash(mods_dir).expect("hash"), rebuilt);
+    }
+}
@@ -0,0 +1,255 @@
+//! The header Factorio writes at the start of a save's `level-init.dat`, or `level.dat` in older
+//! saves: the version that wrote the save, its scenario, the mods it was saved with and their
+//! startup settings, readable without launching the game.
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+use flate2::read::ZlibDecoder;
+use zip::ZipArchive;
+
+use crate::core::{
+    Result,
+    error::{BenchmarkError, BenchmarkErrorKind},
+    propertytree::{self, BufferStream, MapVersion, PropertyTreeData},
+};
+
+/// Files holding the save header, newest format first
+const HEADER_FILES: [&str; 3] = ["level-init.dat", "level.dat0", "level.dat"];
+
+/// Most of the level file read for its header, which also bounds what a corrupt save inflates to.
+/// Older saves keep the whole map in `level.dat`, after the header.
+const MAX_HEADER_SIZE: u64 = 16 * 1024 * 1024;
+
+/// The start of a save's level file
+#[derive(Debug, PartialEq)]
+pub struct SaveHeader {
+    pub version: MapVersion,
+    pub campaign: String,
+    pub level_name: String,
+    pub base_mod: String,
+    pub mods: Vec<SaveMod>,
+    /// Missing when the save's format has more fields before them than BELT knows of
+    pub startup_settings: Option<PropertyTreeData>,
+}
+
+/// A mod the save was made with
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveMod {
+    pub name: String,
+    pub version: String,
+    pub crc: u32,
+}
+
+/// Read the header of the save at `path`
+pub fn read(path: &Path) -> Result<SaveHeader> {
+    let file = File::open(path).map_err(|err| invalid(path, err))?;
+    let mut archive = ZipArchive::new(file).map_err(|err| invalid(path, err.into()))?;
+    // Entries sit in the save's top-level directory
+    let entry_name = HEADER_FILES
+        .iter()
+        .find_map(|name| {
+            archive
+                .file_names()
+                .find(|entry| entry.rsplit('/').next() == Some(name))
+        })
+        .map(str::to_string)
+        .ok_or_else(|| {
+            invalid(
+                path,
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "no level-init.dat or level.dat in the save",
+                ),
+            )
+        })?;
+    tracing::debug!("Reading the save header from {entry_name}");
+
+    let entry = archive
+        .by_name(&entry_name)
+        .map_err(|err| invalid(path, err.into()))?;
+    let data = read_level_start(entry).map_err(|err| invalid(path, err))?;
+    parse_header(&mut data.as_slice()).map_err(|err| invalid(path, err))
+}
+
+/// The first [`MAX_HEADER_SIZE`] bytes of a level file. Since Factorio 1.0 these are zlib streams
+/// inside the zip.
+fn read_level_start(mut entry: impl Read) -> io::Result<Vec<u8>> {
+    let mut magic = [0u8; 2];
+    entry.read_exact(&mut magic)?;
+    let [cmf, flg] = magic;
+    let is_zlib = cmf & 0x0f == 8 && (u16::from(cmf) * 256 + u16::from(flg)).is_multiple_of(31);
+
+    let stream = io::Cursor::new(magic).chain(entry);
+    let mut data = Vec::new();
+    if is_zlib {
+        ZlibDecoder::new(stream)
+            .take(MAX_HEADER_SIZE)
+            .read_to_end(&mut data)?;
+    } else {
+        stream.take(MAX_HEADER_SIZE).read_to_end(&mut data)?;
+    }
+    Ok(data)
+}
+
+fn parse_header<R: BufferStream>(r: &mut R) -> io::Result<SaveHeader> {
+    let version = MapVersion::from_reader(r)?;
+    let campaign = read_string(r)?;
+    let level_name = read_string(r)?;
+    let base_mod = read_string(r)?;
+    let _difficulty = r.read_u8()?;
+    let _finished = r.read_u8()?;
+    let _player_won = r.read_u8()?;
+    let _next_level = read_string(r)?;
+    let _can_continue = r.read_u8()?;
+    let _finished_but_continuing = r.read_u8()?;
+    let _saving_replay = r.read_u8()?;
+    let _allow_non_admin_debug_options = r.read_u8()?;
+    // Version of the game the save was loaded from: major, minor, patch, then the build
+    let mut loaded_from = [0u8; 5];
+    r.read_exact(&mut loaded_from)?;
+    let _allowed_commands = r.read_u8()?;
+
+    let mod_count = r.read_packed_uint_8_32()?;
+    let mut mods = Vec::with_capacity(mod_count.min(1024) as usize);
+    for _ in 0..mod_count {
+        let name = read_string(r)?;
+        let major = read_packed_u16(r)?;
+        let minor = read_packed_u16(r)?;
+        let patch = read_packed_u16(r)?;
+        mods.push(SaveMod {
+            name,
+            version: format!("{major}.{minor}.{patch}"),
+            crc: r.read_u32_le()?,
+        });
+    }
+
+    // The settings follow as a property tree, after their size in bytes
+    let startup_settings = r
+        .read_u32_le()
+        .and_then(|_size| propertytree::read(r))
+        .inspect_err(|err| tracing::debug!("No startup settings in the save header: {err}"))
+        .ok();
+
+    Ok(SaveHeader {
+        version,
+        campaign,
+        level_name,
+        base_mod,
+        mods,
+        startup_settings,
+    })
+}
+
+fn read_string<R: BufferStream>(r: &mut R) -> io::Result<String> {
+    let size = r.read_packed_uint_8_32()?;
+    r.read_string(size)
+}
+
+/// A `u16` stored in one byte when it's below 255
+fn read_packed_u16<R: BufferStream>(r: &mut R) -> io::Result<u16> {
+    let first = r.read_u8()?;
+    if first < 255 {
+        return Ok(first as u16);
+    }
+    let mut buf = [0u8; 2];
+    r.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn invalid(path: &Path, err: io::Error) -> BenchmarkError {
+    BenchmarkErrorKind::InvalidSaveFile {
+        path: path.to_path_buf(),
+        reason: err.to_string(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        io::{Cursor, Write},
+    };
+
+    use flate2::{Compression, write::ZlibEncoder};
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    use super::*;
+
+    /// A save holding `level` as `name`, zlib-compressed like Factorio writes it and deflated
+    fn save_zip(name: &str, level: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(level).expect("compress level");
+        let level = encoder.finish().expect("compress level");
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file(name, options).expect("start entry");
+        zip.write_all(&level).expect("write entry");
+        zip.finish().expect("finish zip").into_inner()
+    }
+
+    #[test]
+    fn test_read_header_from_save() {
+        let string = |s: &str| [&[s.len() as u8][..], s.as_bytes()].concat();
+        let settings = PropertyTreeData::Dictionary(HashMap::from([(
+            "startup".to_string(),
+            PropertyTreeData::Dictionary(HashMap::new()),
+        )]));
+        let settings = propertytree::write(&settings);
+
+        let mut header = Vec::new();
+        for part in [2u16, 0, 55, 12_345] {
+            header.extend_from_slice(&part.to_le_bytes());
+        }
+        header.push(0);
+        header.extend(string(""));
+        header.extend(string("freeplay"));
+        header.extend(string("base"));
+        header.extend([0, 0, 0]);
+        header.extend(string(""));
+        header.extend([1, 0, 0, 0]);
+        header.extend([2, 0, 55, 0x38, 0x44, 0]);
+        header.push(2);
+        header.extend(string("base"));
+        header.extend([2, 0, 55]);
+        header.extend(0xdead_beefu32.to_le_bytes());
+        header.extend(string("big-mod"));
+        header.extend([1, 255, 0x2c, 0x01, 3]);
+        header.extend(7u32.to_le_bytes());
+        header.extend((settings.len() as u32).to_le_bytes());
+        header.extend(settings);
+
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let save = temp_dir.path().join("base.zip");
+        std::fs::write(&save, save_zip("base/level-init.dat", &header)).expect("write save");
+
+        let header = read(&save).expect("read header");
+
+        assert_eq!(header.version.to_string(), "2.0.55-12345");
+        assert_eq!(header.level_name, "freeplay");
+        assert_eq!(header.base_mod, "base");
+        assert_eq!(
+            header.mods,
+            vec![
+                SaveMod {
+                    name: "base".to_string(),
+                    version: "2.0.55".to_string(),
+                    crc: 0xdead_beef,
+                },
+                SaveMod {
+                    name: "big-mod".to_string(),
+                    version: "1.300.3".to_string(),
+                    crc: 7,
+                },
+            ]
+        );
+        assert!(header.startup_settings.is_some());
+        assert!(read(&temp_dir.path().join("missing.zip")).is_err());
+    }
+}
@@ -0,0 +1,447 @@
+//! Optional `saves.toml` manifest describing the save files in a saves directory.
+//!
+//! ```toml
+//! [saves."test-000123-v2-final2.zip"]
+//! label = "Belt weave v2"
+//! description = "Second iteration of the belt weave"
+//! tags = ["weave", "v2"]
+//! family = "Belt weave"
+//! map_seed = 123456789
+//! ```
+//!
+//! A save can also be described by a sidecar next to it, named after the save with a `.toml`
+//! extension, e.g. `test-000123-v2-final2.toml`, holding the same fields at the top level.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use figment::{
+    Figment,
+    providers::{Format, Toml},
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    NameCase,
+    config::BenchmarkConfig,
+    error::{BenchmarkErrorKind, Result},
+};
+
+/// File name of the manifest inside a saves directory
+pub const SAVES_MANIFEST_FILENAME: &str = "saves.toml";
+
+/// Display metadata for a single save file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SaveMetadata {
+    /// Name to use in reports instead of the file name
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Free-form description of what the save tests
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Who made the save
+    #[serde(default)]
+    pub author: Option<String>,
+    /// What the save is expected to show, e.g. which variant should be faster
+    #[serde(default)]
+    pub expected: Option<String>,
+    /// Tags that can be used to filter saves
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Family of variants the save belongs to, e.g. one design of several tested side by side
+    #[serde(default)]
+    pub family: Option<String>,
+    /// Seed of the map, for others to regenerate it
+    #[serde(default)]
+    pub map_seed: Option<u64>,
+    /// Map exchange string, for others to regenerate the map with the same settings
+    #[serde(default)]
+    pub map_exchange_string: Option<String>,
+}
+
+/// Tag marking a save as a scaled clone, e.g. `clones:8` for 8 copies of a build
+const CLONES_TAG_PREFIX: &str = "clones:";
+
+/// Tag naming the scaling series a clone belongs to, e.g. `scaling:smelting`
+const SCALING_TAG_PREFIX: &str = "scaling:";
+
+/// Series of clones without a `scaling:` tag
+pub const DEFAULT_SCALING_SERIES: &str = "clones";
+
+impl SaveMetadata {
+    /// Take every field `other` sets, keeping the others
+    fn merge(&mut self, other: SaveMetadata) {
+        fn take<T>(field: &mut Option<T>, other: Option<T>) {
+            if other.is_some() {
+                *field = other;
+            }
+        }
+
+        take(&mut self.label, other.label);
+        take(&mut self.description, other.description);
+        take(&mut self.author, other.author);
+        take(&mut self.expected, other.expected);
+        take(&mut self.family, other.family);
+        take(&mut self.map_seed, other.map_seed);
+        take(&mut self.map_exchange_string, other.map_exchange_string);
+        if !other.tags.is_empty() {
+            self.tags = other.tags;
+        }
+    }
+
+    /// Number of clones from a `clones:<N>` tag
+    pub fn clones(&self) -> Option<u32> {
+        self.tags
+            .iter()
+            .find_map(|tag| tag.strip_prefix(CLONES_TAG_PREFIX)?.trim().parse().ok())
+    }
+
+    /// Scaling series from a `scaling:<NAME>` tag, when the save is a clone
+    pub fn scaling_series(&self) -> Option<&str> {
+        self.clones()?;
+        Some(
+            self.tags
+                .iter()
+                .find_map(|tag| tag.strip_prefix(SCALING_TAG_PREFIX))
+                .unwrap_or(DEFAULT_SCALING_SERIES),
+        )
+    }
+}
+
+/// Save metadata keyed by save file name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavesManifest {
+    #[serde(default)]
+    pub saves: HashMap<String, SaveMetadata>,
+}
+
+impl SavesManifest {
+    /// Load `saves.toml` from the saves directory (or the directory of a single save file).
+    /// A missing manifest is not an error and yields an empty manifest.
+    pub fn load(saves_dir: &Path) -> Result<Self> {
+        let Some(path) = manifest_path(saves_dir) else {
+            return Ok(Self::default());
+        };
+
+        tracing::debug!("Loading saves manifest from {}", path.display());
+        Figment::from(Toml::file(&path)).extract().map_err(|e| {
+            BenchmarkErrorKind::InvalidSavesManifest {
+                path: path.clone(),
+                reason: e.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Add the sidecar of every save file that has one, its fields taking precedence over those
+    /// of the save in `saves.toml`
+    pub fn with_sidecars(mut self, save_files: &[PathBuf]) -> Result<Self> {
+        for save_file in save_files {
+            let Some(file_name) = save_file.file_name() else {
+                continue;
+            };
+            let sidecar = save_file.with_extension("toml");
+            // A save named like the manifest has none
+            if !sidecar.is_file()
+                || sidecar.file_name() == Some(SAVES_MANIFEST_FILENAME.as_ref())
+                || sidecar == *save_file
+            {
+                continue;
+            }
+
+            tracing::debug!("Loading save metadata from {}", sidecar.display());
+            let metadata: SaveMetadata =
+                Figment::from(Toml::file(&sidecar)).extract().map_err(|e| {
+                    BenchmarkErrorKind::InvalidSavesManifest {
+                        path: sidecar.clone(),
+                        reason: e.to_string(),
+                    }
+                })?;
+            self.saves
+                .entry(file_name.to_string_lossy().into_owned())
+                .or_default()
+                .merge(metadata);
+        }
+        Ok(self)
+    }
+
+    /// Metadata for the given save file, if the manifest lists it
+    pub fn get(&self, save_file: &Path) -> Option<&SaveMetadata> {
+        let file_name = save_file.file_name()?.to_string_lossy();
+        self.saves.get(file_name.as_ref())
+    }
+
+    /// Keep only the save files that carry at least one of the given tags
+    pub fn filter_by_tags(&self, save_files: Vec<PathBuf>, tags: &[String]) -> Vec<PathBuf> {
+        if tags.is_empty() {
+            return save_files;
+        }
+
+        save_files
+            .into_iter()
+            .filter(|save_file| {
+                self.get(save_file)
+                    .is_some_and(|metadata| metadata.tags.iter().any(|tag| tags.contains(tag)))
+            })
+            .collect()
+    }
+}
+
+/// Turns save file stems into display names: strip prefix, strip suffix, regex replacements in
+/// order, then case mapping. Used for every name in results, reports and verbose CSV file names.
+/// Also groups display names into families of variants.
+#[derive(Debug, Clone, Default)]
+pub struct DisplayNameRules {
+    strip_prefix: Option<String>,
+    strip_suffix: Option<String>,
+    replacements: Vec<(Regex, String)>,
+    case: NameCase,
+    group: Option<Regex>,
+}
+
+impl DisplayNameRules {
+    /// Build the rules from the config; replacements are `PATTERN=>REPLACEMENT`
+    pub fn from_config(config: &BenchmarkConfig) -> Result<Self> {
+        let replacements = config
+            .name_replace
+            .iter()
+            .map(|input| {
+                let invalid = |reason: String| BenchmarkErrorKind::InvalidNameReplace {
+                    input: input.clone(),
+                    reason,
+                };
+                let (pattern, replacement) = input
+                    .split_once("=>")
+                    .ok_or_else(|| invalid("expected PATTERN=>REPLACEMENT".to_string()))?;
+                let regex = Regex::new(pattern).map_err(|e| invalid(e.to_string()))?;
+                Ok((regex, replacement.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let group = config
+            .group_regex
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| BenchmarkErrorKind::InvalidGroupRegex {
+                    input: pattern.to_string(),
+                    reason: e.to_string(),
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            strip_prefix: config.strip_prefix.clone(),
+            strip_suffix: config.strip_suffix.clone(),
+            replacements,
+            case: config.name_case,
+            group,
+        })
+    }
+
+    /// Family of a save: the one in its manifest entry, otherwise the first capture group (or
+    /// the whole match) of the group regex in its display name
+    pub fn family(&self, name: &str, metadata: Option<&SaveMetadata>) -> Option<String> {
+        if let Some(family) = metadata.and_then(|metadata| metadata.family.clone()) {
+            return Some(family);
+        }
+
+        let captures = self.group.as_ref()?.captures(name)?;
+        captures
+            .get(1)
+            .or_else(|| captures.get(0))
+            .map(|family| family.as_str().to_string())
+    }
+
+    /// Display name for a save file stem
+    pub fn apply(&self, stem: &str) -> String {
+        let mut name = stem;
+        if let Some(prefix) = self.strip_prefix.as_deref() {
+            name = name.strip_prefix(prefix).unwrap_or(name);
+        }
+        if let Some(suffix) = self.strip_suffix.as_deref() {
+            name = name.strip_suffix(suffix).unwrap_or(name);
+        }
+
+        let mut name = name.to_string();
+        for (regex, replacement) in &self.replacements {
+            name = regex.replace_all(&name, replacement.as_str()).into_owned();
+        }
+
+        self.case.apply(&name)
+    }
+}
+
+fn manifest_path(saves_dir: &Path) -> Option<PathBuf> {
+    let dir = if saves_dir.is_file() {
+        saves_dir.parent()?
+    } else {
+        saves_dir
+    };
+
+    let path = dir.join(SAVES_MANIFEST_FILENAME);
+    path.is_file().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_manifest_and_filter_by_tags() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join(SAVES_MANIFEST_FILENAME),
+            r#"
+[saves."test-000123-v2-final2.zip"]
+label = "Weave v2"
+description = "Second weave"
+tags = ["weave"]
+
+[saves."other.zip"]
+tags = ["baseline"]
+"#,
+        )
+        .expect("write manifest");
+
+        let manifest = SavesManifest::load(dir).expect("load manifest");
+        let weave = dir.join("test-000123-v2-final2.zip");
+        let other = dir.join("other.zip");
+        let unlisted = dir.join("unlisted.zip");
+
+        assert_eq!(
+            manifest.get(&weave).and_then(|m| m.label.as_deref()),
+            Some("Weave v2")
+        );
+        assert!(manifest.get(&unlisted).is_none());
+
+        let filtered =
+            manifest.filter_by_tags(vec![weave.clone(), other, unlisted], &["weave".to_string()]);
+        assert_eq!(filtered, vec![weave]);
+    }
+
+    #[test]
+    fn test_display_name_rules_apply_in_order() {
+        let config = BenchmarkConfig {
+            strip_prefix: Some("test-".to_string()),
+            strip_suffix: Some("-final2".to_string()),
+            name_replace: vec![r"-v(\d+)=> v$1".to_string(), "_=> ".to_string()],
+            name_case: NameCase::Title,
+            ..Default::default()
+        };
+        let rules = DisplayNameRules::from_config(&config).expect("valid rules");
+
+        assert_eq!(rules.apply("test-belt_weave-v2-final2"), "Belt Weave V2");
+        assert_eq!(rules.apply("other"), "Other");
+
+        let invalid = BenchmarkConfig {
+            name_replace: vec!["missing arrow".to_string()],
+            ..Default::default()
+        };
+        assert!(DisplayNameRules::from_config(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_family_prefers_manifest_over_group_regex() {
+        let config = BenchmarkConfig {
+            group_regex: Some(r"^(.+)-v\d+$".to_string()),
+            ..Default::default()
+        };
+        let rules = DisplayNameRules::from_config(&config).expect("valid rules");
+        let metadata = SaveMetadata {
+            family: Some("Weaves".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            rules.family("belt-weave-v2", None).as_deref(),
+            Some("belt-weave")
+        );
+        assert_eq!(
+            rules.family("belt-weave-v2", Some(&metadata)).as_deref(),
+            Some("Weaves")
+        );
+        assert_eq!(rules.family("baseline", None), None);
+        assert_eq!(
+            DisplayNameRules::default().family("belt-weave-v2", None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_clone_tags() {
+        let metadata = |tags: &[&str]| SaveMetadata {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let clone = metadata(&["weave", "clones:8", "scaling:smelting"]);
+        assert_eq!(clone.clones(), Some(8));
+        assert_eq!(clone.scaling_series(), Some("smelting"));
+
+        assert_eq!(
+            metadata(&["clones:2"]).scaling_series(),
+            Some(DEFAULT_SCALING_SERIES)
+        );
+        assert_eq!(metadata(&["clones:many"]).clones(), None);
+        assert_eq!(metadata(&["scaling:smelting"]).scaling_series(), None);
+    }
+
+    #[test]
+    fn test_sidecars_override_manifest() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join(SAVES_MANIFEST_FILENAME),
+            r#"
+[saves."weave.zip"]
+label = "Weave"
+description = "From the manifest"
+tags = ["weave"]
+"#,
+        )
+        .expect("write manifest");
+        std::fs::write(
+            dir.join("weave.toml"),
+            r#"
+description = "From the sidecar"
+author = "Someone"
+expected = "Faster than the baseline"
+"#,
+        )
+        .expect("write sidecar");
+        std::fs::write(dir.join("other.toml"), "tags = [\"baseline\"]").expect("write sidecar");
+
+        let weave = dir.join("weave.zip");
+        let other = dir.join("other.zip");
+        let manifest = SavesManifest::load(dir)
+            .and_then(|manifest| manifest.with_sidecars(&[weave.clone(), other.clone()]))
+            .expect("load manifest");
+
+        let metadata = manifest.get(&weave).expect("weave is described");
+        assert_eq!(metadata.label.as_deref(), Some("Weave"));
+        assert_eq!(metadata.description.as_deref(), Some("From the sidecar"));
+        assert_eq!(metadata.author.as_deref(), Some("Someone"));
+        assert_eq!(metadata.tags, vec!["weave".to_string()]);
+        assert_eq!(
+            manifest.get(&other).map(|m| m.tags.clone()),
+            Some(vec!["baseline".to_string()])
+        );
+
+        std::fs::write(dir.join("broken.toml"), "tags = 1").expect("write sidecar");
+        assert!(
+            SavesManifest::default()
+                .with_sidecars(&[dir.join("broken.zip")])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_missing_manifest_is_empty() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let manifest = SavesManifest::load(temp_dir.path()).expect("load manifest");
+        assert!(manifest.saves.is_empty());
+    }
+}
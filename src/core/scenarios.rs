@@ -0,0 +1,62 @@
+//! Scenario folders benchmarked like saves.
+//!
+//! Factorio only benchmarks saves, so every scenario folder (a directory with a `control.lua`) is
+//! first started as a server in a throwaway write-data directory and run for a few ticks. The map
+//! it saves on exit is what gets benchmarked.
+
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+use crate::core::{
+    FactorioExecutor, Result, factorio::FactorioScenarioRunSpec, utils::is_scenario_dir,
+};
+
+/// Saves generated from scenario folders, removed when dropped
+#[derive(Debug)]
+pub struct ScenarioSaves {
+    dir: TempDir,
+}
+
+impl ScenarioSaves {
+    /// Replace every scenario folder in `save_files` with a save generated from it, keeping the
+    /// order. Saves are named after their scenario folder.
+    pub async fn generate(
+        factorio: &FactorioExecutor,
+        save_files: Vec<PathBuf>,
+        ticks: u32,
+        mods_dir: Option<&Path>,
+    ) -> Result<(Self, Vec<PathBuf>)> {
+        let saves = Self {
+            dir: tempfile::Builder::new()
+                .prefix("belt-scenarios-")
+                .tempdir()?,
+        };
+
+        let mut generated = Vec::with_capacity(save_files.len());
+        for save_file in save_files {
+            if !is_scenario_dir(&save_file) {
+                generated.push(save_file);
+                continue;
+            }
+
+            let name = save_file.file_name().unwrap_or_default().to_string_lossy();
+            let target = saves.dir.path().join(format!("{name}.zip"));
+            tracing::info!(
+                "Generating a save from scenario {} ({ticks} ticks)",
+                save_file.display()
+            );
+            factorio
+                .run_scenario(FactorioScenarioRunSpec {
+                    scenario_dir: &save_file,
+                    ticks,
+                    mods_dir,
+                    save_file: &target,
+                })
+                .await?;
+            generated.push(target);
+        }
+
+        Ok((saves, generated))
+    }
+}
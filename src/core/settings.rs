@@ -1,309 +1,141 @@
 use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::{self, Read},
+    collections::{BTreeSet, HashMap},
+    fmt, io,
     path::Path,
 };
 
-#[derive(Clone, Debug, PartialEq)]
-enum PropertyTreeData {
-    None,
-    Bool(bool),
-    Number(f64),
-    String(String),
-    List(Vec<PropertyTreeData>),
-    Dictionary(HashMap<String, PropertyTreeData>),
-    SignedInteger(i64),
-    UnsignedInteger(u64),
-}
+use serde_json::{Value, json};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-#[repr(u8)]
-enum PropertyTreeType {
-    None = 0,
-    Bool = 1,
-    Number = 2,
-    String = 3,
-    List = 4,
-    Dictionary = 5,
-    SignedInteger = 6,
-    UnsignedInteger = 7,
+use crate::core::propertytree::{MapVersion, PropertyTreeData, VersionedTree};
+
+// Settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModSettingsScopeName {
+    Startup,
+    RuntimeGlobal,
+    RuntimePerUser,
 }
 
-impl From<u8> for PropertyTreeType {
-    fn from(value: u8) -> Self {
-        match value {
-            0 => PropertyTreeType::None,
-            1 => PropertyTreeType::Bool,
-            2 => PropertyTreeType::Number,
-            3 => PropertyTreeType::String,
-            4 => PropertyTreeType::List,
-            5 => PropertyTreeType::Dictionary,
-            6 => PropertyTreeType::SignedInteger,
-            7 => PropertyTreeType::UnsignedInteger,
-            _ => panic!("Invalid PropertyTreeType: {value}"),
+impl ModSettingsScopeName {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModSettingsScopeName::Startup => "startup",
+            ModSettingsScopeName::RuntimeGlobal => "runtime-global",
+            ModSettingsScopeName::RuntimePerUser => "runtime-per-user",
         }
     }
-}
 
-#[derive(Debug, Clone)]
-struct MapVersion {
-    data: [u8; 9],
-}
-
-impl MapVersion {
-    fn from_reader<R: Read>(r: &mut R) -> io::Result<Self> {
-        let mut data = [0u8; 9];
-        r.read_exact(&mut data)?;
-        Ok(Self { data })
-    }
+    pub const ALL: [Self; 3] = [
+        ModSettingsScopeName::Startup,
+        ModSettingsScopeName::RuntimeGlobal,
+        ModSettingsScopeName::RuntimePerUser,
+    ];
 
-    fn to_bytes(&self) -> [u8; 9] {
-        self.data
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scope| scope.as_str() == name)
     }
 }
 
-pub trait BufferStream: Read {
-    fn read_u8(&mut self) -> io::Result<u8> {
-        let mut buf = [0u8; 1];
-        self.read_exact(&mut buf)?;
-        Ok(buf[0])
-    }
-
-    fn read_u32_le(&mut self) -> io::Result<u32> {
-        let mut buf = [0u8; 4];
-        self.read_exact(&mut buf)?;
-        Ok(u32::from_le_bytes(buf))
-    }
-
-    fn read_i64_le(&mut self) -> io::Result<i64> {
-        let mut buf = [0u8; 8];
-        self.read_exact(&mut buf)?;
-        Ok(i64::from_le_bytes(buf))
-    }
-
-    fn read_u64_le(&mut self) -> io::Result<u64> {
-        let mut buf = [0u8; 8];
-        self.read_exact(&mut buf)?;
-        Ok(u64::from_le_bytes(buf))
-    }
-
-    fn read_f64_le(&mut self) -> io::Result<f64> {
-        let mut buf = [0u8; 8];
-        self.read_exact(&mut buf)?;
-        Ok(f64::from_le_bytes(buf))
-    }
-
-    fn read_packed_uint_8_32(&mut self) -> io::Result<u32> {
-        let first = self.read_u8()?;
-        if first < 255 {
-            Ok(first as u32)
-        } else {
-            self.read_u32_le()
-        }
-    }
-
-    fn read_string(&mut self, size: u32) -> io::Result<String> {
-        let mut buf = vec![0u8; size as usize];
-        self.read_exact(&mut buf)?;
-        String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
-    }
+#[derive(Clone, Debug, PartialEq)]
+pub enum ModSettingsValue {
+    String(String),
+    Number(f64),
+    Int(i64),
+    Bool(bool),
+    Color { r: f64, g: f64, b: f64, a: f64 },
 }
 
-impl<R: Read> BufferStream for R {}
-
-fn read_ptree_string<R: BufferStream>(b: &mut R) -> io::Result<String> {
-    let empty = b.read_u8()? != 0;
-    if empty {
-        Ok(String::new())
-    } else {
-        let size = b.read_packed_uint_8_32()?;
-        b.read_string(size)
+impl ModSettingsValue {
+    pub fn to_json(&self) -> Value {
+        match self {
+            ModSettingsValue::String(s) => json!(s),
+            ModSettingsValue::Number(n) => json!(n),
+            ModSettingsValue::Int(i) => json!(i),
+            ModSettingsValue::Bool(b) => json!(b),
+            ModSettingsValue::Color { r, g, b, a } => json!({ "r": r, "g": g, "b": b, "a": a }),
+        }
     }
 }
 
-fn load_ptree<R: BufferStream>(b: &mut R) -> io::Result<PropertyTreeData> {
-    let type_val = b.read_u8()?;
-    let _is_any_type = b.read_u8()?;
-
-    let tree_type = PropertyTreeType::from(type_val);
-
-    match tree_type {
-        PropertyTreeType::None => Ok(PropertyTreeData::None),
-
-        PropertyTreeType::Bool => {
-            let value = b.read_u8()? != 0;
-            Ok(PropertyTreeData::Bool(value))
-        }
-
-        PropertyTreeType::Number => {
-            let value = b.read_f64_le()?;
-            Ok(PropertyTreeData::Number(value))
-        }
-
-        PropertyTreeType::String => {
-            let value = read_ptree_string(b)?;
-            Ok(PropertyTreeData::String(value))
-        }
-
-        PropertyTreeType::List => {
-            let count = b.read_u32_le()?;
-            let mut arr = Vec::with_capacity(count as usize);
-
-            for _ in 0..count {
-                let _key = read_ptree_string(b)?; // discard key for list items
-                let value = load_ptree(b)?;
-                arr.push(value);
-            }
-
-            Ok(PropertyTreeData::List(arr))
-        }
-
-        PropertyTreeType::Dictionary => {
-            let count = b.read_u32_le()?;
-            let mut dict = HashMap::with_capacity(count as usize);
-
-            for _ in 0..count {
-                let key = read_ptree_string(b)?;
-                if key.is_empty() {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "Missing key in PropertyTree Dictionary",
-                    ));
-                }
-                let value = load_ptree(b)?;
-                dict.insert(key, value);
+impl ModSettingsValue {
+    /// Parse `text` as a value of the same type as `self`
+    pub fn parse_like(&self, text: &str) -> Result<Self, String> {
+        let invalid = |kind: &str| format!("'{text}' is not a valid {kind}");
+        match self {
+            ModSettingsValue::String(_) => Ok(ModSettingsValue::String(text.to_string())),
+            ModSettingsValue::Number(_) => text
+                .parse()
+                .map(ModSettingsValue::Number)
+                .map_err(|_| invalid("number")),
+            ModSettingsValue::Int(_) => text
+                .parse()
+                .map(ModSettingsValue::Int)
+                .map_err(|_| invalid("integer")),
+            ModSettingsValue::Bool(_) => text
+                .parse()
+                .map(ModSettingsValue::Bool)
+                .map_err(|_| invalid("bool (true or false)")),
+            ModSettingsValue::Color { .. } => {
+                parse_color(text).ok_or_else(|| invalid("color (#rrggbb[aa] or r,g,b[,a])"))
             }
-
-            Ok(PropertyTreeData::Dictionary(dict))
-        }
-
-        PropertyTreeType::SignedInteger => {
-            let value = b.read_i64_le()?;
-            Ok(PropertyTreeData::SignedInteger(value))
-        }
-
-        PropertyTreeType::UnsignedInteger => {
-            let value = b.read_u64_le()?;
-            Ok(PropertyTreeData::UnsignedInteger(value))
         }
     }
-}
-
-fn save_string(s: &str) -> Vec<u8> {
-    if s.is_empty() {
-        vec![1] // no string
-    } else {
-        let str_bytes = s.as_bytes();
-        let mut result = vec![0];
 
-        if str_bytes.len() < 255 {
-            result.push(str_bytes.len() as u8);
+    /// The type `text` looks like, for settings that don't exist yet: bool, integer, number,
+    /// color, or else string
+    pub fn infer(text: &str) -> Self {
+        if let Ok(b) = text.parse() {
+            ModSettingsValue::Bool(b)
+        } else if let Ok(i) = text.parse() {
+            ModSettingsValue::Int(i)
+        } else if let Ok(n) = text.parse() {
+            ModSettingsValue::Number(n)
+        } else if let Some(color) = parse_color(text) {
+            color
         } else {
-            result.push(255);
-            result.extend_from_slice(&(str_bytes.len() as u32).to_le_bytes());
+            ModSettingsValue::String(text.to_string())
         }
-
-        result.extend_from_slice(str_bytes);
-        result
     }
 }
 
-fn type_tag(tree_type: PropertyTreeType) -> Vec<u8> {
-    vec![tree_type as u8, 0]
-}
-
-fn save_ptree(tree: &PropertyTreeData) -> Vec<u8> {
-    match tree {
-        PropertyTreeData::String(value) => {
-            let mut result = type_tag(PropertyTreeType::String);
-            result.extend_from_slice(&save_string(value));
-            result
-        }
-
-        PropertyTreeData::Bool(value) => {
-            let mut result = type_tag(PropertyTreeType::Bool);
-            result.push(if *value { 1 } else { 0 });
-            result
-        }
-
-        PropertyTreeData::Number(value) => {
-            let mut result = type_tag(PropertyTreeType::Number);
-            result.extend_from_slice(&value.to_le_bytes());
-            result
-        }
-
-        PropertyTreeData::SignedInteger(value) => {
-            let mut result = type_tag(PropertyTreeType::SignedInteger);
-            result.extend_from_slice(&value.to_le_bytes());
-            result
-        }
-
-        PropertyTreeData::UnsignedInteger(value) => {
-            let mut result = type_tag(PropertyTreeType::UnsignedInteger);
-            result.extend_from_slice(&value.to_le_bytes());
-            result
-        }
-
-        PropertyTreeData::None => type_tag(PropertyTreeType::None),
-
-        PropertyTreeData::List(values) => {
-            let mut result = type_tag(PropertyTreeType::List);
-            result.extend_from_slice(&(values.len() as u32).to_le_bytes());
-
-            for value in values {
-                result.extend_from_slice(&save_string("")); // no key for list items
-                result.extend_from_slice(&save_ptree(value));
-            }
-
-            result
-        }
-
-        PropertyTreeData::Dictionary(dict) => {
-            let mut result = type_tag(PropertyTreeType::Dictionary);
-            result.extend_from_slice(&(dict.len() as u32).to_le_bytes());
-
-            for (key, value) in dict {
-                result.extend_from_slice(&save_string(key));
-                result.extend_from_slice(&save_ptree(value));
-            }
-
-            result
+/// A color as `#rrggbb`, `#rrggbbaa` or `r,g,b[,a]`; alpha defaults to fully opaque
+fn parse_color(text: &str) -> Option<ModSettingsValue> {
+    let components: Vec<f64> = if let Some(hex) = text.strip_prefix('#') {
+        if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+            return None;
         }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map(|c| c as f64 / 255.0))
+            .collect::<Result<_, _>>()
+            .ok()?
+    } else {
+        text.split(',')
+            .map(|c| c.trim().parse())
+            .collect::<Result<_, _>>()
+            .ok()?
+    };
+
+    match components[..] {
+        [r, g, b] => Some(ModSettingsValue::Color { r, g, b, a: 1.0 }),
+        [r, g, b, a] => Some(ModSettingsValue::Color { r, g, b, a }),
+        _ => None,
     }
 }
 
-// Settings
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum ModSettingsScopeName {
-    Startup,
-    RuntimeGlobal,
-    RuntimePerUser,
-}
-
-impl ModSettingsScopeName {
-    fn as_str(&self) -> &'static str {
-        match self {
-            ModSettingsScopeName::Startup => "startup",
-            ModSettingsScopeName::RuntimeGlobal => "runtime-global",
-            ModSettingsScopeName::RuntimePerUser => "runtime-per-user",
-        }
+impl fmt::Display for ModSettingsValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_json())
     }
-
-    const ALL: [Self; 3] = [
-        ModSettingsScopeName::Startup,
-        ModSettingsScopeName::RuntimeGlobal,
-        ModSettingsScopeName::RuntimePerUser,
-    ];
 }
 
+/// A setting with different values in two files; `None` where a file doesn't have it
 #[derive(Clone, Debug, PartialEq)]
-pub enum ModSettingsValue {
-    String(String),
-    Number(f64),
-    Int(i64),
-    Bool(bool),
-    Color { r: f64, g: f64, b: f64, a: f64 },
+pub struct ModSettingsDifference {
+    pub scope: ModSettingsScopeName,
+    pub key: String,
+    pub left: Option<ModSettingsValue>,
+    pub right: Option<ModSettingsValue>,
 }
 
 type ModSettingsScope = HashMap<String, ModSettingsValue>;
@@ -335,15 +167,13 @@ impl ModSettingsData {
 
 #[derive(Clone, Debug)]
 pub struct ModSettings {
-    version: MapVersion,
+    pub(crate) version: MapVersion,
     pub settings: ModSettingsData,
 }
 
 impl ModSettings {
-    fn from_reader<R: Read>(mut r: R) -> io::Result<Self> {
-        let mut br = io::BufReader::new(&mut r);
-        let version = MapVersion::from_reader(&mut br)?;
-        let tree = load_ptree(&mut br)?;
+    fn from_tree(file: VersionedTree) -> io::Result<Self> {
+        let VersionedTree { version, tree } = file;
 
         let mut loading = ModSettingsData::default();
 
@@ -440,8 +270,56 @@ impl ModSettings {
     }
 
     pub fn load_from_file(path: impl AsRef<Path>) -> io::Result<Self> {
-        let mut f = File::open(path)?;
-        Self::from_reader(&mut f)
+        Self::from_tree(VersionedTree::load_from_file(path)?)
+    }
+
+    /// The game version and every setting per scope, with keys in alphabetical order
+    pub fn to_json(&self) -> Value {
+        let mut root = serde_json::Map::new();
+        root.insert("version".into(), json!(self.version.to_string()));
+
+        for scope_name in ModSettingsScopeName::ALL {
+            let scope: serde_json::Map<String, Value> = self
+                .settings
+                .scope_ref(scope_name)
+                .iter()
+                .map(|(key, value)| (key.clone(), value.to_json()))
+                .collect();
+            root.insert(scope_name.as_str().into(), Value::Object(scope));
+        }
+
+        Value::Object(root)
+    }
+
+    /// Every setting whose value differs from `other`, per scope in alphabetical order
+    pub fn diff(&self, other: &ModSettings) -> Vec<ModSettingsDifference> {
+        let mut differences = Vec::new();
+
+        for scope in ModSettingsScopeName::ALL {
+            let (left, right) = (
+                self.settings.scope_ref(scope),
+                other.settings.scope_ref(scope),
+            );
+            let keys: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+
+            for key in keys {
+                let (left, right) = (left.get(key), right.get(key));
+                if left != right {
+                    differences.push(ModSettingsDifference {
+                        scope,
+                        key: key.clone(),
+                        left: left.cloned(),
+                        right: right.cloned(),
+                    });
+                }
+            }
+        }
+
+        differences
+    }
+
+    pub fn get(&self, scope: ModSettingsScopeName, key: &str) -> Option<&ModSettingsValue> {
+        self.settings.scope_ref(scope).get(key)
     }
 
     pub fn set(
@@ -497,16 +375,115 @@ impl ModSettings {
         PropertyTreeData::Dictionary(root)
     }
 
-    fn save_bytes(&self) -> Vec<u8> {
-        let mut out = Vec::new();
-        out.extend_from_slice(&self.version.to_bytes());
-        let tree = self.to_ptree();
-        out.extend_from_slice(&save_ptree(&tree));
-        out
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        VersionedTree {
+            version: self.version.clone(),
+            tree: self.to_ptree(),
+        }
+        .save_to_file(path)
     }
+}
 
-    pub fn save_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
-        let bytes = self.save_bytes();
-        fs::write(path, bytes)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_round_trip_dump_and_diff() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path().join("mod-settings.dat");
+
+        let mut settings = ModSettings {
+            // 2.0.55, build 0
+            version: MapVersion {
+                data: [2, 0, 0, 0, 55, 0, 0, 0, 0],
+            },
+            settings: ModSettingsData::default(),
+        };
+        settings.set(
+            ModSettingsScopeName::Startup,
+            "belt-speed",
+            Some(ModSettingsValue::Int(4)),
+        );
+        settings.set(
+            ModSettingsScopeName::RuntimeGlobal,
+            "enabled",
+            Some(ModSettingsValue::Bool(true)),
+        );
+        settings.save_to_file(&path).expect("save settings");
+
+        let mut loaded = ModSettings::load_from_file(&path).expect("load settings");
+        assert_eq!(
+            loaded.to_json(),
+            json!({
+                "version": "2.0.55-0",
+                "startup": { "belt-speed": 4 },
+                "runtime-global": { "enabled": true },
+                "runtime-per-user": {},
+            })
+        );
+
+        loaded.set(
+            ModSettingsScopeName::Startup,
+            "belt-speed",
+            Some(ModSettingsValue::Number(4.5)),
+        );
+        loaded.set(ModSettingsScopeName::RuntimeGlobal, "enabled", None);
+        let differences = settings.diff(&loaded);
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[0].key, "belt-speed");
+        assert_eq!(differences[0].left.as_ref().unwrap().to_string(), "4");
+        assert_eq!(differences[0].right.as_ref().unwrap().to_string(), "4.5");
+        assert_eq!(differences[1].scope, ModSettingsScopeName::RuntimeGlobal);
+        assert_eq!(differences[1].right, None);
+    }
+
+    #[test]
+    fn test_values_parse_as_existing_or_inferred_type() {
+        let int = ModSettingsValue::Int(1);
+        assert_eq!(int.parse_like("5"), Ok(ModSettingsValue::Int(5)));
+        assert!(int.parse_like("5.5").is_err());
+        assert_eq!(
+            ModSettingsValue::String(String::new()).parse_like("true"),
+            Ok(ModSettingsValue::String("true".to_string()))
+        );
+        assert_eq!(
+            ModSettingsValue::Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.0
+            }
+            .parse_like("#ff000080"),
+            Ok(ModSettingsValue::Color {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+                a: 128.0 / 255.0
+            })
+        );
+
+        assert_eq!(
+            ModSettingsValue::infer("false"),
+            ModSettingsValue::Bool(false)
+        );
+        assert_eq!(ModSettingsValue::infer("-3"), ModSettingsValue::Int(-3));
+        assert_eq!(
+            ModSettingsValue::infer("0.5"),
+            ModSettingsValue::Number(0.5)
+        );
+        assert_eq!(
+            ModSettingsValue::infer("1, 0.5, 0"),
+            ModSettingsValue::Color {
+                r: 1.0,
+                g: 0.5,
+                b: 0.0,
+                a: 1.0
+            }
+        );
+        assert_eq!(
+            ModSettingsValue::infer("fast"),
+            ModSettingsValue::String("fast".to_string())
+        );
     }
 }
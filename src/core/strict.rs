@@ -0,0 +1,96 @@
+//! Strict mode (`--strict`): the first warning or error logged during a benchmark session, e.g.
+//! a save without a `.zip` extension, an unknown verbose metric or missing per-tick timings,
+//! aborts the session at the next checkpoint instead of ending up as a footnote to the report.
+//!
+//! [`StrictLayer`] sees every log event; the session calls [`check`] before the first run, after
+//! every run and once its output is written.
+
+use std::sync::{
+    Mutex, PoisonError,
+    atomic::{AtomicBool, Ordering},
+};
+
+use tracing::{Event, Level, Subscriber, field::Field};
+use tracing_subscriber::{layer::Context, registry::LookupSpan};
+
+use crate::core::error::{BenchmarkErrorKind, Result};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The first warning logged in strict mode since the last check
+static FIRST_WARNING: Mutex<Option<String>> = Mutex::new(None);
+
+/// Turn strict mode on or off for the rest of the process
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Fail with the first warning logged since the last check, in strict mode
+pub fn check() -> Result<()> {
+    match take_warning() {
+        Some(message) => Err(BenchmarkErrorKind::StrictModeWarning { message }.into()),
+        None => Ok(()),
+    }
+}
+
+fn take_warning() -> Option<String> {
+    FIRST_WARNING
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+}
+
+/// Records the first warning or error for [`check`] while strict mode is enabled
+pub struct StrictLayer;
+
+impl<S> tracing_subscriber::Layer<S> for StrictLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if !ENABLED.load(Ordering::SeqCst) || *event.metadata().level() > Level::WARN {
+            return;
+        }
+
+        let mut message = String::new();
+        event.record(&mut |field: &Field, value: &dyn std::fmt::Debug| {
+            if field.name() == "message" {
+                message = format!("{value:?}");
+            }
+        });
+        FIRST_WARNING
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::{Registry, layer::SubscriberExt};
+
+    #[test]
+    fn test_check_fails_on_first_warning() {
+        let subscriber = Registry::default().with(StrictLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            set_enabled(true);
+            tracing::info!("Running save");
+            assert!(check().is_ok());
+
+            tracing::warn!("Unknown verbose metric 'wholeUpdat'");
+            tracing::error!("Skipping base");
+            let error = check().expect_err("a warning was logged");
+            assert!(
+                error
+                    .to_string()
+                    .contains("Unknown verbose metric 'wholeUpdat'")
+            );
+            assert!(check().is_ok());
+
+            set_enabled(false);
+            tracing::warn!("Ignored");
+            assert!(check().is_ok());
+        });
+    }
+}
@@ -6,7 +6,7 @@ use serde_json::Value;
 use crate::Result;
 use crate::benchmark::parser::BenchmarkRun;
 use crate::sanitize::parser::ProductionStatistic;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::{path::Path, time::Duration};
 
@@ -41,6 +41,271 @@ impl std::str::FromStr for RunOrder {
     }
 }
 
+/// Page-cache handling for save files before each benchmark run
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CachePolicy {
+    /// Pre-read the save file into the page cache before every run
+    Warm,
+    /// Drop the save file from the page cache before every run, where permitted
+    Cold,
+    /// Leave the page cache as it is - default
+    #[default]
+    Asis,
+}
+
+/// Get a CachePolicy from a string
+impl std::str::FromStr for CachePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "warm" => Ok(CachePolicy::Warm),
+            "cold" => Ok(CachePolicy::Cold),
+            "asis" => Ok(CachePolicy::Asis),
+            _ => Err(BenchmarkErrorKind::InvalidCachePolicy {
+                input: s.to_string(),
+            }
+            .to_string()),
+        }
+    }
+}
+
+/// Order of the saves in results.csv and the report
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortBy {
+    /// Slowest save first
+    Ups,
+    /// Alphabetically by display name
+    Name,
+    /// In the order the saves were found, e.g. to keep A/B/C variants together
+    InputOrder,
+}
+
+/// Get a SortBy from a string
+impl std::str::FromStr for SortBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ups" => Ok(SortBy::Ups),
+            "name" => Ok(SortBy::Name),
+            "input-order" => Ok(SortBy::InputOrder),
+            _ => Err(BenchmarkErrorKind::InvalidSortBy {
+                input: s.to_string(),
+            }
+            .to_string()),
+        }
+    }
+}
+
+/// How `chartdata.json` reduces long per-tick series to at most 500 points
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Downsample {
+    /// Keep the ticks that best preserve the shape of the tick time series (Largest Triangle
+    /// Three Buckets), so single spikes survive
+    Lttb,
+    /// Average consecutive ticks, adding the minimum and maximum of each bucket per metric
+    MinMax,
+    /// Average consecutive ticks - default
+    #[default]
+    Mean,
+}
+
+/// Get a Downsample from a string
+impl std::str::FromStr for Downsample {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "lttb" => Ok(Downsample::Lttb),
+            "minmax" => Ok(Downsample::MinMax),
+            "mean" => Ok(Downsample::Mean),
+            _ => Err(BenchmarkErrorKind::InvalidDownsample {
+                input: s.to_string(),
+            }
+            .to_string()),
+        }
+    }
+}
+
+/// Case mapping applied to save display names
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NameCase {
+    /// Keep the name as it is - default
+    #[default]
+    Asis,
+    Lower,
+    Upper,
+    /// Capitalize the first letter of every word
+    Title,
+}
+
+/// Get a NameCase from a string
+impl std::str::FromStr for NameCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "asis" => Ok(NameCase::Asis),
+            "lower" => Ok(NameCase::Lower),
+            "upper" => Ok(NameCase::Upper),
+            "title" => Ok(NameCase::Title),
+            _ => Err(BenchmarkErrorKind::InvalidNameCase {
+                input: s.to_string(),
+            }
+            .to_string()),
+        }
+    }
+}
+
+impl NameCase {
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            NameCase::Asis => name.to_string(),
+            NameCase::Lower => name.to_lowercase(),
+            NameCase::Upper => name.to_uppercase(),
+            NameCase::Title => {
+                let mut title = String::with_capacity(name.len());
+                let mut word_start = true;
+                for c in name.chars() {
+                    if word_start {
+                        title.extend(c.to_uppercase());
+                    } else {
+                        title.push(c);
+                    }
+                    word_start = !c.is_alphanumeric();
+                }
+                title
+            }
+        }
+    }
+}
+
+/// How effective UPS is computed from a run's measurements
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UpsDefinition {
+    /// Ticks divided by the total execution time - default
+    #[default]
+    Total,
+    /// The inverse of the average tick time
+    Average,
+    /// Like `Total`, capped at the 60 UPS the game runs at normally
+    Capped,
+}
+
+/// Get a UpsDefinition from a string
+impl std::str::FromStr for UpsDefinition {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "total" => Ok(UpsDefinition::Total),
+            "average" => Ok(UpsDefinition::Average),
+            "capped" => Ok(UpsDefinition::Capped),
+            _ => Err(BenchmarkErrorKind::InvalidUpsDefinition {
+                input: s.to_string(),
+            }
+            .to_string()),
+        }
+    }
+}
+
+impl UpsDefinition {
+    /// UPS the game runs at without speed-ups
+    const NORMAL_UPS: f64 = 60.0;
+
+    /// Effective UPS of a run under this definition
+    pub fn effective_ups(self, run: &BenchmarkRun) -> f64 {
+        let from_total = if run.execution_time_ms > 0.0 {
+            1000.0 * run.ticks as f64 / run.execution_time_ms
+        } else {
+            0.0
+        };
+
+        match self {
+            UpsDefinition::Total => from_total,
+            UpsDefinition::Average if run.avg_ms > 0.0 => 1000.0 / run.avg_ms,
+            UpsDefinition::Average => 0.0,
+            UpsDefinition::Capped => from_total.min(Self::NORMAL_UPS),
+        }
+    }
+
+    /// How the definition computes UPS, for the report
+    pub fn description(self) -> &'static str {
+        match self {
+            UpsDefinition::Total => "1000 × ticks / total execution time (ms)",
+            UpsDefinition::Average => "1000 / average tick time (ms)",
+            UpsDefinition::Capped => {
+                "1000 × ticks / total execution time (ms), capped at 60 (normal game speed)"
+            }
+        }
+    }
+}
+
+/// A half-open range of measured ticks, written `start..end`, e.g. `1000..5000`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TickRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl TickRange {
+    pub fn len(self) -> u32 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl std::str::FromStr for TickRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || {
+            BenchmarkErrorKind::InvalidTickRange {
+                input: s.to_string(),
+            }
+            .to_string()
+        };
+
+        let (start, end) = s.trim().split_once("..").ok_or_else(invalid)?;
+        let start: u32 = start.trim().parse().map_err(|_| invalid())?;
+        let end: u32 = end.trim().parse().map_err(|_| invalid())?;
+        if start >= end {
+            return Err(invalid());
+        }
+        Ok(Self { start, end })
+    }
+}
+
+impl TryFrom<String> for TickRange {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TickRange> for String {
+    fn from(range: TickRange) -> Self {
+        range.to_string()
+    }
+}
+
+impl std::fmt::Display for TickRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
 // Formatting related utilities
 /// Helper function to turn a Duration into a nicely formatted string
 pub fn format_duration(duration: Duration) -> String {
@@ -59,6 +324,85 @@ pub fn format_duration(duration: Duration) -> String {
     }
 }
 
+/// Find the candidate closest to `input` by edit distance, for "did you mean" hints
+pub fn suggest_similar<'a>(input: &str, candidates: &'a [String]) -> Option<&'a str> {
+    if let Some(exact) = candidates
+        .iter()
+        .find(|candidate| candidate.eq_ignore_ascii_case(input))
+    {
+        return Some(exact);
+    }
+
+    let max_distance = (input.len() / 3).max(2);
+    candidates
+        .iter()
+        .map(|candidate| (candidate, strsim::levenshtein(input, candidate)))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate.as_str())
+}
+
+/// Parse a human readable duration such as "90s", "30m" or "1h30m" (bare numbers are seconds)
+pub fn parse_duration(input: &str) -> std::result::Result<Duration, String> {
+    let invalid = || {
+        BenchmarkErrorKind::InvalidDuration {
+            input: input.to_string(),
+        }
+        .to_string()
+    };
+
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total_secs = 0u64;
+    let mut number = String::new();
+    for ch in trimmed.chars() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+            continue;
+        }
+
+        let multiplier = match ch {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = number.parse().map_err(|_| invalid())?;
+        total_secs = value
+            .checked_mul(multiplier)
+            .and_then(|secs| total_secs.checked_add(secs))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+
+    if !number.is_empty() {
+        let value: u64 = number.parse().map_err(|_| invalid())?;
+        total_secs = total_secs.checked_add(value).ok_or_else(invalid)?;
+    }
+
+    Ok(Duration::from_secs(total_secs))
+}
+
+/// Parse a percentage such as "3%" or "2.5" (the percent sign is optional)
+pub fn parse_percent(input: &str) -> std::result::Result<f64, String> {
+    input
+        .trim()
+        .trim_end_matches('%')
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|percent| percent.is_finite() && *percent >= 0.0)
+        .ok_or_else(|| {
+            BenchmarkErrorKind::InvalidPercent {
+                input: input.to_string(),
+            }
+            .to_string()
+        })
+}
+
 pub fn process_items(obj: &Value, stat_type: &str, items_vec: &mut Vec<ProductionStatistic>) {
     if let Some(items_obj) = obj.get("items").and_then(|x| x.as_object()) {
         for (item_name, quality_map) in items_obj {
@@ -134,14 +478,26 @@ pub fn find_save_files(saves_dir: &Path, pattern: Option<&str>) -> Result<Vec<Pa
         }
     }
 
+    // A single scenario folder
+    if is_scenario_dir(saves_dir) {
+        return Ok(vec![saves_dir.to_path_buf()]);
+    }
+
     // Set up the whole pattern
     let pattern = pattern.unwrap_or("*");
     let search_pattern = saves_dir.join(format!("{pattern}.zip"));
+    let scenario_pattern = saves_dir.join(pattern);
 
-    // Search using the pattern
-    let saves: Vec<PathBuf> = glob::glob(search_pattern.to_string_lossy().as_ref())?
+    // Search using the pattern, for save files and scenario folders
+    let mut saves: Vec<PathBuf> = glob::glob(search_pattern.to_string_lossy().as_ref())?
         .filter_map(std::result::Result::ok)
         .collect();
+    saves.extend(
+        glob::glob(scenario_pattern.to_string_lossy().as_ref())?
+            .filter_map(std::result::Result::ok)
+            .filter(|path| is_scenario_dir(path)),
+    );
+    saves.sort();
 
     // If empty, return
     if saves.is_empty() {
@@ -173,7 +529,7 @@ pub fn validate_save_files(save_files: &[PathBuf]) -> Result<()> {
         }
 
         // Check extension
-        if save_file.extension().is_none_or(|ext| ext != "zip") {
+        if !is_scenario_dir(save_file) && save_file.extension().is_none_or(|ext| ext != "zip") {
             tracing::warn!(
                 "Save file {} does not have .zip extension",
                 save_file.display()
@@ -251,7 +607,7 @@ pub fn is_executable(path: &Path) -> bool {
     // Fallback for other operating systems.
     #[cfg(not(any(unix, windows)))]
     {
-        metadata.is_file()
+        path.is_file()
     }
 }
 
@@ -271,6 +627,37 @@ pub fn check_save_file(name: String) -> Option<PathBuf> {
         .find(|path| path.exists())
 }
 
+/// Whether the path is a scenario folder, i.e. a directory with a `control.lua`
+pub fn is_scenario_dir(path: &Path) -> bool {
+    path.is_dir() && path.join("control.lua").is_file()
+}
+
+/// Copy a directory and everything in it
+pub fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// The most recently modified save in a directory
+pub fn newest_save_file(saves_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(saves_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "zip"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max()
+        .map(|(_, path)| path)
+}
+
 /// Find mod directory
 pub fn find_mod_directory() -> Option<PathBuf> {
     get_default_user_data_dirs()
@@ -300,14 +687,52 @@ fn get_default_user_data_dirs() -> Vec<PathBuf> {
     paths
 }
 
+/// Reorder the saves of `runs`, keeping the runs of each save together and in order.
+/// `input_order` holds the display names of the saves in the order they were found.
+pub fn sort_saves(runs: &mut [BenchmarkRun], sort_by: SortBy, input_order: &[String]) {
+    let mut ups: HashMap<&str, (f64, u32)> = HashMap::new();
+    for run in runs.iter() {
+        let entry = ups.entry(&run.save_name).or_insert((0.0, 0));
+        entry.0 += run.effective_ups;
+        entry.1 += 1;
+    }
+    let mean_ups: HashMap<String, f64> = ups
+        .into_iter()
+        .map(|(name, (sum, n))| (name.to_string(), sum / n as f64))
+        .collect();
+    let position = |name: &str| {
+        input_order
+            .iter()
+            .position(|input| input == name)
+            .unwrap_or(usize::MAX)
+    };
+
+    runs.sort_by(|a, b| {
+        let by_name = a.save_name.cmp(&b.save_name);
+        match sort_by {
+            SortBy::Ups => mean_ups[&a.save_name]
+                .partial_cmp(&mean_ups[&b.save_name])
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(by_name),
+            SortBy::Name => by_name,
+            SortBy::InputOrder => position(&a.save_name)
+                .cmp(&position(&b.save_name))
+                .then(by_name),
+        }
+    });
+}
+
 // Math related utilities
-/// Calculate the base differences of a list of save's results.
+/// Calculate the base differences of a list of save's results. The same save measured on
+/// different machines counts as separate results.
 pub fn calculate_base_differences(runs: &mut [BenchmarkRun]) {
-    // save_name -> (sum_ups, count)
-    let mut sums: BTreeMap<String, (f64, u32)> = BTreeMap::new();
+    // (save_name, machine) -> (sum_ups, count)
+    let mut sums: BTreeMap<(String, Option<String>), (f64, u32)> = BTreeMap::new();
 
     for r in runs.iter() {
-        let entry = sums.entry(r.save_name.clone()).or_insert((0.0, 0));
+        let entry = sums
+            .entry((r.save_name.clone(), r.machine.clone()))
+            .or_insert((0.0, 0));
         entry.0 += r.effective_ups;
         entry.1 += 1;
     }
@@ -319,7 +744,10 @@ pub fn calculate_base_differences(runs: &mut [BenchmarkRun]) {
         .unwrap_or(0.0);
 
     for r in runs.iter_mut() {
-        let (sum, n) = sums.get(&r.save_name).copied().unwrap_or((0.0, 0));
+        let (sum, n) = sums
+            .get(&(r.save_name.clone(), r.machine.clone()))
+            .copied()
+            .unwrap_or((0.0, 0));
         let save_avg_ups = if n == 0 { 0.0 } else { sum / n as f64 };
 
         r.base_diff = if min_avg_ups > 0.0 {
@@ -330,6 +758,129 @@ pub fn calculate_base_differences(runs: &mut [BenchmarkRun]) {
     }
 }
 
+/// Arithmetic mean of a set of values, `None` when empty
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Sample standard deviation (n - 1), `None` with fewer than two values
+pub fn sample_std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values)?;
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+/// Percentile (0-100) with linear interpolation between closest ranks, `None` when empty
+pub fn percentile(values: &[f64], percent: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let rank = percent.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+/// Two-sided 95% critical value of Student's t-distribution
+pub fn t_critical_95(degrees_of_freedom: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+
+    match degrees_of_freedom {
+        0 => f64::INFINITY,
+        df if df <= TABLE.len() => TABLE[df - 1],
+        _ => 1.960,
+    }
+}
+
+/// 95% confidence interval of the mean, `None` with fewer than two values
+pub fn confidence_interval_95(values: &[f64]) -> Option<(f64, f64)> {
+    let mean = mean(values)?;
+    let std_dev = sample_std_dev(values)?;
+    let half_width = t_critical_95(values.len() - 1) * std_dev / (values.len() as f64).sqrt();
+    Some((mean - half_width, mean + half_width))
+}
+
+/// Least-squares line through a set of points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+    /// Coefficient of determination, 1 when the line explains every point
+    pub r_squared: f64,
+}
+
+impl LinearFit {
+    pub fn at(&self, x: f64) -> f64 {
+        self.intercept + self.slope * x
+    }
+}
+
+/// Ordinary least-squares fit, `None` without at least two distinct x values
+pub fn linear_fit(points: &[(f64, f64)]) -> Option<LinearFit> {
+    let xs: Vec<f64> = points.iter().map(|&(x, _)| x).collect();
+    let ys: Vec<f64> = points.iter().map(|&(_, y)| y).collect();
+    let (mean_x, mean_y) = (mean(&xs)?, mean(&ys)?);
+
+    let ss_xx: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    if ss_xx == 0.0 {
+        return None;
+    }
+    let ss_xy: f64 = points
+        .iter()
+        .map(|&(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+
+    let slope = ss_xy / ss_xx;
+    let intercept = mean_y - slope * mean_x;
+
+    let ss_total: f64 = ys.iter().map(|y| (y - mean_y).powi(2)).sum();
+    let ss_residual: f64 = points
+        .iter()
+        .map(|&(x, y)| (y - (intercept + slope * x)).powi(2))
+        .sum();
+    let r_squared = if ss_total == 0.0 {
+        1.0
+    } else {
+        1.0 - ss_residual / ss_total
+    };
+
+    Some(LinearFit {
+        slope,
+        intercept,
+        r_squared,
+    })
+}
+
+/// Smallest difference between two saves' mean UPS, in percent, that `runs` runs per save can
+/// resolve at 95% confidence, given the run-to-run coefficient of variation in percent
+pub fn minimum_detectable_effect(cv_percent: f64, runs: u32) -> Option<f64> {
+    if runs < 2 {
+        return None;
+    }
+    let degrees_of_freedom = 2 * runs as usize - 2;
+    Some(t_critical_95(degrees_of_freedom) * cv_percent * (2.0 / runs as f64).sqrt())
+}
+
+/// Runs per save needed to resolve `effect_percent`, `None` if it would take more than 1000
+pub fn runs_to_detect(cv_percent: f64, effect_percent: f64) -> Option<u32> {
+    (2..=1000).find(|&runs| {
+        minimum_detectable_effect(cv_percent, runs).is_some_and(|effect| effect <= effect_percent)
+    })
+}
+
 pub fn round_to_precision_window(ticks: u32) -> u32 {
     const ONE_MINUTE: u32 = 3600;
     const TEN_MINUTES: u32 = 36000;
@@ -364,3 +915,69 @@ pub fn round_to_precision_window(ticks: u32) -> u32 {
 pub fn get_os_info() -> String {
     format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_rejects_overflow() {
+        assert_eq!(parse_duration("1h1m1s"), Ok(Duration::from_secs(3661)));
+        assert!(parse_duration("99999999999999999999h").is_err());
+        assert!(parse_duration("5124095576030432h").is_err());
+        assert!(parse_duration(&format!("{}s1s", u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_suggest_similar() {
+        let available = ["wholeUpdate", "gameUpdate", "circuitNetworkUpdate"].map(str::to_string);
+
+        assert_eq!(
+            suggest_similar("wholeUpdte", &available),
+            Some("wholeUpdate")
+        );
+        assert_eq!(
+            suggest_similar("gameupdate", &available),
+            Some("gameUpdate")
+        );
+        assert_eq!(suggest_similar("electricNetwork", &available), None);
+    }
+
+    #[test]
+    fn test_sort_saves_keeps_runs_together() {
+        let run = |save_name: &str, effective_ups: f64| BenchmarkRun {
+            save_name: save_name.to_string(),
+            effective_ups,
+            ..Default::default()
+        };
+        let mut results = vec![
+            run("variant-a", 60.0),
+            run("variant-c", 40.0),
+            run("variant-b", 80.0),
+            run("variant-a", 62.0),
+        ];
+        let names = |results: &[BenchmarkRun]| {
+            results
+                .iter()
+                .map(|run| run.save_name.clone())
+                .collect::<Vec<_>>()
+        };
+        let input_order = ["variant-a", "variant-b", "variant-c"].map(str::to_string);
+
+        sort_saves(&mut results, SortBy::Ups, &input_order);
+        assert_eq!(
+            names(&results),
+            ["variant-c", "variant-a", "variant-a", "variant-b"]
+        );
+        assert_eq!(results[1].effective_ups, 60.0);
+
+        sort_saves(&mut results, SortBy::InputOrder, &input_order);
+        assert_eq!(
+            names(&results),
+            ["variant-a", "variant-a", "variant-b", "variant-c"]
+        );
+
+        assert_eq!("input-order".parse(), Ok(SortBy::InputOrder));
+        assert!("fastest".parse::<SortBy>().is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Factorio installations of several versions side by side, e.g. extracted headless archives:
+//!
+//! ```text
+//! versions/
+//! ├── factorio_2.0.50/bin/x64/factorio
+//! └── factorio-headless_2.0.55/bin/x64/factorio
+//! ```
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::LazyLock,
+};
+
+use regex::Regex;
+
+use crate::core::error::{BenchmarkErrorKind, Result};
+
+/// Executable locations inside an installation, across platforms
+const EXECUTABLE_PATHS: [&str; 4] = [
+    "bin/x64/factorio",
+    "bin/x64/factorio.exe",
+    "factorio.app/Contents/MacOS/factorio",
+    "Contents/MacOS/factorio",
+];
+
+static VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?P<major>[0-9]+)\.(?P<minor>[0-9]+)\.(?P<patch>[0-9]+)")
+        .expect("Regex building failed")
+});
+
+/// A `major.minor.patch` Factorio version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FactorioVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl FactorioVersion {
+    /// The first version in `text`, e.g. in a directory name
+    pub fn find_in(text: &str) -> Option<Self> {
+        let captures = VERSION_REGEX.captures(text)?;
+        Some(Self {
+            major: captures["major"].parse().ok()?,
+            minor: captures["minor"].parse().ok()?,
+            patch: captures["patch"].parse().ok()?,
+        })
+    }
+}
+
+impl FromStr for FactorioVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::find_in(s)
+            .filter(|version| version.to_string() == s.trim())
+            .ok_or_else(|| {
+                BenchmarkErrorKind::InvalidFactorioVersion {
+                    input: s.to_string(),
+                }
+                .to_string()
+            })
+    }
+}
+
+impl fmt::Display for FactorioVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// An installation found in a versions directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledVersion {
+    pub version: FactorioVersion,
+    pub executable: PathBuf,
+}
+
+/// Every installation directly inside `dir` whose name contains a version, ordered by version
+pub fn find_installed(dir: &Path) -> Result<Vec<InstalledVersion>> {
+    if !dir.is_dir() {
+        return Err(BenchmarkErrorKind::VersionsDirectoryNotFound {
+            path: dir.to_path_buf(),
+        }
+        .into());
+    }
+
+    let mut installed = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(version) = path
+            .file_name()
+            .and_then(|name| FactorioVersion::find_in(&name.to_string_lossy()))
+        else {
+            continue;
+        };
+        let Some(executable) = EXECUTABLE_PATHS
+            .iter()
+            .map(|relative| path.join(relative))
+            .find(|executable| executable.is_file())
+        else {
+            tracing::debug!("No Factorio executable in {}", path.display());
+            continue;
+        };
+
+        installed.push(InstalledVersion {
+            version,
+            executable,
+        });
+    }
+
+    installed.sort_by_key(|installed| installed.version);
+    installed.dedup_by_key(|installed| installed.version);
+    Ok(installed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_installed_versions_in_order() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+        for name in [
+            "factorio_2.0.9",
+            "factorio-headless_2.0.10",
+            "factorio_2.1.0",
+        ] {
+            let bin = dir.join(name).join("bin/x64");
+            std::fs::create_dir_all(&bin).expect("create bin");
+            std::fs::write(bin.join("factorio"), "").expect("write executable");
+        }
+        std::fs::create_dir_all(dir.join("factorio_2.0.11")).expect("create empty install");
+        std::fs::create_dir_all(dir.join("mods")).expect("create unrelated dir");
+
+        let versions: Vec<String> = find_installed(dir)
+            .expect("find versions")
+            .iter()
+            .map(|installed| installed.version.to_string())
+            .collect();
+
+        assert_eq!(versions, vec!["2.0.9", "2.0.10", "2.1.0"]);
+        assert!("2.0.55".parse::<FactorioVersion>().is_ok());
+        assert!("2.0".parse::<FactorioVersion>().is_err());
+    }
+}
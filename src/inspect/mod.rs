@@ -0,0 +1,35 @@
+//! Inspect module
+//!
+//! Prints what a save was made with, read from its header without launching the game.
+
+use std::path::Path;
+
+use crate::core::{Result, save_header};
+
+/// Print the header of the save at `path`
+pub fn run(path: &Path) -> Result<()> {
+    let header = save_header::read(path)?;
+
+    println!("Factorio version:  {}", header.version);
+    let scenario = if header.campaign.is_empty() {
+        header.level_name.clone()
+    } else {
+        format!("{}/{}", header.campaign, header.level_name)
+    };
+    println!("Scenario:          {scenario} ({})", header.base_mod);
+    println!("Mods:");
+    for save_mod in &header.mods {
+        println!(
+            "  {} {} (crc {:08x})",
+            save_mod.name, save_mod.version, save_mod.crc
+        );
+    }
+    match &header.startup_settings {
+        Some(settings) => println!(
+            "Startup settings:\n{}",
+            serde_json::to_string_pretty(&settings.to_json())?
+        ),
+        None => println!("Startup settings:  (not readable in this save format)"),
+    }
+    Ok(())
+}
@@ -2,24 +2,44 @@
 //!
 //! Parses CLI arguments, sets up logging, and dispatches to subcommands.
 
+mod analyze;
 mod benchmark;
+mod bisect;
 mod blueprint;
+mod clean;
+mod compare;
+mod completions;
 mod core;
+mod inspect;
+mod merge;
+mod metrics;
+mod migrate;
 mod sanitize;
+mod self_test;
+mod settings;
+mod stats;
+mod verify;
 
+use crate::benchmark::parser::TickWindow;
 use crate::core::{
-    GlobalConfig, Result, RunOrder,
-    config::{self, BenchmarkConfig, BlueprintConfig, SanitizeConfig},
+    CachePolicy, Downsample, GlobalConfig, NameCase, Result, RunOrder, SortBy, TickRange,
+    UpsDefinition,
+    config::{self, BenchmarkConfig, BlueprintConfig, RetentionConfig, SanitizeConfig},
     error::BenchmarkErrorKind,
+    output::{raw_log, report},
+    strict,
 };
 use clap::{CommandFactory, Parser, Subcommand};
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
     sync::{
         Arc,
         atomic::{AtomicBool, Ordering},
     },
+    time::Duration,
 };
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[derive(Parser)]
 #[command(name = "belt")]
@@ -44,6 +64,14 @@ struct Cli {
     )]
     verbose: bool,
 
+    #[arg(
+        long,
+        global = true,
+        help_heading = "Global Options",
+        help = "Name of this machine in the results [default: the host name]"
+    )]
+    machine: Option<String>,
+
     #[arg(
         long,
         global = true,
@@ -75,6 +103,8 @@ struct Cli {
     headless: bool,
 }
 
+// Parsed once per invocation, so the size of the benchmark variant doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     #[command(next_help_heading = "Benchmark Options")]
@@ -86,6 +116,28 @@ enum Commands {
         #[arg(long, help = "Number of ticks to run each benchmark")]
         ticks: Option<u32>,
 
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Run N ticks before the measured ticks and exclude them from the results"
+        )]
+        settle_ticks: Option<u32>,
+
+        #[arg(
+            long,
+            value_name = "START..END",
+            help = "Only count measured ticks START to END (exclusive) in the results and verbose CSVs"
+        )]
+        tick_range: Option<TickRange>,
+
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u32).range(1..),
+            help = "Split every run into segments of N ticks and report their tick times separately, to show drift within long runs"
+        )]
+        segment_ticks: Option<u32>,
+
         #[arg(long, help = "Number of benchmark runs per save file")]
         runs: Option<u32>,
 
@@ -98,6 +150,13 @@ enum Commands {
         #[arg(long, help = "Path to handlebars report template")]
         template_path: Option<PathBuf>,
 
+        #[arg(
+            long = "var",
+            value_name = "KEY=VALUE",
+            help = "Variable for a custom report template, rendered as {{vars.KEY}} (repeatable)"
+        )]
+        vars: Vec<String>,
+
         #[arg(long, help = "Directory containing mods to use")]
         mods_dir: Option<PathBuf>,
 
@@ -110,13 +169,43 @@ enum Commands {
         #[arg(
             long,
             value_delimiter = ',',
-            help = "Export per-tick CSV data for specified Factorio benchmark metrics (e.g., 'wholeUpdate,gameUpdate'). Use 'all' to export all metrics."
+            help = "Export per-tick CSV data for specified Factorio benchmark metrics (e.g., 'wholeUpdate,gameUpdate' or '*Update'). Use 'all' to export all metrics."
         )]
         verbose_metrics: Option<Vec<String>>,
 
+        #[arg(
+            long,
+            value_name = "NAME=EXPR",
+            help = "Export a metric computed from the verbose metrics, e.g. 'gameUpdateOther=gameUpdate - entityUpdate' (repeatable)"
+        )]
+        derive: Vec<String>,
+
         #[arg(long, help = "Prefix to strip from save file names in output")]
         strip_prefix: Option<String>,
 
+        #[arg(long, help = "Suffix to strip from save file names in output")]
+        strip_suffix: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "PATTERN=>REPLACEMENT",
+            help = "Regex replacement applied to save names in output; repeat to apply several in order"
+        )]
+        name_replace: Option<Vec<String>>,
+
+        #[arg(
+            long,
+            help = "Case mapping of save names in output: asis, lower, upper, title"
+        )]
+        name_case: Option<NameCase>,
+
+        #[arg(
+            long,
+            value_name = "REGEX",
+            help = "Group saves into families by the first capture group of this regex in their display name, e.g. '^(.+)-v[0-9]+$'"
+        )]
+        group_regex: Option<String>,
+
         #[arg(long, help = "Record CPU frequency data during benchmark runs")]
         record_cpu: bool,
 
@@ -126,6 +215,259 @@ enum Commands {
             long_help = "Append benchmark rows to existing output CSV files. Existing CSV headers must match the current output format and selected verbose metrics. Reports are regenerated from available CSV data, so details not stored in results.csv may not be preserved."
         )]
         append: bool,
+
+        #[arg(
+            long,
+            help = "Page-cache handling before each run: warm (pre-read save), cold (drop save from cache), or asis"
+        )]
+        cache_policy: Option<CachePolicy>,
+
+        #[arg(
+            long,
+            help = "Order of the saves in results.csv and the report: ups (slowest first), name, or input-order [default: results by UPS, report by name]"
+        )]
+        sort_by: Option<SortBy>,
+
+        #[arg(
+            long,
+            help = "How effective UPS is computed: total (ticks / total execution time), average (1000 / avg_ms) or capped (total, capped at 60)"
+        )]
+        ups_definition: Option<UpsDefinition>,
+
+        #[arg(
+            long,
+            value_name = "PERCENT",
+            help = "Stop running a save once its 95% confidence interval differs from the baseline (first save) by more than this percentage"
+        )]
+        stop_early_margin: Option<f64>,
+
+        #[arg(
+            long,
+            value_parser = core::parse_duration,
+            help = "Time to spend per save (e.g. '30m'); the runs count per save is derived from the first run's duration"
+        )]
+        time_budget: Option<Duration>,
+
+        #[arg(
+            long,
+            help = "Ticks to run scenario folders for before saving the map that gets benchmarked [default: 60]"
+        )]
+        scenario_ticks: Option<u32>,
+
+        #[arg(
+            long,
+            help = "Run this many Factorio instances at once, each pinned to its own cores with its own write directory"
+        )]
+        jobs: Option<u32>,
+
+        #[arg(
+            long,
+            value_name = "NODE",
+            help = "Bind Factorio's cores and memory to this NUMA node (Linux only)"
+        )]
+        numa_node: Option<u32>,
+
+        #[arg(
+            long,
+            value_name = "CORES",
+            help = "Pin Factorio to these CPU cores, e.g. 0-7,16 (Linux and Windows)"
+        )]
+        cpu_affinity: Option<String>,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Only benchmark saves with one of these tags in the saves directory's saves.toml"
+        )]
+        tags: Option<Vec<String>>,
+
+        #[arg(
+            long,
+            value_name = "COMMAND",
+            help = "Shell command run once per save before its first run, e.g. \"./prepare.sh {save}\"; a failure skips that save"
+        )]
+        pre_save_hook: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "COMMAND",
+            help = "Shell command run after every run, with {save} and {run} substituted; a failure skips the save's remaining runs"
+        )]
+        post_run_hook: Option<String>,
+
+        #[arg(
+            long,
+            alias = "preflight",
+            help = "Run each save once with the belt-sanitizer mod before benchmarking and add its findings to the report"
+        )]
+        sanitize_check: bool,
+
+        #[arg(
+            long,
+            help = "Skip saves whose sanitize check found benchmark-affecting issues (implies --sanitize-check)"
+        )]
+        skip_sanitize_issues: bool,
+
+        #[arg(
+            long,
+            help = "Attribute each save's tick time to its planets and platforms by entity count (implies --sanitize-check)"
+        )]
+        surface_stats: bool,
+
+        #[arg(
+            long,
+            help = "Record the belt-sanitizer mod's event markers (train departures, rocket launches) and line them up with the tick times in the report (implies --verbose-metrics wholeUpdate)"
+        )]
+        markers: bool,
+
+        #[arg(
+            long,
+            help = "List every individual run per save in the report (collapsible in HTML reports)"
+        )]
+        report_runs: bool,
+
+        #[arg(
+            long,
+            help = "Run even if another BELT session holds the output or mods directory, taking over its lock"
+        )]
+        force: bool,
+
+        #[arg(
+            long,
+            help = "Also write <save>_metric_summary.csv with the mean, median, p95, p99, stddev and max of every verbose metric over all ticks and runs"
+        )]
+        metric_summary: bool,
+
+        #[arg(
+            long,
+            help = "Write chartdata.json with the aggregates of every save and its verbose metrics downsampled per tick, for embedding charts in websites"
+        )]
+        chart_data: bool,
+
+        #[arg(
+            long,
+            help = "How chartdata.json reduces long per-tick series: lttb keeps the ticks that best preserve their shape, minmax adds each bucket's minimum and maximum, mean averages consecutive ticks"
+        )]
+        downsample: Option<Downsample>,
+
+        #[arg(
+            long,
+            help = "Write metrics.prom with the UPS and tick times of every save and run in the Prometheus text format"
+        )]
+        prometheus: bool,
+
+        #[arg(
+            long,
+            value_name = "URL",
+            help = "Push metrics.prom to a Pushgateway, e.g. http://localhost:9091/metrics/job/belt; implies --prometheus"
+        )]
+        push_url: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "MS",
+            help = "Flag saves whose mean tick time is below this as measuring engine overhead rather than the factory; 0 disables [default: 1]"
+        )]
+        min_workload_ms: Option<f64>,
+
+        #[arg(
+            long,
+            help = "Replace local paths, the user name and the host name in the report and journal, for sharing results publicly"
+        )]
+        anonymize: bool,
+
+        #[arg(
+            long,
+            help = "Store each run's complete Factorio output, zstd-compressed, in raw_logs/ inside the output directory"
+        )]
+        save_raw_logs: bool,
+
+        #[arg(
+            long,
+            help = "Reuse cached runs of saves whose contents, Factorio build, ticks and mods didn't change"
+        )]
+        use_cache: bool,
+
+        #[arg(
+            long,
+            value_name = "PERCENT",
+            help = "Flag runs during which other processes used more than this percentage of all CPU cores"
+        )]
+        background_load_threshold: Option<f64>,
+
+        #[arg(
+            long,
+            help = "Retry runs flagged by --background-load-threshold up to this many times [default: 0]"
+        )]
+        background_load_retries: Option<u32>,
+
+        #[arg(
+            long,
+            help = "Set the CPU governor to performance and disable turbo for the session, restoring them afterwards (Linux, needs root)"
+        )]
+        lock_frequency: bool,
+
+        #[arg(
+            long,
+            help = "Abort the session on the first warning, e.g. an unknown verbose metric or missing per-tick timings"
+        )]
+        strict: bool,
+
+        #[arg(
+            long,
+            help = "Abort before the first run if the saves were saved with different mods or mod versions"
+        )]
+        strict_mods: bool,
+
+        #[arg(
+            long,
+            num_args = 2,
+            value_names = ["MODS_DIR_A", "MODS_DIR_B"],
+            help = "Benchmark every save with both mods directories in alternating runs and report the UPS change of B against A, paired by run and tested for significance"
+        )]
+        ab_mods: Option<Vec<PathBuf>>,
+
+        #[arg(
+            long,
+            help = "Let Factorio make all runs of a save in one process with --benchmark-runs, loading the save only once. Faster, but the runs of a save are no longer interleaved with other saves"
+        )]
+        native_runs: bool,
+
+        #[arg(
+            long,
+            help = "Fail the session on a save newer than the Factorio binary instead of skipping it"
+        )]
+        abort_on_newer_saves: bool,
+
+        #[arg(
+            long,
+            help = "Leave ticks dominated by chart refreshes and CRC computations out of the results"
+        )]
+        exclude_interference: bool,
+
+        #[arg(
+            long,
+            help = "Decimal places of millisecond values in the report [default: 3]"
+        )]
+        ms_decimals: Option<usize>,
+
+        #[arg(long, help = "Decimal places of UPS values in the report [default: 0]")]
+        ups_decimals: Option<usize>,
+
+        #[arg(
+            long,
+            help = "Decimal places of percentages in the report [default: 2]"
+        )]
+        percent_decimals: Option<usize>,
+
+        #[arg(
+            long,
+            help = "Separator between groups of thousands in report numbers, e.g. ','"
+        )]
+        thousands_separator: Option<char>,
+
+        #[arg(long, help = "Decimal separator of report numbers [default: .]")]
+        decimal_separator: Option<char>,
     },
     #[command(next_help_heading = "Blueprint Options")]
     Blueprint {
@@ -161,6 +503,12 @@ enum Commands {
 
         #[arg(long, help = "Number of construction bots to use")]
         bot_count: Option<u32>,
+
+        #[arg(
+            long,
+            help = "Benchmark the generated saves with the [benchmark] config settings and write the results to --output"
+        )]
+        benchmark: bool,
     },
     #[command(next_help_heading = "Sanitize Options")]
     Sanitize {
@@ -188,6 +536,291 @@ enum Commands {
             help = "Fluids to preserve during sanitization (comma-separated)"
         )]
         fluids: Option<String>,
+
+        #[arg(long, help = "Report entity counts per planet and space platform")]
+        surface_stats: bool,
+
+        #[arg(
+            long,
+            help = "Load each save as a headless server and query it over RCON instead of running the belt-sanitizer mod; reports no production statistics"
+        )]
+        rcon: bool,
+    },
+    #[command(next_help_heading = "Metrics Options")]
+    /// List the verbose metrics Factorio reports, for use with --verbose-metrics
+    Metrics {
+        /// Save file to run a 1-tick verbose benchmark on
+        #[arg(required_unless_present = "log")]
+        save_file: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Read the metrics from an existing verbose benchmark log instead"
+        )]
+        log: Option<PathBuf>,
+
+        #[arg(long, help = "Directory containing mods to use")]
+        mods_dir: Option<PathBuf>,
+    },
+    #[command(next_help_heading = "Analyze Options")]
+    /// Regenerate results from logs archived with --save-raw-logs, using the current parser
+    Analyze {
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Output directory of a session run with --save-raw-logs, or its raw_logs directory"
+        )]
+        from_raw: PathBuf,
+
+        #[arg(
+            long,
+            help = "Directory to write results.csv to [default: the session's output directory]"
+        )]
+        output: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "How effective UPS is computed: total, average or capped [default: total]"
+        )]
+        ups_definition: Option<UpsDefinition>,
+
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Settle ticks the session was run with, excluded from the results [default: 0]"
+        )]
+        settle_ticks: Option<u32>,
+
+        #[arg(
+            long,
+            value_name = "START..END",
+            help = "Only count measured ticks START to END (exclusive)"
+        )]
+        tick_range: Option<TickRange>,
+    },
+    #[command(next_help_heading = "Merge Options")]
+    /// Merge the results of several sessions, deduplicating runs and regenerating the report
+    Merge {
+        /// Output directories of the sessions to merge
+        #[arg(value_name = "DIR", required = true)]
+        dirs: Vec<PathBuf>,
+
+        #[arg(
+            long,
+            alias = "out",
+            help = "Directory to write the merged results and report to"
+        )]
+        output: PathBuf,
+    },
+    /// Upgrade the results.csv and verbose metrics CSVs of an older BELT version in place
+    Migrate {
+        /// Output directory of a benchmark session
+        #[arg(value_name = "DIR")]
+        dir: PathBuf,
+    },
+    #[command(next_help_heading = "Bisect Options")]
+    /// Find the Factorio version that introduced a UPS regression on a save
+    Bisect {
+        #[arg(long, help = "Last version known to be fast, e.g. 2.0.50")]
+        good: core::versions::FactorioVersion,
+
+        #[arg(long, help = "Version known to be slow, e.g. 2.0.55")]
+        bad: core::versions::FactorioVersion,
+
+        #[arg(long, help = "Save file to benchmark on every version")]
+        save: PathBuf,
+
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Directory with one Factorio installation per version, e.g. factorio_2.0.50/"
+        )]
+        versions_dir: PathBuf,
+
+        #[arg(
+            long,
+            value_parser = core::parse_percent,
+            default_value = "3%",
+            help = "UPS drop from the good version that counts as a regression"
+        )]
+        threshold: f64,
+
+        #[arg(long, help = "Number of ticks to run each benchmark")]
+        ticks: Option<u32>,
+
+        #[arg(long, help = "Number of runs per version")]
+        runs: Option<u32>,
+
+        #[arg(long, help = "Directory containing mods to use")]
+        mods_dir: Option<PathBuf>,
+
+        #[arg(long, help = "Directory to write bisect.csv to")]
+        output: Option<PathBuf>,
+    },
+    /// Inspect or edit Factorio's mod-settings.dat
+    Settings {
+        #[command(subcommand)]
+        command: SettingsCommands,
+    },
+    /// Work with report templates
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommands,
+    },
+    /// List, or remove, the session outputs and cached runs the retention policy expires
+    Clean {
+        #[arg(
+            long,
+            help = "Remove what the retention policy expires instead of listing it"
+        )]
+        apply_retention: bool,
+
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Directory holding one output directory per session"
+        )]
+        outputs_dir: Option<PathBuf>,
+
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Number of most recent sessions to keep"
+        )]
+        keep_last: Option<usize>,
+
+        #[arg(
+            long,
+            value_name = "GB",
+            help = "Total size the sessions and cached runs may take up"
+        )]
+        max_size_gb: Option<f64>,
+    },
+    /// Summarize the benchmarking history in the session output directories: time spent, runs
+    /// executed and the most benchmarked saves. Nothing leaves the machine.
+    Stats {
+        /// Directory holding one output directory per session, or a single session's; defaults
+        /// to `outputs_dir` of the `[retention]` config
+        #[arg(value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+    /// Print what a save was made with, read from its header: the Factorio version, the
+    /// scenario, its mods with their versions and the startup settings
+    Inspect {
+        /// Path to the save
+        #[arg(value_name = "SAVE")]
+        save: PathBuf,
+    },
+    /// Check an output directory for missing, modified or inconsistent files before publishing it
+    Verify {
+        /// Output directory of a benchmark session
+        #[arg(value_name = "DIR")]
+        dir: PathBuf,
+    },
+    #[command(next_help_heading = "Compare Options")]
+    /// Compare a session's results against a stored baseline, failing if any save regressed
+    Compare {
+        /// Output directory or results.csv of the baseline
+        #[arg(value_name = "BASELINE")]
+        baseline: PathBuf,
+
+        /// Output directory or results.csv to compare against the baseline
+        #[arg(value_name = "CANDIDATE")]
+        candidate: PathBuf,
+
+        #[arg(
+            long,
+            value_parser = core::parse_percent,
+            default_value = "3%",
+            help = "UPS drop from the baseline that counts as a regression"
+        )]
+        threshold: f64,
+    },
+    #[command(next_help_heading = "Self-test Options")]
+    /// Time BELT's own parsing, CSV writing and chart data on generated fixture data
+    SelfTest {
+        #[arg(
+            long,
+            default_value_t = 20000,
+            help = "Ticks per run of the fixture data"
+        )]
+        ticks: u32,
+
+        #[arg(
+            long,
+            default_value_t = 3,
+            help = "Times to process the fixture data, keeping the fastest"
+        )]
+        iterations: u32,
+    },
+    /// Print a completion script, e.g. `belt completions bash > ~/.local/share/bash-completion/completions/belt`
+    Completions {
+        /// Shell to complete in: bash, zsh or fish
+        #[arg(value_name = "SHELL")]
+        shell: completions::Shell,
+    },
+    /// Print the candidates for a word of a command line; called by the completion scripts
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// Position of the word to complete
+        #[arg(long)]
+        index: usize,
+
+        /// The command line, starting with `belt`
+        #[arg(last = true)]
+        words: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SettingsCommands {
+    /// Print every setting of a mod-settings.dat as JSON
+    Dump {
+        /// Path to the mod-settings.dat
+        #[arg(value_name = "DAT")]
+        file: PathBuf,
+
+        #[arg(
+            long,
+            help = "Print the raw property tree, e.g. of other files in the same format"
+        )]
+        raw: bool,
+    },
+    /// List the settings that differ between two mod-settings.dat files
+    Diff {
+        #[arg(value_name = "A")]
+        left: PathBuf,
+
+        #[arg(value_name = "B")]
+        right: PathBuf,
+    },
+    /// Change settings of a mod-settings.dat in place, e.g. startup.some-setting=10
+    Set {
+        /// Path to the mod-settings.dat
+        #[arg(value_name = "DAT")]
+        file: PathBuf,
+
+        /// Settings to change, as SCOPE.SETTING=VALUE
+        #[arg(value_name = "ASSIGNMENT", required = true)]
+        assignments: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplateCommands {
+    /// Render a report template against sample results, reporting syntax errors and variables
+    /// the report doesn't provide
+    Check {
+        /// Path to the Handlebars template
+        #[arg(value_name = "TEMPLATE")]
+        template: PathBuf,
+
+        #[arg(
+            long = "var",
+            value_name = "KEY=VALUE",
+            help = "Variable the template renders as {{vars.KEY}} (repeatable)"
+        )]
+        vars: Vec<String>,
     },
 }
 
@@ -239,16 +872,17 @@ async fn main() -> Result<()> {
         })
     };
 
-    // Toggle the tracing level
-    if cli.verbose {
-        tracing_subscriber::fmt()
-            .with_max_level(tracing::Level::DEBUG)
-            .init();
+    // Toggle the tracing level; warnings are also seen by --strict
+    let level = if cli.verbose {
+        tracing::Level::DEBUG
     } else {
-        tracing_subscriber::fmt()
-            .with_max_level(tracing::Level::INFO)
-            .init();
-    }
+        tracing::Level::INFO
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .finish()
+        .with(strict::StrictLayer)
+        .init();
 
     // Build global config: config file -> env vars -> CLI args
     let mut global_config = GlobalConfig::from_figment(&figment).unwrap_or_default();
@@ -258,20 +892,27 @@ async fn main() -> Result<()> {
     if cli.verbose {
         global_config.verbose = cli.verbose;
     }
+    if cli.machine.is_some() {
+        global_config.machine = cli.machine;
+    }
 
     // Listen to CTRL+C
     let needs_shutdown = matches!(
         &command,
-        Commands::Benchmark { .. } | Commands::Sanitize { .. } | Commands::Blueprint { .. }
+        Commands::Benchmark { .. }
+            | Commands::Sanitize { .. }
+            | Commands::Blueprint { .. }
+            | Commands::Bisect { .. }
     );
     let running = Arc::new(AtomicBool::new(true));
     let shutdown_task = if needs_shutdown {
+        core::platform::kill_children_on_exit();
         let r = running.clone();
         Some(tokio::spawn(async move {
-            if let Err(e) = tokio::signal::ctrl_c().await {
-                tracing::warn!("Failed to listen for CTRL+C: {e}");
+            if let Err(e) = core::platform::shutdown_signal().await {
+                tracing::warn!("Failed to listen for shutdown signals: {e}");
             }
-            tracing::info!("Received CTRL+C. Initiating graceful shutdown...");
+            tracing::info!("Received a shutdown signal. Initiating graceful shutdown...");
             r.store(false, Ordering::SeqCst);
         }))
     } else {
@@ -283,16 +924,66 @@ async fn main() -> Result<()> {
         Commands::Benchmark {
             saves_dir,
             ticks,
+            settle_ticks,
+            tick_range,
+            segment_ticks,
             runs,
             pattern,
             output,
             template_path,
+            vars,
             mods_dir,
             run_order,
             verbose_metrics,
+            derive,
             strip_prefix,
+            strip_suffix,
+            name_replace,
+            name_case,
+            group_regex,
             record_cpu,
             append,
+            cache_policy,
+            sort_by,
+            ups_definition,
+            stop_early_margin,
+            time_budget,
+            scenario_ticks,
+            tags,
+            pre_save_hook,
+            post_run_hook,
+            sanitize_check,
+            skip_sanitize_issues,
+            surface_stats,
+            markers,
+            report_runs,
+            force,
+            metric_summary,
+            chart_data,
+            downsample,
+            prometheus,
+            push_url,
+            min_workload_ms,
+            anonymize,
+            save_raw_logs,
+            use_cache,
+            background_load_threshold,
+            background_load_retries,
+            lock_frequency,
+            strict,
+            strict_mods,
+            ab_mods,
+            native_runs,
+            abort_on_newer_saves,
+            exclude_interference,
+            jobs,
+            numa_node,
+            cpu_affinity,
+            ms_decimals,
+            ups_decimals,
+            percent_decimals,
+            thousands_separator,
+            decimal_separator,
         } => {
             async {
                 let mut benchmark_config =
@@ -307,6 +998,15 @@ async fn main() -> Result<()> {
                 if let Some(v) = ticks {
                     benchmark_config.ticks = v;
                 }
+                if let Some(v) = settle_ticks {
+                    benchmark_config.settle_ticks = v;
+                }
+                if let Some(v) = tick_range {
+                    benchmark_config.tick_range = Some(v);
+                }
+                if let Some(v) = segment_ticks {
+                    benchmark_config.segment_ticks = Some(v);
+                }
                 if let Some(v) = runs {
                     benchmark_config.runs = v;
                 }
@@ -319,6 +1019,7 @@ async fn main() -> Result<()> {
                 if let Some(v) = template_path {
                     benchmark_config.template_path = Some(v);
                 }
+                benchmark_config.extra_vars.extend(template_vars(&vars)?);
                 if let Some(v) = mods_dir {
                     benchmark_config.mods_dir = Some(v);
                 }
@@ -328,15 +1029,161 @@ async fn main() -> Result<()> {
                 if let Some(v) = verbose_metrics {
                     benchmark_config.verbose_metrics = v;
                 }
+                for definition in derive {
+                    let (name, expression) = definition.split_once('=').ok_or_else(|| {
+                        BenchmarkErrorKind::InvalidDerivedMetric {
+                            name: definition.clone(),
+                            reason: "expected NAME=EXPR".to_string(),
+                        }
+                    })?;
+                    benchmark_config
+                        .derived_metrics
+                        .insert(name.trim().to_string(), expression.to_string());
+                }
                 if let Some(v) = strip_prefix {
                     benchmark_config.strip_prefix = Some(v);
                 }
+                if let Some(v) = strip_suffix {
+                    benchmark_config.strip_suffix = Some(v);
+                }
+                if let Some(v) = name_replace {
+                    benchmark_config.name_replace = v;
+                }
+                if let Some(v) = name_case {
+                    benchmark_config.name_case = v;
+                }
+                if let Some(v) = group_regex {
+                    benchmark_config.group_regex = Some(v);
+                }
                 if cli.headless {
                     benchmark_config.headless = true;
                 }
                 if record_cpu {
                     benchmark_config.record_cpu = true;
                 }
+                if let Some(v) = cache_policy {
+                    benchmark_config.cache_policy = v;
+                }
+                if let Some(v) = sort_by {
+                    benchmark_config.sort_by = Some(v);
+                }
+                if let Some(v) = ups_definition {
+                    benchmark_config.ups_definition = v;
+                }
+                if let Some(v) = stop_early_margin {
+                    benchmark_config.stop_early_margin = Some(v);
+                }
+                if let Some(v) = time_budget {
+                    benchmark_config.time_budget = Some(v);
+                }
+                if let Some(v) = scenario_ticks {
+                    benchmark_config.scenario_ticks = v;
+                }
+                if let Some(v) = tags {
+                    benchmark_config.tags = v;
+                }
+                if let Some(v) = pre_save_hook {
+                    benchmark_config.pre_save_hook = Some(v);
+                }
+                if let Some(v) = post_run_hook {
+                    benchmark_config.post_run_hook = Some(v);
+                }
+                if sanitize_check {
+                    benchmark_config.sanitize_check = true;
+                }
+                if skip_sanitize_issues {
+                    benchmark_config.skip_sanitize_issues = true;
+                }
+                if surface_stats {
+                    benchmark_config.surface_stats = true;
+                }
+                if markers {
+                    benchmark_config.markers = true;
+                }
+                if report_runs {
+                    benchmark_config.report_runs = true;
+                }
+                if force {
+                    benchmark_config.force = true;
+                }
+                if metric_summary {
+                    benchmark_config.metric_summary = true;
+                }
+                if chart_data {
+                    benchmark_config.chart_data = true;
+                }
+                if let Some(v) = downsample {
+                    benchmark_config.downsample = v;
+                }
+                if prometheus {
+                    benchmark_config.prometheus = true;
+                }
+                if let Some(v) = push_url {
+                    benchmark_config.push_url = Some(v);
+                }
+                if let Some(v) = min_workload_ms {
+                    benchmark_config.min_workload_ms = v;
+                }
+                if anonymize {
+                    benchmark_config.anonymize = true;
+                }
+                if save_raw_logs {
+                    benchmark_config.save_raw_logs = true;
+                }
+                if use_cache {
+                    benchmark_config.use_cache = true;
+                }
+                if let Some(v) = background_load_threshold {
+                    benchmark_config.background_load_threshold = Some(v);
+                }
+                if let Some(v) = background_load_retries {
+                    benchmark_config.background_load_retries = v;
+                }
+                if lock_frequency {
+                    benchmark_config.lock_frequency = true;
+                }
+                if strict {
+                    benchmark_config.strict = true;
+                }
+                if strict_mods {
+                    benchmark_config.strict_mods = true;
+                }
+                if let Some(v) = ab_mods.and_then(|dirs| <[PathBuf; 2]>::try_from(dirs).ok()) {
+                    benchmark_config.ab_mods = Some(v);
+                }
+                if native_runs {
+                    benchmark_config.native_runs = true;
+                }
+                if abort_on_newer_saves {
+                    benchmark_config.abort_on_newer_saves = true;
+                }
+                if exclude_interference {
+                    benchmark_config.exclude_interference = true;
+                }
+                if let Some(v) = jobs {
+                    benchmark_config.jobs = v;
+                }
+                if let Some(v) = numa_node {
+                    benchmark_config.numa_node = Some(v);
+                }
+                if let Some(v) = cpu_affinity {
+                    benchmark_config.cpu_affinity = Some(v);
+                }
+                if let Some(v) = ms_decimals {
+                    benchmark_config.ms_decimals = Some(v);
+                }
+                if let Some(v) = ups_decimals {
+                    benchmark_config.ups_decimals = Some(v);
+                }
+                if let Some(v) = percent_decimals {
+                    benchmark_config.percent_decimals = Some(v);
+                }
+                if let Some(v) = thousands_separator {
+                    benchmark_config.thousands_separator = Some(v);
+                }
+                if let Some(v) = decimal_separator {
+                    benchmark_config.decimal_separator = Some(v);
+                }
 
                 benchmark::run(global_config, benchmark_config, &running).await
             }
@@ -355,6 +1202,7 @@ async fn main() -> Result<()> {
             output,
             prefix,
             bot_count,
+            benchmark,
         } => {
             let mut blueprint_config = BlueprintConfig::from_figment(&figment).unwrap_or_default();
             blueprint_config.blueprints_dir = blueprints_dir;
@@ -385,7 +1233,19 @@ async fn main() -> Result<()> {
             if let Some(v) = bot_count {
                 blueprint_config.bot_count = Some(v);
             }
-            blueprint::run(global_config, blueprint_config, &running).await
+            if benchmark {
+                blueprint_config.benchmark = true;
+            }
+            let save_benchmark_config = blueprint_config
+                .benchmark
+                .then(|| BenchmarkConfig::from_figment(&figment).unwrap_or_default());
+            blueprint::run(
+                global_config,
+                blueprint_config,
+                save_benchmark_config,
+                &running,
+            )
+            .await
         }
 
         Commands::Sanitize {
@@ -396,6 +1256,8 @@ async fn main() -> Result<()> {
             data_dir,
             items,
             fluids,
+            surface_stats,
+            rcon,
         } => {
             async {
                 let mut sanitize_config =
@@ -423,6 +1285,12 @@ async fn main() -> Result<()> {
                 if let Some(v) = fluids {
                     sanitize_config.fluids = Some(v);
                 }
+                if surface_stats {
+                    sanitize_config.surface_stats = true;
+                }
+                if rcon {
+                    sanitize_config.rcon = true;
+                }
                 if cli.headless {
                     sanitize_config.headless = true;
                 }
@@ -430,6 +1298,168 @@ async fn main() -> Result<()> {
             }
             .await
         }
+
+        Commands::Metrics {
+            save_file,
+            log,
+            mods_dir,
+        } => {
+            let source = match (log.as_deref(), save_file.as_deref()) {
+                (Some(log), _) => metrics::MetricsSource::Log(log),
+                (None, Some(save_file)) => metrics::MetricsSource::Save {
+                    save_file,
+                    mods_dir: mods_dir.as_deref(),
+                    headless: cli.headless,
+                },
+                (None, None) => unreachable!("clap requires SAVE_FILE unless --log is given"),
+            };
+            metrics::run(global_config, source).await
+        }
+
+        Commands::Analyze {
+            from_raw,
+            output,
+            ups_definition,
+            settle_ticks,
+            tick_range,
+        } => {
+            // Default to the session directory, next to the raw_logs directory
+            let output = output.unwrap_or_else(|| {
+                if from_raw.ends_with(raw_log::RAW_LOGS_DIR) {
+                    from_raw.parent().unwrap_or(&from_raw).to_path_buf()
+                } else {
+                    from_raw.clone()
+                }
+            });
+            // Raw logs may come from another computer, so only an explicit name labels them
+            analyze::from_raw(
+                &from_raw,
+                &output,
+                ups_definition.unwrap_or_default(),
+                TickWindow {
+                    settle_ticks: settle_ticks.unwrap_or(0),
+                    range: tick_range,
+                },
+                global_config.machine.as_deref(),
+            )
+        }
+
+        Commands::Merge { dirs, output } => merge::run(&dirs, &output),
+
+        Commands::Migrate { dir } => migrate::run(&dir),
+
+        Commands::Bisect {
+            good,
+            bad,
+            save,
+            versions_dir,
+            threshold,
+            ticks,
+            runs,
+            mods_dir,
+            output,
+        } => {
+            let mut benchmark_config = BenchmarkConfig::from_figment(&figment).unwrap_or_default();
+            if let Some(v) = ticks {
+                benchmark_config.ticks = v;
+            }
+            if let Some(v) = runs {
+                benchmark_config.runs = v;
+            }
+            if let Some(v) = mods_dir {
+                benchmark_config.mods_dir = Some(v);
+            }
+            if let Some(v) = output {
+                benchmark_config.output = Some(v);
+            }
+            if cli.headless {
+                benchmark_config.headless = true;
+            }
+            let options = bisect::BisectOptions {
+                good,
+                bad,
+                save,
+                versions_dir,
+                threshold_percent: threshold,
+            };
+            bisect::run(benchmark_config, options, &running).await
+        }
+
+        Commands::Settings { command } => match command {
+            SettingsCommands::Dump { file, raw } => settings::dump(&file, raw),
+            SettingsCommands::Diff { left, right } => settings::diff(&left, &right),
+            SettingsCommands::Set { file, assignments } => settings::set(&file, &assignments),
+        },
+
+        Commands::Template { command } => match command {
+            TemplateCommands::Check { template, vars } => {
+                report::check_template(&template, &template_vars(&vars)?)?;
+                println!("{} renders with every report variable", template.display());
+                Ok(())
+            }
+        },
+
+        Commands::Clean {
+            apply_retention,
+            outputs_dir,
+            keep_last,
+            max_size_gb,
+        } => {
+            let mut retention_config = RetentionConfig::from_figment(&figment).unwrap_or_default();
+            if let Some(v) = outputs_dir {
+                retention_config.outputs_dir = Some(v);
+            }
+            if let Some(v) = keep_last {
+                retention_config.keep_last = Some(v);
+            }
+            if let Some(v) = max_size_gb {
+                retention_config.max_size_gb = Some(v);
+            }
+            clean::run(&retention_config, apply_retention)
+        }
+
+        Commands::Stats { dir } => {
+            let dir = dir
+                .or_else(|| {
+                    RetentionConfig::from_figment(&figment)
+                        .ok()
+                        .and_then(|retention_config| retention_config.outputs_dir)
+                })
+                .ok_or(BenchmarkErrorKind::NoOutputsDirectory)?;
+            stats::run(&dir)
+        }
+
+        Commands::Inspect { save } => inspect::run(&save),
+
+        Commands::Verify { dir } => verify::run(&dir),
+
+        Commands::Compare {
+            baseline,
+            candidate,
+            threshold,
+        } => compare::run(&baseline, &candidate, threshold),
+
+        Commands::SelfTest { ticks, iterations } => self_test::run(ticks, iterations),
+
+        Commands::Completions { shell } => {
+            print!("{}", completions::script(shell));
+            Ok(())
+        }
+
+        Commands::Complete { index, words } => {
+            let saves_dir = |subcommand: &str| {
+                let saves_dir = match subcommand {
+                    "benchmark" => BenchmarkConfig::from_figment(&figment).ok()?.saves_dir,
+                    "sanitize" => SanitizeConfig::from_figment(&figment).ok()?.saves_dir,
+                    _ => return None,
+                };
+                (!saves_dir.as_os_str().is_empty()).then_some(saves_dir)
+            };
+            for candidate in completions::complete(&Cli::command(), &words, index, saves_dir) {
+                println!("{candidate}");
+            }
+            Ok(())
+        }
     };
 
     // Await shutdown if needed
@@ -453,6 +1483,25 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// `KEY=VALUE` definitions of `--var` by key
+fn template_vars(definitions: &[String]) -> Result<HashMap<String, String>> {
+    definitions
+        .iter()
+        .map(|definition| {
+            definition
+                .split_once('=')
+                .filter(|(key, _)| !key.trim().is_empty())
+                .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+                .ok_or_else(|| {
+                    BenchmarkErrorKind::InvalidTemplateVar {
+                        input: definition.clone(),
+                    }
+                    .into()
+                })
+        })
+        .collect()
+}
+
 fn require_saves_dir(saves_dir: &Path, section: &str) -> Result<()> {
     if saves_dir.as_os_str().is_empty() {
         return Err(BenchmarkErrorKind::ConfigLoadError(format!(
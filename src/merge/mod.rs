@@ -0,0 +1,161 @@
+//! Merge module
+//!
+//! Combines the `results.csv` files of several sessions, e.g. parts of a comparison run on
+//! different days, into one set of results and a fresh report.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    Result,
+    benchmark::{parser::BenchmarkRun, runner},
+    core::{
+        error::BenchmarkErrorKind,
+        output::{
+            CsvWriter, ReportOptions, WriteData, csv::read_benchmark_csv, report::ReportWriter,
+            write_result,
+        },
+        utils,
+    },
+};
+
+/// Merge the results of `session_dirs` into `output_dir`, writing `results.csv`, `summary.csv`
+/// and the report
+pub fn run(session_dirs: &[PathBuf], output_dir: &Path) -> Result<()> {
+    let mut sessions = Vec::new();
+    for dir in session_dirs {
+        let csv_path = dir.join("results.csv");
+        if !csv_path.is_file() {
+            return Err(BenchmarkErrorKind::ResultsNotFound { path: dir.clone() }.into());
+        }
+        sessions.push(read_benchmark_csv(&csv_path)?);
+    }
+
+    let (mut results, duplicates) = merge_runs(sessions);
+    if duplicates > 0 {
+        tracing::info!("Skipped {duplicates} duplicate run(s)");
+    }
+    utils::calculate_base_differences(&mut results);
+
+    write_result(
+        &CsvWriter::new(),
+        &WriteData::Benchmark(results.clone()),
+        output_dir,
+        false,
+    )?;
+    write_result(
+        &ReportWriter::new(),
+        &WriteData::Report {
+            data: results.clone(),
+            template_path: None,
+            options: ReportOptions::default(),
+        },
+        output_dir,
+        false,
+    )?;
+
+    tracing::info!(
+        "Merged {} runs from {} sessions into {}",
+        results.len(),
+        session_dirs.len(),
+        output_dir.display()
+    );
+    Ok(())
+}
+
+/// Combine the runs of every session and renumber each save's runs per machine in session order
+/// so their indexes don't collide. Runs identical to ones an earlier session already contributed
+/// are dropped, so sessions that overlap (e.g. a directory merged twice or a copy of appended
+/// results) count once, while identical runs within one session are kept. Returns the merged
+/// runs, slowest save first, and the number of duplicates dropped.
+fn merge_runs(sessions: Vec<Vec<BenchmarkRun>>) -> (Vec<BenchmarkRun>, usize) {
+    let mut merged_counts: HashMap<String, usize> = HashMap::new();
+    let mut duplicates = 0;
+    let mut results_map: HashMap<(String, Option<String>), Vec<BenchmarkRun>> = HashMap::new();
+
+    for session in sessions {
+        let mut session_counts: HashMap<String, usize> = HashMap::new();
+        for mut run in session {
+            let identity = run_identity(&run);
+            let session_count = session_counts.entry(identity.clone()).or_default();
+            *session_count += 1;
+            let merged_count = merged_counts.entry(identity).or_default();
+            if *session_count <= *merged_count {
+                duplicates += 1;
+                continue;
+            }
+            *merged_count = *session_count;
+
+            let runs = results_map
+                .entry((run.save_name.clone(), run.machine.clone()))
+                .or_default();
+            run.index = runs.len() as u32;
+            runs.push(run);
+        }
+    }
+
+    (runner::sort_by_performance(results_map), duplicates)
+}
+
+/// Everything measured about a run; the index and base difference depend on the other runs
+fn run_identity(run: &BenchmarkRun) -> String {
+    format!(
+        "{}|{}|{}|{}|{}|{}|{}|{}|{:?}|{}|{:?}|{:?}|{:?}",
+        run.save_name,
+        run.ticks,
+        run.execution_time_ms,
+        run.avg_ms,
+        run.min_ms,
+        run.max_ms,
+        run.effective_ups,
+        run.factorio_version,
+        run.factorio_build,
+        run.platform,
+        run.core_set,
+        run.numa_node,
+        run.machine
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_runs_renumbers_and_deduplicates() {
+        let run = |save_name: &str, index: u32, ups: f64| BenchmarkRun {
+            save_name: save_name.to_string(),
+            index,
+            effective_ups: ups,
+            ..Default::default()
+        };
+
+        let (merged, duplicates) = merge_runs(vec![
+            vec![run("alpha", 0, 100.0), run("alpha", 1, 110.0)],
+            vec![
+                run("alpha", 0, 100.0),
+                run("alpha", 0, 120.0),
+                run("beta", 0, 50.0),
+            ],
+            vec![run("beta", 0, 50.0), run("beta", 1, 50.0)],
+        ]);
+
+        assert_eq!(duplicates, 2);
+        let runs: Vec<(&str, u32, f64)> = merged
+            .iter()
+            .map(|run| (run.save_name.as_str(), run.index, run.effective_ups))
+            .collect();
+        assert_eq!(
+            runs,
+            vec![
+                ("beta", 0, 50.0),
+                ("beta", 1, 50.0),
+                ("alpha", 0, 100.0),
+                ("alpha", 1, 110.0),
+                ("alpha", 2, 120.0)
+            ]
+        );
+    }
+}
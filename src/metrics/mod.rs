@@ -0,0 +1,115 @@
+//! Metrics module
+//!
+//! Lists the verbose metrics a Factorio version reports, for use with `--verbose-metrics`.
+
+use std::path::Path;
+
+use crate::{
+    Result,
+    benchmark::parser,
+    core::{
+        FactorioExecutor, GlobalConfig, error::BenchmarkErrorKind, factorio::FactorioTickRunSpec,
+        metrics::describe_metric,
+    },
+};
+
+/// Where to read the verbose metric header from
+#[derive(Debug, Clone)]
+pub enum MetricsSource<'a> {
+    /// Run a 1-tick verbose benchmark of this save
+    Save {
+        save_file: &'a Path,
+        mods_dir: Option<&'a Path>,
+        headless: bool,
+    },
+    /// Parse an existing verbose benchmark log
+    Log(&'a Path),
+}
+
+/// Print every verbose metric with a short description, either from a 1-tick verbose benchmark
+/// of a save or from an existing verbose benchmark log.
+pub async fn run(global_config: GlobalConfig, source: MetricsSource<'_>) -> Result<()> {
+    let (path, output) = match source {
+        MetricsSource::Log(log) => (log, std::fs::read_to_string(log)?),
+        MetricsSource::Save {
+            save_file,
+            mods_dir,
+            headless,
+        } => {
+            let factorio = FactorioExecutor::discover(global_config.factorio_path)?;
+            tracing::info!(
+                "Using Factorio at: {}",
+                factorio.executable_path().display()
+            );
+
+            if mods_dir.is_none() {
+                factorio.sync_mods_for_save(save_file).await?;
+            }
+
+            let output = factorio
+                .run_for_ticks(FactorioTickRunSpec {
+                    save_file,
+                    ticks: 1,
+                    runs: 1,
+                    mods_dir,
+                    verbose_metrics: Some("all".to_string()),
+                    headless,
+                    record_cpu: false,
+                    sample_background_load: false,
+                })
+                .await?;
+
+            (save_file, output.verbose_data.unwrap_or_default())
+        }
+    };
+
+    let metrics = metric_names_from_log(&output)?.ok_or_else(|| {
+        BenchmarkErrorKind::VerboseHeaderNotFound {
+            path: path.to_path_buf(),
+        }
+    })?;
+
+    let width = metrics.iter().map(String::len).max().unwrap_or(0);
+    for metric in &metrics {
+        let descriptor = describe_metric(metric);
+        let line = format!(
+            "{metric:<width$}  {:<4} {:<15} {}",
+            descriptor.unit.raw_label(),
+            descriptor.category.label(),
+            descriptor.description
+        );
+        println!("{}", line.trim_end());
+    }
+
+    Ok(())
+}
+
+/// Find the verbose CSV header in Factorio output and return its metric names
+fn metric_names_from_log(log: &str) -> Result<Option<Vec<String>>> {
+    log.lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("tick,timestamp,"))
+        .map(parser::verbose_metric_names)
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_names_from_log() {
+        let log = "   0.000 Factorio 2.0.72 (build 84292, linux64, full, space-age)\n\
+                   Performed 1 updates in 1.000 ms\n\
+                   tick,timestamp,wholeUpdate,gameUpdate,Inserter\n\
+                   t0,0,100,90,10\n";
+
+        let metrics = metric_names_from_log(log).unwrap().unwrap();
+        assert_eq!(metrics, vec!["wholeUpdate", "gameUpdate", "Inserter"]);
+        assert!(
+            metric_names_from_log("no verbose output")
+                .unwrap()
+                .is_none()
+        );
+    }
+}
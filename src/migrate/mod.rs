@@ -0,0 +1,289 @@
+//! Migrate module
+//!
+//! Upgrades the `results.csv` and verbose metrics CSVs of an output directory written by an older
+//! version of BELT to the current schema, so `analyze`, `merge` and `verify` keep working on
+//! historical data. Every file that changes is backed up next to it first.
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    Result,
+    core::{
+        error::BenchmarkErrorKind,
+        output::{CsvWriter, WriteData, csv::read_benchmark_csv, write_result},
+        utils,
+    },
+};
+
+/// Suffix of the per-save verbose metrics CSVs
+const VERBOSE_CSV_SUFFIX: &str = "_verbose_metrics.csv";
+
+/// Appended to the name of every file before it's migrated
+const BACKUP_SUFFIX: &str = ".bak";
+
+/// Columns of `results.csv` that were renamed, as (old name, current name)
+const RENAMED_COLUMNS: [(&str, &str); 2] = [
+    ("base_diff", "percentage_improvement"),
+    ("run", "run_index"),
+];
+
+/// Columns of `results.csv` added later, with the value older results get
+const ADDED_COLUMNS: [(&str, &str); 3] = [
+    ("factorio_version", "unknown"),
+    ("platform", "unknown"),
+    ("percentage_improvement", "0"),
+];
+
+/// Migrate the results in `output_dir` in place
+pub fn run(output_dir: &Path) -> Result<()> {
+    let csv_path = output_dir.join("results.csv");
+    if !csv_path.is_file() {
+        return Err(BenchmarkErrorKind::ResultsNotFound {
+            path: output_dir.to_path_buf(),
+        }
+        .into());
+    }
+
+    let mut migrated = 0;
+    if migrate_results_csv(&csv_path)? {
+        migrated += 1;
+    }
+    for verbose_path in verbose_csvs(output_dir)? {
+        if migrate_verbose_csv(&verbose_path)? {
+            migrated += 1;
+        }
+    }
+
+    if migrated == 0 {
+        tracing::info!("{} is already up to date", output_dir.display());
+    } else {
+        tracing::info!(
+            "Migrated {migrated} file(s) in {}; the originals were kept as *{BACKUP_SUFFIX}",
+            output_dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// Rename and add columns, then rewrite the file as the current version writes it. Returns
+/// whether anything changed.
+fn migrate_results_csv(csv_path: &Path) -> Result<bool> {
+    let mut reader = csv::Reader::from_path(csv_path)?;
+    let mut headers: Vec<String> = reader.headers()?.iter().map(str::to_string).collect();
+    let records = reader
+        .records()
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut changes = Vec::new();
+    for (old, current) in RENAMED_COLUMNS {
+        if !headers_contain(&headers, current)
+            && let Some(header) = headers.iter_mut().find(|header| *header == old)
+        {
+            *header = current.to_string();
+            changes.push(format!("renamed {old} to {current}"));
+        }
+    }
+    let added: Vec<(&str, &str)> = ADDED_COLUMNS
+        .into_iter()
+        .filter(|(name, _)| !headers_contain(&headers, name))
+        .collect();
+    for (name, _) in &added {
+        changes.push(format!("added {name}"));
+    }
+    if changes.is_empty() {
+        return Ok(false);
+    }
+
+    backup(csv_path)?;
+    let mut writer = csv::Writer::from_path(csv_path)?;
+    writer.write_record(
+        headers
+            .iter()
+            .map(String::as_str)
+            .chain(added.iter().map(|(name, _)| *name)),
+    )?;
+    for record in &records {
+        writer.write_record(record.iter().chain(added.iter().map(|(_, value)| *value)))?;
+    }
+    writer.flush()?;
+
+    // Parse it like every other command does, and write it back in the current column order
+    let mut results =
+        read_benchmark_csv(csv_path).map_err(|err| BenchmarkErrorKind::MigrationFailed {
+            path: csv_path.to_path_buf(),
+            reason: err.to_string(),
+        })?;
+    if added
+        .iter()
+        .any(|(name, _)| *name == "percentage_improvement")
+    {
+        utils::calculate_base_differences(&mut results);
+    }
+    let output_dir = csv_path.parent().unwrap_or_else(|| Path::new("."));
+    write_result(
+        &CsvWriter::new(),
+        &WriteData::Benchmark(results),
+        output_dir,
+        false,
+    )?;
+
+    tracing::info!("{}: {}", csv_path.display(), changes.join(", "));
+    Ok(true)
+}
+
+/// Start with `tick,run` columns, with plain tick numbers. Older exports kept Factorio's `t`
+/// prefix, and those without a `run` column are split into runs where the tick restarts.
+fn migrate_verbose_csv(csv_path: &Path) -> Result<bool> {
+    let mut reader = csv::Reader::from_path(csv_path)?;
+    let headers: Vec<String> = reader.headers()?.iter().map(str::to_string).collect();
+    let records = reader
+        .records()
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let tick_index = headers
+        .iter()
+        .position(|header| header == "tick")
+        .ok_or_else(|| BenchmarkErrorKind::MigrationFailed {
+            path: csv_path.to_path_buf(),
+            reason: "no tick column".to_string(),
+        })?;
+    let run_index = headers.iter().position(|header| header == "run");
+    let prefixed_ticks = records.iter().any(|record| {
+        record
+            .get(tick_index)
+            .is_some_and(|tick| tick.starts_with('t'))
+    });
+    if tick_index == 0 && run_index == Some(1) && !prefixed_ticks {
+        return Ok(false);
+    }
+
+    let metrics: Vec<usize> = (0..headers.len())
+        .filter(|&index| index != tick_index && Some(index) != run_index)
+        .collect();
+
+    backup(csv_path)?;
+    let mut writer = csv::Writer::from_path(csv_path)?;
+    let mut header_row = vec!["tick", "run"];
+    header_row.extend(metrics.iter().map(|&index| headers[index].as_str()));
+    writer.write_record(&header_row)?;
+
+    let mut run = 0u32;
+    let mut previous_tick: Option<u64> = None;
+    for record in &records {
+        let tick = record
+            .get(tick_index)
+            .unwrap_or_default()
+            .trim_start_matches('t');
+        let run_value = match run_index {
+            Some(index) => record.get(index).unwrap_or_default().to_string(),
+            None => {
+                let tick_number = tick.parse::<u64>().ok();
+                if let (Some(tick_number), Some(previous)) = (tick_number, previous_tick)
+                    && tick_number <= previous
+                {
+                    run += 1;
+                }
+                previous_tick = tick_number.or(previous_tick);
+                run.to_string()
+            }
+        };
+
+        let mut row = vec![tick.to_string(), run_value];
+        row.extend(
+            metrics
+                .iter()
+                .map(|&index| record.get(index).unwrap_or_default().to_string()),
+        );
+        writer.write_record(&row)?;
+    }
+    writer.flush()?;
+
+    tracing::info!(
+        "{}: normalized the tick and run columns",
+        csv_path.display()
+    );
+    Ok(true)
+}
+
+fn verbose_csvs(output_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(output_dir)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().ends_with(VERBOSE_CSV_SUFFIX))
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn headers_contain(headers: &[String], name: &str) -> bool {
+    headers.iter().any(|header| header == name)
+}
+
+/// Copy `path` to `<path>.bak`, keeping an existing backup, which holds the oldest version
+fn backup(path: &Path) -> Result<()> {
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(BACKUP_SUFFIX);
+    let backup_path = PathBuf::from(backup_path);
+    if !backup_path.exists() {
+        std::fs::copy(path, &backup_path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_old_results_and_verbose_csvs() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join("results.csv"),
+            "save_name,run,execution_time_ms,avg_ms,min_ms,max_ms,effective_ups,ticks\n\
+             fast,0,100,1,1,1,60000,6000\n\
+             slow,0,200,2,2,2,30000,6000\n",
+        )
+        .expect("write results");
+        std::fs::write(
+            dir.join("fast_verbose_metrics.csv"),
+            "wholeUpdate,tick\n10,t0\n11,t1\n12,t0\n13,t1\n",
+        )
+        .expect("write verbose metrics");
+
+        run(dir).expect("migrate");
+
+        let results = read_benchmark_csv(&dir.join("results.csv")).expect("read results");
+        let slow = results
+            .iter()
+            .find(|run| run.save_name == "slow")
+            .expect("slow save");
+        let fast = results
+            .iter()
+            .find(|run| run.save_name == "fast")
+            .expect("fast save");
+        assert_eq!(slow.base_diff, 0.0);
+        assert_eq!(fast.base_diff, 100.0);
+        assert_eq!(fast.platform, "unknown");
+        assert!(dir.join("results.csv.bak").is_file());
+
+        let verbose = std::fs::read_to_string(dir.join("fast_verbose_metrics.csv"))
+            .expect("read verbose metrics");
+        assert_eq!(
+            verbose,
+            "tick,run,wholeUpdate\n0,0,10\n1,0,11\n0,1,12\n1,1,13\n"
+        );
+
+        // Migrating twice changes nothing
+        run(dir).expect("migrate again");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("fast_verbose_metrics.csv")).expect("read"),
+            verbose
+        );
+    }
+}
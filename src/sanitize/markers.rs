@@ -0,0 +1,85 @@
+//! Tick-stamped events the belt-sanitizer mod records during a benchmark run, such as a train
+//! departing or a rocket launching, so tick time spikes can be lined up with what caused them.
+//!
+//! With the `belt-sanitizer-event-markers` setting enabled, the mod writes
+//! `script-output/belt/markers.json`:
+//!
+//! ```json
+//! {"markers": [{"tick": 120, "event": "rocket-launched"}]}
+//! ```
+//!
+//! Ticks count from the start of the benchmark, like the `tick` column of the verbose metrics.
+
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// Startup setting of the belt-sanitizer mod that turns marker recording on
+pub const MARKERS_SETTING: &str = "belt-sanitizer-event-markers";
+
+/// File the mod writes the markers of a run to, in its script-output directory
+pub const MARKERS_FILENAME: &str = "markers.json";
+
+/// An event the mod recorded during a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    pub tick: u64,
+    pub event: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarkersFile {
+    #[serde(default)]
+    markers: Vec<Marker>,
+}
+
+/// Take the markers of the last run out of `data_dir`, ordered by tick, so the next run doesn't
+/// see them again
+pub fn take_markers(data_dir: &Path) -> Result<Vec<Marker>> {
+    let path = data_dir.join(MARKERS_FILENAME);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+    let mut markers = serde_json::from_str::<MarkersFile>(&contents)?.markers;
+    markers.sort_by_key(|marker| marker.tick);
+    Ok(markers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_markers_sorts_and_removes_file() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+        fs::write(
+            dir.join(MARKERS_FILENAME),
+            r#"{"markers": [{"tick": 300, "event": "rocket-launched"}, {"tick": 12, "event": "train-departed"}]}"#,
+        )
+        .expect("write markers");
+
+        let markers = take_markers(dir).expect("take markers");
+
+        assert_eq!(
+            markers,
+            vec![
+                Marker {
+                    tick: 12,
+                    event: "train-departed".to_string()
+                },
+                Marker {
+                    tick: 300,
+                    event: "rocket-launched".to_string()
+                },
+            ]
+        );
+        assert!(!dir.join(MARKERS_FILENAME).exists());
+        assert!(take_markers(dir).expect("no markers").is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod markers;
 pub mod parser;
 pub mod runner;
 
@@ -2,6 +2,7 @@
 
 use std::{fs, path::Path};
 
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::{
@@ -9,34 +10,133 @@ use crate::{
     core::{config::SanitizeConfig, error::BenchmarkErrorKind, utils},
 };
 
-pub fn report(config: &SanitizeConfig) -> Result<()> {
+/// What the sanitizer found in a single save
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SanitizerFindings {
+    /// Issues that can distort benchmark results, such as pollution or enemies
+    pub warnings: Vec<String>,
+    /// Items and fluids produced or consumed during the sanitizer run
+    pub production: Vec<String>,
+    /// Total pollution across all surfaces
+    pub total_pollution: Option<f64>,
+    /// Enemy evolution factor, the highest across surfaces
+    pub evolution_factor: Option<f64>,
+    /// Enemy units, spawners and worms across all surfaces
+    pub enemy_count: u64,
+    /// Seed of the map, to regenerate it
+    #[serde(default)]
+    pub map_seed: Option<u64>,
+    /// Map exchange string, to regenerate the map with the same settings
+    #[serde(default)]
+    pub map_exchange_string: Option<String>,
+    /// Entity counts per surface, when the sanitizer reported them
+    #[serde(default)]
+    pub surfaces: Vec<SurfaceStatistics>,
+}
+
+/// Entities on one planet or space platform
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SurfaceStatistics {
+    pub name: String,
+    pub platform: bool,
+    pub entity_count: u64,
+}
+
+impl SanitizerFindings {
+    /// Whether the save has issues that affect benchmark results
+    pub fn has_blocking_issues(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+}
+
+pub fn report(config: &SanitizeConfig) -> Result<SanitizerFindings> {
     let path = config
         .data_dir
         .clone()
         .or_else(utils::check_sanitizer)
         .ok_or(BenchmarkErrorKind::SanitizerNotFound)?;
 
-    parse_sanitizer(&path)?;
-
-    Ok(())
+    parse_sanitizer(&path)
 }
 
-fn parse_sanitizer(path: &Path) -> Result<()> {
+fn parse_sanitizer(path: &Path) -> Result<SanitizerFindings> {
     tracing::debug!("Found sanitizer at {}. Parsing...", &path.display());
 
     let contents = fs::read_to_string(path.join("sanitizer.json"))?;
     tracing::debug!("{contents}");
     let json: Value = serde_json::from_str(&contents)?;
 
-    report_detection_warnings(&json)?;
-    report_production_statistics(&json)?;
+    let findings = SanitizerFindings {
+        production: production_statistics(&json)?,
+        ..snapshot_findings(&json)
+    };
+    log_findings(&findings);
 
     fs::remove_dir_all(path)?;
     tracing::debug!("Removed: {}", path.display());
-    Ok(())
+    Ok(findings)
+}
+
+/// Findings from the state of a map queried over RCON, which has no production statistics
+pub fn parse_rcon_snapshot(output: &str) -> Result<SanitizerFindings> {
+    tracing::debug!("{output}");
+    let json: Value = serde_json::from_str(output.trim())?;
+
+    let findings = snapshot_findings(&json);
+    log_findings(&findings);
+    Ok(findings)
+}
+
+/// Everything but the production statistics, from the `snapshot` of the map's state
+fn snapshot_findings(json: &Value) -> SanitizerFindings {
+    SanitizerFindings {
+        warnings: detection_warnings(json),
+        production: Vec::new(),
+        total_pollution: json["snapshot"]["total_pollution"].as_f64(),
+        evolution_factor: evolution_factor(json),
+        enemy_count: enemy_count(json),
+        map_seed: map_seed(json),
+        map_exchange_string: json["snapshot"]["map_exchange_string"]
+            .as_str()
+            .map(str::to_string),
+        surfaces: surface_statistics(json),
+    }
+}
+
+fn log_findings(findings: &SanitizerFindings) {
+    if let Some(seed) = findings.map_seed {
+        tracing::info!("Map seed: {seed}");
+    }
+    if let Some(exchange_string) = &findings.map_exchange_string {
+        tracing::info!("Map exchange string: {exchange_string}");
+    }
+
+    for surface in &findings.surfaces {
+        tracing::info!(
+            "Surface {}: {} entities",
+            surface.name,
+            surface.entity_count
+        );
+    }
+
+    if findings.warnings.is_empty() {
+        tracing::debug!("No benchmark-affecting issues found");
+    } else {
+        tracing::warn!("Benchmark-affecting issues found!");
+        for warning in &findings.warnings {
+            tracing::warn!("  - {warning}");
+        }
+    }
+
+    if !findings.production.is_empty() {
+        tracing::info!("Production found:");
+        for message in &findings.production {
+            tracing::info!("  - {message}");
+        }
+    }
 }
 
-fn report_detection_warnings(json: &Value) -> Result<()> {
+fn detection_warnings(json: &Value) -> Vec<String> {
     let snapshot = &json["snapshot"];
     let mut warnings = Vec::new();
 
@@ -66,6 +166,15 @@ fn report_detection_warnings(json: &Value) -> Result<()> {
                 ));
             }
 
+            // Requested chunks, e.g. by radars, are generated during the benchmark
+            let ungenerated_chunks = surface["ungenerated_chunks"].as_u64().unwrap_or(0);
+            if ungenerated_chunks > 0 {
+                warnings.push(format!(
+                    "{ungenerated_chunks} chunk(s) waiting to be generated on surface '{}'",
+                    surface["name"].as_str().unwrap_or("unknown")
+                ));
+            }
+
             if let Some(entities_map) = surface["active_entities"].as_object() {
                 for (entity_type, count_value) in entities_map {
                     if count_value.as_u64().unwrap_or(0) > 0 {
@@ -80,19 +189,60 @@ fn report_detection_warnings(json: &Value) -> Result<()> {
         }
     }
 
-    if warnings.is_empty() {
-        tracing::debug!("No benchmark-affecting issues found");
-    } else {
-        tracing::warn!("Benchmark-affecting issues found!");
-        for warning in warnings {
-            tracing::warn!("  - {warning}");
-        }
-    }
+    warnings
+}
+
+fn surfaces(json: &Value) -> impl Iterator<Item = &Value> {
+    json["snapshot"]["surfaces"]
+        .as_array()
+        .into_iter()
+        .flatten()
+}
+
+fn enemy_count(json: &Value) -> u64 {
+    surfaces(json)
+        .map(|surface| {
+            surface["enemy_units"].as_u64().unwrap_or(0)
+                + surface["enemy_spawners"].as_u64().unwrap_or(0)
+                + surface["enemy_worms"].as_u64().unwrap_or(0)
+        })
+        .sum()
+}
+
+fn evolution_factor(json: &Value) -> Option<f64> {
+    // Evolution is per surface since 2.0, older sanitizer versions report a single factor
+    surfaces(json)
+        .filter_map(|surface| surface["evolution_factor"].as_f64())
+        .reduce(f64::max)
+        .or_else(|| json["snapshot"]["evolution_factor"].as_f64())
+}
 
-    Ok(())
+/// Surfaces with an entity count, reported by the sanitizer when its surface statistics are on
+fn surface_statistics(json: &Value) -> Vec<SurfaceStatistics> {
+    surfaces(json)
+        .filter_map(|surface| {
+            Some(SurfaceStatistics {
+                name: surface["name"].as_str().unwrap_or("unknown").to_string(),
+                // A space platform's surface carries the platform's name
+                platform: match &surface["platform"] {
+                    Value::Bool(platform) => *platform,
+                    Value::Null => false,
+                    _ => true,
+                },
+                entity_count: surface["entity_count"].as_u64()?,
+            })
+        })
+        .collect()
 }
 
-fn report_production_statistics(json: &Value) -> Result<()> {
+fn map_seed(json: &Value) -> Option<u64> {
+    // Fall back to the seed of the first surface that reports one, usually nauvis
+    json["snapshot"]["map_seed"]
+        .as_u64()
+        .or_else(|| surfaces(json).find_map(|surface| surface["seed"].as_u64()))
+}
+
+fn production_statistics(json: &Value) -> Result<Vec<String>> {
     let production_statistics = match json.get("production_stats") {
         Some(stats) => stats,
         None => return Err(BenchmarkErrorKind::NoProductionStatistics.into()),
@@ -139,16 +289,7 @@ fn report_production_statistics(json: &Value) -> Result<()> {
         }
     }
 
-    if messages.is_empty() {
-        return Ok(());
-    }
-
-    tracing::info!("Production found:");
-    for message in messages {
-        tracing::info!("  - {message}");
-    }
-
-    Ok(())
+    Ok(messages)
 }
 
 #[derive(Debug)]
@@ -158,3 +299,61 @@ pub struct ProductionStatistic {
     pub quality: Option<String>,
     pub count: f32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_environment_columns_from_snapshot() {
+        let json: Value = serde_json::json!({
+            "snapshot": {
+                "total_pollution": 1234.5,
+                "surfaces": [
+                    { "name": "nauvis", "enemy_units": 3, "enemy_spawners": 2, "enemy_worms": 1, "evolution_factor": 0.25 },
+                    { "name": "vulcanus", "enemy_worms": 4, "evolution_factor": 0.5 }
+                ]
+            }
+        });
+
+        assert_eq!(json["snapshot"]["total_pollution"].as_f64(), Some(1234.5));
+        assert_eq!(enemy_count(&json), 10);
+        assert_eq!(evolution_factor(&json), Some(0.5));
+        assert_eq!(evolution_factor(&serde_json::json!({})), None);
+        assert!(surface_statistics(&json).is_empty());
+
+        let json: Value = serde_json::json!({
+            "snapshot": {
+                "surfaces": [{ "name": "nauvis", "ungenerated_chunks": 12 }]
+            }
+        });
+        assert_eq!(
+            detection_warnings(&json),
+            vec!["12 chunk(s) waiting to be generated on surface 'nauvis'".to_string()]
+        );
+
+        let json: Value = serde_json::json!({
+            "snapshot": {
+                "surfaces": [
+                    { "name": "nauvis", "entity_count": 1200 },
+                    { "name": "platform-1", "platform": "Express", "entity_count": 300 }
+                ]
+            }
+        });
+        assert_eq!(
+            surface_statistics(&json),
+            vec![
+                SurfaceStatistics {
+                    name: "nauvis".to_string(),
+                    platform: false,
+                    entity_count: 1200,
+                },
+                SurfaceStatistics {
+                    name: "platform-1".to_string(),
+                    platform: true,
+                    entity_count: 300,
+                },
+            ]
+        );
+    }
+}
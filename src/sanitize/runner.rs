@@ -1,7 +1,7 @@
 //! Running and collecting logs of sanitization on save file(s)
 
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         Arc,
         atomic::{AtomicBool, Ordering},
@@ -17,11 +17,11 @@ use crate::{
         FactorioExecutor,
         config::SanitizeConfig,
         factorio::FactorioTickRunSpec,
-        format_duration,
+        format_duration, rcon,
         settings::{ModSettings, ModSettingsScopeName, ModSettingsValue},
         utils,
     },
-    sanitize::parser,
+    sanitize::parser::{self, SanitizerFindings},
 };
 
 pub struct SanitizeRunner {
@@ -71,72 +71,107 @@ impl SanitizeRunner {
                 progress.set_message(save_name.clone());
             }
 
-            if self.config.mods_dir.is_none() {
-                self.factorio.sync_mods_for_save(save_file).await?;
-            }
+            self.sanitize_save(save_file).await?;
+        }
 
-            // Update belt-sanitizer mod settings
-            if let Some(ref mods_dir) = self.config.mods_dir.clone().or(utils::find_mod_directory())
-            {
-                let dat_file = &mods_dir.join("mod-settings.dat");
-                let mut ms = ModSettings::load_from_file(dat_file)?;
+        if !running.load(Ordering::SeqCst) {
+            progress.finish_with_message("Sanitization interrupted");
+        } else {
+            progress.finish_with_message("Sanitization complete!");
+        }
 
-                // Disable blueprint-mode just to be sure
+        Ok(())
+    }
+
+    /// Run the sanitizer mod on a single save and return its findings
+    pub async fn sanitize_save(&self, save_file: &Path) -> Result<SanitizerFindings> {
+        if self.config.mods_dir.is_none() {
+            self.factorio.sync_mods_for_save(save_file).await?;
+        }
+
+        if self.config.rcon {
+            return self.query_over_rcon(save_file).await;
+        }
+
+        // Update belt-sanitizer mod settings
+        let mods_dir = self
+            .config
+            .mods_dir
+            .clone()
+            .or_else(|| self.factorio.session_mods_dir().map(Path::to_path_buf))
+            .or_else(utils::find_mod_directory);
+        if let Some(ref mods_dir) = mods_dir {
+            let dat_file = &mods_dir.join("mod-settings.dat");
+            let mut ms = ModSettings::load_from_file(dat_file)?;
+
+            // Disable blueprint-mode just to be sure
+            ms.set(
+                ModSettingsScopeName::Startup,
+                "belt-sanitizer-blueprint-mode",
+                Some(ModSettingsValue::Bool(false)),
+            );
+
+            // Prod check tick
+            ms.set(
+                ModSettingsScopeName::Startup,
+                "belt-sanitizer-target-tick",
+                Some(ModSettingsValue::Int(self.config.ticks as i64)),
+            );
+
+            // Items
+            if let Some(ref items) = self.config.items {
                 ms.set(
                     ModSettingsScopeName::Startup,
-                    "belt-sanitizer-blueprint-mode",
-                    Some(ModSettingsValue::Bool(false)),
+                    "belt-sanitizer-production-items",
+                    Some(ModSettingsValue::String(items.clone())),
                 );
+            }
+
+            // Per-surface entity counts
+            ms.set(
+                ModSettingsScopeName::Startup,
+                "belt-sanitizer-surface-statistics",
+                Some(ModSettingsValue::Bool(self.config.surface_stats)),
+            );
 
-                // Prod check tick
+            // Fluids
+            if let Some(ref fluids) = self.config.fluids {
                 ms.set(
                     ModSettingsScopeName::Startup,
-                    "belt-sanitizer-target-tick",
-                    Some(ModSettingsValue::Int(self.config.ticks as i64)),
+                    "belt-sanitizer-production-fluids",
+                    Some(ModSettingsValue::String(fluids.clone())),
                 );
-
-                // Items
-                if let Some(ref items) = self.config.items {
-                    ms.set(
-                        ModSettingsScopeName::Startup,
-                        "belt-sanitizer-production-items",
-                        Some(ModSettingsValue::String(items.clone())),
-                    );
-                }
-
-                // Fluids
-                if let Some(ref fluids) = self.config.fluids {
-                    ms.set(
-                        ModSettingsScopeName::Startup,
-                        "belt-sanitizer-production-fluids",
-                        Some(ModSettingsValue::String(fluids.clone())),
-                    );
-                }
-
-                ms.save_to_file(dat_file)?;
             }
 
-            let _output = self
-                .factorio
-                .run_for_ticks(FactorioTickRunSpec {
-                    save_file,
-                    ticks: self.config.ticks,
-                    mods_dir: self.config.mods_dir.as_deref(),
-                    verbose_all_metrics: false,
-                    headless: self.config.headless,
-                    record_cpu: false,
-                })
-                .await?;
-
-            parser::report(&self.config)?;
+            ms.save_to_file(dat_file)?;
         }
 
-        if !running.load(Ordering::SeqCst) {
-            progress.finish_with_message("Sanitization interrupted");
-        } else {
-            progress.finish_with_message("Sanitization complete!");
-        }
+        let _output = self
+            .factorio
+            .run_for_ticks(FactorioTickRunSpec {
+                save_file,
+                ticks: self.config.ticks,
+                runs: 1,
+                mods_dir: self.config.mods_dir.as_deref(),
+                verbose_metrics: None,
+                headless: self.config.headless,
+                record_cpu: false,
+                sample_background_load: false,
+            })
+            .await?;
+
+        parser::report(&self.config)
+    }
 
-        Ok(())
+    /// Load the save as a server and query the map's state over RCON, without the mod. There
+    /// are no production statistics, since the server isn't run for `ticks`.
+    async fn query_over_rcon(&self, save_file: &Path) -> Result<SanitizerFindings> {
+        let mut server = self
+            .factorio
+            .start_rcon_server(save_file, self.config.mods_dir.as_deref())?;
+        let output = async { server.connect().await?.lua(rcon::SNAPSHOT_LUA).await }.await;
+        server.stop().await?;
+
+        parser::parse_rcon_snapshot(&output?)
     }
 }
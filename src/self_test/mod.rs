@@ -0,0 +1,252 @@
+//! Self-test module
+//!
+//! Times BELT's own post-processing on generated fixture data: parsing Factorio's output, writing
+//! the results and verbose metrics CSVs, and building the chart data. The fixture is the same for
+//! a given tick count, so the throughput of releases can be compared.
+
+use std::{
+    fmt::Write as _,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    Result,
+    benchmark::{
+        parser::{BenchmarkRun, TickWindow},
+        runner::{self, VerboseData},
+    },
+    core::{
+        Downsample, UpsDefinition,
+        output::{CsvWriter, WriteData, chart_data, write_result},
+    },
+};
+
+/// Saves in the fixture
+const SAVES: usize = 4;
+
+/// Runs per save in the fixture
+const RUNS: usize = 3;
+
+/// Verbose metrics of the fixture, after `tick` and `timestamp`
+const METRICS: &[&str] = &[
+    "wholeUpdate",
+    "latencyUpdate",
+    "gameUpdate",
+    "planetsUpdate",
+    "controlBehaviorUpdate",
+    "transportLinesUpdate",
+    "electricNetworkUpdate",
+    "fluidFlowUpdate",
+    "entityUpdate",
+    "trains",
+    "scriptUpdate",
+    "crcComputation",
+];
+
+/// Factorio's output for one save: its log and its verbose metrics
+struct Fixture {
+    save_name: String,
+    summary: String,
+    verbose_data: String,
+}
+
+/// Fastest time of each stage over the iterations
+#[derive(Debug, Default)]
+struct Timings {
+    parse: Duration,
+    write_csv: Duration,
+    chart_data: Duration,
+}
+
+/// Time the post-processing of `SAVES` saves of `RUNS` runs of `ticks` ticks each, keeping the
+/// fastest of `iterations`, and print the throughput of every stage
+pub fn run(ticks: u32, iterations: u32) -> Result<()> {
+    let fixtures: Vec<Fixture> = (0..SAVES).map(|save| fixture(save, ticks)).collect();
+    let verbose_bytes: usize = fixtures.iter().map(|f| f.verbose_data.len()).sum();
+    let total_ticks = (SAVES * RUNS) as f64 * f64::from(ticks);
+
+    let mut best: Option<Timings> = None;
+    for iteration in 0..iterations.max(1) {
+        let timings = time_pipeline(&fixtures)?;
+        tracing::debug!("Iteration {}: {timings:?}", iteration + 1);
+        best = Some(match best {
+            Some(best) => Timings {
+                parse: best.parse.min(timings.parse),
+                write_csv: best.write_csv.min(timings.write_csv),
+                chart_data: best.chart_data.min(timings.chart_data),
+            },
+            None => timings,
+        });
+    }
+    let best = best.unwrap_or_default();
+
+    println!(
+        "Fixture: {SAVES} saves x {RUNS} runs x {ticks} ticks, {:.1} MB of verbose metrics; fastest of {} iteration(s)",
+        verbose_bytes as f64 / 1e6,
+        iterations.max(1)
+    );
+    println!(
+        "{:<12}  {:>10}  {:>14}  {:>10}",
+        "Stage", "Time", "Ticks/s", "MB/s"
+    );
+    for (stage, elapsed) in [
+        ("Parse", best.parse),
+        ("Write CSV", best.write_csv),
+        ("Chart data", best.chart_data),
+        ("Total", best.parse + best.write_csv + best.chart_data),
+    ] {
+        let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+        println!(
+            "{stage:<12}  {:>8.1}ms  {:>14.0}  {:>10.1}",
+            seconds * 1000.0,
+            total_ticks / seconds,
+            verbose_bytes as f64 / 1e6 / seconds
+        );
+    }
+    Ok(())
+}
+
+/// Run every stage once on `fixtures`, in a temporary output directory
+fn time_pipeline(fixtures: &[Fixture]) -> Result<Timings> {
+    let output_dir = tempfile::tempdir()?;
+
+    let start = Instant::now();
+    let mut results = Vec::new();
+    let mut verbose_data = Vec::new();
+    for fixture in fixtures {
+        let (runs, save_verbose_data) = parse(fixture)?;
+        results.extend(runs);
+        verbose_data.push(save_verbose_data);
+    }
+    let parse = start.elapsed();
+
+    let start = Instant::now();
+    write_csvs(results, verbose_data, output_dir.path())?;
+    let write_csv = start.elapsed();
+
+    let start = Instant::now();
+    chart_data::write_chart_data(output_dir.path(), Downsample::default())?;
+    let chart_data = start.elapsed();
+
+    Ok(Timings {
+        parse,
+        write_csv,
+        chart_data,
+    })
+}
+
+/// Parse a fixture the way the runner parses Factorio's output
+fn parse(fixture: &Fixture) -> Result<(Vec<BenchmarkRun>, Vec<VerboseData>)> {
+    let save_file = Path::new(&fixture.save_name).with_extension("zip");
+    let mut runs = Vec::new();
+    let mut verbose_data = Vec::new();
+    for (run, csv_data) in runner::parse_runs(
+        &fixture.summary,
+        Some(fixture.verbose_data.clone()),
+        &save_file,
+        &fixture.save_name,
+        0,
+        UpsDefinition::default(),
+        TickWindow::default(),
+    )? {
+        if let Some(csv_data) = csv_data {
+            verbose_data.push(VerboseData::spool(&fixture.save_name, &csv_data)?);
+        }
+        runs.push(run);
+    }
+    Ok((runs, verbose_data))
+}
+
+/// Write `results.csv` and every save's verbose metrics CSV with all metrics
+fn write_csvs(
+    results: Vec<BenchmarkRun>,
+    verbose_data: Vec<Vec<VerboseData>>,
+    output_dir: &Path,
+) -> Result<()> {
+    let writer = CsvWriter::new();
+    write_result(&writer, &WriteData::Benchmark(results), output_dir, false)?;
+    for data in verbose_data {
+        let data = WriteData::Verbose {
+            data,
+            metrics_to_export: vec!["all".to_string()],
+            derived_metrics: Vec::new(),
+            metric_summary: false,
+        };
+        write_result(&writer, &data, output_dir, false)?;
+    }
+    Ok(())
+}
+
+/// The output of Factorio benchmarking save `save` `RUNS` times in one process, with per-tick
+/// timings from a fixed pseudo-random sequence
+fn fixture(save: usize, ticks: u32) -> Fixture {
+    // xorshift64, seeded per save
+    let mut state = 0x9e37_79b9_7f4a_7c15_u64 ^ (save as u64 + 1);
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let mut summary =
+        String::from("0.000 2025-07-09 17:16:57; Factorio 2.0.55 (build 83138, linux64, full)\n");
+    let mut verbose_data = format!("tick,timestamp,{}\n", METRICS.join(","));
+    let base_ns = 1_000_000 + save as u64 * 250_000;
+    for _ in 0..RUNS {
+        let mut timestamp = 0;
+        let (mut total_ns, mut min_ns, mut max_ns) = (0, u64::MAX, 0);
+        for tick in 0..ticks {
+            let whole_update = base_ns + next() % (base_ns / 2);
+            timestamp += whole_update;
+            total_ns += whole_update;
+            min_ns = min_ns.min(whole_update);
+            max_ns = max_ns.max(whole_update);
+
+            let _ = write!(verbose_data, "t{tick},{timestamp},{whole_update}");
+            for _ in 1..METRICS.len() {
+                let _ = write!(verbose_data, ",{}", next() % (whole_update / 4));
+            }
+            verbose_data.push('\n');
+        }
+
+        let ms = |ns: u64| ns as f64 / 1e6;
+        let _ = write!(
+            summary,
+            "   Performed {ticks} updates in {:.3} ms\n   avg: {:.3} ms, min: {:.3} ms, max: {:.3} ms\n   checksum: 2846200395\n",
+            ms(total_ns),
+            ms(total_ns) / f64::from(ticks.max(1)),
+            ms(min_ns),
+            ms(max_ns)
+        );
+    }
+    summary.push_str("   7.737 Goodbye\n");
+
+    Fixture {
+        save_name: format!("self-test-{save}"),
+        summary,
+        verbose_data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_processes_every_run_of_the_fixture() {
+        let fixture = fixture(1, 50);
+        assert_eq!(fixture.verbose_data, super::fixture(1, 50).verbose_data);
+
+        let (runs, verbose_data) = parse(&fixture).unwrap();
+        assert_eq!(runs.len(), RUNS);
+        assert_eq!(verbose_data.len(), RUNS);
+        assert!(
+            runs.iter()
+                .all(|run| run.ticks == 50 && run.effective_ups > 0.0)
+        );
+
+        assert!(time_pipeline(&[fixture]).is_ok());
+    }
+}
@@ -0,0 +1,155 @@
+//! Settings module
+//!
+//! Inspects and edits Factorio's binary `mod-settings.dat`, e.g. to compare the mod settings of
+//! two benchmark environments or to switch settings between benchmark variants.
+
+use std::path::Path;
+
+use crate::core::{
+    Result,
+    error::{BenchmarkError, BenchmarkErrorKind},
+    propertytree::VersionedTree,
+    settings::{ModSettings, ModSettingsScopeName, ModSettingsValue},
+};
+
+/// Print every setting of `path` as pretty JSON, or with `raw` its whole property tree
+pub fn dump(path: &Path, raw: bool) -> Result<()> {
+    let json = if raw {
+        let file = VersionedTree::load_from_file(path).map_err(|err| invalid(path, err))?;
+        serde_json::json!({
+            "version": file.version.to_string(),
+            "tree": file.tree.to_json(),
+        })
+    } else {
+        load(path)?.to_json()
+    };
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
+
+/// Print the settings that differ between `left` and `right`
+pub fn diff(left: &Path, right: &Path) -> Result<()> {
+    let differences = load(left)?.diff(&load(right)?);
+    if differences.is_empty() {
+        println!("No differences");
+        return Ok(());
+    }
+
+    let missing = || "(not set)".to_string();
+    for difference in differences {
+        println!(
+            "{}.{}: {} -> {}",
+            difference.scope.as_str(),
+            difference.key,
+            difference
+                .left
+                .map_or_else(missing, |value| value.to_string()),
+            difference
+                .right
+                .map_or_else(missing, |value| value.to_string()),
+        );
+    }
+    Ok(())
+}
+
+/// Apply `SCOPE.SETTING=VALUE` assignments to `path` and write it back. Values keep the type of
+/// the existing setting; new settings get the type their value looks like.
+pub fn set(path: &Path, assignments: &[String]) -> Result<()> {
+    let mut settings = load(path)?;
+    for assignment in assignments {
+        let (scope, key, value) = parse_assignment(&settings, assignment)?;
+        tracing::info!("{}.{key} = {value}", scope.as_str());
+        settings.set(scope, key, Some(value));
+    }
+
+    settings.save_to_file(path)?;
+    tracing::info!("Wrote {}", path.display());
+    Ok(())
+}
+
+fn parse_assignment(
+    settings: &ModSettings,
+    assignment: &str,
+) -> Result<(ModSettingsScopeName, String, ModSettingsValue)> {
+    let invalid = |reason: String| BenchmarkErrorKind::InvalidSettingAssignment {
+        input: assignment.to_string(),
+        reason,
+    };
+
+    let (name, text) = assignment
+        .split_once('=')
+        .ok_or_else(|| invalid("expected SCOPE.SETTING=VALUE".to_string()))?;
+    let (scope, key) = name
+        .split_once('.')
+        .filter(|(_, key)| !key.is_empty())
+        .ok_or_else(|| invalid("expected SCOPE.SETTING=VALUE".to_string()))?;
+    let scope = ModSettingsScopeName::from_name(scope).ok_or_else(|| {
+        invalid(format!(
+            "unknown scope '{scope}', expected startup, runtime-global or runtime-per-user"
+        ))
+    })?;
+
+    let value = match settings.get(scope, key) {
+        Some(existing) => existing.parse_like(text).map_err(invalid)?,
+        None => ModSettingsValue::infer(text),
+    };
+    Ok((scope, key.to_string(), value))
+}
+
+fn load(path: &Path) -> Result<ModSettings> {
+    ModSettings::load_from_file(path).map_err(|err| invalid(path, err))
+}
+
+fn invalid(path: &Path, err: std::io::Error) -> BenchmarkError {
+    BenchmarkErrorKind::InvalidModSettings {
+        path: path.to_path_buf(),
+        reason: err.to_string(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{propertytree::MapVersion, settings::ModSettingsData};
+
+    #[test]
+    fn test_set_keeps_existing_types() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let path = temp_dir.path().join("mod-settings.dat");
+        let mut settings = ModSettings {
+            version: MapVersion {
+                data: [2, 0, 0, 0, 55, 0, 0, 0, 0],
+            },
+            settings: ModSettingsData::default(),
+        };
+        settings.set(
+            ModSettingsScopeName::Startup,
+            "belt-speed",
+            Some(ModSettingsValue::Number(1.0)),
+        );
+        settings.save_to_file(&path).expect("save settings");
+
+        set(
+            &path,
+            &[
+                "startup.belt-speed=2".to_string(),
+                "runtime-global.enabled=true".to_string(),
+            ],
+        )
+        .expect("set settings");
+
+        let settings = load(&path).expect("load settings");
+        assert_eq!(
+            settings.get(ModSettingsScopeName::Startup, "belt-speed"),
+            Some(&ModSettingsValue::Number(2.0))
+        );
+        assert_eq!(
+            settings.get(ModSettingsScopeName::RuntimeGlobal, "enabled"),
+            Some(&ModSettingsValue::Bool(true))
+        );
+        assert!(set(&path, &["startup.belt-speed=fast".to_string()]).is_err());
+        assert!(set(&path, &["global.enabled=false".to_string()]).is_err());
+        assert!(set(&path, &["startup.belt-speed".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! Stats module
+//!
+//! Summarizes the benchmarking history kept in the session output directories, read from their
+//! journals and never sent anywhere: how much time went into benchmarking, how many runs were
+//! executed and which saves were benchmarked most.
+
+use std::{collections::HashMap, path::Path, time::Duration};
+
+use chrono::DateTime;
+
+use crate::core::{
+    Result,
+    error::BenchmarkErrorKind,
+    format_duration,
+    journal::{self, JOURNAL_FILENAME, JournalEntry},
+};
+
+/// Saves listed as the most benchmarked
+const TOP_SAVES: usize = 5;
+
+/// Totals over the journals of a set of sessions
+#[derive(Debug, Default, PartialEq)]
+struct Stats {
+    sessions: usize,
+    /// Time the sessions' commands ran, Factorio and hooks alike
+    command_time: Duration,
+    /// From the first command of each session to the end of its last one
+    session_time: Duration,
+    runs: u32,
+    /// Runs per save, most benchmarked first
+    saves: Vec<(String, u32)>,
+}
+
+/// Print the stats of the sessions in `outputs_dir`, or of `outputs_dir` itself when it's a
+/// session's output directory
+pub fn run(outputs_dir: &Path) -> Result<()> {
+    if !outputs_dir.is_dir() {
+        return Err(BenchmarkErrorKind::OutputsDirectoryNotFound {
+            path: outputs_dir.to_path_buf(),
+        }
+        .into());
+    }
+
+    let stats = collect(outputs_dir)?;
+    if stats.sessions == 0 {
+        tracing::info!("No sessions found in {}", outputs_dir.display());
+        return Ok(());
+    }
+
+    println!("Sessions:             {}", stats.sessions);
+    println!("Runs:                 {}", stats.runs);
+    println!(
+        "Benchmark time:       {:.1} h",
+        stats.command_time.as_secs_f64() / 3600.0
+    );
+    println!(
+        "Average session:      {}",
+        format_duration(stats.session_time / stats.sessions as u32)
+    );
+    if !stats.saves.is_empty() {
+        println!("Most benchmarked saves:");
+        for (save_name, runs) in stats.saves.iter().take(TOP_SAVES) {
+            println!("  {save_name}: {runs} run(s)");
+        }
+    }
+    Ok(())
+}
+
+fn collect(outputs_dir: &Path) -> Result<Stats> {
+    let mut session_dirs = Vec::new();
+    if outputs_dir.join(JOURNAL_FILENAME).is_file() {
+        session_dirs.push(outputs_dir.to_path_buf());
+    } else {
+        for entry in std::fs::read_dir(outputs_dir)? {
+            let path = entry?.path();
+            if path.join(JOURNAL_FILENAME).is_file() {
+                session_dirs.push(path);
+            }
+        }
+    }
+
+    let mut stats = Stats::default();
+    let mut runs_per_save: HashMap<String, u32> = HashMap::new();
+    for session_dir in &session_dirs {
+        let entries = journal::read_journal(session_dir)?;
+        let mut first_start = None;
+        let mut last_end = None;
+        for entry in entries {
+            match entry {
+                JournalEntry::Command {
+                    started_at,
+                    ended_at,
+                    ..
+                } => {
+                    let (Ok(started_at), Ok(ended_at)) = (
+                        DateTime::parse_from_rfc3339(&started_at),
+                        DateTime::parse_from_rfc3339(&ended_at),
+                    ) else {
+                        continue;
+                    };
+                    stats.command_time += (ended_at - started_at).to_std().unwrap_or_default();
+                    first_start =
+                        Some(first_start.map_or(started_at, |first| started_at.min(first)));
+                    last_end = Some(last_end.map_or(ended_at, |last| ended_at.max(last)));
+                }
+                JournalEntry::Runs { save_name, runs } => {
+                    stats.runs += runs;
+                    *runs_per_save.entry(save_name).or_default() += runs;
+                }
+                _ => {}
+            }
+        }
+
+        stats.sessions += 1;
+        if let (Some(first_start), Some(last_end)) = (first_start, last_end) {
+            stats.session_time += (last_end - first_start).to_std().unwrap_or_default();
+        }
+    }
+
+    stats.saves = runs_per_save.into_iter().collect();
+    stats
+        .saves
+        .sort_by(|(a_name, a_runs), (b_name, b_runs)| b_runs.cmp(a_runs).then(a_name.cmp(b_name)));
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_sums_sessions_runs_and_saves() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let write_session = |name: &str, lines: &[&str]| {
+            let dir = temp_dir.path().join(name);
+            std::fs::create_dir(&dir).expect("create session");
+            std::fs::write(dir.join(JOURNAL_FILENAME), lines.join("\n")).expect("write journal");
+        };
+        write_session(
+            "first",
+            &[
+                r#"{"event":"command","command":["factorio"],"started_at":"2026-01-01T10:00:00+00:00","ended_at":"2026-01-01T10:30:00+00:00","exit_code":0}"#,
+                r#"{"event":"command","command":["factorio"],"started_at":"2026-01-01T10:40:00+00:00","ended_at":"2026-01-01T11:00:00+00:00","exit_code":0}"#,
+                r#"{"event":"runs","save_name":"base","runs":3}"#,
+                r#"{"event":"runs","save_name":"other","runs":1}"#,
+            ],
+        );
+        write_session(
+            "second",
+            &[
+                r#"{"event":"command","command":["factorio"],"started_at":"2026-01-02T10:00:00+00:00","ended_at":"2026-01-02T11:00:00+00:00","exit_code":0}"#,
+                r#"{"event":"runs","save_name":"other","runs":5}"#,
+            ],
+        );
+        std::fs::create_dir(temp_dir.path().join("not-a-session")).expect("create dir");
+
+        let stats = collect(temp_dir.path()).expect("collect stats");
+
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(stats.runs, 9);
+        assert_eq!(stats.command_time, Duration::from_secs(110 * 60));
+        assert_eq!(stats.session_time, Duration::from_secs(120 * 60));
+        assert_eq!(
+            stats.saves,
+            vec![("other".to_string(), 6), ("base".to_string(), 3)]
+        );
+        assert_eq!(collect(&temp_dir.path().join("first")).unwrap().sessions, 1);
+    }
+}
@@ -0,0 +1,279 @@
+//! Verify module
+//!
+//! Checks an output directory for internal consistency before its results are published:
+//! run counts against the journal, verbose tick counts against `results.csv`, files the report
+//! links to, and the checksums the journal recorded for every artifact.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    sync::LazyLock,
+};
+
+use regex::Regex;
+
+use crate::{
+    Result,
+    benchmark::parser::BenchmarkRun,
+    core::{
+        error::BenchmarkErrorKind,
+        journal::{self, JOURNAL_FILENAME, JournalEntry},
+        output::csv::read_benchmark_csv,
+    },
+};
+
+/// Suffix of the per-save verbose metrics CSVs
+const VERBOSE_CSV_SUFFIX: &str = "_verbose_metrics.csv";
+
+/// Markdown link and image targets, and HTML `src`/`href` attributes
+static LINK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\]\(([^)\s]+)\)|(?:src|href)="([^"]+)""#).expect("valid link regex")
+});
+
+/// Verify `output_dir`, logging every issue found
+pub fn run(output_dir: &Path) -> Result<()> {
+    let csv_path = output_dir.join("results.csv");
+    if !csv_path.is_file() {
+        return Err(BenchmarkErrorKind::ResultsNotFound {
+            path: output_dir.to_path_buf(),
+        }
+        .into());
+    }
+
+    let issues = check(output_dir)?;
+    for issue in &issues {
+        tracing::warn!("{issue}");
+    }
+
+    if !issues.is_empty() {
+        return Err(BenchmarkErrorKind::VerificationFailed {
+            path: output_dir.to_path_buf(),
+            issues: issues.len(),
+        }
+        .into());
+    }
+
+    tracing::info!("{} is consistent", output_dir.display());
+    Ok(())
+}
+
+/// Every inconsistency in `output_dir`, as a readable description
+fn check(output_dir: &Path) -> Result<Vec<String>> {
+    let mut issues = Vec::new();
+
+    let results = match read_benchmark_csv(&output_dir.join("results.csv")) {
+        Ok(results) => results,
+        Err(err) => return Ok(vec![format!("results.csv can't be read: {err}")]),
+    };
+
+    if output_dir.join(JOURNAL_FILENAME).is_file() {
+        let entries = journal::read_journal(output_dir)?;
+        issues.extend(check_run_counts(&results, &entries));
+        issues.extend(check_checksums(output_dir, &entries));
+    } else {
+        tracing::warn!("No {JOURNAL_FILENAME} found; run counts and checksums aren't verified");
+    }
+
+    issues.extend(check_verbose_ticks(output_dir, &results)?);
+    issues.extend(check_report_links(output_dir)?);
+
+    Ok(issues)
+}
+
+/// Rows per save in `results.csv` against the runs the journal recorded
+fn check_run_counts(results: &[BenchmarkRun], entries: &[JournalEntry]) -> Vec<String> {
+    let mut expected: BTreeMap<&str, u32> = BTreeMap::new();
+    for entry in entries {
+        if let JournalEntry::Runs { save_name, runs } = entry {
+            *expected.entry(save_name.as_str()).or_default() += runs;
+        }
+    }
+    // Journals of older versions don't record runs
+    if expected.is_empty() {
+        return Vec::new();
+    }
+
+    let mut actual: BTreeMap<&str, u32> = BTreeMap::new();
+    for result in results {
+        *actual.entry(result.save_name.as_str()).or_default() += 1;
+    }
+
+    let mut saves: Vec<&str> = expected.keys().chain(actual.keys()).copied().collect();
+    saves.sort_unstable();
+    saves.dedup();
+
+    saves
+        .into_iter()
+        .filter_map(|save| {
+            let expected = expected.get(save).copied().unwrap_or(0);
+            let actual = actual.get(save).copied().unwrap_or(0);
+            (expected != actual).then(|| {
+                format!(
+                    "results.csv has {actual} run(s) of {save}, the journal recorded {expected}"
+                )
+            })
+        })
+        .collect()
+}
+
+/// Artifacts against the checksums the journal recorded last for them
+fn check_checksums(output_dir: &Path, entries: &[JournalEntry]) -> Vec<String> {
+    // Appended sessions record the files they rewrote again
+    let mut checksums: BTreeMap<&str, Option<&str>> = BTreeMap::new();
+    for entry in entries {
+        if let JournalEntry::Artifact { path, sha256 } = entry {
+            checksums.insert(path.as_str(), sha256.as_deref());
+        }
+    }
+
+    let mut issues = Vec::new();
+    for (path, expected) in checksums {
+        let file = output_dir.join(path);
+        if !file.is_file() {
+            issues.push(format!("{path} is recorded in the journal but missing"));
+            continue;
+        }
+
+        let Some(expected) = expected else {
+            continue;
+        };
+        match journal::sha256_file(&file) {
+            Ok(actual) if actual == expected => {}
+            Ok(_) => issues.push(format!("{path} was modified after the benchmark")),
+            Err(err) => issues.push(format!("{path} can't be read: {err}")),
+        }
+    }
+    issues
+}
+
+/// Ticks per run in every verbose metrics CSV against the ticks of the save in `results.csv`
+fn check_verbose_ticks(output_dir: &Path, results: &[BenchmarkRun]) -> Result<Vec<String>> {
+    let ticks: HashMap<&str, u32> = results
+        .iter()
+        .map(|result| (result.save_name.as_str(), result.ticks))
+        .collect();
+
+    let mut issues = Vec::new();
+    for entry in std::fs::read_dir(output_dir)? {
+        let path = entry?.path();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let Some(save_name) = file_name.strip_suffix(VERBOSE_CSV_SUFFIX) else {
+            continue;
+        };
+        let Some(&expected) = ticks.get(save_name) else {
+            issues.push(format!("{file_name} belongs to no save in results.csv"));
+            continue;
+        };
+
+        let mut reader = csv::Reader::from_path(&path)?;
+        let run_column = reader.headers()?.iter().position(|header| header == "run");
+        let Some(run_column) = run_column else {
+            issues.push(format!("{file_name} has no run column"));
+            continue;
+        };
+
+        let mut rows_per_run: BTreeMap<String, u32> = BTreeMap::new();
+        for record in reader.records() {
+            let record = record?;
+            let run = record.get(run_column).unwrap_or_default().to_string();
+            *rows_per_run.entry(run).or_default() += 1;
+        }
+
+        for (run, rows) in rows_per_run {
+            if rows != expected {
+                issues.push(format!(
+                    "{file_name} has {rows} tick(s) for run {run}, expected {expected}"
+                ));
+            }
+        }
+    }
+
+    issues.sort();
+    Ok(issues)
+}
+
+/// Local files linked from the reports, e.g. charts and archived profiler reports
+fn check_report_links(output_dir: &Path) -> Result<Vec<String>> {
+    let mut issues = Vec::new();
+    for entry in std::fs::read_dir(output_dir)? {
+        let path = entry?.path();
+        let is_report = path
+            .extension()
+            .is_some_and(|extension| extension == "md" || extension == "html");
+        if !is_report {
+            continue;
+        }
+
+        let report = std::fs::read_to_string(&path)?;
+        let report_name = path.file_name().unwrap_or_default().to_string_lossy();
+        for captures in LINK_REGEX.captures_iter(&report) {
+            let Some(target) = captures.get(1).or_else(|| captures.get(2)) else {
+                continue;
+            };
+            let target = target.as_str();
+            let target = target.split('#').next().unwrap_or_default();
+            if target.is_empty() || target.contains("://") || target.starts_with("mailto:") {
+                continue;
+            }
+
+            if !output_dir.join(target).exists() {
+                issues.push(format!("{report_name} links to {target}, which is missing"));
+            }
+        }
+    }
+
+    issues.sort();
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_finds_inconsistencies() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let dir = temp_dir.path();
+
+        std::fs::write(
+            dir.join("results.csv"),
+            "save_name,run_index,execution_time_ms,avg_ms,min_ms,max_ms,effective_ups,percentage_improvement,ticks,factorio_version,platform\n\
+             alpha,0,10,1,1,1,300,0,3,2.0,linux\n",
+        )
+        .expect("write results");
+        std::fs::write(
+            dir.join("alpha_verbose_metrics.csv"),
+            "tick,run,wholeUpdate\n0,0,1\n1,0,1\n",
+        )
+        .expect("write verbose metrics");
+        std::fs::write(dir.join("results.md"), "![chart](charts/alpha.png)\n")
+            .expect("write report");
+        let checksum = journal::sha256_file(&dir.join("results.csv")).expect("checksum");
+        std::fs::write(
+            dir.join(JOURNAL_FILENAME),
+            format!(
+                "{{\"event\":\"runs\",\"save_name\":\"alpha\",\"runs\":2}}\n\
+                 {{\"event\":\"artifact\",\"path\":\"results.csv\",\"sha256\":\"{checksum}\"}}\n\
+                 {{\"event\":\"artifact\",\"path\":\"results.md\",\"sha256\":\"0000\"}}\n\
+                 {{\"event\":\"artifact\",\"path\":\"summary.csv\"}}\n"
+            ),
+        )
+        .expect("write journal");
+
+        let issues = check(dir).expect("check");
+
+        assert_eq!(
+            issues,
+            vec![
+                "results.csv has 1 run(s) of alpha, the journal recorded 2",
+                "results.md was modified after the benchmark",
+                "summary.csv is recorded in the journal but missing",
+                "alpha_verbose_metrics.csv has 2 tick(s) for run 0, expected 3",
+                "results.md links to charts/alpha.png, which is missing",
+            ]
+        );
+    }
+}
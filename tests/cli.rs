@@ -191,22 +191,163 @@ fn test_benchmark_command_accepts_record_cpu_toggle() -> Result<(), Box<dyn Erro
     Ok(())
 }
 
+#[cfg(unix)]
 #[test]
-fn test_analyze_subcommand_is_removed() -> Result<(), Box<dyn Error>> {
+fn test_benchmark_command_generates_saves_from_scenario_folders() -> Result<(), Box<dyn Error>> {
+    use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    let scenario_dir = temp_path.join("saves").join("belt-weave");
+    std::fs::create_dir_all(&scenario_dir)?;
+    std::fs::write(scenario_dir.join("control.lua"), "")?;
+
+    // Saves the scenario into the write-data directory from --config, like a server on exit
+    let fake_factorio_exe = temp_path.join("factorio");
+    std::fs::write(
+        &fake_factorio_exe,
+        r#"#!/bin/sh
+if [ "$1" = "--config" ]; then config="$2"; shift 2; fi
+if [ "$1" = "--start-server-load-scenario" ]; then
+    write_data=$(sed -n 's/^write-data=//p' "$config")
+    test -f "$write_data/scenarios/$2/control.lua" || exit 1
+    mkdir -p "$write_data/saves" && touch "$write_data/saves/$2.zip"
+    exit 0
+fi
+echo 'Performed 10 updates in 100.000 ms'
+echo 'avg: 10.000 ms, min: 10.000 ms, max: 10.000 ms'
+"#,
+    )?;
+    std::fs::set_permissions(&fake_factorio_exe, Permissions::from_mode(0o755))?;
+
     let mut cmd = cargo_bin_cmd!("belt");
-    cmd.arg("analyze").arg("--help");
+
+    cmd.arg("benchmark")
+        .arg(temp_path.join("saves"))
+        .arg("--output")
+        .arg(temp_path)
+        .arg("--factorio-path")
+        .arg(&fake_factorio_exe)
+        .arg("--mods-dir")
+        .arg(temp_path)
+        .arg("--runs")
+        .arg("1");
 
     let output = cmd.output()?;
     assert!(
-        !output.status.success(),
-        "Analyze subcommand should be unavailable"
+        output.status.success(),
+        "Command should succeed. Stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
     );
 
-    let stderr = String::from_utf8_lossy(&output.stderr);
+    let results = std::fs::read_to_string(temp_path.join("results.csv"))?;
     assert!(
-        stderr.contains("unrecognized subcommand") || stderr.contains("unknown subcommand"),
-        "Expected clap to reject removed subcommand. Stderr: {stderr}"
+        results
+            .lines()
+            .nth(1)
+            .is_some_and(|row| row.starts_with("belt-weave,"))
     );
 
     Ok(())
 }
+
+#[cfg(unix)]
+#[test]
+fn test_time_budget_calibrates_when_last_save_is_skipped() -> Result<(), Box<dyn Error>> {
+    use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+    let temp_dir = tempdir()?;
+    let temp_path = temp_dir.path();
+
+    let saves_dir = temp_path.join("saves");
+    std::fs::create_dir(&saves_dir)?;
+    File::create(saves_dir.join("a.zip"))?;
+    File::create(saves_dir.join("b.zip"))?;
+
+    // Slow enough that the budget fits a handful of runs
+    let fake_factorio_exe = temp_path.join("factorio");
+    std::fs::write(
+        &fake_factorio_exe,
+        r#"#!/bin/sh
+sleep 0.2
+echo 'Performed 10 updates in 100.000 ms'
+echo 'avg: 10.000 ms, min: 10.000 ms, max: 10.000 ms'
+"#,
+    )?;
+    std::fs::set_permissions(&fake_factorio_exe, Permissions::from_mode(0o755))?;
+
+    let mut cmd = cargo_bin_cmd!("belt");
+
+    cmd.arg("benchmark")
+        .arg(&saves_dir)
+        .arg("--output")
+        .arg(temp_path)
+        .arg("--factorio-path")
+        .arg(&fake_factorio_exe)
+        .arg("--time-budget")
+        .arg("1s")
+        .arg("--pre-save-hook")
+        .arg("case {save} in *b.zip) exit 1;; esac");
+
+    let output = cmd.output()?;
+    assert!(
+        output.status.success(),
+        "Command should succeed. Stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // b.zip, the last save of the schedule, is skipped; a.zip still gets its calibrated runs
+    let results = std::fs::read_to_string(temp_path.join("results.csv"))?;
+    let runs_of = |save: &str| {
+        results
+            .lines()
+            .filter(|row| row.starts_with(&format!("{save},")))
+            .count()
+    };
+    assert_eq!(runs_of("b"), 0);
+    assert!(runs_of("a") > 1, "results.csv:\n{results}");
+
+    Ok(())
+}
+
+#[test]
+fn test_analyze_from_raw_regenerates_results() -> Result<(), Box<dyn Error>> {
+    let temp_dir = tempdir()?;
+    let raw_logs_dir = temp_dir.path().join("raw_logs");
+    std::fs::create_dir(&raw_logs_dir)?;
+
+    for (file_name, log) in [
+        (
+            "fast_run0.log.zst",
+            "Performed 100 updates in 200.000 ms\navg: 2.000 ms, min: 1.000 ms, max: 3.000 ms\n",
+        ),
+        (
+            "slow_run0.log.zst",
+            "Performed 100 updates in 400.000 ms\navg: 4.000 ms, min: 3.000 ms, max: 5.000 ms\n",
+        ),
+    ] {
+        std::fs::write(
+            raw_logs_dir.join(file_name),
+            zstd::encode_all(log.as_bytes(), 0)?,
+        )?;
+    }
+
+    let mut cmd = cargo_bin_cmd!("belt");
+    cmd.arg("analyze").arg("--from-raw").arg(&raw_logs_dir);
+
+    let output = cmd.output()?;
+    assert!(
+        output.status.success(),
+        "Command should succeed. Stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let results = std::fs::read_to_string(temp_dir.path().join("results.csv"))?;
+    let rows: Vec<&str> = results.lines().skip(1).collect();
+    assert_eq!(rows.len(), 2);
+    assert!(rows[0].starts_with("slow,"));
+    assert!(rows[1].starts_with("fast,0,200,2,1,3,500,100,"));
+
+    Ok(())
+}
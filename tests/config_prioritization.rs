@@ -1,9 +1,9 @@
 //! Tests for configuration loading and precedence.
 
-use belt::core::RunOrder;
 use belt::core::config::{
     BenchmarkConfig, BlueprintConfig, GlobalConfig, SanitizeConfig, create_figment_from_file,
 };
+use belt::core::{CachePolicy, RunOrder};
 use std::io::Write;
 use std::sync::{LazyLock, Mutex};
 use tempfile::{NamedTempFile, TempDir};
@@ -57,6 +57,7 @@ fn test_benchmark_config_default_values() {
         assert!(!config.headless);
         assert!(config.record_cpu);
         assert!(config.verbose_metrics.is_empty());
+        assert_eq!(config.cache_policy, CachePolicy::Asis);
     });
 }
 
@@ -334,6 +335,52 @@ run_order = "{}"
     });
 }
 
+#[test]
+fn test_cache_policy_variants_from_config() {
+    with_env_lock(|| {
+        for (variant_name, expected_variant) in [
+            ("warm", CachePolicy::Warm),
+            ("cold", CachePolicy::Cold),
+            ("asis", CachePolicy::Asis),
+        ] {
+            let config_content = format!(
+                r#"
+[benchmark]
+cache_policy = "{}"
+"#,
+                variant_name
+            );
+
+            let config_file = create_config_file(&config_content);
+            let figment = create_figment_from_file(&config_file.path().to_path_buf())
+                .expect("Failed to create figment");
+            let config = BenchmarkConfig::from_figment(&figment).expect("Failed to load config");
+
+            assert_eq!(config.cache_policy, expected_variant);
+        }
+    });
+}
+
+#[test]
+fn test_time_budget_from_config() {
+    with_env_lock(|| {
+        let config_content = r#"
+[benchmark]
+time_budget = "1h30m"
+"#;
+
+        let config_file = create_config_file(config_content);
+        let figment = create_figment_from_file(&config_file.path().to_path_buf())
+            .expect("Failed to create figment");
+        let config = BenchmarkConfig::from_figment(&figment).expect("Failed to load config");
+
+        assert_eq!(
+            config.time_budget,
+            Some(std::time::Duration::from_secs(90 * 60))
+        );
+    });
+}
+
 #[test]
 fn test_path_options_from_config_file() {
     with_env_lock(|| {
@@ -385,3 +432,29 @@ fn test_nonexistent_config_file_error() {
         assert!(err.to_string().contains("Configuration file not found"));
     });
 }
+
+#[test]
+fn test_verbose_metrics_defaults_from_config_and_env() {
+    with_env_lock(|| {
+        let config_content = r#"
+[benchmark]
+verbose_metrics = ["wholeUpdate", "gameUpdate"]
+"#;
+        let config_file = create_config_file(config_content);
+        let figment = create_figment_from_file(&config_file.path().to_path_buf())
+            .expect("Failed to create figment");
+        let config = BenchmarkConfig::from_figment(&figment).expect("Failed to load config");
+        assert_eq!(config.verbose_metrics, vec!["wholeUpdate", "gameUpdate"]);
+
+        // Comma-separated like on the command line
+        unsafe {
+            std::env::set_var("BELT_BENCHMARK__VERBOSE_METRICS", "entityUpdate,*Update");
+            std::env::set_var("BELT_BENCHMARK__TAGS", "weave");
+        }
+        let figment = create_figment_from_file(&config_file.path().to_path_buf())
+            .expect("Failed to create figment");
+        let config = BenchmarkConfig::from_figment(&figment).expect("Failed to load config");
+        assert_eq!(config.verbose_metrics, vec!["entityUpdate", "*Update"]);
+        assert_eq!(config.tags, vec!["weave"]);
+    });
+}